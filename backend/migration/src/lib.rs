@@ -31,6 +31,26 @@ mod m20240101_000028_add_sortie_to_sessions;
 mod m20240101_000029_add_sortie_to_questionnaires;
 mod m20240101_000030_create_dive_directors;
 mod m20240101_000031_add_sortie_to_email_jobs;
+mod m20240101_000032_create_level_promotions;
+mod m20240101_000033_create_skill_validation_history;
+mod m20240101_000034_add_validation_source;
+mod m20240101_000035_create_session_skill_plans;
+mod m20240101_000036_add_competency_skill_mapping;
+mod m20240101_000037_create_certifications;
+mod m20240101_000038_create_diving_levels;
+mod m20240101_000039_create_certification_equivalences;
+mod m20240101_000040_add_skill_validator_rule;
+mod m20240101_000041_create_level_document_versions;
+mod m20240101_000042_create_skill_document_fields;
+mod m20240101_000043_create_completion_certificates;
+mod m20240101_000044_add_validation_stage_workflow;
+mod m20240101_000045_add_skill_validity_period;
+mod m20240101_000046_create_skill_validation_evidence;
+mod m20240101_000047_create_notification_preferences;
+mod m20240101_000048_create_quizzes;
+mod m20240101_000049_create_mentor_assignments;
+mod m20240101_000050_create_teaching_qualifications;
+mod m20240101_000051_create_validation_requests;
 
 pub struct Migrator;
 
@@ -69,6 +89,26 @@ impl MigratorTrait for Migrator {
         Box::new(m20240101_000029_add_sortie_to_questionnaires::Migration),
         Box::new(m20240101_000030_create_dive_directors::Migration),
         Box::new(m20240101_000031_add_sortie_to_email_jobs::Migration),
+        Box::new(m20240101_000032_create_level_promotions::Migration),
+        Box::new(m20240101_000033_create_skill_validation_history::Migration),
+        Box::new(m20240101_000034_add_validation_source::Migration),
+        Box::new(m20240101_000035_create_session_skill_plans::Migration),
+        Box::new(m20240101_000036_add_competency_skill_mapping::Migration),
+        Box::new(m20240101_000037_create_certifications::Migration),
+        Box::new(m20240101_000038_create_diving_levels::Migration),
+        Box::new(m20240101_000039_create_certification_equivalences::Migration),
+        Box::new(m20240101_000040_add_skill_validator_rule::Migration),
+        Box::new(m20240101_000041_create_level_document_versions::Migration),
+        Box::new(m20240101_000042_create_skill_document_fields::Migration),
+        Box::new(m20240101_000043_create_completion_certificates::Migration),
+        Box::new(m20240101_000044_add_validation_stage_workflow::Migration),
+        Box::new(m20240101_000045_add_skill_validity_period::Migration),
+        Box::new(m20240101_000046_create_skill_validation_evidence::Migration),
+        Box::new(m20240101_000047_create_notification_preferences::Migration),
+        Box::new(m20240101_000048_create_quizzes::Migration),
+        Box::new(m20240101_000049_create_mentor_assignments::Migration),
+        Box::new(m20240101_000050_create_teaching_qualifications::Migration),
+        Box::new(m20240101_000051_create_validation_requests::Migration),
         ]
     }
 }
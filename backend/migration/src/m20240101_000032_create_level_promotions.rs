@@ -0,0 +1,110 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Promotions de niveau détectées quand tous les acquis d'un niveau sont validés
+        manager
+            .create_table(
+                Table::create()
+                    .table(LevelPromotions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LevelPromotions::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(LevelPromotions::PersonId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(LevelPromotions::Level)
+                            .string_len(10)
+                            .not_null(),
+                    )
+                    // proposed, applied, rejected
+                    .col(
+                        ColumnDef::new(LevelPromotions::Status)
+                            .string_len(20)
+                            .not_null()
+                            .default("proposed"),
+                    )
+                    // Date de la dernière validation finale du niveau
+                    .col(ColumnDef::new(LevelPromotions::CompletedAt).date().not_null())
+                    // Liste JSON des encadrants ayant validé les acquis
+                    .col(ColumnDef::new(LevelPromotions::ValidatedByIds).json().not_null())
+                    .col(ColumnDef::new(LevelPromotions::PromotedAt).timestamp().null())
+                    .col(ColumnDef::new(LevelPromotions::PromotedById).uuid().null())
+                    .col(
+                        ColumnDef::new(LevelPromotions::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(LevelPromotions::UpdatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_level_promotions_person")
+                            .from(LevelPromotions::Table, LevelPromotions::PersonId)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_level_promotions_promoted_by")
+                            .from(LevelPromotions::Table, LevelPromotions::PromotedById)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Une seule promotion par personne et par niveau
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_level_promotions_unique")
+                    .table(LevelPromotions::Table)
+                    .col(LevelPromotions::PersonId)
+                    .col(LevelPromotions::Level)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LevelPromotions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LevelPromotions {
+    Table,
+    Id,
+    PersonId,
+    Level,
+    Status,
+    CompletedAt,
+    ValidatedByIds,
+    PromotedAt,
+    PromotedById,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum People {
+    Table,
+    Id,
+}
@@ -0,0 +1,136 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Historique append-only de toutes les transitions d'étape
+        // (skill_validations ne garde que l'état courant)
+        manager
+            .create_table(
+                Table::create()
+                    .table(SkillValidationHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SkillValidationHistory::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    // Pas de clé étrangère: l'historique survit à la suppression de la validation
+                    .col(ColumnDef::new(SkillValidationHistory::ValidationId).uuid().null())
+                    .col(ColumnDef::new(SkillValidationHistory::PersonId).uuid().not_null())
+                    .col(ColumnDef::new(SkillValidationHistory::SkillId).uuid().not_null())
+                    // created, advanced, updated, rollback, deleted
+                    .col(
+                        ColumnDef::new(SkillValidationHistory::Action)
+                            .string_len(20)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(SkillValidationHistory::FromStageId).uuid().null())
+                    .col(ColumnDef::new(SkillValidationHistory::ToStageId).uuid().null())
+                    .col(ColumnDef::new(SkillValidationHistory::ValidatedAt).date().null())
+                    .col(ColumnDef::new(SkillValidationHistory::ValidatedById).uuid().null())
+                    // Personne ayant effectué l'action (encadrant ou admin)
+                    .col(ColumnDef::new(SkillValidationHistory::PerformedById).uuid().null())
+                    .col(ColumnDef::new(SkillValidationHistory::Notes).text().null())
+                    .col(
+                        ColumnDef::new(SkillValidationHistory::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_skill_validation_history_person")
+                            .from(SkillValidationHistory::Table, SkillValidationHistory::PersonId)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_skill_validation_history_skill")
+                            .from(SkillValidationHistory::Table, SkillValidationHistory::SkillId)
+                            .to(CompetencySkills::Table, CompetencySkills::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_skill_validation_history_validated_by")
+                            .from(SkillValidationHistory::Table, SkillValidationHistory::ValidatedById)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_skill_validation_history_performed_by")
+                            .from(SkillValidationHistory::Table, SkillValidationHistory::PerformedById)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_skill_validation_history_person_skill")
+                    .table(SkillValidationHistory::Table)
+                    .col(SkillValidationHistory::PersonId)
+                    .col(SkillValidationHistory::SkillId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Reprise de l'état courant: une entrée "created" par validation existante
+        let backfill = r#"
+            INSERT INTO skill_validation_history
+                (id, validation_id, person_id, skill_id, action, from_stage_id, to_stage_id,
+                 validated_at, validated_by_id, performed_by_id, notes, created_at)
+            SELECT gen_random_uuid(), id, person_id, skill_id, 'created', NULL, stage_id,
+                   validated_at, validated_by_id, validated_by_id, notes, updated_at
+            FROM skill_validations
+        "#;
+
+        manager.get_connection().execute_unprepared(backfill).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SkillValidationHistory::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SkillValidationHistory {
+    Table,
+    Id,
+    ValidationId,
+    PersonId,
+    SkillId,
+    Action,
+    FromStageId,
+    ToStageId,
+    ValidatedAt,
+    ValidatedById,
+    PerformedById,
+    Notes,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum People {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum CompetencySkills {
+    Table,
+    Id,
+}
@@ -0,0 +1,124 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Origine de la validation: session et palanquée de la plongée
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SkillValidations::Table)
+                    .add_column(ColumnDef::new(SkillValidations::SessionId).uuid().null())
+                    .add_column(ColumnDef::new(SkillValidations::PalanqueeId).uuid().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_skill_validations_session")
+                    .from(SkillValidations::Table, SkillValidations::SessionId)
+                    .to(Sessions::Table, Sessions::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_skill_validations_palanquee")
+                    .from(SkillValidations::Table, SkillValidations::PalanqueeId)
+                    .to(Palanquees::Table, Palanquees::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .to_owned(),
+            )
+            .await?;
+
+        // L'historique garde l'origine de chaque transition (sans clé étrangère,
+        // comme validation_id, pour survivre aux suppressions)
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SkillValidationHistory::Table)
+                    .add_column(ColumnDef::new(SkillValidationHistory::SessionId).uuid().null())
+                    .add_column(ColumnDef::new(SkillValidationHistory::PalanqueeId).uuid().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SkillValidationHistory::Table)
+                    .drop_column(SkillValidationHistory::PalanqueeId)
+                    .drop_column(SkillValidationHistory::SessionId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk_skill_validations_palanquee")
+                    .table(SkillValidations::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk_skill_validations_session")
+                    .table(SkillValidations::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SkillValidations::Table)
+                    .drop_column(SkillValidations::PalanqueeId)
+                    .drop_column(SkillValidations::SessionId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum SkillValidations {
+    Table,
+    SessionId,
+    PalanqueeId,
+}
+
+#[derive(DeriveIden)]
+enum SkillValidationHistory {
+    Table,
+    SessionId,
+    PalanqueeId,
+}
+
+#[derive(DeriveIden)]
+enum Sessions {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Palanquees {
+    Table,
+    Id,
+}
@@ -0,0 +1,156 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Acquis planifiés (épinglés) pour un élève lors d'une session
+        manager
+            .create_table(
+                Table::create()
+                    .table(SessionSkillPlans::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SessionSkillPlans::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(SessionSkillPlans::SessionId).uuid().not_null())
+                    .col(ColumnDef::new(SessionSkillPlans::PersonId).uuid().not_null())
+                    .col(ColumnDef::new(SessionSkillPlans::SkillId).uuid().not_null())
+                    .col(ColumnDef::new(SessionSkillPlans::PalanqueeId).uuid().null())
+                    // Étape visée pendant la session
+                    .col(ColumnDef::new(SessionSkillPlans::TargetStageId).uuid().null())
+                    // Encadrant chargé de travailler l'acquis
+                    .col(ColumnDef::new(SessionSkillPlans::EncadrantId).uuid().null())
+                    .col(ColumnDef::new(SessionSkillPlans::PlannedById).uuid().null())
+                    .col(ColumnDef::new(SessionSkillPlans::Notes).text().null())
+                    .col(
+                        ColumnDef::new(SessionSkillPlans::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_session_skill_plans_session")
+                            .from(SessionSkillPlans::Table, SessionSkillPlans::SessionId)
+                            .to(Sessions::Table, Sessions::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_session_skill_plans_person")
+                            .from(SessionSkillPlans::Table, SessionSkillPlans::PersonId)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_session_skill_plans_skill")
+                            .from(SessionSkillPlans::Table, SessionSkillPlans::SkillId)
+                            .to(CompetencySkills::Table, CompetencySkills::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_session_skill_plans_palanquee")
+                            .from(SessionSkillPlans::Table, SessionSkillPlans::PalanqueeId)
+                            .to(Palanquees::Table, Palanquees::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_session_skill_plans_target_stage")
+                            .from(SessionSkillPlans::Table, SessionSkillPlans::TargetStageId)
+                            .to(ValidationStages::Table, ValidationStages::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_session_skill_plans_encadrant")
+                            .from(SessionSkillPlans::Table, SessionSkillPlans::EncadrantId)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_session_skill_plans_planned_by")
+                            .from(SessionSkillPlans::Table, SessionSkillPlans::PlannedById)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Un acquis n'est épinglé qu'une fois par élève et par session
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_session_skill_plans_unique")
+                    .table(SessionSkillPlans::Table)
+                    .col(SessionSkillPlans::SessionId)
+                    .col(SessionSkillPlans::PersonId)
+                    .col(SessionSkillPlans::SkillId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SessionSkillPlans::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SessionSkillPlans {
+    Table,
+    Id,
+    SessionId,
+    PersonId,
+    SkillId,
+    PalanqueeId,
+    TargetStageId,
+    EncadrantId,
+    PlannedById,
+    Notes,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Sessions {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum People {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum CompetencySkills {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Palanquees {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum ValidationStages {
+    Table,
+    Id,
+}
@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Acquis de la hiérarchie vers lequel la compétence historique a été migrée
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Competencies::Table)
+                    .add_column(ColumnDef::new(Competencies::SkillId).uuid().null())
+                    .add_column(ColumnDef::new(Competencies::MigratedAt).timestamp().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_competencies_skill")
+                    .from(Competencies::Table, Competencies::SkillId)
+                    .to(CompetencySkills::Table, CompetencySkills::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk_competencies_skill")
+                    .table(Competencies::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Competencies::Table)
+                    .drop_column(Competencies::MigratedAt)
+                    .drop_column(Competencies::SkillId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Competencies {
+    Table,
+    SkillId,
+    MigratedAt,
+}
+
+#[derive(DeriveIden)]
+enum CompetencySkills {
+    Table,
+    Id,
+}
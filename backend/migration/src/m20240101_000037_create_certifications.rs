@@ -0,0 +1,207 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Brevets et niveaux d'un plongeur: une ligne par niveau (remplace people.diving_level)
+        manager
+            .create_table(
+                Table::create()
+                    .table(Certifications::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Certifications::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Certifications::PersonId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(Certifications::Level)
+                            .string_len(10)
+                            .not_null(),
+                    )
+                    // obtained, preparing
+                    .col(
+                        ColumnDef::new(Certifications::Status)
+                            .string_len(20)
+                            .not_null()
+                            .default("obtained"),
+                    )
+                    .col(ColumnDef::new(Certifications::ObtainedAt).date().null())
+                    // Moniteur ou club ayant délivré le brevet (texte libre)
+                    .col(ColumnDef::new(Certifications::IssuedBy).string_len(255).null())
+                    // Encadrant du club ayant délivré le brevet, si connu
+                    .col(ColumnDef::new(Certifications::IssuedById).uuid().null())
+                    .col(
+                        ColumnDef::new(Certifications::CertificateNumber)
+                            .string_len(100)
+                            .null(),
+                    )
+                    // internal (validation au club), external (carte présentée)
+                    .col(
+                        ColumnDef::new(Certifications::Source)
+                            .string_len(20)
+                            .not_null()
+                            .default("external"),
+                    )
+                    .col(
+                        ColumnDef::new(Certifications::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Certifications::UpdatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_certifications_person")
+                            .from(Certifications::Table, Certifications::PersonId)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_certifications_issued_by")
+                            .from(Certifications::Table, Certifications::IssuedById)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Un niveau n'apparaît qu'une fois par plongeur
+        // (codes enregistrés en majuscules, voir CertificationService)
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_certifications_person_level")
+                    .table(Certifications::Table)
+                    .col(Certifications::PersonId)
+                    .col(Certifications::Level)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+
+        // Reprise de la chaîne séparée par des virgules ("N1,N2,preparing_N3"),
+        // niveaux en majuscules comme à chaque écriture
+        db.execute_unprepared(
+            r#"
+            INSERT INTO certifications (id, person_id, level, status, source, created_at, updated_at)
+            SELECT DISTINCT ON (p.id, lvl.level)
+                gen_random_uuid(),
+                p.id,
+                lvl.level,
+                CASE WHEN lvl.preparing THEN 'preparing' ELSE 'obtained' END,
+                'external',
+                NOW(),
+                NOW()
+            FROM people p
+            CROSS JOIN LATERAL (
+                SELECT
+                    upper(regexp_replace(trim(part), '^preparing_', '')) AS level,
+                    trim(part) LIKE 'preparing\_%' AS preparing
+                FROM unnest(string_to_array(p.diving_level, ',')) AS part
+                WHERE trim(part) <> ''
+            ) lvl
+            WHERE p.diving_level IS NOT NULL
+            ORDER BY p.id, lvl.level, lvl.preparing ASC
+            "#,
+        )
+        .await?;
+
+        // Les niveaux obtenus via une promotion du club sont des brevets internes
+        db.execute_unprepared(
+            r#"
+            UPDATE certifications c
+            SET source = 'internal',
+                obtained_at = COALESCE(lp.promoted_at::date, lp.completed_at),
+                issued_by_id = lp.promoted_by_id
+            FROM level_promotions lp
+            WHERE lp.person_id = c.person_id
+              AND lp.level = c.level
+              AND lp.status = 'applied'
+              AND c.status = 'obtained'
+            "#,
+        )
+        .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(People::Table)
+                    .drop_column(People::DivingLevel)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(People::Table)
+                    .add_column(ColumnDef::new(People::DivingLevel).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                UPDATE people p
+                SET diving_level = agg.levels
+                FROM (
+                    SELECT person_id,
+                           string_agg(
+                               CASE WHEN status = 'preparing' THEN 'preparing_' || level ELSE level END,
+                               ',' ORDER BY status, level
+                           ) AS levels
+                    FROM certifications
+                    GROUP BY person_id
+                ) agg
+                WHERE agg.person_id = p.id
+                "#,
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Certifications::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Certifications {
+    Table,
+    Id,
+    PersonId,
+    Level,
+    Status,
+    ObtainedAt,
+    IssuedBy,
+    IssuedById,
+    CertificateNumber,
+    Source,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum People {
+    Table,
+    Id,
+    DivingLevel,
+}
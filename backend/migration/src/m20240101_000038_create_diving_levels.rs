@@ -0,0 +1,129 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Niveaux FFESSM existants: (code, nom, famille, rang, niveau parent, encadrant)
+const DEFAULT_LEVELS: &[(&str, &str, &str, i32, Option<&str>, bool)] = &[
+    ("N1", "Niveau 1", "plongeur", 10, None, false),
+    ("PE40", "Plongeur Encadré 40m", "competence", 11, Some("N2"), false),
+    ("PA20", "Plongeur Autonome 20m", "competence", 11, Some("N2"), false),
+    ("N2", "Niveau 2", "plongeur", 20, None, false),
+    ("PA40", "Plongeur Autonome 40m", "competence", 21, Some("N3"), false),
+    ("PE60", "Plongeur Encadré 60m", "competence", 21, Some("N3"), false),
+    ("PA60", "Plongeur Autonome 60m", "competence", 21, Some("N3"), false),
+    ("N3", "Niveau 3", "plongeur", 30, None, false),
+    ("E1", "Initiateur (E1)", "encadrant", 35, None, true),
+    ("N4", "Niveau 4 - Guide de Palanquée", "plongeur", 40, None, false),
+    ("N5", "Niveau 5 - Directeur de Plongée", "plongeur", 50, None, false),
+    ("E2", "Encadrant E2", "encadrant", 55, None, true),
+    ("E3", "Moniteur Fédéral 1 (E3)", "encadrant", 60, None, true),
+    ("E4", "Moniteur Fédéral 2 (E4)", "encadrant", 70, None, true),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Référentiel des niveaux (remplace l'énumération codée en dur)
+        manager
+            .create_table(
+                Table::create()
+                    .table(DivingLevels::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(DivingLevels::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(DivingLevels::Code)
+                            .string_len(10)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(DivingLevels::Name).string_len(255).not_null())
+                    // plongeur, encadrant, competence, specialite
+                    .col(
+                        ColumnDef::new(DivingLevels::Family)
+                            .string_len(20)
+                            .not_null(),
+                    )
+                    // Plus le rang est élevé, plus le niveau est haut
+                    .col(ColumnDef::new(DivingLevels::Rank).integer().not_null())
+                    .col(ColumnDef::new(DivingLevels::ParentCode).string_len(10).null())
+                    .col(
+                        ColumnDef::new(DivingLevels::IsInstructor)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(DivingLevels::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(DivingLevels::UpdatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_diving_levels_parent")
+                            .from(DivingLevels::Table, DivingLevels::ParentCode)
+                            .to(DivingLevels::Table, DivingLevels::Code)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Les niveaux parents d'abord pour respecter la clé étrangère
+        let mut levels: Vec<_> = DEFAULT_LEVELS.iter().collect();
+        levels.sort_by_key(|(_, _, _, _, parent, _)| parent.is_some());
+
+        for (code, name, family, rank, parent, is_instructor) in levels {
+            let parent = parent
+                .map(|p| format!("'{}'", p))
+                .unwrap_or_else(|| "NULL".to_string());
+            manager
+                .get_connection()
+                .execute_unprepared(&format!(
+                    "INSERT INTO diving_levels (id, code, name, family, rank, parent_code, is_instructor, created_at, updated_at) \
+                     VALUES (gen_random_uuid(), '{}', '{}', '{}', {}, {}, {}, NOW(), NOW()) \
+                     ON CONFLICT (code) DO NOTHING",
+                    code,
+                    name.replace('\'', "''"),
+                    family,
+                    rank,
+                    parent,
+                    is_instructor
+                ))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(DivingLevels::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum DivingLevels {
+    Table,
+    Id,
+    Code,
+    Name,
+    Family,
+    Rank,
+    ParentCode,
+    IsInstructor,
+    CreatedAt,
+    UpdatedAt,
+}
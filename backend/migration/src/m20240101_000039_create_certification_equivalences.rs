@@ -0,0 +1,238 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Aptitudes nécessaires aux équivalences qui n'existaient pas dans le référentiel
+const EXTRA_LEVELS: &[(&str, &str, i32, &str)] = &[
+    ("PE20", "Plongeur Encadré 20m", 10, "N1"),
+    ("PA12", "Plongeur Autonome 12m", 10, "N1"),
+];
+
+/// Équivalences usuelles (système, code, libellé, aptitude FFESSM)
+const DEFAULT_EQUIVALENCES: &[(&str, &str, &str, &str)] = &[
+    ("CMAS", "1*", "Plongeur 1 étoile", "N1"),
+    ("CMAS", "2*", "Plongeur 2 étoiles", "N2"),
+    ("CMAS", "3*", "Plongeur 3 étoiles", "N3"),
+    ("PADI", "OWD", "Open Water Diver", "PE20"),
+    ("PADI", "OWD", "Open Water Diver", "PA12"),
+    ("PADI", "AOWD", "Advanced Open Water Diver", "PE40"),
+    ("PADI", "AOWD", "Advanced Open Water Diver", "PA20"),
+    ("PADI", "RESCUE", "Rescue Diver", "PE40"),
+    ("PADI", "RESCUE", "Rescue Diver", "PA20"),
+    ("PADI", "DM", "Divemaster", "N4"),
+    ("SSI", "OWD", "Open Water Diver", "PE20"),
+    ("SSI", "OWD", "Open Water Diver", "PA12"),
+    ("SSI", "AA", "Advanced Adventurer", "PE40"),
+    ("SSI", "AA", "Advanced Adventurer", "PA20"),
+    ("SSI", "DMT", "Dive Master", "N4"),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for (code, name, rank, parent) in EXTRA_LEVELS {
+            db.execute_unprepared(&format!(
+                "INSERT INTO diving_levels (id, code, name, family, rank, parent_code, is_instructor, created_at, updated_at) \
+                 VALUES (gen_random_uuid(), '{}', '{}', 'competence', {}, '{}', false, NOW(), NOW()) \
+                 ON CONFLICT (code) DO NOTHING",
+                code, name, rank, parent
+            ))
+            .await?;
+        }
+
+        // Correspondance entre un brevet d'un autre système et les aptitudes FFESSM
+        manager
+            .create_table(
+                Table::create()
+                    .table(CertificationEquivalences::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CertificationEquivalences::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    // CMAS, PADI, SSI...
+                    .col(
+                        ColumnDef::new(CertificationEquivalences::System)
+                            .string_len(20)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CertificationEquivalences::ExternalCode)
+                            .string_len(50)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CertificationEquivalences::ExternalName)
+                            .string_len(255)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CertificationEquivalences::Level)
+                            .string_len(10)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CertificationEquivalences::Notes).text().null())
+                    .col(
+                        ColumnDef::new(CertificationEquivalences::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CertificationEquivalences::UpdatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_certification_equivalences_level")
+                            .from(CertificationEquivalences::Table, CertificationEquivalences::Level)
+                            .to(DivingLevels::Table, DivingLevels::Code)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Un brevet peut donner plusieurs aptitudes, chacune une seule fois
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_certification_equivalences_unique")
+                    .table(CertificationEquivalences::Table)
+                    .col(CertificationEquivalences::System)
+                    .col(CertificationEquivalences::ExternalCode)
+                    .col(CertificationEquivalences::Level)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        for (system, code, name, level) in DEFAULT_EQUIVALENCES {
+            db.execute_unprepared(&format!(
+                "INSERT INTO certification_equivalences (id, system, external_code, external_name, level, created_at, updated_at) \
+                 VALUES (gen_random_uuid(), '{}', '{}', '{}', '{}', NOW(), NOW()) \
+                 ON CONFLICT DO NOTHING",
+                system, code, name, level
+            ))
+            .await?;
+        }
+
+        // Brevets d'autres systèmes présentés par les plongeurs
+        manager
+            .create_table(
+                Table::create()
+                    .table(ExternalCertifications::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ExternalCertifications::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ExternalCertifications::PersonId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(ExternalCertifications::System)
+                            .string_len(20)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ExternalCertifications::ExternalCode)
+                            .string_len(50)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ExternalCertifications::ObtainedAt).date().null())
+                    .col(
+                        ColumnDef::new(ExternalCertifications::CertificateNumber)
+                            .string_len(100)
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(ExternalCertifications::IssuedBy)
+                            .string_len(255)
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(ExternalCertifications::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_external_certifications_person")
+                            .from(ExternalCertifications::Table, ExternalCertifications::PersonId)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_external_certifications_unique")
+                    .table(ExternalCertifications::Table)
+                    .col(ExternalCertifications::PersonId)
+                    .col(ExternalCertifications::System)
+                    .col(ExternalCertifications::ExternalCode)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ExternalCertifications::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(CertificationEquivalences::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CertificationEquivalences {
+    Table,
+    Id,
+    System,
+    ExternalCode,
+    ExternalName,
+    Level,
+    Notes,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ExternalCertifications {
+    Table,
+    Id,
+    PersonId,
+    System,
+    ExternalCode,
+    ObtainedAt,
+    CertificateNumber,
+    IssuedBy,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum DivingLevels {
+    Table,
+    Code,
+}
+
+#[derive(DeriveIden)]
+enum People {
+    Table,
+    Id,
+}
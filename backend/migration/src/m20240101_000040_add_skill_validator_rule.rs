@@ -0,0 +1,61 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Spécialités utilisées dans les règles de validation (code, nom)
+const SPECIALTY_LEVELS: &[(&str, &str)] = &[
+    ("PN", "Plongeur Nitrox"),
+    ("PNC", "Plongeur Nitrox Confirmé"),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Règle d'éligibilité des validateurs (ex: "E2 OR (N4 AND E1)").
+        // Sans règle, min_validator_level s'applique comme auparavant.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CompetencySkills::Table)
+                    .add_column(
+                        ColumnDef::new(CompetencySkills::ValidatorRule)
+                            .text()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        for (code, name) in SPECIALTY_LEVELS {
+            manager
+                .get_connection()
+                .execute_unprepared(&format!(
+                    "INSERT INTO diving_levels (id, code, name, family, rank, parent_code, is_instructor, created_at, updated_at) \
+                     VALUES (gen_random_uuid(), '{}', '{}', 'specialite', 0, NULL, false, NOW(), NOW()) \
+                     ON CONFLICT (code) DO NOTHING",
+                    code, name
+                ))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CompetencySkills::Table)
+                    .drop_column(CompetencySkills::ValidatorRule)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum CompetencySkills {
+    Table,
+    ValidatorRule,
+}
@@ -0,0 +1,355 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Versions successives du modèle PDF d'un niveau
+        manager
+            .create_table(
+                Table::create()
+                    .table(LevelDocumentVersions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LevelDocumentVersions::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(LevelDocumentVersions::Level)
+                            .string_len(10)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(LevelDocumentVersions::Version).integer().not_null())
+                    .col(
+                        ColumnDef::new(LevelDocumentVersions::FileName)
+                            .string_len(255)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(LevelDocumentVersions::FileData).binary().not_null())
+                    .col(ColumnDef::new(LevelDocumentVersions::PageCount).integer().not_null())
+                    .col(ColumnDef::new(LevelDocumentVersions::Notes).text().null())
+                    .col(
+                        ColumnDef::new(LevelDocumentVersions::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_level_document_versions_level_version")
+                    .table(LevelDocumentVersions::Table)
+                    .col(LevelDocumentVersions::Level)
+                    .col(LevelDocumentVersions::Version)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Le document actuel devient la version 1
+        db.execute_unprepared(
+            r#"
+            INSERT INTO level_document_versions (id, level, version, file_name, file_data, page_count, created_at)
+            SELECT gen_random_uuid(), level, 1, file_name, file_data, page_count, updated_at
+            FROM level_documents
+            "#,
+        )
+        .await?;
+
+        // level_documents ne garde que la version active du niveau
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(LevelDocuments::Table)
+                    .add_column(ColumnDef::new(LevelDocuments::CurrentVersionId).uuid().null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_level_documents_current_version")
+                            .from_tbl(LevelDocuments::Table)
+                            .from_col(LevelDocuments::CurrentVersionId)
+                            .to_tbl(LevelDocumentVersions::Table)
+                            .to_col(LevelDocumentVersions::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        db.execute_unprepared(
+            r#"
+            UPDATE level_documents d
+            SET current_version_id = v.id
+            FROM level_document_versions v
+            WHERE v.level = d.level AND v.version = 1
+            "#,
+        )
+        .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(LevelDocuments::Table)
+                    .drop_column(LevelDocuments::FileName)
+                    .drop_column(LevelDocuments::FileData)
+                    .drop_column(LevelDocuments::PageCount)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Chaque version a son propre placement des acquis
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SkillDocumentPositions::Table)
+                    .add_column(ColumnDef::new(SkillDocumentPositions::VersionId).uuid().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        db.execute_unprepared(
+            r#"
+            UPDATE skill_document_positions p
+            SET version_id = v.id
+            FROM level_document_versions v
+            WHERE v.level = p.level AND v.version = 1
+            "#,
+        )
+        .await?;
+
+        // Positions sans document: inutilisables
+        db.execute_unprepared("DELETE FROM skill_document_positions WHERE version_id IS NULL")
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SkillDocumentPositions::Table)
+                    .modify_column(ColumnDef::new(SkillDocumentPositions::VersionId).uuid().not_null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_skill_document_positions_version")
+                            .from_tbl(SkillDocumentPositions::Table)
+                            .from_col(SkillDocumentPositions::VersionId)
+                            .to_tbl(LevelDocumentVersions::Table)
+                            .to_col(LevelDocumentVersions::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_skill_document_positions_skill_level")
+                    .table(SkillDocumentPositions::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_skill_document_positions_version_skill")
+                    .table(SkillDocumentPositions::Table)
+                    .col(SkillDocumentPositions::VersionId)
+                    .col(SkillDocumentPositions::SkillId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Documents générés et version du modèle utilisée
+        manager
+            .create_table(
+                Table::create()
+                    .table(LevelDocumentGenerations::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LevelDocumentGenerations::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(LevelDocumentGenerations::PersonId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(LevelDocumentGenerations::Level)
+                            .string_len(10)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(LevelDocumentGenerations::VersionId).uuid().not_null())
+                    .col(ColumnDef::new(LevelDocumentGenerations::Version).integer().not_null())
+                    .col(
+                        ColumnDef::new(LevelDocumentGenerations::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_level_document_generations_person")
+                            .from(LevelDocumentGenerations::Table, LevelDocumentGenerations::PersonId)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_level_document_generations_version")
+                            .from(LevelDocumentGenerations::Table, LevelDocumentGenerations::VersionId)
+                            .to(LevelDocumentVersions::Table, LevelDocumentVersions::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        manager
+            .drop_table(Table::drop().table(LevelDocumentGenerations::Table).to_owned())
+            .await?;
+
+        // Seule la version active de chaque niveau est conservée
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(LevelDocuments::Table)
+                    .add_column(ColumnDef::new(LevelDocuments::FileName).string_len(255).null())
+                    .add_column(ColumnDef::new(LevelDocuments::FileData).binary().null())
+                    .add_column(ColumnDef::new(LevelDocuments::PageCount).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        db.execute_unprepared(
+            r#"
+            UPDATE level_documents d
+            SET file_name = v.file_name, file_data = v.file_data, page_count = v.page_count
+            FROM level_document_versions v
+            WHERE v.id = d.current_version_id
+            "#,
+        )
+        .await?;
+        db.execute_unprepared("DELETE FROM level_documents WHERE file_data IS NULL")
+            .await?;
+        db.execute_unprepared(
+            r#"
+            DELETE FROM skill_document_positions p
+            USING level_documents d
+            WHERE d.level = p.level AND p.version_id IS DISTINCT FROM d.current_version_id
+            "#,
+        )
+        .await?;
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE level_documents
+                ALTER COLUMN file_name SET NOT NULL,
+                ALTER COLUMN file_data SET NOT NULL,
+                ALTER COLUMN page_count SET NOT NULL
+            "#,
+        )
+        .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_skill_document_positions_version_skill")
+                    .table(SkillDocumentPositions::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SkillDocumentPositions::Table)
+                    .drop_foreign_key(Alias::new("fk_skill_document_positions_version"))
+                    .drop_column(SkillDocumentPositions::VersionId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_skill_document_positions_skill_level")
+                    .table(SkillDocumentPositions::Table)
+                    .col(SkillDocumentPositions::SkillId)
+                    .col(SkillDocumentPositions::Level)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(LevelDocuments::Table)
+                    .drop_foreign_key(Alias::new("fk_level_documents_current_version"))
+                    .drop_column(LevelDocuments::CurrentVersionId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(LevelDocumentVersions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LevelDocumentVersions {
+    Table,
+    Id,
+    Level,
+    Version,
+    FileName,
+    FileData,
+    PageCount,
+    Notes,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum LevelDocuments {
+    Table,
+    FileName,
+    FileData,
+    PageCount,
+    CurrentVersionId,
+}
+
+#[derive(DeriveIden)]
+enum SkillDocumentPositions {
+    Table,
+    SkillId,
+    Level,
+    VersionId,
+}
+
+#[derive(DeriveIden)]
+enum LevelDocumentGenerations {
+    Table,
+    Id,
+    PersonId,
+    Level,
+    VersionId,
+    Version,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum People {
+    Table,
+    Id,
+}
@@ -0,0 +1,114 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Champs de formulaire (AcroForm) du modèle associés aux acquis,
+        // en alternative aux positions x/y
+        manager
+            .create_table(
+                Table::create()
+                    .table(SkillDocumentFields::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SkillDocumentFields::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(SkillDocumentFields::VersionId).uuid().not_null())
+                    .col(ColumnDef::new(SkillDocumentFields::SkillId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(SkillDocumentFields::Level)
+                            .string_len(10)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SkillDocumentFields::FieldName)
+                            .string_len(255)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SkillDocumentFields::Content)
+                            .string_len(20)
+                            .not_null()
+                            .default("summary"),
+                    )
+                    .col(
+                        ColumnDef::new(SkillDocumentFields::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SkillDocumentFields::UpdatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_skill_document_fields_version")
+                            .from(SkillDocumentFields::Table, SkillDocumentFields::VersionId)
+                            .to(LevelDocumentVersions::Table, LevelDocumentVersions::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_skill_document_fields_skill")
+                            .from(SkillDocumentFields::Table, SkillDocumentFields::SkillId)
+                            .to(CompetencySkills::Table, CompetencySkills::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Un champ ne reçoit qu'une seule valeur
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_skill_document_fields_version_field")
+                    .table(SkillDocumentFields::Table)
+                    .col(SkillDocumentFields::VersionId)
+                    .col(SkillDocumentFields::FieldName)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SkillDocumentFields::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SkillDocumentFields {
+    Table,
+    Id,
+    VersionId,
+    SkillId,
+    Level,
+    FieldName,
+    Content,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum LevelDocumentVersions {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum CompetencySkills {
+    Table,
+    Id,
+}
@@ -0,0 +1,107 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Attestations de fin de niveau, vérifiables publiquement par leur code
+        manager
+            .create_table(
+                Table::create()
+                    .table(CompletionCertificates::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CompletionCertificates::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(CompletionCertificates::PersonId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(CompletionCertificates::Level)
+                            .string_len(10)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CompletionCertificates::VerificationCode)
+                            .string_len(20)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    // Nom et encadrants figés à l'émission: l'attestation reste
+                    // vérifiable telle qu'imprimée
+                    .col(
+                        ColumnDef::new(CompletionCertificates::PersonName)
+                            .string_len(255)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CompletionCertificates::CompletedAt).date().not_null())
+                    .col(ColumnDef::new(CompletionCertificates::ValidatorNames).json().not_null())
+                    .col(
+                        ColumnDef::new(CompletionCertificates::IssuedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CompletionCertificates::IssuedById).uuid().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_completion_certificates_person")
+                            .from(CompletionCertificates::Table, CompletionCertificates::PersonId)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_completion_certificates_issued_by")
+                            .from(CompletionCertificates::Table, CompletionCertificates::IssuedById)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Une seule attestation par personne et par niveau
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_completion_certificates_unique")
+                    .table(CompletionCertificates::Table)
+                    .col(CompletionCertificates::PersonId)
+                    .col(CompletionCertificates::Level)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CompletionCertificates::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CompletionCertificates {
+    Table,
+    Id,
+    PersonId,
+    Level,
+    VerificationCode,
+    PersonName,
+    CompletedAt,
+    ValidatorNames,
+    IssuedAt,
+    IssuedById,
+}
+
+#[derive(DeriveIden)]
+enum People {
+    Table,
+    Id,
+}
@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Workflow des étapes: transitions autorisées (NULL = progression libre
+        // vers l'avant), règle de validateur propre à l'étape, auto-évaluation
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ValidationStages::Table)
+                    .add_column(ColumnDef::new(ValidationStages::AllowedNextStageIds).json().null())
+                    .add_column(ColumnDef::new(ValidationStages::ValidatorRule).text().null())
+                    .add_column(
+                        ColumnDef::new(ValidationStages::SelfAssessable)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ValidationStages::Table)
+                    .drop_column(ValidationStages::AllowedNextStageIds)
+                    .drop_column(ValidationStages::ValidatorRule)
+                    .drop_column(ValidationStages::SelfAssessable)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum ValidationStages {
+    Table,
+    AllowedNextStageIds,
+    ValidatorRule,
+    SelfAssessable,
+}
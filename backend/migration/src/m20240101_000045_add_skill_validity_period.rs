@@ -0,0 +1,61 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Durée de validité (en mois) d'une validation. Celle de l'acquis
+        // prime sur celle de son domaine; NULL: validité illimitée.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CompetencySkills::Table)
+                    .add_column(ColumnDef::new(CompetencySkills::ValidityMonths).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CompetencyDomains::Table)
+                    .add_column(ColumnDef::new(CompetencyDomains::ValidityMonths).integer().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CompetencyDomains::Table)
+                    .drop_column(CompetencyDomains::ValidityMonths)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CompetencySkills::Table)
+                    .drop_column(CompetencySkills::ValidityMonths)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum CompetencySkills {
+    Table,
+    ValidityMonths,
+}
+
+#[derive(Iden)]
+enum CompetencyDomains {
+    Table,
+    ValidityMonths,
+}
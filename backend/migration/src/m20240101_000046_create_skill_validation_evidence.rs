@@ -0,0 +1,207 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Preuves jointes à une validation: fichier (photo, vidéo, scan) ou
+        // référence de carnet de plongée. Le contenu des fichiers est dans le
+        // stockage, la table ne garde que la clé.
+        manager
+            .create_table(
+                Table::create()
+                    .table(SkillValidationEvidence::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SkillValidationEvidence::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    // Pas de clé étrangère: comme l'historique, les preuves
+                    // survivent à la suppression de la validation
+                    .col(ColumnDef::new(SkillValidationEvidence::ValidationId).uuid().not_null())
+                    .col(ColumnDef::new(SkillValidationEvidence::PersonId).uuid().not_null())
+                    .col(ColumnDef::new(SkillValidationEvidence::SkillId).uuid().not_null())
+                    // file, dive_log
+                    .col(
+                        ColumnDef::new(SkillValidationEvidence::Kind)
+                            .string_len(20)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(SkillValidationEvidence::FileName).string_len(255).null())
+                    .col(ColumnDef::new(SkillValidationEvidence::ContentType).string_len(100).null())
+                    .col(ColumnDef::new(SkillValidationEvidence::SizeBytes).big_integer().null())
+                    .col(ColumnDef::new(SkillValidationEvidence::StorageKey).string_len(255).null())
+                    .col(ColumnDef::new(SkillValidationEvidence::Reference).string_len(255).null())
+                    .col(ColumnDef::new(SkillValidationEvidence::Caption).text().null())
+                    .col(ColumnDef::new(SkillValidationEvidence::UploadedById).uuid().null())
+                    .col(
+                        ColumnDef::new(SkillValidationEvidence::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_skill_validation_evidence_person")
+                            .from(SkillValidationEvidence::Table, SkillValidationEvidence::PersonId)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_skill_validation_evidence_skill")
+                            .from(SkillValidationEvidence::Table, SkillValidationEvidence::SkillId)
+                            .to(CompetencySkills::Table, CompetencySkills::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_skill_validation_evidence_uploaded_by")
+                            .from(SkillValidationEvidence::Table, SkillValidationEvidence::UploadedById)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_skill_validation_evidence_validation")
+                    .table(SkillValidationEvidence::Table)
+                    .col(SkillValidationEvidence::ValidationId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Fil de commentaires d'une validation, lisible par l'élève
+        manager
+            .create_table(
+                Table::create()
+                    .table(SkillValidationComments::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SkillValidationComments::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(SkillValidationComments::ValidationId).uuid().not_null())
+                    .col(ColumnDef::new(SkillValidationComments::PersonId).uuid().not_null())
+                    .col(ColumnDef::new(SkillValidationComments::SkillId).uuid().not_null())
+                    // Commentaire auquel celui-ci répond
+                    .col(ColumnDef::new(SkillValidationComments::ParentId).uuid().null())
+                    .col(ColumnDef::new(SkillValidationComments::AuthorId).uuid().null())
+                    .col(ColumnDef::new(SkillValidationComments::Body).text().not_null())
+                    .col(
+                        ColumnDef::new(SkillValidationComments::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SkillValidationComments::UpdatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_skill_validation_comments_person")
+                            .from(SkillValidationComments::Table, SkillValidationComments::PersonId)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_skill_validation_comments_skill")
+                            .from(SkillValidationComments::Table, SkillValidationComments::SkillId)
+                            .to(CompetencySkills::Table, CompetencySkills::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_skill_validation_comments_parent")
+                            .from(SkillValidationComments::Table, SkillValidationComments::ParentId)
+                            .to(SkillValidationComments::Table, SkillValidationComments::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_skill_validation_comments_author")
+                            .from(SkillValidationComments::Table, SkillValidationComments::AuthorId)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_skill_validation_comments_validation")
+                    .table(SkillValidationComments::Table)
+                    .col(SkillValidationComments::ValidationId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SkillValidationComments::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(SkillValidationEvidence::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SkillValidationEvidence {
+    Table,
+    Id,
+    ValidationId,
+    PersonId,
+    SkillId,
+    Kind,
+    FileName,
+    ContentType,
+    SizeBytes,
+    StorageKey,
+    Reference,
+    Caption,
+    UploadedById,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum SkillValidationComments {
+    Table,
+    Id,
+    ValidationId,
+    PersonId,
+    SkillId,
+    ParentId,
+    AuthorId,
+    Body,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum People {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum CompetencySkills {
+    Table,
+    Id,
+}
@@ -0,0 +1,143 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Préférences de notification (opt-in: pas de ligne = aucun email)
+        manager
+            .create_table(
+                Table::create()
+                    .table(NotificationPreferences::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NotificationPreferences::PersonId)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    // Résumé quotidien des nouvelles validations
+                    .col(
+                        ColumnDef::new(NotificationPreferences::ProgressDigest)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    // Email quand un module ou un domaine est complet
+                    .col(
+                        ColumnDef::new(NotificationPreferences::MilestoneEmails)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    // Fin de la période couverte par le dernier résumé envoyé
+                    .col(ColumnDef::new(NotificationPreferences::LastDigestAt).timestamp().null())
+                    .col(
+                        ColumnDef::new(NotificationPreferences::UpdatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_notification_preferences_person")
+                            .from(NotificationPreferences::Table, NotificationPreferences::PersonId)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Modules et domaines complétés par un élève (un seul email par étape)
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProgressMilestones::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ProgressMilestones::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ProgressMilestones::PersonId).uuid().not_null())
+                    // module, domain
+                    .col(
+                        ColumnDef::new(ProgressMilestones::UnitType)
+                            .string_len(20)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ProgressMilestones::UnitId).uuid().not_null())
+                    .col(ColumnDef::new(ProgressMilestones::CompletedAt).date().not_null())
+                    .col(ColumnDef::new(ProgressMilestones::NotifiedAt).timestamp().null())
+                    .col(
+                        ColumnDef::new(ProgressMilestones::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_progress_milestones_person")
+                            .from(ProgressMilestones::Table, ProgressMilestones::PersonId)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_progress_milestones_person_unit")
+                    .table(ProgressMilestones::Table)
+                    .col(ProgressMilestones::PersonId)
+                    .col(ProgressMilestones::UnitType)
+                    .col(ProgressMilestones::UnitId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProgressMilestones::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(NotificationPreferences::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NotificationPreferences {
+    Table,
+    PersonId,
+    ProgressDigest,
+    MilestoneEmails,
+    LastDigestAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ProgressMilestones {
+    Table,
+    Id,
+    PersonId,
+    UnitType,
+    UnitId,
+    CompletedAt,
+    NotifiedAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum People {
+    Table,
+    Id,
+}
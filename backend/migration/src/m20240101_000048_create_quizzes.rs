@@ -0,0 +1,300 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Banque de questions théoriques (QCM), classées par niveau et domaine
+        manager
+            .create_table(
+                Table::create()
+                    .table(QuizQuestions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(QuizQuestions::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(QuizQuestions::DivingLevel).string_len(10).not_null())
+                    .col(ColumnDef::new(QuizQuestions::DomainId).uuid().null())
+                    .col(ColumnDef::new(QuizQuestions::Question).text().not_null())
+                    // Propositions (tableau JSON de textes)
+                    .col(ColumnDef::new(QuizQuestions::Choices).json().not_null())
+                    // Indices des bonnes propositions (tableau JSON)
+                    .col(ColumnDef::new(QuizQuestions::CorrectChoices).json().not_null())
+                    .col(ColumnDef::new(QuizQuestions::Explanation).text().null())
+                    .col(
+                        ColumnDef::new(QuizQuestions::Points)
+                            .integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .col(
+                        ColumnDef::new(QuizQuestions::IsActive)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(ColumnDef::new(QuizQuestions::CreatedAt).timestamp().not_null())
+                    .col(ColumnDef::new(QuizQuestions::UpdatedAt).timestamp().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_quiz_questions_domain")
+                            .from(QuizQuestions::Table, QuizQuestions::DomainId)
+                            .to(CompetencyDomains::Table, CompetencyDomains::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_quiz_questions_level")
+                    .table(QuizQuestions::Table)
+                    .col(QuizQuestions::DivingLevel)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Questionnaires en ligne (entraînement ou examen théorique). Un
+        // questionnaire rattaché à un acquis le valide quand il est réussi.
+        manager
+            .create_table(
+                Table::create()
+                    .table(Quizzes::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Quizzes::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Quizzes::Title).string_len(255).not_null())
+                    .col(ColumnDef::new(Quizzes::Description).text().null())
+                    .col(ColumnDef::new(Quizzes::DivingLevel).string_len(10).not_null())
+                    // Limite le tirage aux questions d'un domaine
+                    .col(ColumnDef::new(Quizzes::DomainId).uuid().null())
+                    // Nombre de questions tirées au sort (toutes si NULL)
+                    .col(ColumnDef::new(Quizzes::QuestionCount).integer().null())
+                    .col(
+                        ColumnDef::new(Quizzes::PassPercentage)
+                            .integer()
+                            .not_null()
+                            .default(75),
+                    )
+                    .col(ColumnDef::new(Quizzes::TimeLimitMinutes).integer().null())
+                    .col(ColumnDef::new(Quizzes::MaxAttempts).integer().null())
+                    // Montre les bonnes réponses après l'envoi
+                    .col(
+                        ColumnDef::new(Quizzes::ShowCorrections)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(Quizzes::SkillId).uuid().null())
+                    .col(ColumnDef::new(Quizzes::StageId).uuid().null())
+                    .col(
+                        ColumnDef::new(Quizzes::IsPublished)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    // Encadrant responsable: validateur des acquis obtenus
+                    .col(ColumnDef::new(Quizzes::CreatedById).uuid().null())
+                    .col(ColumnDef::new(Quizzes::CreatedAt).timestamp().not_null())
+                    .col(ColumnDef::new(Quizzes::UpdatedAt).timestamp().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_quizzes_domain")
+                            .from(Quizzes::Table, Quizzes::DomainId)
+                            .to(CompetencyDomains::Table, CompetencyDomains::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_quizzes_skill")
+                            .from(Quizzes::Table, Quizzes::SkillId)
+                            .to(CompetencySkills::Table, CompetencySkills::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_quizzes_stage")
+                            .from(Quizzes::Table, Quizzes::StageId)
+                            .to(ValidationStages::Table, ValidationStages::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_quizzes_created_by")
+                            .from(Quizzes::Table, Quizzes::CreatedById)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Passages d'un questionnaire (en ligne ou résultat saisi d'un examen papier)
+        manager
+            .create_table(
+                Table::create()
+                    .table(QuizAttempts::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(QuizAttempts::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(QuizAttempts::QuizId).uuid().not_null())
+                    .col(ColumnDef::new(QuizAttempts::PersonId).uuid().not_null())
+                    // Questions tirées, dans l'ordre présenté (tableau JSON d'IDs)
+                    .col(ColumnDef::new(QuizAttempts::QuestionIds).json().not_null())
+                    // Réponses envoyées: { question_id: [indices] }
+                    .col(ColumnDef::new(QuizAttempts::Answers).json().null())
+                    .col(ColumnDef::new(QuizAttempts::Score).integer().null())
+                    .col(ColumnDef::new(QuizAttempts::MaxScore).integer().not_null())
+                    .col(ColumnDef::new(QuizAttempts::Percentage).float().null())
+                    .col(ColumnDef::new(QuizAttempts::Passed).boolean().null())
+                    .col(ColumnDef::new(QuizAttempts::StartedAt).timestamp().not_null())
+                    .col(ColumnDef::new(QuizAttempts::SubmittedAt).timestamp().null())
+                    // Résultat saisi par un encadrant (examen sur papier)
+                    .col(ColumnDef::new(QuizAttempts::RecordedById).uuid().null())
+                    // Validation d'acquis créée par la réussite
+                    .col(ColumnDef::new(QuizAttempts::ValidationId).uuid().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_quiz_attempts_quiz")
+                            .from(QuizAttempts::Table, QuizAttempts::QuizId)
+                            .to(Quizzes::Table, Quizzes::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_quiz_attempts_person")
+                            .from(QuizAttempts::Table, QuizAttempts::PersonId)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_quiz_attempts_recorded_by")
+                            .from(QuizAttempts::Table, QuizAttempts::RecordedById)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_quiz_attempts_quiz_person")
+                    .table(QuizAttempts::Table)
+                    .col(QuizAttempts::QuizId)
+                    .col(QuizAttempts::PersonId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(QuizAttempts::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Quizzes::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(QuizQuestions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum QuizQuestions {
+    Table,
+    Id,
+    DivingLevel,
+    DomainId,
+    Question,
+    Choices,
+    CorrectChoices,
+    Explanation,
+    Points,
+    IsActive,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Quizzes {
+    Table,
+    Id,
+    Title,
+    Description,
+    DivingLevel,
+    DomainId,
+    QuestionCount,
+    PassPercentage,
+    TimeLimitMinutes,
+    MaxAttempts,
+    ShowCorrections,
+    SkillId,
+    StageId,
+    IsPublished,
+    CreatedById,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum QuizAttempts {
+    Table,
+    Id,
+    QuizId,
+    PersonId,
+    QuestionIds,
+    Answers,
+    Score,
+    MaxScore,
+    Percentage,
+    Passed,
+    StartedAt,
+    SubmittedAt,
+    RecordedById,
+    ValidationId,
+}
+
+#[derive(DeriveIden)]
+enum CompetencyDomains {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum CompetencySkills {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum ValidationStages {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum People {
+    Table,
+    Id,
+}
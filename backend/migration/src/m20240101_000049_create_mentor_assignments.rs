@@ -0,0 +1,107 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Encadrant référent d'un élève pour la préparation d'un niveau
+        manager
+            .create_table(
+                Table::create()
+                    .table(MentorAssignments::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MentorAssignments::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(MentorAssignments::StudentId).uuid().not_null())
+                    .col(ColumnDef::new(MentorAssignments::MentorId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(MentorAssignments::DivingLevel)
+                            .string_len(10)
+                            .not_null(),
+                    )
+                    // Dernière alerte de stagnation envoyée au référent
+                    .col(ColumnDef::new(MentorAssignments::StallNotifiedAt).timestamp().null())
+                    .col(
+                        ColumnDef::new(MentorAssignments::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MentorAssignments::UpdatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_mentor_assignments_student")
+                            .from(MentorAssignments::Table, MentorAssignments::StudentId)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_mentor_assignments_mentor")
+                            .from(MentorAssignments::Table, MentorAssignments::MentorId)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Un seul référent par élève et par niveau
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_mentor_assignments_student_level")
+                    .table(MentorAssignments::Table)
+                    .col(MentorAssignments::StudentId)
+                    .col(MentorAssignments::DivingLevel)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_mentor_assignments_mentor")
+                    .table(MentorAssignments::Table)
+                    .col(MentorAssignments::MentorId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MentorAssignments::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MentorAssignments {
+    Table,
+    Id,
+    StudentId,
+    MentorId,
+    DivingLevel,
+    StallNotifiedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum People {
+    Table,
+    Id,
+}
@@ -0,0 +1,174 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Périodicité du recyclage des titres d'encadrement (NULL = aucune)
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(DivingLevels::Table)
+                    .add_column(ColumnDef::new(DivingLevels::RecyclingMonths).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Titres d'encadrement (initiateur, MF1, MF2...) et leur échéance
+        manager
+            .create_table(
+                Table::create()
+                    .table(TeachingQualifications::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TeachingQualifications::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TeachingQualifications::PersonId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(TeachingQualifications::Level)
+                            .string_len(10)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TeachingQualifications::ObtainedAt).date().not_null())
+                    // Fin de validité sans recyclage (NULL = illimitée)
+                    .col(ColumnDef::new(TeachingQualifications::ExpiresAt).date().null())
+                    .col(
+                        ColumnDef::new(TeachingQualifications::CertificateNumber)
+                            .string_len(50)
+                            .null(),
+                    )
+                    .col(ColumnDef::new(TeachingQualifications::Notes).text().null())
+                    .col(
+                        ColumnDef::new(TeachingQualifications::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TeachingQualifications::UpdatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_teaching_qualifications_person")
+                            .from(TeachingQualifications::Table, TeachingQualifications::PersonId)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_teaching_qualifications_person_level")
+                    .table(TeachingQualifications::Table)
+                    .col(TeachingQualifications::PersonId)
+                    .col(TeachingQualifications::Level)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Recyclages suivis, chacun prolongeant la validité du titre
+        manager
+            .create_table(
+                Table::create()
+                    .table(TeachingRecyclings::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TeachingRecyclings::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TeachingRecyclings::QualificationId).uuid().not_null())
+                    .col(ColumnDef::new(TeachingRecyclings::RecycledAt).date().not_null())
+                    .col(ColumnDef::new(TeachingRecyclings::Notes).text().null())
+                    .col(ColumnDef::new(TeachingRecyclings::RecordedById).uuid().null())
+                    .col(
+                        ColumnDef::new(TeachingRecyclings::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_teaching_recyclings_qualification")
+                            .from(TeachingRecyclings::Table, TeachingRecyclings::QualificationId)
+                            .to(TeachingQualifications::Table, TeachingQualifications::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_teaching_recyclings_recorded_by")
+                            .from(TeachingRecyclings::Table, TeachingRecyclings::RecordedById)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TeachingRecyclings::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(TeachingQualifications::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(DivingLevels::Table)
+                    .drop_column(DivingLevels::RecyclingMonths)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum DivingLevels {
+    Table,
+    RecyclingMonths,
+}
+
+#[derive(DeriveIden)]
+enum TeachingQualifications {
+    Table,
+    Id,
+    PersonId,
+    Level,
+    ObtainedAt,
+    ExpiresAt,
+    CertificateNumber,
+    Notes,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum TeachingRecyclings {
+    Table,
+    Id,
+    QualificationId,
+    RecycledAt,
+    Notes,
+    RecordedById,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum People {
+    Table,
+    Id,
+}
@@ -0,0 +1,172 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Demandes de validation d'un acquis, faites par l'élève ou par un
+        // encadrant pour lui, en attente d'un validateur habilité
+        manager
+            .create_table(
+                Table::create()
+                    .table(ValidationRequests::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ValidationRequests::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ValidationRequests::PersonId).uuid().not_null())
+                    .col(ColumnDef::new(ValidationRequests::SkillId).uuid().not_null())
+                    // Étape demandée (NULL: étape finale)
+                    .col(ColumnDef::new(ValidationRequests::StageId).uuid().null())
+                    .col(ColumnDef::new(ValidationRequests::RequestedById).uuid().null())
+                    // Contexte: session et date où l'acquis a été démontré
+                    .col(ColumnDef::new(ValidationRequests::SessionId).uuid().null())
+                    .col(ColumnDef::new(ValidationRequests::DemonstratedAt).date().null())
+                    .col(ColumnDef::new(ValidationRequests::Comment).text().null())
+                    // pending, approved, rejected
+                    .col(
+                        ColumnDef::new(ValidationRequests::Status)
+                            .string_len(20)
+                            .not_null()
+                            .default("pending"),
+                    )
+                    .col(ColumnDef::new(ValidationRequests::ReviewedById).uuid().null())
+                    .col(ColumnDef::new(ValidationRequests::ReviewedAt).timestamp().null())
+                    .col(ColumnDef::new(ValidationRequests::ReviewComment).text().null())
+                    // Validation créée à l'acceptation
+                    .col(ColumnDef::new(ValidationRequests::ValidationId).uuid().null())
+                    .col(
+                        ColumnDef::new(ValidationRequests::CreatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ValidationRequests::UpdatedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_validation_requests_person")
+                            .from(ValidationRequests::Table, ValidationRequests::PersonId)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_validation_requests_skill")
+                            .from(ValidationRequests::Table, ValidationRequests::SkillId)
+                            .to(CompetencySkills::Table, CompetencySkills::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_validation_requests_stage")
+                            .from(ValidationRequests::Table, ValidationRequests::StageId)
+                            .to(ValidationStages::Table, ValidationStages::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_validation_requests_requested_by")
+                            .from(ValidationRequests::Table, ValidationRequests::RequestedById)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_validation_requests_session")
+                            .from(ValidationRequests::Table, ValidationRequests::SessionId)
+                            .to(Sessions::Table, Sessions::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_validation_requests_reviewed_by")
+                            .from(ValidationRequests::Table, ValidationRequests::ReviewedById)
+                            .to(People::Table, People::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_validation_requests_status")
+                    .table(ValidationRequests::Table)
+                    .col(ValidationRequests::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_validation_requests_person_skill")
+                    .table(ValidationRequests::Table)
+                    .col(ValidationRequests::PersonId)
+                    .col(ValidationRequests::SkillId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ValidationRequests::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ValidationRequests {
+    Table,
+    Id,
+    PersonId,
+    SkillId,
+    StageId,
+    RequestedById,
+    SessionId,
+    DemonstratedAt,
+    Comment,
+    Status,
+    ReviewedById,
+    ReviewedAt,
+    ReviewComment,
+    ValidationId,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum People {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum CompetencySkills {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum ValidationStages {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Sessions {
+    Table,
+    Id,
+}
@@ -3,8 +3,9 @@ use crate::handlers::import::ImportState;
 use crate::handlers::auth::{AuthState, PasswordAuthState};
 use crate::handlers::*;
 use crate::middleware::acl::{acl_auth_middleware, AclState};
-use crate::services::{AuthService, EmailService};
+use crate::services::{AuthService, EmailService, ProgressNotificationService, Storage};
 use axum::{
+    extract::DefaultBodyLimit,
     middleware,
     routing::{get, post},
     Router,
@@ -38,6 +39,32 @@ pub fn create_app(db: DatabaseConnection, config: Config) -> Router {
         expiration_hours: config.magic_link.expiration_hours,
     });
 
+    // Competency state (validations avec détection de passage de niveau)
+    let competency_state = Arc::new(CompetencyState {
+        db: db.clone(),
+        email_service: email_service.clone(),
+        admin_emails: config.admin.emails.clone(),
+        auto_promote: config.competencies.auto_promote,
+        mentor_stall_weeks: config.notifications.mentor_stall_weeks,
+    });
+
+    // Preuves jointes aux validations (fichiers dans le stockage configuré)
+    let storage = Storage::from_config(&config.storage).unwrap_or_else(|e| panic!("{}", e));
+    let max_upload_bytes = config.storage.max_upload_mb * 1024 * 1024;
+    let evidence_state = Arc::new(EvidenceState {
+        db: db.clone(),
+        storage,
+        max_upload_bytes,
+    });
+
+    // Résumé quotidien des validations envoyé aux élèves abonnés, et alertes
+    // aux référents des élèves qui ne progressent plus
+    tokio::spawn(ProgressNotificationService::run_digest_scheduler(
+        db.clone(),
+        email_service.clone(),
+        config.notifications.clone(),
+    ));
+
     let config_arc = Arc::new(config);
 
     // ACL state for middleware
@@ -98,15 +125,19 @@ pub fn create_app(db: DatabaseConnection, config: Config) -> Router {
         ))
         .with_state(auth_state);
     
-    // Public routes - questionnaires (submit via magic link token)
+    // Public routes - questionnaires (read via magic link token)
     let questionnaire_public_routes = Router::new()
         .route(
             "/api/v1/questionnaires/by-token/:token",
             get(get_questionnaire_by_token),
         )
-        .route("/api/v1/questionnaires/submit", post(submit_questionnaire))
         .with_state(db.clone());
 
+    // Public routes - questionnaire submission (notifies the student's mentors)
+    let questionnaire_submit_routes = Router::new()
+        .route("/api/v1/questionnaires/submit", post(submit_questionnaire))
+        .with_state((db.clone(), email_service.clone()));
+
     // Authenticated routes - questionnaires (self-registration)
     let questionnaire_auth_routes = Router::new()
         .route("/api/v1/questionnaires/register", post(create_questionnaire))
@@ -114,13 +145,18 @@ pub fn create_app(db: DatabaseConnection, config: Config) -> Router {
             acl_state.clone(),
             acl_auth_middleware,
         ))
-        .with_state(db.clone());
+        .with_state((db.clone(), email_service.clone()));
 
     // Public routes - session summary by token
     let summary_public_routes = Router::new()
         .route("/api/v1/sessions/summary/:token", get(get_session_summary_by_token))
         .with_state((db.clone(), config_arc.clone()));
 
+    // Public routes - vérification des attestations de fin de niveau
+    let certificate_public_routes = Router::new()
+        .route("/api/v1/certificates/verify/:code", get(verify_completion_certificate))
+        .with_state((db.clone(), config_arc.clone()));
+
     // Admin-only routes for sessions and questionnaires
     // Uses ACL middleware which injects AuthUser with permissions
     let admin_routes = Router::new()
@@ -134,10 +170,23 @@ pub fn create_app(db: DatabaseConnection, config: Config) -> Router {
         .route("/api/v1/emails/:id/sent", post(mark_email_sent))
         .route("/api/v1/people", post(create_person).get(list_people))
         .route("/api/v1/people/:id", get(get_person).put(update_person).delete(delete_person))
-        // Legacy flat competencies (backward compatibility)
+        // Niveaux et brevets d'un plongeur
+        .route("/api/v1/people/:id/certifications", get(list_person_certifications).post(create_person_certification))
+        .route("/api/v1/certifications/:id", axum::routing::put(update_certification).delete(delete_certification))
+        // Référentiel des niveaux (codes, familles, rangs)
+        .route("/api/v1/diving-levels", get(list_diving_levels).post(create_diving_level))
+        .route("/api/v1/diving-levels/:id", axum::routing::put(update_diving_level).delete(delete_diving_level))
+        // Équivalences CMAS/PADI/SSI et brevets externes des plongeurs
+        .route("/api/v1/certification-equivalences", get(list_certification_equivalences).post(create_certification_equivalence))
+        .route("/api/v1/certification-equivalences/:id", axum::routing::put(update_certification_equivalence).delete(delete_certification_equivalence))
+        .route("/api/v1/people/:id/external-certifications", get(list_person_external_certifications).post(create_person_external_certification))
+        .route("/api/v1/external-certifications/:id", axum::routing::delete(delete_external_certification))
+        // Legacy flat competencies (read-only view derived from the hierarchy)
         .route("/api/v1/competencies", post(create_competency).get(list_competencies))
         .route("/api/v1/competencies/by-level", get(list_competencies_by_level))
         .route("/api/v1/competencies/:id", get(get_competency).put(update_competency).delete(delete_competency))
+        .route("/api/v1/competencies/migration/preview", post(preview_legacy_competency_migration))
+        .route("/api/v1/competencies/migration", post(migrate_legacy_competencies))
         // New hierarchical competency system
         // Validation stages (étapes de progression)
         .route("/api/v1/validation-stages", get(list_validation_stages).post(create_validation_stage))
@@ -151,13 +200,47 @@ pub fn create_app(db: DatabaseConnection, config: Config) -> Router {
         // Competency skills (acquis individuels)
         .route("/api/v1/competency-skills", get(list_competency_skills).post(create_competency_skill))
         .route("/api/v1/competency-skills/:id", axum::routing::put(update_competency_skill).delete(delete_competency_skill))
+        .route("/api/v1/competency-skills/:id/eligible-validators", get(list_eligible_validators))
+        // Import/export du référentiel complet d'un niveau
+        .route("/api/v1/competency-frameworks/bundles", get(list_framework_bundles))
+        .route("/api/v1/competency-frameworks/bundles/:code/import", post(import_framework_bundle))
+        .route("/api/v1/competency-frameworks/:level/export", get(export_competency_framework))
+        .route("/api/v1/competency-frameworks/:level/import", post(import_competency_framework))
         // Skill validations (progression des élèves)
-        .route("/api/v1/skill-validations", get(list_skill_validations).post(create_skill_validation))
-        .route("/api/v1/skill-validations/:id", axum::routing::put(update_skill_validation).delete(delete_skill_validation))
+        .route("/api/v1/skill-validations", get(list_skill_validations))
         .route("/api/v1/skill-validations/logs", get(get_validation_logs))
         // Hierarchy views
         .route("/api/v1/my-competencies", get(get_my_competencies))
         .route("/api/v1/person-competencies/:person_id", get(get_person_competencies))
+        .route("/api/v1/my-competencies/timeline", get(get_my_timeline))
+        .route("/api/v1/person-competencies/:person_id/timeline", get(get_person_timeline))
+        // Préférences des emails de progression (résumé quotidien, modules complétés)
+        .route("/api/v1/my-notification-preferences", get(get_my_notification_preferences).put(update_my_notification_preferences))
+        // Tableau de progression des élèves préparant un niveau (JSON, CSV ou XLSX)
+        .route("/api/v1/competency-progress/:level", get(get_cohort_progress))
+        // Examens théoriques: banque de questions, questionnaires et passages
+        .route("/api/v1/quiz-questions", get(list_quiz_questions).post(create_quiz_question))
+        .route("/api/v1/quiz-questions/:id", axum::routing::put(update_quiz_question).delete(delete_quiz_question))
+        .route("/api/v1/quizzes", get(list_quizzes).post(create_quiz))
+        .route("/api/v1/quizzes/:id", get(get_quiz).put(update_quiz).delete(delete_quiz))
+        .route("/api/v1/quizzes/:id/attempts", get(list_quiz_attempts).post(start_quiz_attempt))
+        .route("/api/v1/quiz-attempts/:id", get(get_quiz_attempt))
+        .route("/api/v1/my-quizzes", get(list_my_quizzes))
+        // Encadrants référents des élèves et tableau de bord de leurs élèves
+        .route("/api/v1/mentor-assignments", get(list_mentor_assignments).post(create_mentor_assignment))
+        .route("/api/v1/mentor-assignments/:id", axum::routing::put(update_mentor_assignment).delete(delete_mentor_assignment))
+        .route("/api/v1/my-mentees", get(get_my_mentees))
+        // Titres d'encadrement (initiateur, MF1, MF2) et recyclages
+        .route("/api/v1/teaching-qualifications", get(list_teaching_qualifications).post(create_teaching_qualification))
+        .route("/api/v1/teaching-qualifications/:id", axum::routing::put(update_teaching_qualification).delete(delete_teaching_qualification))
+        .route("/api/v1/teaching-qualifications/:id/recyclings", post(record_teaching_recycling))
+        .route("/api/v1/my-teaching-qualifications", get(get_my_teaching_qualifications))
+        // Demandes de validation d'acquis (élèves et file des validateurs)
+        .route("/api/v1/validation-requests", post(create_validation_request))
+        .route("/api/v1/validation-requests/queue", get(get_validation_request_queue))
+        .route("/api/v1/validation-requests/:id", axum::routing::delete(delete_validation_request))
+        .route("/api/v1/validation-requests/:id/reject", post(reject_validation_request))
+        .route("/api/v1/my-validation-requests", get(list_my_validation_requests))
         // Groups and permissions management
         .route("/api/v1/permissions", get(list_permissions))
         .route("/api/v1/groups", get(list_groups))
@@ -167,13 +250,24 @@ pub fn create_app(db: DatabaseConnection, config: Config) -> Router {
         .route("/api/v1/level-documents/:level", get(get_level_document).delete(delete_level_document))
         .route("/api/v1/level-documents/:level/upload", post(upload_level_document))
         .route("/api/v1/level-documents/:level/download", get(download_level_document))
+        .route("/api/v1/level-documents/:level/versions", get(list_level_document_versions))
+        .route("/api/v1/level-documents/:level/versions/:version/download", get(download_level_document_version))
+        .route("/api/v1/level-documents/:level/versions/:version/activate", post(activate_level_document_version))
+        .route("/api/v1/level-documents/:level/versions/:version/positions/copy", post(copy_level_document_positions))
         .route("/api/v1/level-documents/:level/page/:page", get(get_document_page_info))
         .route("/api/v1/level-documents/:level/positions", get(list_skill_positions).post(set_skill_position).put(batch_update_positions))
+        .route("/api/v1/level-documents/:level/positions/detect", get(detect_skill_positions))
+        .route("/api/v1/level-documents/:level/fields", get(list_template_fields).put(set_template_fields))
         .route("/api/v1/level-documents/:level/positions/:skill_id", axum::routing::delete(delete_skill_position))
+        .route("/api/v1/level-documents/:level/generate", post(generate_filled_documents_batch))
         .route("/api/v1/level-documents/:level/generate/:person_id", get(generate_filled_document))
         // Palanquées et rotations
         .route("/api/v1/sessions/:session_id/palanquees", get(get_session_palanquees))
         .route("/api/v1/sessions/:session_id/fiche-securite", get(download_fiche_securite))
+        // Plan de formation par session (acquis à travailler, acquis épinglés)
+        .route("/api/v1/sessions/:session_id/training-plan", get(get_session_training_plan))
+        .route("/api/v1/sessions/:session_id/training-plan/pins", post(pin_session_skill))
+        .route("/api/v1/sessions/:session_id/training-plan/pins/:pin_id", axum::routing::delete(unpin_session_skill))
         .route("/api/v1/rotations", post(create_rotation))
         .route("/api/v1/sessions/:session_id/rotations", get(list_rotations))
         .route("/api/v1/rotations/:id", axum::routing::delete(delete_rotation))
@@ -199,12 +293,54 @@ pub fn create_app(db: DatabaseConnection, config: Config) -> Router {
     let admin_detail_routes = Router::new()
         .route("/api/v1/questionnaires-detail", get(list_questionnaires_detail))
         .route("/api/v1/sessions/:id/summary", get(get_session_summary))
+        // Attestation de fin de niveau (PDF avec code de vérification)
+        .route("/api/v1/people/:id/certificates/:level", get(download_completion_certificate))
         .layer(middleware::from_fn_with_state(
             acl_state.clone(),
             acl_auth_middleware,
         ))
         .with_state((db.clone(), config_arc));
     
+    // Competency routes with side effects (level promotions, notifications)
+    let competency_routes = Router::new()
+        .route("/api/v1/skill-validations", post(create_skill_validation))
+        .route("/api/v1/skill-validations/bulk", post(bulk_create_skill_validations))
+        .route("/api/v1/skill-validations/:id", axum::routing::put(update_skill_validation).delete(delete_skill_validation))
+        .route("/api/v1/notifications/progress-digests/send", post(send_progress_digests))
+        .route("/api/v1/notifications/mentor-alerts/send", post(send_mentor_stall_alerts))
+        // Passages de questionnaire pouvant valider un acquis
+        .route("/api/v1/quiz-attempts/:id/submit", post(submit_quiz_attempt))
+        .route("/api/v1/quizzes/:id/results", post(record_quiz_result))
+        .route("/api/v1/validation-requests/:id/approve", post(approve_validation_request))
+        .route("/api/v1/level-promotions", get(list_level_promotions))
+        .route("/api/v1/level-promotions/detect", post(detect_level_promotions))
+        .route("/api/v1/level-promotions/:id/apply", post(apply_level_promotion))
+        .route("/api/v1/level-promotions/:id/reject", post(reject_level_promotion))
+        .layer(middleware::from_fn_with_state(
+            acl_state.clone(),
+            acl_auth_middleware,
+        ))
+        .with_state(competency_state);
+
+    // Preuves et commentaires des validations (élève concerné et encadrants)
+    let evidence_routes = Router::new()
+        .route(
+            "/api/v1/skill-validations/:id/evidence",
+            get(list_validation_evidence)
+                .post(upload_validation_evidence)
+                // Marge pour l'enveloppe multipart autour du fichier
+                .layer(DefaultBodyLimit::max(max_upload_bytes + 1024 * 1024)),
+        )
+        .route("/api/v1/skill-validations/:id/evidence/:evidence_id", axum::routing::delete(delete_validation_evidence))
+        .route("/api/v1/skill-validations/:id/evidence/:evidence_id/download", get(download_validation_evidence))
+        .route("/api/v1/skill-validations/:id/comments", get(list_validation_comments).post(create_validation_comment))
+        .route("/api/v1/skill-validation-comments/:id", axum::routing::put(update_validation_comment).delete(delete_validation_comment))
+        .layer(middleware::from_fn_with_state(
+            acl_state.clone(),
+            acl_auth_middleware,
+        ))
+        .with_state(evidence_state);
+
     // Admin-only routes for import (different state)
     let import_routes = Router::new()
         .route("/api/v1/import", post(import_csv))
@@ -232,10 +368,14 @@ pub fn create_app(db: DatabaseConnection, config: Config) -> Router {
         .merge(change_password_route)
         .merge(impersonation_routes)
         .merge(questionnaire_public_routes)
+        .merge(questionnaire_submit_routes)
         .merge(questionnaire_auth_routes)
         .merge(summary_public_routes)
+        .merge(certificate_public_routes)
         .merge(admin_routes)
         .merge(admin_detail_routes)
+        .merge(competency_routes)
+        .merge(evidence_routes)
         .merge(import_routes)
         .merge(email_service_routes);
 
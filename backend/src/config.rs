@@ -10,6 +10,14 @@ pub struct Config {
     pub jwt: JwtConfig,
     pub magic_link: MagicLinkConfig,
     pub smtp: SmtpConfig,
+    #[serde(default)]
+    pub competencies: CompetenciesConfig,
+    #[serde(default)]
+    pub club: ClubConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,6 +65,94 @@ pub struct SmtpConfig {
     pub from_name: String,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompetenciesConfig {
+    /// Applique directement le passage de niveau quand tous les acquis sont validés
+    /// (sinon la promotion est seulement proposée aux admins)
+    #[serde(default)]
+    pub auto_promote: bool,
+}
+
+/// Identité du club imprimée sur les documents délivrés (attestations)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClubConfig {
+    #[serde(default)]
+    pub name: String,
+    /// Numéro d'affiliation FFESSM
+    #[serde(default)]
+    pub affiliation: Option<String>,
+    #[serde(default)]
+    pub city: Option<String>,
+    /// Logo JPEG affiché en tête des attestations
+    #[serde(default)]
+    pub logo_path: Option<String>,
+}
+
+/// Stockage des fichiers joints aux validations (photos, vidéos, carnets)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageConfig {
+    /// Backend de stockage: seul "local" (système de fichiers) est disponible
+    #[serde(default = "default_storage_backend")]
+    pub backend: String,
+    /// Répertoire racine pour le backend local
+    #[serde(default = "default_storage_path")]
+    pub path: String,
+    /// Taille maximale d'un fichier envoyé, en Mo
+    #[serde(default = "default_max_upload_mb")]
+    pub max_upload_mb: usize,
+}
+
+fn default_storage_backend() -> String {
+    "local".to_string()
+}
+
+fn default_storage_path() -> String {
+    "./data/storage".to_string()
+}
+
+fn default_max_upload_mb() -> usize {
+    50
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            backend: default_storage_backend(),
+            path: default_storage_path(),
+            max_upload_mb: default_max_upload_mb(),
+        }
+    }
+}
+
+/// Notifications de progression envoyées aux élèves et à leurs référents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationsConfig {
+    /// Heure d'envoi du résumé quotidien des validations (UTC, 0-23)
+    #[serde(default = "default_digest_hour")]
+    pub digest_hour: u32,
+    /// Semaines sans progression sur un module commencé avant d'alerter le
+    /// référent de l'élève
+    #[serde(default = "default_mentor_stall_weeks")]
+    pub mentor_stall_weeks: i64,
+}
+
+fn default_digest_hour() -> u32 {
+    18
+}
+
+fn default_mentor_stall_weeks() -> i64 {
+    4
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            digest_hour: default_digest_hour(),
+            mentor_stall_weeks: default_mentor_stall_weeks(),
+        }
+    }
+}
+
 impl Config {
     pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
         let contents = fs::read_to_string(path)?;
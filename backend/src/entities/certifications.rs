@@ -0,0 +1,47 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Statuts d'un niveau
+pub const STATUS_OBTAINED: &str = "obtained";
+pub const STATUS_PREPARING: &str = "preparing";
+
+/// Origine d'un brevet
+pub const SOURCE_INTERNAL: &str = "internal";
+pub const SOURCE_EXTERNAL: &str = "external";
+
+/// Niveau ou brevet détenu (ou préparé) par un plongeur
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "certifications")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub person_id: Uuid,
+    pub level: String,
+    pub status: String, // obtained, preparing
+    pub obtained_at: Option<Date>,
+    /// Moniteur ou club ayant délivré le brevet
+    pub issued_by: Option<String>,
+    pub issued_by_id: Option<Uuid>,
+    pub certificate_number: Option<String>,
+    pub source: String, // internal, external
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::people::Entity",
+        from = "Column::PersonId",
+        to = "super::people::Column::Id"
+    )]
+    Person,
+}
+
+impl Related<super::people::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Person.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
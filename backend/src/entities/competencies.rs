@@ -10,6 +10,9 @@ pub struct Model {
     pub name: String,
     pub description: Option<String>,
     pub sort_order: i32,
+    /// Acquis de la hiérarchie issu de la migration (None tant que non migrée)
+    pub skill_id: Option<Uuid>,
+    pub migrated_at: Option<DateTime>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
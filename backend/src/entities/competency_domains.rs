@@ -9,6 +9,9 @@ pub struct Model {
     pub diving_level: String,
     pub name: String,
     pub sort_order: i32,
+    /// Durée de validité par défaut des validations des acquis du domaine,
+    /// en mois (None: validité illimitée)
+    pub validity_months: Option<i32>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
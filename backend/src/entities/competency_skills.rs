@@ -13,6 +13,12 @@ pub struct Model {
     pub sort_order: i32,
     /// Niveau minimum requis pour valider cette compétence (ex: "E2", "E3", "N4")
     pub min_validator_level: String,
+    /// Règle d'éligibilité des validateurs (ex: "E2 OR (N4 AND E1)"),
+    /// prioritaire sur min_validator_level
+    pub validator_rule: Option<String>,
+    /// Durée de validité d'une validation en mois, prioritaire sur celle du
+    /// domaine (None: celle du domaine)
+    pub validity_months: Option<i32>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
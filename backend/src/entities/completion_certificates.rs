@@ -0,0 +1,48 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Attestation de fin de niveau délivrée à un plongeur
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "completion_certificates")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub person_id: Uuid,
+    pub level: String,
+    /// Code imprimé sur l'attestation (XXXX-XXXX-XXXX)
+    #[sea_orm(unique)]
+    pub verification_code: String,
+    /// Nom du plongeur à l'émission
+    pub person_name: String,
+    /// Date de la dernière validation finale du niveau
+    pub completed_at: Date,
+    /// Noms des encadrants validateurs à l'émission (tableau JSON)
+    pub validator_names: Json,
+    pub issued_at: DateTime,
+    pub issued_by_id: Option<Uuid>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::people::Entity",
+        from = "Column::PersonId",
+        to = "super::people::Column::Id"
+    )]
+    Person,
+}
+
+impl Related<super::people::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Person.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// Retourne les noms des encadrants validateurs
+    pub fn validators(&self) -> Vec<String> {
+        serde_json::from_value(self.validator_names.clone()).unwrap_or_default()
+    }
+}
@@ -0,0 +1,34 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Familles de niveaux
+pub const FAMILY_DIVER: &str = "plongeur";
+pub const FAMILY_INSTRUCTOR: &str = "encadrant";
+pub const FAMILY_COMPETENCY: &str = "competence";
+pub const FAMILY_SPECIALTY: &str = "specialite";
+
+/// Niveau, brevet ou qualification connu du club
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "diving_levels")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(unique)]
+    pub code: String,
+    pub name: String,
+    pub family: String, // plongeur, encadrant, competence, specialite
+    /// Plus le rang est élevé, plus le niveau est haut
+    pub rank: i32,
+    /// Niveau auquel se rattache une compétence intermédiaire (ex: PE40 -> N2)
+    pub parent_code: Option<String>,
+    pub is_instructor: bool,
+    /// Périodicité du recyclage d'un titre d'encadrement (None: aucune)
+    pub recycling_months: Option<i32>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,33 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Document de compétences généré et version du modèle utilisée
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "level_document_generations")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub person_id: Uuid,
+    pub level: String,
+    pub version_id: Uuid,
+    pub version: i32,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::level_document_versions::Entity",
+        from = "Column::VersionId",
+        to = "super::level_document_versions::Column::Id"
+    )]
+    Version,
+}
+
+impl Related<super::level_document_versions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Version.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
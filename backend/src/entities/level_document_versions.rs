@@ -0,0 +1,64 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Version d'un modèle PDF de niveau, avec ses propres positions d'acquis
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "level_document_versions")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub level: String,
+    pub version: i32,
+    pub file_name: String,
+    #[serde(skip_serializing)]
+    pub file_data: Vec<u8>,
+    pub page_count: i32,
+    pub notes: Option<String>,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::skill_document_positions::Entity")]
+    Positions,
+}
+
+impl Related<super::skill_document_positions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Positions.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+// DTO pour la liste des versions
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LevelDocumentVersionInfo {
+    pub id: Uuid,
+    pub level: String,
+    pub version: i32,
+    pub file_name: String,
+    pub page_count: i32,
+    pub notes: Option<String>,
+    pub is_current: bool,
+    pub positions_count: u64,
+    /// Nombre de documents générés avec cette version
+    pub generated_count: u64,
+    pub created_at: DateTime,
+}
+
+// DTO pour recopier les positions d'une version vers une autre
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CopyPositionsRequest {
+    pub from_version: i32,
+    /// Remplace les positions déjà placées sur la version cible
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CopyPositionsResult {
+    pub copied: u64,
+    /// Positions et champs ignorés (absents de la version cible ou déjà placés)
+    pub skipped: u64,
+}
@@ -1,6 +1,9 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use super::level_document_versions;
+
+/// Modèle PDF d'un niveau: pointe vers la version active
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "level_documents")]
 pub struct Model {
@@ -8,16 +11,26 @@ pub struct Model {
     pub id: Uuid,
     #[sea_orm(unique)]
     pub level: String,
-    pub file_name: String,
-    #[serde(skip_serializing)]
-    pub file_data: Vec<u8>,
-    pub page_count: i32,
+    pub current_version_id: Option<Uuid>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::level_document_versions::Entity",
+        from = "Column::CurrentVersionId",
+        to = "super::level_document_versions::Column::Id"
+    )]
+    CurrentVersion,
+}
+
+impl Related<super::level_document_versions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CurrentVersion.def()
+    }
+}
 
 impl ActiveModelBehavior for ActiveModel {}
 
@@ -28,20 +41,59 @@ pub struct LevelDocumentInfo {
     pub level: String,
     pub file_name: String,
     pub page_count: i32,
+    /// Numéro de la version active
+    pub version: i32,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
 
-impl From<Model> for LevelDocumentInfo {
-    fn from(model: Model) -> Self {
+impl LevelDocumentInfo {
+    pub fn new(doc: Model, version: &level_document_versions::Model) -> Self {
         Self {
-            id: model.id,
-            level: model.level,
-            file_name: model.file_name,
-            page_count: model.page_count,
-            created_at: model.created_at,
-            updated_at: model.updated_at,
+            id: doc.id,
+            level: doc.level,
+            file_name: version.file_name.clone(),
+            page_count: version.page_count,
+            version: version.version,
+            created_at: doc.created_at,
+            updated_at: doc.updated_at,
         }
     }
 }
 
+/// Format de sortie d'une génération groupée
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchFormat {
+    /// Archive ZIP d'un PDF par personne
+    #[default]
+    Zip,
+    /// PDF unique avec un signet par personne
+    Pdf,
+}
+
+// Requête de génération des livrets de plusieurs personnes
+#[derive(Clone, Debug, Deserialize)]
+pub struct BatchGenerateRequest {
+    #[serde(default)]
+    pub person_ids: Vec<Uuid>,
+    /// Ajoute toutes les personnes qui préparent le niveau
+    #[serde(default)]
+    pub all_preparing: bool,
+    #[serde(default)]
+    pub format: BatchFormat,
+    /// Aplatit les champs de formulaire remplis
+    #[serde(default = "default_flatten")]
+    pub flatten: bool,
+}
+
+// Options de génération d'un document
+#[derive(Clone, Debug, Deserialize)]
+pub struct GenerateQuery {
+    #[serde(default = "default_flatten")]
+    pub flatten: bool,
+}
+
+fn default_flatten() -> bool {
+    true
+}
@@ -0,0 +1,50 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Statuts possibles d'une promotion de niveau
+pub const STATUS_PROPOSED: &str = "proposed";
+pub const STATUS_APPLIED: &str = "applied";
+pub const STATUS_REJECTED: &str = "rejected";
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "level_promotions")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub person_id: Uuid,
+    pub level: String,
+    pub status: String, // proposed, applied, rejected
+    /// Date de la dernière validation finale qui complète le niveau
+    pub completed_at: Date,
+    /// Encadrants ayant validé les acquis du niveau (tableau JSON d'UUID)
+    pub validated_by_ids: Json,
+    pub promoted_at: Option<DateTime>,
+    pub promoted_by_id: Option<Uuid>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::people::Entity",
+        from = "Column::PersonId",
+        to = "super::people::Column::Id"
+    )]
+    Person,
+}
+
+impl Related<super::people::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Person.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// Retourne les UUID des encadrants validateurs
+    pub fn validator_ids(&self) -> Vec<Uuid> {
+        serde_json::from_value(self.validated_by_ids.clone()).unwrap_or_default()
+    }
+}
@@ -0,0 +1,35 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Encadrant référent d'un élève pour un niveau
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "mentor_assignments")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub student_id: Uuid,
+    pub mentor_id: Uuid,
+    pub diving_level: String,
+    /// Dernière alerte de stagnation envoyée au référent
+    pub stall_notified_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::people::Entity",
+        from = "Column::StudentId",
+        to = "super::people::Column::Id"
+    )]
+    Student,
+    #[sea_orm(
+        belongs_to = "super::people::Entity",
+        from = "Column::MentorId",
+        to = "super::people::Column::Id"
+    )]
+    Mentor,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
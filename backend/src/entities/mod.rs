@@ -14,9 +14,31 @@ pub mod competency_skills;
 pub mod skill_validations;
 pub mod level_documents;
 pub mod skill_document_positions;
+pub mod level_document_versions;
+pub mod level_document_generations;
+pub mod skill_document_fields;
 pub mod rotations;
 pub mod palanquees;
 pub mod palanquee_members;
 pub mod sorties;
 pub mod dive_directors;
+pub mod level_promotions;
+pub mod skill_validation_history;
+pub mod session_skill_plans;
 
+pub mod certifications;
+pub mod diving_levels;
+pub mod certification_equivalences;
+pub mod external_certifications;
+pub mod completion_certificates;
+pub mod skill_validation_evidence;
+pub mod skill_validation_comments;
+pub mod notification_preferences;
+pub mod progress_milestones;
+pub mod quiz_questions;
+pub mod quizzes;
+pub mod quiz_attempts;
+pub mod mentor_assignments;
+pub mod teaching_qualifications;
+pub mod teaching_recyclings;
+pub mod validation_requests;
@@ -17,7 +17,6 @@ pub struct Model {
     pub default_wants_2nd_reg: bool,
     pub default_wants_stab: bool,
     pub default_stab_size: Option<String>,
-    pub diving_level: Option<String>,
     pub group_id: Option<Uuid>,
     #[serde(skip_serializing)]
     pub password_hash: Option<String>,
@@ -16,4 +16,23 @@ pub use super::palanquees::Entity as Palanquees;
 pub use super::palanquee_members::Entity as PalanqueeMembers;
 pub use super::sorties::Entity as Sorties;
 pub use super::dive_directors::Entity as DiveDirectors;
+pub use super::level_promotions::Entity as LevelPromotions;
+pub use super::skill_validation_history::Entity as SkillValidationHistory;
+pub use super::session_skill_plans::Entity as SessionSkillPlans;
 
+pub use super::certifications::Entity as Certifications;
+pub use super::diving_levels::Entity as DivingLevels;
+pub use super::certification_equivalences::Entity as CertificationEquivalences;
+pub use super::external_certifications::Entity as ExternalCertifications;
+pub use super::completion_certificates::Entity as CompletionCertificates;
+pub use super::skill_validation_evidence::Entity as SkillValidationEvidence;
+pub use super::skill_validation_comments::Entity as SkillValidationComments;
+pub use super::notification_preferences::Entity as NotificationPreferences;
+pub use super::progress_milestones::Entity as ProgressMilestones;
+pub use super::quiz_questions::Entity as QuizQuestions;
+pub use super::quizzes::Entity as Quizzes;
+pub use super::quiz_attempts::Entity as QuizAttempts;
+pub use super::mentor_assignments::Entity as MentorAssignments;
+pub use super::teaching_qualifications::Entity as TeachingQualifications;
+pub use super::teaching_recyclings::Entity as TeachingRecyclings;
+pub use super::validation_requests::Entity as ValidationRequests;
@@ -0,0 +1,63 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Passage d'un questionnaire par un élève
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "quiz_attempts")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub quiz_id: Uuid,
+    pub person_id: Uuid,
+    /// Questions tirées, dans l'ordre présenté (tableau JSON d'IDs)
+    pub question_ids: Json,
+    /// Réponses envoyées: { question_id: [indices] }
+    pub answers: Option<Json>,
+    pub score: Option<i32>,
+    pub max_score: i32,
+    pub percentage: Option<f32>,
+    pub passed: Option<bool>,
+    pub started_at: DateTime,
+    /// None tant que le passage est en cours
+    pub submitted_at: Option<DateTime>,
+    /// Résultat saisi par un encadrant (examen sur papier)
+    pub recorded_by_id: Option<Uuid>,
+    /// Validation d'acquis créée par la réussite
+    pub validation_id: Option<Uuid>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::quizzes::Entity",
+        from = "Column::QuizId",
+        to = "super::quizzes::Column::Id"
+    )]
+    Quiz,
+    #[sea_orm(
+        belongs_to = "super::people::Entity",
+        from = "Column::PersonId",
+        to = "super::people::Column::Id"
+    )]
+    Person,
+}
+
+impl Related<super::quizzes::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Quiz.def()
+    }
+}
+
+impl Related<super::people::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Person.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn question_list(&self) -> Vec<Uuid> {
+        serde_json::from_value(self.question_ids.clone()).unwrap_or_default()
+    }
+}
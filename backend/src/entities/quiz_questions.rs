@@ -0,0 +1,51 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Question théorique (QCM) de la banque, classée par niveau et domaine
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "quiz_questions")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub diving_level: String,
+    pub domain_id: Option<Uuid>,
+    pub question: String,
+    /// Propositions (tableau JSON de textes)
+    pub choices: Json,
+    /// Indices des bonnes propositions (tableau JSON)
+    pub correct_choices: Json,
+    pub explanation: Option<String>,
+    pub points: i32,
+    /// Les questions retirées ne sont plus tirées dans les questionnaires
+    pub is_active: bool,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::competency_domains::Entity",
+        from = "Column::DomainId",
+        to = "super::competency_domains::Column::Id"
+    )]
+    Domain,
+}
+
+impl Related<super::competency_domains::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Domain.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn choice_list(&self) -> Vec<String> {
+        serde_json::from_value(self.choices.clone()).unwrap_or_default()
+    }
+
+    pub fn correct_list(&self) -> Vec<usize> {
+        serde_json::from_value(self.correct_choices.clone()).unwrap_or_default()
+    }
+}
@@ -0,0 +1,44 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Questionnaire en ligne (entraînement ou examen théorique d'un niveau)
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "quizzes")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub diving_level: String,
+    /// Limite le tirage aux questions d'un domaine
+    pub domain_id: Option<Uuid>,
+    /// Nombre de questions tirées au sort (toutes si None)
+    pub question_count: Option<i32>,
+    pub pass_percentage: i32,
+    pub time_limit_minutes: Option<i32>,
+    pub max_attempts: Option<i32>,
+    /// Montre les bonnes réponses après l'envoi
+    pub show_corrections: bool,
+    /// Acquis validé (à l'étape `stage_id`) quand le questionnaire est réussi
+    pub skill_id: Option<Uuid>,
+    pub stage_id: Option<Uuid>,
+    pub is_published: bool,
+    /// Encadrant responsable: validateur des acquis obtenus
+    pub created_by_id: Option<Uuid>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::quiz_attempts::Entity")]
+    Attempts,
+}
+
+impl Related<super::quiz_attempts::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Attempts.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
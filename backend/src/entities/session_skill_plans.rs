@@ -0,0 +1,49 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Acquis épinglé au plan de formation d'un élève pour une session
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "session_skill_plans")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub person_id: Uuid,
+    pub skill_id: Uuid,
+    pub palanquee_id: Option<Uuid>,
+    pub target_stage_id: Option<Uuid>,
+    pub encadrant_id: Option<Uuid>,
+    pub planned_by_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::sessions::Entity",
+        from = "Column::SessionId",
+        to = "super::sessions::Column::Id"
+    )]
+    Session,
+    #[sea_orm(
+        belongs_to = "super::competency_skills::Entity",
+        from = "Column::SkillId",
+        to = "super::competency_skills::Column::Id"
+    )]
+    Skill,
+}
+
+impl Related<super::sessions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Session.def()
+    }
+}
+
+impl Related<super::competency_skills::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Skill.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,83 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Contenu écrit dans un champ de formulaire
+pub const CONTENT_DATE: &str = "date";
+pub const CONTENT_VALIDATOR: &str = "validator";
+pub const CONTENT_LEVEL: &str = "level";
+/// "date - validateur (niveau)" pour les modèles à un seul champ par acquis
+pub const CONTENT_SUMMARY: &str = "summary";
+pub const CONTENTS: [&str; 4] = [CONTENT_DATE, CONTENT_VALIDATOR, CONTENT_LEVEL, CONTENT_SUMMARY];
+
+/// Champ AcroForm d'une version du modèle associé à un acquis
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "skill_document_fields")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub version_id: Uuid,
+    pub skill_id: Uuid,
+    pub level: String,
+    /// Nom complet du champ (noms des parents séparés par des points)
+    pub field_name: String,
+    pub content: String, // date, validator, level, summary
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::competency_skills::Entity",
+        from = "Column::SkillId",
+        to = "super::competency_skills::Column::Id"
+    )]
+    CompetencySkill,
+    #[sea_orm(
+        belongs_to = "super::level_document_versions::Entity",
+        from = "Column::VersionId",
+        to = "super::level_document_versions::Column::Id"
+    )]
+    Version,
+}
+
+impl Related<super::competency_skills::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CompetencySkill.def()
+    }
+}
+
+impl Related<super::level_document_versions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Version.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+// DTO pour l'association d'un champ à un acquis
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SkillFieldInput {
+    pub field_name: String,
+    pub skill_id: Uuid,
+    #[serde(default = "default_content")]
+    pub content: String,
+}
+
+fn default_content() -> String {
+    CONTENT_SUMMARY.to_string()
+}
+
+// Champ du modèle et acquis éventuellement associé
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TemplateFieldInfo {
+    pub field_name: String,
+    /// Type AcroForm (Tx, Btn, Ch, Sig)
+    pub field_type: Option<String>,
+    pub page: Option<i32>,
+    /// [x1, y1, x2, y2] du premier widget
+    pub rect: Option<[f32; 4]>,
+    pub skill_id: Option<Uuid>,
+    pub skill_name: Option<String>,
+    pub content: Option<String>,
+}
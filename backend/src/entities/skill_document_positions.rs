@@ -8,6 +8,8 @@ pub struct Model {
     pub id: Uuid,
     pub skill_id: Uuid,
     pub level: String,
+    /// Version du modèle PDF sur laquelle la position a été placée
+    pub version_id: Uuid,
     pub page: i32,
     pub x: f32,
     pub y: f32,
@@ -26,6 +28,12 @@ pub enum Relation {
         to = "super::competency_skills::Column::Id"
     )]
     CompetencySkill,
+    #[sea_orm(
+        belongs_to = "super::level_document_versions::Entity",
+        from = "Column::VersionId",
+        to = "super::level_document_versions::Column::Id"
+    )]
+    Version,
 }
 
 impl Related<super::competency_skills::Entity> for Entity {
@@ -34,6 +42,12 @@ impl Related<super::competency_skills::Entity> for Entity {
     }
 }
 
+impl Related<super::level_document_versions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Version.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
 
 // DTO pour la création/mise à jour
@@ -70,3 +84,42 @@ pub struct SkillPositionWithInfo {
     pub font_size: f32,
 }
 
+
+// Position proposée par la détection automatique, à vérifier avant
+// enregistrement via batch_update_positions
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DetectedSkillPosition {
+    pub skill_id: Uuid,
+    pub skill_name: String,
+    pub skill_number: i32,
+    pub module_name: String,
+    pub domain_name: String,
+    pub page: i32,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub font_size: f32,
+    /// Texte du document reconnu comme le libellé de l'acquis
+    pub matched_text: String,
+    /// Entre 0 et 1 (1 = libellé trouvé tel quel sur une ligne)
+    pub confidence: f32,
+    /// L'acquis a déjà une position sur cette version
+    pub already_placed: bool,
+}
+
+// Acquis du niveau dont le libellé n'a pas été trouvé dans le document
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UndetectedSkill {
+    pub skill_id: Uuid,
+    pub skill_name: String,
+    pub module_name: String,
+    pub domain_name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PositionDetectionResult {
+    pub version: i32,
+    pub suggestions: Vec<DetectedSkillPosition>,
+    pub unmatched: Vec<UndetectedSkill>,
+}
@@ -0,0 +1,44 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Commentaire sur une validation d'acquis, lisible par l'élève
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "skill_validation_comments")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub validation_id: Uuid,
+    /// Élève concerné
+    pub person_id: Uuid,
+    pub skill_id: Uuid,
+    /// Commentaire auquel celui-ci répond (fil de discussion)
+    pub parent_id: Option<Uuid>,
+    pub author_id: Option<Uuid>,
+    pub body: String,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::people::Entity",
+        from = "Column::PersonId",
+        to = "super::people::Column::Id"
+    )]
+    Person,
+    #[sea_orm(
+        belongs_to = "super::competency_skills::Entity",
+        from = "Column::SkillId",
+        to = "super::competency_skills::Column::Id"
+    )]
+    Skill,
+}
+
+impl Related<super::competency_skills::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Skill.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
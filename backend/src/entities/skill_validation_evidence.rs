@@ -0,0 +1,54 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Types de preuve
+pub const KIND_FILE: &str = "file";
+pub const KIND_DIVE_LOG: &str = "dive_log";
+
+/// Preuve jointe à une validation d'acquis (photo, vidéo, référence de carnet)
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "skill_validation_evidence")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    /// Validation concernée (conservée même si la validation est supprimée)
+    pub validation_id: Uuid,
+    /// Élève concerné: seul lui et les encadrants voient la preuve
+    pub person_id: Uuid,
+    pub skill_id: Uuid,
+    pub kind: String, // file, dive_log
+    pub file_name: Option<String>,
+    pub content_type: Option<String>,
+    pub size_bytes: Option<i64>,
+    /// Clé du fichier dans le stockage
+    pub storage_key: Option<String>,
+    /// Référence de carnet de plongée (ex: numéro de plongée)
+    pub reference: Option<String>,
+    pub caption: Option<String>,
+    pub uploaded_by_id: Option<Uuid>,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::people::Entity",
+        from = "Column::PersonId",
+        to = "super::people::Column::Id"
+    )]
+    Person,
+    #[sea_orm(
+        belongs_to = "super::competency_skills::Entity",
+        from = "Column::SkillId",
+        to = "super::competency_skills::Column::Id"
+    )]
+    Skill,
+}
+
+impl Related<super::competency_skills::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Skill.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,55 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Types de transition enregistrés dans l'historique
+pub const ACTION_CREATED: &str = "created";
+pub const ACTION_ADVANCED: &str = "advanced";
+pub const ACTION_UPDATED: &str = "updated";
+pub const ACTION_ROLLBACK: &str = "rollback";
+pub const ACTION_DELETED: &str = "deleted";
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "skill_validation_history")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    /// Validation concernée (conservée même si la validation est supprimée)
+    pub validation_id: Option<Uuid>,
+    pub person_id: Uuid,
+    pub skill_id: Uuid,
+    pub action: String, // created, advanced, updated, rollback, deleted
+    pub from_stage_id: Option<Uuid>,
+    pub to_stage_id: Option<Uuid>,
+    pub validated_at: Option<Date>,
+    pub validated_by_id: Option<Uuid>,
+    /// Auteur de l'action (peut différer du validateur, ex: admin)
+    pub performed_by_id: Option<Uuid>,
+    pub notes: Option<String>,
+    pub session_id: Option<Uuid>,
+    pub palanquee_id: Option<Uuid>,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::people::Entity",
+        from = "Column::PersonId",
+        to = "super::people::Column::Id"
+    )]
+    Person,
+    #[sea_orm(
+        belongs_to = "super::competency_skills::Entity",
+        from = "Column::SkillId",
+        to = "super::competency_skills::Column::Id"
+    )]
+    Skill,
+}
+
+impl Related<super::competency_skills::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Skill.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
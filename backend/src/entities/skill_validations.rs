@@ -12,6 +12,9 @@ pub struct Model {
     pub validated_at: Date,
     pub validated_by_id: Uuid,
     pub notes: Option<String>,
+    /// Session d'où provient la validation (validation groupée après plongée)
+    pub session_id: Option<Uuid>,
+    pub palanquee_id: Option<Uuid>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
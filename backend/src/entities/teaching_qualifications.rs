@@ -0,0 +1,46 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Titre d'encadrement détenu par un plongeur (initiateur, MF1, MF2...)
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "teaching_qualifications")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub person_id: Uuid,
+    /// Niveau d'encadrement du référentiel (E1, E2, E3, E4...)
+    pub level: String,
+    pub obtained_at: Date,
+    /// Fin de validité faute de recyclage (None: illimitée)
+    pub expires_at: Option<Date>,
+    pub certificate_number: Option<String>,
+    pub notes: Option<String>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::people::Entity",
+        from = "Column::PersonId",
+        to = "super::people::Column::Id"
+    )]
+    Person,
+    #[sea_orm(has_many = "super::teaching_recyclings::Entity")]
+    Recyclings,
+}
+
+impl Related<super::people::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Person.def()
+    }
+}
+
+impl Related<super::teaching_recyclings::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Recyclings.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,33 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Recyclage d'un titre d'encadrement
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "teaching_recyclings")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub qualification_id: Uuid,
+    pub recycled_at: Date,
+    pub notes: Option<String>,
+    pub recorded_by_id: Option<Uuid>,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::teaching_qualifications::Entity",
+        from = "Column::QualificationId",
+        to = "super::teaching_qualifications::Column::Id"
+    )]
+    Qualification,
+}
+
+impl Related<super::teaching_qualifications::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Qualification.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
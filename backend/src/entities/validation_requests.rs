@@ -0,0 +1,56 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Statuts d'une demande de validation
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_APPROVED: &str = "approved";
+pub const STATUS_REJECTED: &str = "rejected";
+
+/// Demande de validation d'un acquis, en attente d'un validateur habilité
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "validation_requests")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub person_id: Uuid,
+    pub skill_id: Uuid,
+    /// Étape demandée (None: étape finale)
+    pub stage_id: Option<Uuid>,
+    /// L'élève lui-même ou un encadrant pour lui
+    pub requested_by_id: Option<Uuid>,
+    pub session_id: Option<Uuid>,
+    pub demonstrated_at: Option<Date>,
+    pub comment: Option<String>,
+    pub status: String, // pending, approved, rejected
+    pub reviewed_by_id: Option<Uuid>,
+    pub reviewed_at: Option<DateTime>,
+    pub review_comment: Option<String>,
+    /// Validation créée à l'acceptation
+    pub validation_id: Option<Uuid>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::people::Entity",
+        from = "Column::PersonId",
+        to = "super::people::Column::Id"
+    )]
+    Person,
+    #[sea_orm(
+        belongs_to = "super::competency_skills::Entity",
+        from = "Column::SkillId",
+        to = "super::competency_skills::Column::Id"
+    )]
+    Skill,
+}
+
+impl Related<super::competency_skills::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Skill.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
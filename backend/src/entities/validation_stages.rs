@@ -14,6 +14,14 @@ pub struct Model {
     pub icon: String,
     pub sort_order: i32,
     pub is_final: bool,
+    /// Étapes accessibles depuis celle-ci (tableau JSON d'ids). NULL: toute
+    /// étape d'ordre supérieur ou égal.
+    pub allowed_next_stage_ids: Option<Json>,
+    /// Règle que doit satisfaire le validateur pour placer un acquis à cette
+    /// étape (ex: "E3"), en plus de la règle de l'acquis
+    pub validator_rule: Option<String>,
+    /// Un élève peut placer lui-même ses acquis à cette étape (auto-évaluation)
+    pub self_assessable: bool,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
@@ -32,3 +40,12 @@ impl Related<super::skill_validations::Entity> for Entity {
 
 impl ActiveModelBehavior for ActiveModel {}
 
+impl Model {
+    /// Étapes suivantes autorisées, `None` si la progression est libre
+    pub fn allowed_next(&self) -> Option<Vec<Uuid>> {
+        self.allowed_next_stage_ids
+            .clone()
+            .map(|ids| serde_json::from_value(ids).unwrap_or_default())
+    }
+}
+
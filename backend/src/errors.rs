@@ -35,6 +35,9 @@ pub enum AppError {
     #[error("Token already consumed")]
     TokenConsumed,
 
+    #[error("Gone: {0}")]
+    Gone(String),
+
     #[allow(dead_code)]
     #[error("Email sending failed: {0}")]
     EmailError(String),
@@ -65,6 +68,7 @@ impl IntoResponse for AppError {
             }
             AppError::InvalidToken => (StatusCode::UNAUTHORIZED, "Token expired or invalid"),
             AppError::TokenConsumed => (StatusCode::GONE, "Token already consumed"),
+            AppError::Gone(ref msg) => (StatusCode::GONE, msg.as_str()),
             AppError::EmailError(ref msg) => {
                 tracing::error!("Email error: {}", msg);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Email sending failed")
@@ -1,5 +1,5 @@
 use crate::entities::prelude::*;
-use crate::entities::people;
+use crate::entities::{certifications, diving_levels, people};
 use crate::errors::AppError;
 use crate::models::auth::{AuthResponse, GoogleCallbackRequest, GoogleIdTokenRequest, ImpersonateRequest, ImpersonateResponse};
 use crate::services::{AuthService, EmailService};
@@ -32,19 +32,32 @@ pub async fn get_oauth_config(
     })
 }
 
-/// Niveaux qui permettent de valider des compétences (encadrants)
-const VALIDATOR_LEVELS: &[&str] = &["E1", "E2", "E3", "E4"];
-
-/// Vérifie si un utilisateur peut valider des compétences basé sur son niveau de plongée
-/// Le diving_level peut être une liste séparée par des virgules (ex: "N1,N2,N3,N4,N5,E2")
-fn can_validate_from_diving_level(diving_level: Option<&String>) -> bool {
-    diving_level
-        .map(|levels| {
-            levels.split(',')
-                .map(|l| l.trim())
-                .any(|level| VALIDATOR_LEVELS.contains(&level))
-        })
-        .unwrap_or(false)
+/// Vérifie si un utilisateur peut valider des compétences: il doit avoir
+/// obtenu au moins un niveau marqué encadrant dans le référentiel des niveaux
+async fn can_validate_from_certifications(
+    db: &DatabaseConnection,
+    person: Option<&people::Model>,
+) -> Result<bool, AppError> {
+    let Some(person) = person else {
+        return Ok(false);
+    };
+
+    let instructor_levels: Vec<String> = DivingLevels::find()
+        .filter(diving_levels::Column::IsInstructor.eq(true))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|l| l.code)
+        .collect();
+
+    let validator_level = Certifications::find()
+        .filter(certifications::Column::PersonId.eq(person.id))
+        .filter(certifications::Column::Status.eq(certifications::STATUS_OBTAINED))
+        .filter(certifications::Column::Level.is_in(instructor_levels))
+        .one(db)
+        .await?;
+
+    Ok(validator_level.is_some())
 }
 
 pub async fn google_callback(
@@ -75,9 +88,8 @@ pub async fn google_callback(
     }
     
     // Vérifier si l'utilisateur peut valider des compétences (basé sur son niveau de plongée)
-    let can_validate_competencies = is_admin || can_validate_from_diving_level(
-        person.as_ref().and_then(|p| p.diving_level.as_ref())
-    );
+    let can_validate_competencies = is_admin
+        || can_validate_from_certifications(state.db.as_ref(), person.as_ref()).await?;
     
     // Générer la réponse d'authentification
     let response = state.auth_service.generate_auth_response(
@@ -122,9 +134,8 @@ pub async fn google_id_token_callback(
     }
     
     // Vérifier si l'utilisateur peut valider des compétences (basé sur son niveau de plongée)
-    let can_validate_competencies = is_admin || can_validate_from_diving_level(
-        person.as_ref().and_then(|p| p.diving_level.as_ref())
-    );
+    let can_validate_competencies = is_admin
+        || can_validate_from_certifications(state.db.as_ref(), person.as_ref()).await?;
     
     // Générer la réponse d'authentification
     let response = state.auth_service.generate_auth_response(
@@ -164,12 +175,12 @@ pub async fn impersonate_user(
         .ok_or(AppError::NotFound("Utilisateur non trouvé".to_string()))?;
     
     // Vérifier si l'utilisateur impersonnifié peut valider des compétences
-    let can_validate_competencies = can_validate_from_diving_level(target_user.diving_level.as_ref());
+    let can_validate_competencies =
+        can_validate_from_certifications(state.db.as_ref(), Some(&target_user)).await?;
     
     tracing::info!(
-        "Impersonating {} - diving_level: {:?}, can_validate: {}",
+        "Impersonating {} - can_validate: {}",
         target_user.email,
-        target_user.diving_level,
         can_validate_competencies
     );
     
@@ -261,7 +272,7 @@ pub async fn request_temp_password(
         .filter(people::Column::Email.eq(&email))
         .one(state.db.as_ref())
         .await
-        .map_err(|e| AppError::Database(e))?;
+        .map_err(AppError::Database)?;
 
     // Pour des raisons de sécurité, on renvoie toujours le même message
     // même si l'utilisateur n'existe pas
@@ -286,7 +297,7 @@ pub async fn request_temp_password(
     active.updated_at = Set(Utc::now().naive_utc());
     
     active.update(state.db.as_ref()).await
-        .map_err(|e| AppError::Database(e))?;
+        .map_err(AppError::Database)?;
 
     // Envoyer l'email avec le mot de passe temporaire
     let person_name = format!("{} {}", person.first_name, person.last_name);
@@ -355,7 +366,7 @@ pub async fn email_login(
         .filter(people::Column::Email.eq(&email))
         .one(state.db.as_ref())
         .await
-        .map_err(|e| AppError::Database(e))?
+        .map_err(AppError::Database)?
         .ok_or_else(|| AppError::Unauthorized("Email ou mot de passe incorrect".to_string()))?;
 
     // Vérifier le mot de passe (essayer d'abord le temp password, puis le password normal)
@@ -388,11 +399,12 @@ pub async fn email_login(
         active.temp_password = Set(None);
         active.temp_password_expires_at = Set(None);
         active.updated_at = Set(Utc::now().naive_utc());
-        active.update(state.db.as_ref()).await.map_err(|e| AppError::Database(e))?;
+        active.update(state.db.as_ref()).await.map_err(AppError::Database)?;
     }
 
     let is_admin = state.auth_service.is_admin(&email);
-    let can_validate_competencies = is_admin || can_validate_from_diving_level(person.diving_level.as_ref());
+    let can_validate_competencies = is_admin
+        || can_validate_from_certifications(state.db.as_ref(), Some(&person)).await?;
     let person_name = format!("{} {}", person.first_name, person.last_name);
     
     let response = state.auth_service.generate_auth_response_with_password_status(
@@ -435,7 +447,7 @@ pub async fn change_password(
         .filter(people::Column::Email.eq(&auth.claims.email))
         .one(state.db.as_ref())
         .await
-        .map_err(|e| AppError::Database(e))?
+        .map_err(AppError::Database)?
         .ok_or_else(|| AppError::NotFound("Utilisateur non trouvé".to_string()))?;
 
     // Hasher le nouveau mot de passe
@@ -450,7 +462,7 @@ pub async fn change_password(
     active.updated_at = Set(Utc::now().naive_utc());
     
     active.update(state.db.as_ref()).await
-        .map_err(|e| AppError::Database(e))?;
+        .map_err(AppError::Database)?;
 
     tracing::info!("Password changed for: {}", auth.claims.email);
 
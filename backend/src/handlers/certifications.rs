@@ -0,0 +1,225 @@
+use crate::entities::certifications::{self, SOURCE_EXTERNAL, SOURCE_INTERNAL, STATUS_OBTAINED, STATUS_PREPARING};
+use crate::entities::prelude::*;
+use crate::entities::people;
+use crate::errors::AppError;
+use crate::middleware::acl::{check_permission, AuthUser};
+use crate::services::CertificationService;
+use crate::models::{
+    CertificationResponse, CreateCertificationRequest, Permission, UpdateCertificationRequest,
+};
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use chrono::{NaiveDate, Utc};
+use sea_orm::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+fn parse_obtained_at(date_str: Option<&str>) -> Result<Option<NaiveDate>, AppError> {
+    date_str
+        .map(|d| {
+            NaiveDate::parse_from_str(d, "%Y-%m-%d")
+                .map_err(|_| AppError::Validation("Format de date invalide (YYYY-MM-DD)".to_string()))
+        })
+        .transpose()
+}
+
+fn check_status(status: &str) -> Result<(), AppError> {
+    if status != STATUS_OBTAINED && status != STATUS_PREPARING {
+        return Err(AppError::Validation(format!("Statut de niveau invalide: {}", status)));
+    }
+    Ok(())
+}
+
+fn check_source(source: &str) -> Result<(), AppError> {
+    if source != SOURCE_INTERNAL && source != SOURCE_EXTERNAL {
+        return Err(AppError::Validation(format!("Origine de brevet invalide: {}", source)));
+    }
+    Ok(())
+}
+
+fn certification_response(
+    cert: certifications::Model,
+    people_map: &HashMap<Uuid, people::Model>,
+) -> CertificationResponse {
+    CertificationResponse {
+        id: cert.id,
+        person_id: cert.person_id,
+        issued_by_name: cert
+            .issued_by_id
+            .and_then(|id| people_map.get(&id))
+            .map(|p| format!("{} {}", p.first_name, p.last_name)),
+        level: cert.level,
+        status: cert.status,
+        obtained_at: cert.obtained_at.map(|d| d.format("%Y-%m-%d").to_string()),
+        issued_by: cert.issued_by,
+        issued_by_id: cert.issued_by_id,
+        certificate_number: cert.certificate_number,
+        source: cert.source,
+    }
+}
+
+async fn issuers_map(
+    db: &DatabaseConnection,
+    certs: &[certifications::Model],
+) -> Result<HashMap<Uuid, people::Model>, AppError> {
+    let ids: Vec<Uuid> = certs.iter().filter_map(|c| c.issued_by_id).collect();
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    Ok(People::find()
+        .filter(people::Column::Id.is_in(ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p))
+        .collect())
+}
+
+/// Liste les niveaux et brevets d'un plongeur
+pub async fn list_person_certifications(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(person_id): Path<Uuid>,
+) -> Result<Json<Vec<CertificationResponse>>, AppError> {
+    check_permission(&auth, Permission::UsersView)?;
+
+    let certs = Certifications::find()
+        .filter(certifications::Column::PersonId.eq(person_id))
+        .order_by_asc(certifications::Column::ObtainedAt)
+        .order_by_asc(certifications::Column::Level)
+        .all(db.as_ref())
+        .await?;
+    let people_map = issuers_map(db.as_ref(), &certs).await?;
+
+    Ok(Json(
+        certs
+            .into_iter()
+            .map(|c| certification_response(c, &people_map))
+            .collect(),
+    ))
+}
+
+/// Ajoute un niveau ou un brevet à un plongeur
+pub async fn create_person_certification(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(person_id): Path<Uuid>,
+    Json(payload): Json<CreateCertificationRequest>,
+) -> Result<Json<CertificationResponse>, AppError> {
+    check_permission(&auth, Permission::UsersEdit)?;
+    payload
+        .validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
+
+    People::find_by_id(person_id)
+        .one(db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Person not found".to_string()))?;
+
+    let status = payload.status.unwrap_or_else(|| STATUS_OBTAINED.to_string());
+    check_status(&status)?;
+    let source = payload.source.unwrap_or_else(|| SOURCE_EXTERNAL.to_string());
+    check_source(&source)?;
+    let obtained_at = parse_obtained_at(payload.obtained_at.as_deref())?;
+
+    let level = CertificationService::normalize_level(&payload.level);
+    let existing = Certifications::find()
+        .filter(certifications::Column::PersonId.eq(person_id))
+        .filter(certifications::Column::Level.eq(&level))
+        .one(db.as_ref())
+        .await?;
+    if existing.is_some() {
+        return Err(AppError::Validation(format!(
+            "Le niveau {} est déjà enregistré pour ce plongeur",
+            level
+        )));
+    }
+
+    let now = Utc::now().naive_utc();
+    let cert = certifications::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        person_id: Set(person_id),
+        level: Set(level),
+        status: Set(status),
+        obtained_at: Set(obtained_at),
+        issued_by: Set(payload.issued_by),
+        issued_by_id: Set(payload.issued_by_id),
+        certificate_number: Set(payload.certificate_number),
+        source: Set(source),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db.as_ref())
+    .await?;
+
+    let people_map = issuers_map(db.as_ref(), std::slice::from_ref(&cert)).await?;
+    Ok(Json(certification_response(cert, &people_map)))
+}
+
+/// Met à jour un niveau ou un brevet
+pub async fn update_certification(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateCertificationRequest>,
+) -> Result<Json<CertificationResponse>, AppError> {
+    check_permission(&auth, Permission::UsersEdit)?;
+    payload
+        .validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let cert = Certifications::find_by_id(id)
+        .one(db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Brevet non trouvé".to_string()))?;
+
+    let mut active: certifications::ActiveModel = cert.into();
+    if let Some(status) = payload.status {
+        check_status(&status)?;
+        active.status = Set(status);
+    }
+    if let Some(source) = payload.source {
+        check_source(&source)?;
+        active.source = Set(source);
+    }
+    if payload.obtained_at.is_some() {
+        active.obtained_at = Set(parse_obtained_at(payload.obtained_at.as_deref())?);
+    }
+    if let Some(issued_by) = payload.issued_by {
+        active.issued_by = Set(Some(issued_by));
+    }
+    if let Some(issued_by_id) = payload.issued_by_id {
+        active.issued_by_id = Set(Some(issued_by_id));
+    }
+    if let Some(certificate_number) = payload.certificate_number {
+        active.certificate_number = Set(Some(certificate_number));
+    }
+    active.updated_at = Set(Utc::now().naive_utc());
+
+    let cert = active.update(db.as_ref()).await?;
+    let people_map = issuers_map(db.as_ref(), std::slice::from_ref(&cert)).await?;
+    Ok(Json(certification_response(cert, &people_map)))
+}
+
+/// Supprime un niveau ou un brevet
+pub async fn delete_certification(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_permission(&auth, Permission::UsersEdit)?;
+
+    let cert = Certifications::find_by_id(id)
+        .one(db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Brevet non trouvé".to_string()))?;
+
+    cert.delete(db.as_ref()).await?;
+
+    Ok(Json(serde_json::json!({ "message": "Brevet supprimé" })))
+}
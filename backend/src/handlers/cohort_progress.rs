@@ -0,0 +1,59 @@
+use crate::errors::AppError;
+use crate::middleware::acl::{check_permission, AuthUser};
+use crate::models::{CohortProgressQuery, DashboardFormat, Permission};
+use crate::services::{CohortProgressService, LevelService};
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use sea_orm::DatabaseConnection;
+use std::sync::Arc;
+
+/// Progression de tous les élèves préparant un niveau, par domaine, module
+/// et acquis, en JSON ou exportée en CSV/XLSX
+pub async fn get_cohort_progress(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(level): Path<String>,
+    Query(query): Query<CohortProgressQuery>,
+) -> Result<Response, AppError> {
+    check_permission(&auth, Permission::CompetenciesValidate)?;
+
+    let level = LevelService::catalog(db.as_ref())
+        .await?
+        .parse(&level)
+        .map(|l| l.to_string())
+        .ok_or_else(|| AppError::Validation(format!("Niveau inconnu: {}", level)))?;
+    if query.inactive_weeks < 0 {
+        return Err(AppError::Validation(
+            "Le nombre de semaines doit être positif".to_string(),
+        ));
+    }
+
+    let progress = CohortProgressService::build(db.as_ref(), &level, query.inactive_weeks).await?;
+
+    let (content_type, extension, body) = match query.format {
+        DashboardFormat::Json => return Ok(Json(progress).into_response()),
+        DashboardFormat::Csv => (
+            "text/csv; charset=utf-8",
+            "csv",
+            CohortProgressService::to_csv(&progress)?.into_bytes(),
+        ),
+        DashboardFormat::Xlsx => (
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            "xlsx",
+            CohortProgressService::to_xlsx(&progress)?,
+        ),
+    };
+
+    let headers = [
+        (header::CONTENT_TYPE, content_type.to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"progression_{}.{}\"", level, extension),
+        ),
+    ];
+    Ok((headers, body).into_response())
+}
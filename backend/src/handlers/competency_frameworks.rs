@@ -0,0 +1,113 @@
+use crate::errors::AppError;
+use crate::middleware::acl::{check_permission, AuthUser};
+use crate::models::{
+    CompetencyFramework, FrameworkBundleInfo, FrameworkExportQuery, FrameworkFormat,
+    FrameworkImportMode, FrameworkImportQuery, FrameworkImportResult, Permission,
+};
+use crate::services::{CompetencyFrameworkService, LevelService};
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+use std::sync::Arc;
+
+async fn parse_level(db: &DatabaseConnection, level: &str) -> Result<String, AppError> {
+    LevelService::catalog(db)
+        .await?
+        .parse(level)
+        .map(|l| l.to_string())
+        .ok_or_else(|| AppError::Validation(format!("Niveau inconnu: {}", level)))
+}
+
+/// Exporte le référentiel complet d'un niveau en JSON ou CSV
+pub async fn export_competency_framework(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(level): Path<String>,
+    Query(query): Query<FrameworkExportQuery>,
+) -> Result<Response, AppError> {
+    check_permission(&auth, Permission::CompetenciesView)?;
+    let level = parse_level(db.as_ref(), &level).await?;
+
+    let framework = CompetencyFrameworkService::export(db.as_ref(), &level).await?;
+
+    match query.format {
+        FrameworkFormat::Json => Ok(Json(framework).into_response()),
+        FrameworkFormat::Csv => {
+            let csv = CompetencyFrameworkService::to_csv(&framework)?;
+            let headers = [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"referentiel_{}.csv\"", level),
+                ),
+            ];
+            Ok((headers, csv).into_response())
+        }
+    }
+}
+
+/// Importe un référentiel (corps JSON ou CSV selon `format`),
+/// en remplacement complet ou en fusion par nom selon `mode`
+pub async fn import_competency_framework(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(level): Path<String>,
+    Query(query): Query<FrameworkImportQuery>,
+    body: String,
+) -> Result<Json<FrameworkImportResult>, AppError> {
+    check_permission(&auth, Permission::CompetenciesEdit)?;
+    let level = parse_level(db.as_ref(), &level).await?;
+
+    let framework = match query.format {
+        FrameworkFormat::Json => serde_json::from_str::<CompetencyFramework>(&body)
+            .map_err(|e| AppError::Validation(format!("JSON invalide: {}", e)))?,
+        FrameworkFormat::Csv => CompetencyFrameworkService::from_csv(&level, &body)?,
+    };
+
+    let result =
+        CompetencyFrameworkService::import(db.as_ref(), &level, &framework, query.mode).await?;
+
+    Ok(Json(result))
+}
+
+/// Liste les référentiels officiels FFESSM embarqués
+pub async fn list_framework_bundles(
+    Extension(auth): Extension<AuthUser>,
+) -> Result<Json<Vec<FrameworkBundleInfo>>, AppError> {
+    check_permission(&auth, Permission::CompetenciesView)?;
+    Ok(Json(CompetencyFrameworkService::bundles()))
+}
+
+#[derive(Deserialize)]
+pub struct BundleImportQuery {
+    #[serde(default)]
+    pub mode: FrameworkImportMode,
+}
+
+/// Importe un référentiel officiel sur son niveau
+pub async fn import_framework_bundle(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(code): Path<String>,
+    Query(query): Query<BundleImportQuery>,
+) -> Result<Json<FrameworkImportResult>, AppError> {
+    check_permission(&auth, Permission::CompetenciesEdit)?;
+
+    let framework = CompetencyFrameworkService::bundle(&code)
+        .ok_or(AppError::NotFound("Référentiel non trouvé".to_string()))?;
+
+    let result = CompetencyFrameworkService::import(
+        db.as_ref(),
+        &framework.diving_level,
+        &framework,
+        query.mode,
+    )
+    .await?;
+
+    Ok(Json(result))
+}
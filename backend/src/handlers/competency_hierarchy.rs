@@ -1,20 +1,25 @@
 use crate::entities::prelude::*;
 use crate::entities::{
-    competency_domains, competency_modules, competency_skills, people, skill_validations,
-    validation_stages,
+    competency_domains, competency_modules, competency_skills, palanquee_members, people,
+    questionnaires, skill_validation_history, skill_validations, validation_stages,
 };
 use crate::errors::AppError;
-use crate::middleware::acl::{check_permission, AuthUser};
+use crate::middleware::acl::{check_permission, current_person, AuthUser};
 use crate::models::{
+    BulkSkillValidationRequest, BulkSkillValidationResponse, BulkSkillValidationResult,
     CompetencyDomainResponse, CompetencyDomainWithProgress, CompetencyHierarchyResponse,
     CompetencyModuleResponse, CompetencyModuleWithProgress, CompetencySkillResponse,
     CompetencySkillWithValidation, CreateCompetencyDomainRequest, CreateCompetencyModuleRequest,
     CreateCompetencySkillRequest, CreateSkillValidationRequest, CreateValidationStageRequest,
-    Permission, ProgressStats, SkillValidationInfo, SkillValidationResponse,
+    EligibleValidatorResponse, EligibleValidatorsResponse, Permission, ProgressStats, SkillValidationInfo, SkillValidationResponse,
     UpdateCompetencyDomainRequest, UpdateCompetencyModuleRequest, UpdateCompetencySkillRequest,
-    UpdateSkillValidationRequest, UpdateValidationStageRequest, ValidationLogEntry, ValidationStageResponse,
+    UpdateSkillValidationRequest, UpdateValidationStageRequest, ValidationHistoryEntry, ValidationLogEntry,
+    ValidationStageResponse,
+};
+use crate::services::{
+    EmailService, LevelService, ProgressNotificationService, PromotionService, SkillValidationService,
+    ValidationHistoryService, ValidationSource,
 };
-use crate::models::diving_level::DivingLevel;
 use axum::{
     extract::{Path, Query, State},
     Extension, Json,
@@ -27,6 +32,44 @@ use std::sync::Arc;
 use uuid::Uuid;
 use validator::Validate;
 
+/// State pour les routes de validation qui déclenchent des effets de bord
+/// (détection de passage de niveau, notifications)
+pub struct CompetencyState {
+    pub db: Arc<DatabaseConnection>,
+    pub email_service: Arc<EmailService>,
+    pub admin_emails: Vec<String>,
+    pub auto_promote: bool,
+    /// Semaines sans progression avant d'alerter le référent d'un élève
+    pub mentor_stall_weeks: i64,
+}
+
+/// Suites d'une validation finale: passage de niveau (proposé ou appliqué)
+/// et modules ou domaines complétés. Les erreurs sont seulement loggées.
+pub async fn after_final_validation(state: &CompetencyState, person_id: Uuid, skill_id: Uuid) {
+    if let Err(e) = PromotionService::handle_final_validation(
+        state.db.as_ref(),
+        &state.email_service,
+        &state.admin_emails,
+        state.auto_promote,
+        person_id,
+        skill_id,
+    )
+    .await
+    {
+        tracing::error!("Failed to check level completion: {}", e);
+    }
+    if let Err(e) = ProgressNotificationService::handle_final_validation(
+        state.db.as_ref(),
+        &state.email_service,
+        person_id,
+        skill_id,
+    )
+    .await
+    {
+        tracing::error!("Failed to record progress milestones: {}", e);
+    }
+}
+
 // ============================================================================
 // VALIDATION STAGES HANDLERS
 // ============================================================================
@@ -44,6 +87,7 @@ pub async fn list_validation_stages(
         .into_iter()
         .map(|s| ValidationStageResponse {
             id: s.id,
+            allowed_next_stage_ids: s.allowed_next(),
             code: s.code,
             name: s.name,
             description: s.description,
@@ -51,12 +95,44 @@ pub async fn list_validation_stages(
             icon: s.icon,
             sort_order: s.sort_order,
             is_final: s.is_final,
+            validator_rule: s.validator_rule,
+            self_assessable: s.self_assessable,
         })
         .collect();
 
     Ok(Json(response))
 }
 
+/// Vérifie que les étapes suivantes existent. Une liste vide rétablit la
+/// progression libre (aucune restriction de transition).
+async fn allowed_next_stages(
+    db: &DatabaseConnection,
+    ids: &[Uuid],
+) -> Result<Option<serde_json::Value>, AppError> {
+    if ids.is_empty() {
+        return Ok(None);
+    }
+
+    let found = ValidationStages::find()
+        .filter(validation_stages::Column::Id.is_in(ids.to_vec()))
+        .count(db)
+        .await? as usize;
+    let mut unique = ids.to_vec();
+    unique.sort();
+    unique.dedup();
+    if found != unique.len() {
+        return Err(AppError::NotFound("Étape suivante non trouvée".to_string()));
+    }
+
+    Ok(Some(serde_json::json!(unique)))
+}
+
+/// Règle de validateur d'une étape, vérifiée contre le référentiel des niveaux
+async fn stage_validator_rule(db: &DatabaseConnection, rule: &str) -> Result<Option<String>, AppError> {
+    let catalog = LevelService::catalog(db).await?;
+    SkillValidationService::normalize_rule(&catalog, rule)
+}
+
 pub async fn create_validation_stage(
     Extension(auth): Extension<AuthUser>,
     State(db): State<Arc<DatabaseConnection>>,
@@ -74,6 +150,15 @@ pub async fn create_validation_stage(
         .map(|s| s.sort_order)
         .unwrap_or(0);
 
+    let allowed_next_stage_ids = match &payload.allowed_next_stage_ids {
+        Some(ids) => allowed_next_stages(db.as_ref(), ids).await?,
+        None => None,
+    };
+    let validator_rule = match payload.validator_rule.as_deref() {
+        Some(rule) => stage_validator_rule(db.as_ref(), rule).await?,
+        None => None,
+    };
+
     let stage = validation_stages::ActiveModel {
         id: Set(Uuid::new_v4()),
         code: Set(payload.code),
@@ -83,6 +168,9 @@ pub async fn create_validation_stage(
         icon: Set(payload.icon.unwrap_or_else(|| "⏳".to_string())),
         sort_order: Set(payload.sort_order.unwrap_or(max_order + 1)),
         is_final: Set(payload.is_final.unwrap_or(false)),
+        allowed_next_stage_ids: Set(allowed_next_stage_ids),
+        validator_rule: Set(validator_rule),
+        self_assessable: Set(payload.self_assessable.unwrap_or(false)),
         created_at: Set(now),
         updated_at: Set(now),
     };
@@ -93,6 +181,7 @@ pub async fn create_validation_stage(
 
     Ok(Json(ValidationStageResponse {
         id: stage.id,
+        allowed_next_stage_ids: stage.allowed_next(),
         code: stage.code,
         name: stage.name,
         description: stage.description,
@@ -100,6 +189,8 @@ pub async fn create_validation_stage(
         icon: stage.icon,
         sort_order: stage.sort_order,
         is_final: stage.is_final,
+        validator_rule: stage.validator_rule,
+        self_assessable: stage.self_assessable,
     }))
 }
 
@@ -141,6 +232,15 @@ pub async fn update_validation_stage(
     if let Some(is_final) = payload.is_final {
         stage.is_final = Set(is_final);
     }
+    if let Some(ids) = &payload.allowed_next_stage_ids {
+        stage.allowed_next_stage_ids = Set(allowed_next_stages(db.as_ref(), ids).await?);
+    }
+    if let Some(rule) = &payload.validator_rule {
+        stage.validator_rule = Set(stage_validator_rule(db.as_ref(), rule).await?);
+    }
+    if let Some(self_assessable) = payload.self_assessable {
+        stage.self_assessable = Set(self_assessable);
+    }
     stage.updated_at = Set(Utc::now().naive_utc());
 
     let updated = stage.update(db.as_ref()).await.map_err(|e| {
@@ -149,6 +249,7 @@ pub async fn update_validation_stage(
 
     Ok(Json(ValidationStageResponse {
         id: updated.id,
+        allowed_next_stage_ids: updated.allowed_next(),
         code: updated.code,
         name: updated.name,
         description: updated.description,
@@ -156,6 +257,8 @@ pub async fn update_validation_stage(
         icon: updated.icon,
         sort_order: updated.sort_order,
         is_final: updated.is_final,
+        validator_rule: updated.validator_rule,
+        self_assessable: updated.self_assessable,
     }))
 }
 
@@ -238,6 +341,7 @@ pub async fn list_competency_domains(
             diving_level: domain.diving_level,
             name: domain.name,
             sort_order: domain.sort_order,
+            validity_months: domain.validity_months,
             modules,
         });
     }
@@ -268,6 +372,7 @@ pub async fn create_competency_domain(
         diving_level: Set(payload.diving_level),
         name: Set(payload.name),
         sort_order: Set(payload.sort_order.unwrap_or(max_order + 1)),
+        validity_months: Set(payload.validity_months),
         created_at: Set(now),
         updated_at: Set(now),
     };
@@ -281,6 +386,7 @@ pub async fn create_competency_domain(
         diving_level: domain.diving_level,
         name: domain.name,
         sort_order: domain.sort_order,
+        validity_months: domain.validity_months,
         modules: None,
     }))
 }
@@ -311,6 +417,9 @@ pub async fn update_competency_domain(
     if let Some(sort_order) = payload.sort_order {
         domain.sort_order = Set(sort_order);
     }
+    if let Some(validity_months) = payload.validity_months {
+        domain.validity_months = Set(Some(validity_months).filter(|m| *m > 0));
+    }
     domain.updated_at = Set(Utc::now().naive_utc());
 
     let updated = domain.update(db.as_ref()).await.map_err(|e| {
@@ -322,6 +431,7 @@ pub async fn update_competency_domain(
         diving_level: updated.diving_level,
         name: updated.name,
         sort_order: updated.sort_order,
+        validity_months: updated.validity_months,
         modules: None,
     }))
 }
@@ -393,6 +503,8 @@ pub async fn list_competency_modules(
                         description: s.description,
                         sort_order: s.sort_order,
                         min_validator_level: s.min_validator_level,
+                        validator_rule: s.validator_rule,
+                        validity_months: s.validity_months,
                     })
                     .collect(),
             )
@@ -554,6 +666,8 @@ pub async fn list_competency_skills(
             description: s.description,
             sort_order: s.sort_order,
             min_validator_level: s.min_validator_level,
+            validator_rule: s.validator_rule,
+            validity_months: s.validity_months,
         })
         .collect();
 
@@ -575,6 +689,14 @@ pub async fn create_competency_skill(
         .map_err(|_| AppError::Database(DbErr::Custom("Query failed".to_string())))?
         .ok_or(AppError::NotFound("Module non trouvé".to_string()))?;
 
+    let validator_rule = match payload.validator_rule.as_deref() {
+        Some(rule) => {
+            let catalog = LevelService::catalog(db.as_ref()).await?;
+            SkillValidationService::normalize_rule(&catalog, rule)?
+        }
+        None => None,
+    };
+
     let now = Utc::now().naive_utc();
     let max_order = CompetencySkills::find()
         .filter(competency_skills::Column::ModuleId.eq(payload.module_id))
@@ -592,6 +714,8 @@ pub async fn create_competency_skill(
         description: Set(payload.description),
         sort_order: Set(payload.sort_order.unwrap_or(max_order + 1)),
         min_validator_level: Set(payload.min_validator_level.unwrap_or_else(|| "E2".to_string())),
+        validator_rule: Set(validator_rule),
+        validity_months: Set(payload.validity_months),
         created_at: Set(now),
         updated_at: Set(now),
     };
@@ -607,6 +731,8 @@ pub async fn create_competency_skill(
         description: skill.description,
         sort_order: skill.sort_order,
         min_validator_level: skill.min_validator_level,
+        validator_rule: skill.validator_rule,
+        validity_months: skill.validity_months,
     }))
 }
 
@@ -642,6 +768,13 @@ pub async fn update_competency_skill(
     if let Some(min_validator_level) = payload.min_validator_level {
         skill.min_validator_level = Set(min_validator_level);
     }
+    if let Some(rule) = payload.validator_rule {
+        let catalog = LevelService::catalog(db.as_ref()).await?;
+        skill.validator_rule = Set(SkillValidationService::normalize_rule(&catalog, &rule)?);
+    }
+    if let Some(validity_months) = payload.validity_months {
+        skill.validity_months = Set(Some(validity_months).filter(|m| *m > 0));
+    }
     skill.updated_at = Set(Utc::now().naive_utc());
 
     let updated = skill.update(db.as_ref()).await.map_err(|e| {
@@ -655,6 +788,8 @@ pub async fn update_competency_skill(
         description: updated.description,
         sort_order: updated.sort_order,
         min_validator_level: updated.min_validator_level,
+        validator_rule: updated.validator_rule,
+        validity_months: updated.validity_months,
     }))
 }
 
@@ -678,6 +813,37 @@ pub async fn delete_competency_skill(
     Ok(Json(serde_json::json!({ "message": "Acquis supprimé" })))
 }
 
+/// Liste les membres du club pouvant valider un acquis selon sa règle
+pub async fn list_eligible_validators(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EligibleValidatorsResponse>, AppError> {
+    check_permission(&auth, Permission::CompetenciesView)?;
+
+    let skill = CompetencySkills::find_by_id(id)
+        .one(db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Acquis non trouvé".to_string()))?;
+
+    let validators = SkillValidationService::eligible_validators(db.as_ref(), &skill)
+        .await?
+        .into_iter()
+        .map(|(person, level)| EligibleValidatorResponse {
+            person_id: person.id,
+            first_name: person.first_name,
+            last_name: person.last_name,
+            diving_level: Some(level),
+        })
+        .collect();
+
+    Ok(Json(EligibleValidatorsResponse {
+        skill_id: skill.id,
+        rule: SkillValidationService::requirement(&skill),
+        validators,
+    }))
+}
+
 // ============================================================================
 // SKILL VALIDATIONS HANDLERS
 // ============================================================================
@@ -748,6 +914,7 @@ pub async fn list_skill_validations(
             stage_id: v.stage_id,
             stage: stage.map(|s| ValidationStageResponse {
                 id: s.id,
+                allowed_next_stage_ids: s.allowed_next(),
                 code: s.code,
                 name: s.name,
                 description: s.description,
@@ -755,23 +922,36 @@ pub async fn list_skill_validations(
                 icon: s.icon,
                 sort_order: s.sort_order,
                 is_final: s.is_final,
+                validator_rule: s.validator_rule,
+                self_assessable: s.self_assessable,
             }),
             validated_at: v.validated_at.to_string(),
             validated_by_id: v.validated_by_id,
             validated_by_name: validated_by.map(|p| format!("{} {}", p.first_name, p.last_name)),
             notes: v.notes,
+            session_id: v.session_id,
+            palanquee_id: v.palanquee_id,
         });
     }
 
     Ok(Json(response))
 }
 
+/// Date de validation au format YYYY-MM-DD, aujourd'hui par défaut
+fn parse_validated_at(date_str: Option<&str>) -> Result<NaiveDate, AppError> {
+    match date_str {
+        Some(date_str) => NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
+            .map_err(|_| AppError::Validation("Format de date invalide (YYYY-MM-DD)".to_string())),
+        None => Ok(Utc::now().naive_utc().date()),
+    }
+}
+
 pub async fn create_skill_validation(
     Extension(auth): Extension<AuthUser>,
-    State(db): State<Arc<DatabaseConnection>>,
+    State(state): State<Arc<CompetencyState>>,
     Json(payload): Json<CreateSkillValidationRequest>,
 ) -> Result<Json<SkillValidationResponse>, AppError> {
-    check_permission(&auth, Permission::CompetenciesValidate)?;
+    let db = state.db.clone();
     payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
 
     // Verify skill exists and get min_validator_level
@@ -782,51 +962,7 @@ pub async fn create_skill_validation(
         .ok_or(AppError::NotFound("Acquis non trouvé".to_string()))?;
 
     // Get the validator - use impersonated user if impersonating
-    let validator_email = auth.claims.impersonating
-        .as_ref()
-        .map(|imp| imp.user_email.as_str())
-        .unwrap_or(&auth.claims.email);
-    
-    let validator = People::find()
-        .filter(people::Column::Email.eq(validator_email))
-        .one(db.as_ref())
-        .await
-        .map_err(|_| AppError::Database(DbErr::Custom("Query failed".to_string())))?
-        .ok_or(AppError::NotFound("Validateur non trouvé".to_string()))?;
-
-    // Check if validator has the required level
-    if let Some(validator_level_str) = &validator.diving_level {
-        if let Some(min_level) = DivingLevel::parse(&skill.min_validator_level) {
-            // Parse validator's highest level
-            let validator_levels: Vec<&str> = validator_level_str.split(',').collect();
-            let mut has_required_level = false;
-
-            for level_str in validator_levels {
-                let trimmed = level_str.trim();
-                if trimmed.starts_with("preparing_") {
-                    continue;
-                }
-                if let Some(level) = DivingLevel::parse(trimmed) {
-                    if level.hierarchy() >= min_level.hierarchy() {
-                        has_required_level = true;
-                        break;
-                    }
-                }
-            }
-
-            if !has_required_level {
-                return Err(AppError::Forbidden(format!(
-                    "Niveau minimum requis pour valider: {} (votre niveau: {})",
-                    skill.min_validator_level,
-                    validator_level_str
-                )));
-            }
-        }
-    } else {
-        return Err(AppError::Forbidden(
-            "Vous n'avez pas de niveau de plongée enregistré".to_string(),
-        ));
-    }
+    let validator = current_person(db.as_ref(), &auth).await?;
 
     // Verify stage exists
     let stage = ValidationStages::find_by_id(payload.stage_id)
@@ -835,6 +971,12 @@ pub async fn create_skill_validation(
         .map_err(|_| AppError::Database(DbErr::Custom("Query failed".to_string())))?
         .ok_or(AppError::NotFound("Étape non trouvée".to_string()))?;
 
+    // Un élève peut s'auto-évaluer sur les étapes prévues pour cela;
+    // valider les acquis des autres requiert la permission
+    if payload.person_id != validator.id || !stage.self_assessable {
+        check_permission(&auth, Permission::CompetenciesValidate)?;
+    }
+
     // Verify person exists
     let person = People::find_by_id(payload.person_id)
         .one(db.as_ref())
@@ -843,73 +985,48 @@ pub async fn create_skill_validation(
         .ok_or(AppError::NotFound("Élève non trouvé".to_string()))?;
 
     // Parse validated_at date
-    let validated_at = if let Some(date_str) = &payload.validated_at {
-        NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
-            .map_err(|_| AppError::Validation("Format de date invalide (YYYY-MM-DD)".to_string()))?
+    let validated_at = parse_validated_at(payload.validated_at.as_deref())?;
+
+    // Plongée d'origine: mêmes vérifications que la validation groupée.
+    // Sans plongée indiquée, le service conserve la source enregistrée.
+    let source = if payload.palanquee_id.is_some() || payload.session_id.is_some() {
+        let (session_id, student_ids) =
+            resolve_dive_students(db.as_ref(), payload.palanquee_id, payload.session_id).await?;
+        if !student_ids.contains(&payload.person_id) {
+            return Err(AppError::Validation(
+                "L'élève n'a pas participé à cette plongée".to_string(),
+            ));
+        }
+        ValidationSource {
+            session_id: Some(session_id),
+            palanquee_id: payload.palanquee_id,
+        }
     } else {
-        Utc::now().naive_utc().date()
+        ValidationSource::default()
     };
 
-    let now = Utc::now().naive_utc();
-
-    // Check if validation already exists (upsert)
-    let existing = SkillValidations::find()
-        .filter(skill_validations::Column::PersonId.eq(payload.person_id))
-        .filter(skill_validations::Column::SkillId.eq(payload.skill_id))
-        .one(db.as_ref())
-        .await
-        .map_err(|e| AppError::Database(DbErr::Custom(format!("Query failed: {}", e))))?;
+    // Rules of the stage workflow (transitions, validator level) are checked
+    // by the service; real admins (not impersonating) may bypass transitions
+    let is_real_admin = auth.claims.is_admin && auth.claims.impersonating.is_none();
 
-    // Check if trying to go backwards - only admins (not impersonating) can do this
-    if let Some(ref existing_validation) = existing {
-        let current_stage = ValidationStages::find_by_id(existing_validation.stage_id)
-            .one(db.as_ref())
-            .await
-            .map_err(|_| AppError::Database(DbErr::Custom("Query failed".to_string())))?;
-        
-        if let Some(current_stage) = current_stage {
-            // Check if new stage is lower than current
-            if stage.sort_order < current_stage.sort_order {
-                // Only allow if admin AND not impersonating
-                let is_real_admin = auth.claims.is_admin && auth.claims.impersonating.is_none();
-                if !is_real_admin {
-                    return Err(AppError::Forbidden(
-                        "Vous ne pouvez pas revenir en arrière sur une étape de validation. Seuls les administrateurs peuvent le faire.".to_string()
-                    ));
-                }
-            }
-        }
+    let validation = SkillValidationService::upsert(
+        db.as_ref(),
+        &validator,
+        is_real_admin,
+        payload.person_id,
+        &skill,
+        &stage,
+        validated_at,
+        payload.notes,
+        source,
+    )
+    .await?;
+
+    // Une validation finale peut compléter le niveau: proposer (ou appliquer) le passage
+    if stage.is_final {
+        after_final_validation(&state, validation.person_id, validation.skill_id).await;
     }
 
-    let validation = if let Some(existing) = existing {
-        // Update existing
-        let mut existing: skill_validations::ActiveModel = existing.into();
-        existing.stage_id = Set(payload.stage_id);
-        existing.validated_at = Set(validated_at);
-        existing.validated_by_id = Set(validator.id);
-        existing.notes = Set(payload.notes);
-        existing.updated_at = Set(now);
-        existing.update(db.as_ref()).await.map_err(|e| {
-            AppError::Database(DbErr::Custom(format!("Failed to update: {}", e)))
-        })?
-    } else {
-        // Create new
-        let new_validation = skill_validations::ActiveModel {
-            id: Set(Uuid::new_v4()),
-            person_id: Set(payload.person_id),
-            skill_id: Set(payload.skill_id),
-            stage_id: Set(payload.stage_id),
-            validated_at: Set(validated_at),
-            validated_by_id: Set(validator.id),
-            notes: Set(payload.notes),
-            created_at: Set(now),
-            updated_at: Set(now),
-        };
-        new_validation.insert(db.as_ref()).await.map_err(|e| {
-            AppError::Database(DbErr::Custom(format!("Failed to create: {}", e)))
-        })?
-    };
-
     Ok(Json(SkillValidationResponse {
         id: validation.id,
         person_id: validation.person_id,
@@ -919,6 +1036,7 @@ pub async fn create_skill_validation(
         stage_id: validation.stage_id,
         stage: Some(ValidationStageResponse {
             id: stage.id,
+            allowed_next_stage_ids: stage.allowed_next(),
             code: stage.code,
             name: stage.name,
             description: stage.description,
@@ -926,21 +1044,223 @@ pub async fn create_skill_validation(
             icon: stage.icon,
             sort_order: stage.sort_order,
             is_final: stage.is_final,
+            validator_rule: stage.validator_rule,
+            self_assessable: stage.self_assessable,
         }),
         validated_at: validation.validated_at.to_string(),
         validated_by_id: validation.validated_by_id,
         validated_by_name: Some(format!("{} {}", validator.first_name, validator.last_name)),
         notes: validation.notes,
+        session_id: validation.session_id,
+        palanquee_id: validation.palanquee_id,
+    }))
+}
+
+/// Message d'erreur lisible pour un résultat de validation groupée
+fn bulk_item_error(error: AppError) -> String {
+    match error {
+        AppError::Forbidden(msg) | AppError::NotFound(msg) | AppError::Validation(msg) => msg,
+        other => other.to_string(),
+    }
+}
+
+/// Plongée d'une validation (palanquée ou session) et élèves qui y ont
+/// participé. Une palanquée doit appartenir à la session indiquée.
+async fn resolve_dive_students(
+    db: &DatabaseConnection,
+    palanquee_id: Option<Uuid>,
+    session_id: Option<Uuid>,
+) -> Result<(Uuid, Vec<Uuid>), AppError> {
+    if let Some(palanquee_id) = palanquee_id {
+        let palanquee = Palanquees::find_by_id(palanquee_id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Palanquée non trouvée".to_string()))?;
+        let rotation = Rotations::find_by_id(palanquee.rotation_id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Rotation non trouvée".to_string()))?;
+        if session_id.is_some_and(|id| id != rotation.session_id) {
+            return Err(AppError::Validation(
+                "La palanquée n'appartient pas à cette session".to_string(),
+            ));
+        }
+
+        let questionnaire_ids: Vec<Uuid> = PalanqueeMembers::find()
+            .filter(palanquee_members::Column::PalanqueeId.eq(palanquee_id))
+            .filter(palanquee_members::Column::Role.eq("P"))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|m| m.questionnaire_id)
+            .collect();
+
+        let student_ids: Vec<Uuid> = Questionnaires::find()
+            .filter(questionnaires::Column::Id.is_in(questionnaire_ids))
+            .filter(questionnaires::Column::IsEncadrant.eq(false))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|q| q.person_id)
+            .collect();
+
+        Ok((rotation.session_id, student_ids))
+    } else if let Some(session_id) = session_id {
+        let session = Sessions::find_by_id(session_id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Session non trouvée".to_string()))?;
+
+        // Pour une plongée de sortie, les inscriptions sont rattachées à la sortie
+        let registration = match session.sortie_id {
+            Some(sortie_id) => questionnaires::Column::SortieId.eq(sortie_id),
+            None => questionnaires::Column::SessionId.eq(session_id),
+        };
+
+        let student_ids: Vec<Uuid> = Questionnaires::find()
+            .filter(registration)
+            .filter(questionnaires::Column::IsEncadrant.eq(false))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|q| q.person_id)
+            .collect();
+
+        Ok((session_id, student_ids))
+    } else {
+        Err(AppError::Validation(
+            "Une palanquée ou une session est requise".to_string(),
+        ))
+    }
+}
+
+/// Valide les mêmes acquis pour tous les plongeurs (rôle P, hors encadrants)
+/// d'une palanquée ou d'une session. Chaque couple (élève, acquis) est traité
+/// indépendamment avec les mêmes règles que la validation unitaire.
+pub async fn bulk_create_skill_validations(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<Arc<CompetencyState>>,
+    Json(payload): Json<BulkSkillValidationRequest>,
+) -> Result<Json<BulkSkillValidationResponse>, AppError> {
+    let db = state.db.clone();
+    check_permission(&auth, Permission::CompetenciesValidate)?;
+    payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
+
+    // Résolution de la plongée et des élèves concernés
+    let (session_id, student_ids) =
+        resolve_dive_students(db.as_ref(), payload.palanquee_id, payload.session_id).await?;
+
+    let source = ValidationSource {
+        session_id: Some(session_id),
+        palanquee_id: payload.palanquee_id,
+    };
+
+    // Get the validator - use impersonated user if impersonating
+    let validator = current_person(db.as_ref(), &auth).await?;
+
+    let is_real_admin = auth.claims.is_admin && auth.claims.impersonating.is_none();
+    let validated_at = parse_validated_at(payload.validated_at.as_deref())?;
+
+    let skill_ids: Vec<Uuid> = payload.items.iter().map(|i| i.skill_id).collect();
+    let skills: HashMap<Uuid, competency_skills::Model> = CompetencySkills::find()
+        .filter(competency_skills::Column::Id.is_in(skill_ids))
+        .all(db.as_ref())
+        .await?
+        .into_iter()
+        .map(|s| (s.id, s))
+        .collect();
+
+    let stages: HashMap<Uuid, validation_stages::Model> = ValidationStages::find()
+        .all(db.as_ref())
+        .await?
+        .into_iter()
+        .map(|s| (s.id, s))
+        .collect();
+
+    let target_ids: Vec<Uuid> = match &payload.person_ids {
+        Some(ids) => ids.clone(),
+        None => student_ids.clone(),
+    };
+    let students: HashMap<Uuid, people::Model> = People::find()
+        .filter(people::Column::Id.is_in(target_ids.clone()))
+        .all(db.as_ref())
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p))
+        .collect();
+
+    let mut results = Vec::new();
+    for person_id in target_ids {
+        let person_name = students
+            .get(&person_id)
+            .map(|p| format!("{} {}", p.first_name, p.last_name));
+
+        for item in &payload.items {
+            let skill = skills.get(&item.skill_id);
+            let outcome = if !student_ids.contains(&person_id) {
+                Err(AppError::Validation(
+                    "Cet élève ne fait pas partie des plongeurs de cette plongée".to_string(),
+                ))
+            } else {
+                match (skill, stages.get(&item.stage_id)) {
+                    (None, _) => Err(AppError::NotFound("Acquis non trouvé".to_string())),
+                    (_, None) => Err(AppError::NotFound("Étape non trouvée".to_string())),
+                    (Some(skill), Some(stage)) => SkillValidationService::upsert(
+                        db.as_ref(),
+                        &validator,
+                        is_real_admin,
+                        person_id,
+                        skill,
+                        stage,
+                        validated_at,
+                        item.notes.clone().or_else(|| payload.notes.clone()),
+                        source,
+                    )
+                    .await
+                    .map(|v| (v, stage.is_final)),
+                }
+            };
+
+            let (validation_id, error) = match outcome {
+                Ok((validation, is_final)) => {
+                    if is_final {
+                        after_final_validation(&state, validation.person_id, validation.skill_id).await;
+                    }
+                    (Some(validation.id), None)
+                }
+                Err(e) => (None, Some(bulk_item_error(e))),
+            };
+
+            results.push(BulkSkillValidationResult {
+                person_id,
+                person_name: person_name.clone(),
+                skill_id: item.skill_id,
+                skill_name: skill.map(|s| s.name.clone()),
+                stage_id: item.stage_id,
+                success: error.is_none(),
+                validation_id,
+                error,
+            });
+        }
+    }
+
+    let success_count = results.iter().filter(|r| r.success).count();
+    Ok(Json(BulkSkillValidationResponse {
+        session_id,
+        palanquee_id: payload.palanquee_id,
+        success_count,
+        error_count: results.len() - success_count,
+        results,
     }))
 }
 
 pub async fn update_skill_validation(
     Extension(auth): Extension<AuthUser>,
-    State(db): State<Arc<DatabaseConnection>>,
+    State(state): State<Arc<CompetencyState>>,
     Path(id): Path<Uuid>,
     Json(payload): Json<UpdateSkillValidationRequest>,
 ) -> Result<Json<SkillValidationResponse>, AppError> {
-    check_permission(&auth, Permission::CompetenciesValidate)?;
+    let db = state.db.clone();
     payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
 
     let validation = SkillValidations::find_by_id(id)
@@ -949,48 +1269,52 @@ pub async fn update_skill_validation(
         .map_err(|_| AppError::Database(DbErr::Custom("Query failed".to_string())))?
         .ok_or(AppError::NotFound("Validation non trouvée".to_string()))?;
 
-    // Get the validator (current user)
-    let validator = People::find()
-        .filter(people::Column::Email.eq(&auth.claims.email))
-        .one(db.as_ref())
-        .await
-        .map_err(|_| AppError::Database(DbErr::Custom("Query failed".to_string())))?
-        .ok_or(AppError::NotFound("Validateur non trouvé".to_string()))?;
-
-    let mut validation_model: skill_validations::ActiveModel = validation.clone().into();
+    // Get the validator - use impersonated user if impersonating
+    let validator = current_person(db.as_ref(), &auth).await?;
 
-    if let Some(stage_id) = payload.stage_id {
-        validation_model.stage_id = Set(stage_id);
-    }
-    if let Some(date_str) = &payload.validated_at {
-        let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
-            .map_err(|_| AppError::Validation("Format de date invalide".to_string()))?;
-        validation_model.validated_at = Set(date);
-    }
-    if payload.notes.is_some() {
-        validation_model.notes = Set(payload.notes);
+    let stage = ValidationStages::find_by_id(payload.stage_id.unwrap_or(validation.stage_id))
+        .one(db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Étape non trouvée".to_string()))?;
+    if validation.person_id != validator.id || !stage.self_assessable {
+        check_permission(&auth, Permission::CompetenciesValidate)?;
     }
-    validation_model.validated_by_id = Set(validator.id);
-    validation_model.updated_at = Set(Utc::now().naive_utc());
-
-    let updated = validation_model.update(db.as_ref()).await.map_err(|e| {
-        AppError::Database(DbErr::Custom(format!("Failed to update: {}", e)))
-    })?;
 
-    // Load related data for response
-    let stage = ValidationStages::find_by_id(updated.stage_id)
+    let skill = CompetencySkills::find_by_id(validation.skill_id)
         .one(db.as_ref())
-        .await
-        .ok()
-        .flatten();
+        .await?
+        .ok_or(AppError::NotFound("Acquis non trouvé".to_string()))?;
 
-    let person = People::find_by_id(updated.person_id)
-        .one(db.as_ref())
-        .await
-        .ok()
-        .flatten();
+    let validated_at = match &payload.validated_at {
+        Some(date_str) => NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
+            .map_err(|_| AppError::Validation("Format de date invalide".to_string()))?,
+        None => validation.validated_at,
+    };
+
+    // Mêmes règles que la création: workflow des étapes et niveau du validateur
+    let is_real_admin = auth.claims.is_admin && auth.claims.impersonating.is_none();
+    let updated = SkillValidationService::upsert(
+        db.as_ref(),
+        &validator,
+        is_real_admin,
+        validation.person_id,
+        &skill,
+        &stage,
+        validated_at,
+        payload.notes.or(validation.notes),
+        ValidationSource {
+            session_id: validation.session_id,
+            palanquee_id: validation.palanquee_id,
+        },
+    )
+    .await?;
+
+    // Passage à une étape finale: mêmes suites qu'une création
+    if stage.is_final {
+        after_final_validation(&state, updated.person_id, updated.skill_id).await;
+    }
 
-    let skill = CompetencySkills::find_by_id(updated.skill_id)
+    let person = People::find_by_id(updated.person_id)
         .one(db.as_ref())
         .await
         .ok()
@@ -1001,30 +1325,36 @@ pub async fn update_skill_validation(
         person_id: updated.person_id,
         person_name: person.map(|p| format!("{} {}", p.first_name, p.last_name)),
         skill_id: updated.skill_id,
-        skill_name: skill.map(|s| s.name),
+        skill_name: Some(skill.name),
         stage_id: updated.stage_id,
-        stage: stage.map(|s| ValidationStageResponse {
-            id: s.id,
-            code: s.code,
-            name: s.name,
-            description: s.description,
-            color: s.color,
-            icon: s.icon,
-            sort_order: s.sort_order,
-            is_final: s.is_final,
+        stage: Some(ValidationStageResponse {
+            id: stage.id,
+            allowed_next_stage_ids: stage.allowed_next(),
+            code: stage.code,
+            name: stage.name,
+            description: stage.description,
+            color: stage.color,
+            icon: stage.icon,
+            sort_order: stage.sort_order,
+            is_final: stage.is_final,
+            validator_rule: stage.validator_rule,
+            self_assessable: stage.self_assessable,
         }),
         validated_at: updated.validated_at.to_string(),
         validated_by_id: updated.validated_by_id,
         validated_by_name: Some(format!("{} {}", validator.first_name, validator.last_name)),
         notes: updated.notes,
+        session_id: updated.session_id,
+        palanquee_id: updated.palanquee_id,
     }))
 }
 
 pub async fn delete_skill_validation(
     Extension(auth): Extension<AuthUser>,
-    State(db): State<Arc<DatabaseConnection>>,
+    State(state): State<Arc<CompetencyState>>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<serde_json::Value>, AppError> {
+    let db = state.db.clone();
     // Seuls les vrais admins (non impersonnifiés) peuvent supprimer des validations
     let is_real_admin = auth.claims.is_admin && auth.claims.impersonating.is_none();
     if !is_real_admin {
@@ -1039,9 +1369,18 @@ pub async fn delete_skill_validation(
         .map_err(|_| AppError::Database(DbErr::Custom("Query failed".to_string())))?
         .ok_or(AppError::NotFound("Validation non trouvée".to_string()))?;
 
-    validation.delete(db.as_ref()).await.map_err(|e| {
+    let admin_id = People::find()
+        .filter(people::Column::Email.eq(&auth.claims.email))
+        .one(db.as_ref())
+        .await?
+        .map(|p| p.id);
+
+    let txn = db.begin().await?;
+    ValidationHistoryService::record_deletion(&txn, &validation, admin_id).await?;
+    validation.delete(&txn).await.map_err(|e| {
         AppError::Database(DbErr::Custom(format!("Failed to delete: {}", e)))
     })?;
+    txn.commit().await?;
 
     Ok(Json(serde_json::json!({ "message": "Validation supprimée" })))
 }
@@ -1058,10 +1397,9 @@ pub async fn get_validation_logs(
         ));
     }
 
-    // Load all validations ordered by date desc
-    let validations = SkillValidations::find()
-        .order_by_desc(skill_validations::Column::ValidatedAt)
-        .order_by_desc(skill_validations::Column::CreatedAt)
+    // Load the full transition history, most recent first
+    let history = SkillValidationHistory::find()
+        .order_by_desc(skill_validation_history::Column::CreatedAt)
         .all(db.as_ref())
         .await
         .map_err(|e| AppError::Database(DbErr::Custom(format!("Query failed: {}", e))))?;
@@ -1104,11 +1442,20 @@ pub async fn get_validation_logs(
 
     let mut response: Vec<ValidationLogEntry> = Vec::new();
 
-    for validation in validations {
-        let student = people_map.get(&validation.person_id);
-        let instructor = people_map.get(&validation.validated_by_id);
-        let skill = skills_map.get(&validation.skill_id);
-        let stage = stages_map.get(&validation.stage_id);
+    for entry in history {
+        let student = people_map.get(&entry.person_id);
+        let instructor = entry
+            .validated_by_id
+            .or(entry.performed_by_id)
+            .and_then(|id| people_map.get(&id));
+        let performed_by = entry.performed_by_id.and_then(|id| people_map.get(&id));
+        let skill = skills_map.get(&entry.skill_id);
+        // Pour une suppression, on affiche l'étape qui a été retirée
+        let stage = entry
+            .to_stage_id
+            .or(entry.from_stage_id)
+            .and_then(|id| stages_map.get(&id));
+        let previous_stage = entry.from_stage_id.and_then(|id| stages_map.get(&id));
 
         // Get module and domain from skill
         let (module_name, domain_name, diving_level) = if let Some(sk) = skill {
@@ -1126,8 +1473,14 @@ pub async fn get_validation_logs(
         };
 
         response.push(ValidationLogEntry {
-            id: validation.id,
-            validated_at: validation.validated_at.to_string(),
+            id: entry.id,
+            validation_id: entry.validation_id,
+            action: entry.action,
+            validated_at: entry
+                .validated_at
+                .unwrap_or_else(|| entry.created_at.date())
+                .to_string(),
+            recorded_at: entry.created_at.to_string(),
             student_name: student.map(|p| format!("{} {}", p.first_name, p.last_name)).unwrap_or_else(|| "?".to_string()),
             student_email: student.map(|p| p.email.clone()).unwrap_or_else(|| "?".to_string()),
             instructor_name: instructor.map(|p| format!("{} {}", p.first_name, p.last_name)).unwrap_or_else(|| "?".to_string()),
@@ -1139,7 +1492,9 @@ pub async fn get_validation_logs(
             stage_name: stage.map(|s| s.name.clone()).unwrap_or_else(|| "?".to_string()),
             stage_color: stage.map(|s| s.color.clone()).unwrap_or_else(|| "#888888".to_string()),
             is_final: stage.map(|s| s.is_final).unwrap_or(false),
-            notes: validation.notes,
+            previous_stage_name: previous_stage.map(|s| s.name.clone()),
+            performed_by_name: performed_by.map(|p| format!("{} {}", p.first_name, p.last_name)),
+            notes: entry.notes,
         });
     }
 
@@ -1163,19 +1518,8 @@ pub async fn get_my_competencies(
     State(db): State<Arc<DatabaseConnection>>,
     Query(query): Query<MyCompetenciesQuery>,
 ) -> Result<Json<CompetencyHierarchyResponse>, AppError> {
-    // Use impersonated user's email if impersonating, otherwise use own email
-    let target_email = auth.claims.impersonating
-        .as_ref()
-        .map(|imp| imp.user_email.as_str())
-        .unwrap_or(&auth.claims.email);
-    
-    // Get target user's person record
-    let person = People::find()
-        .filter(people::Column::Email.eq(target_email))
-        .one(db.as_ref())
-        .await
-        .map_err(|_| AppError::Database(DbErr::Custom("Query failed".to_string())))?
-        .ok_or(AppError::NotFound("Profil non trouvé".to_string()))?;
+    // Use impersonated user's person record if impersonating, otherwise own
+    let person = current_person(db.as_ref(), &auth).await?;
 
     get_competency_hierarchy_for_person(&db, &query.diving_level, person.id).await
 }
@@ -1211,7 +1555,123 @@ pub async fn get_person_competencies(
     get_competency_hierarchy_for_person(&db, &query.diving_level, person_id).await
 }
 
-async fn get_competency_hierarchy_for_person(
+#[derive(Deserialize)]
+pub struct TimelineQuery {
+    pub skill_id: Option<Uuid>,
+}
+
+/// Frise chronologique de la progression de l'utilisateur courant
+pub async fn get_my_timeline(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Query(query): Query<TimelineQuery>,
+) -> Result<Json<Vec<ValidationHistoryEntry>>, AppError> {
+    let person = current_person(db.as_ref(), &auth).await?;
+
+    get_timeline_for_person(&db, person.id, query.skill_id).await
+}
+
+/// Frise chronologique d'un élève (encadrants/admins, ou soi-même)
+pub async fn get_person_timeline(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(person_id): Path<Uuid>,
+    Query(query): Query<TimelineQuery>,
+) -> Result<Json<Vec<ValidationHistoryEntry>>, AppError> {
+    // Utilisateur effectif: l'utilisateur impersonnifié le cas échéant
+    let is_viewing_self = current_person(db.as_ref(), &auth)
+        .await
+        .is_ok_and(|me| me.id == person_id);
+
+    if !is_viewing_self {
+        check_permission(&auth, Permission::CompetenciesValidate)?;
+    }
+
+    get_timeline_for_person(&db, person_id, query.skill_id).await
+}
+
+/// Construit la frise à partir de l'historique des transitions (ordre chronologique)
+async fn get_timeline_for_person(
+    db: &DatabaseConnection,
+    person_id: Uuid,
+    skill_id: Option<Uuid>,
+) -> Result<Json<Vec<ValidationHistoryEntry>>, AppError> {
+    let mut select = SkillValidationHistory::find()
+        .filter(skill_validation_history::Column::PersonId.eq(person_id));
+    if let Some(skill_id) = skill_id {
+        select = select.filter(skill_validation_history::Column::SkillId.eq(skill_id));
+    }
+    let history = select
+        .order_by_asc(skill_validation_history::Column::CreatedAt)
+        .all(db)
+        .await?;
+
+    let stages: HashMap<Uuid, validation_stages::Model> = ValidationStages::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|s| (s.id, s))
+        .collect();
+
+    let skill_ids: Vec<Uuid> = history.iter().map(|h| h.skill_id).collect();
+    let skills: HashMap<Uuid, String> = CompetencySkills::find()
+        .filter(competency_skills::Column::Id.is_in(skill_ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|s| (s.id, s.name))
+        .collect();
+
+    let people_ids: Vec<Uuid> = history
+        .iter()
+        .flat_map(|h| [h.validated_by_id, h.performed_by_id])
+        .flatten()
+        .collect();
+    let names: HashMap<Uuid, String> = People::find()
+        .filter(people::Column::Id.is_in(people_ids))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|p| (p.id, format!("{} {}", p.first_name, p.last_name)))
+        .collect();
+
+    let stage_response = |id: Option<Uuid>| {
+        id.and_then(|id| stages.get(&id)).map(|s| ValidationStageResponse {
+            id: s.id,
+            code: s.code.clone(),
+            name: s.name.clone(),
+            description: s.description.clone(),
+            color: s.color.clone(),
+            icon: s.icon.clone(),
+            sort_order: s.sort_order,
+            is_final: s.is_final,
+            allowed_next_stage_ids: s.allowed_next(),
+            validator_rule: s.validator_rule.clone(),
+            self_assessable: s.self_assessable,
+        })
+    };
+
+    let response = history
+        .into_iter()
+        .map(|h| ValidationHistoryEntry {
+            id: h.id,
+            skill_id: h.skill_id,
+            skill_name: skills.get(&h.skill_id).cloned(),
+            from_stage: stage_response(h.from_stage_id),
+            to_stage: stage_response(h.to_stage_id),
+            validated_at: h.validated_at.map(|d| d.to_string()),
+            validated_by_name: h.validated_by_id.and_then(|id| names.get(&id).cloned()),
+            performed_by_name: h.performed_by_id.and_then(|id| names.get(&id).cloned()),
+            notes: h.notes,
+            recorded_at: h.created_at.to_string(),
+            action: h.action,
+        })
+        .collect();
+
+    Ok(Json(response))
+}
+
+pub(crate) async fn get_competency_hierarchy_for_person(
     db: &DatabaseConnection,
     diving_level: &str,
     person_id: Uuid,
@@ -1258,6 +1718,7 @@ async fn get_competency_hierarchy_for_person(
         .await
         .map_err(|e| AppError::Database(DbErr::Custom(format!("Query failed: {}", e))))?;
 
+    let today = Utc::now().naive_utc().date();
     let mut domains_with_progress: Vec<CompetencyDomainWithProgress> = Vec::new();
 
     for domain in domains {
@@ -1286,9 +1747,16 @@ async fn get_competency_hierarchy_for_person(
             for skill in skills {
                 module_stats.total += 1;
 
+                let validity_months = skill.validity_months.or(domain.validity_months);
                 let validation_info = if let Some(validation) = validations.get(&skill.id) {
                     if let Some(stage) = stages.get(&validation.stage_id) {
-                        if stage.is_final {
+                        // Une validation expirée reste affichée, "à revalider"
+                        let expires_at =
+                            SkillValidationService::expires_at(validation.validated_at, validity_months);
+                        let expired = expires_at.is_some_and(|expires| expires <= today);
+                        if expired {
+                            module_stats.to_revalidate += 1;
+                        } else if stage.is_final {
                             module_stats.validated += 1;
                         } else {
                             module_stats.in_progress += 1;
@@ -1310,6 +1778,8 @@ async fn get_competency_hierarchy_for_person(
                             validated_at: validation.validated_at.to_string(),
                             validated_by_name: validator_name,
                             notes: validation.notes.clone(),
+                            expires_at: expires_at.map(|d| d.to_string()),
+                            expired,
                         })
                     } else {
                         module_stats.not_started += 1;
@@ -1326,6 +1796,8 @@ async fn get_competency_hierarchy_for_person(
                     description: skill.description,
                     sort_order: skill.sort_order,
                     min_validator_level: skill.min_validator_level,
+                    validator_rule: skill.validator_rule,
+                    validity_months,
                     validation: validation_info,
                 });
             }
@@ -1351,6 +1823,7 @@ async fn get_competency_hierarchy_for_person(
             domain_stats.validated += module.progress.validated;
             domain_stats.in_progress += module.progress.in_progress;
             domain_stats.not_started += module.progress.not_started;
+            domain_stats.to_revalidate += module.progress.to_revalidate;
         }
         if domain_stats.total > 0 {
             domain_stats.percentage =
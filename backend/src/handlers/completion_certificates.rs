@@ -0,0 +1,76 @@
+use crate::config::Config;
+use crate::entities::prelude::*;
+use crate::entities::people;
+use crate::errors::AppError;
+use crate::middleware::acl::{check_permission, AuthUser};
+use crate::models::{CertificateVerificationResponse, Permission};
+use crate::services::CertificateService;
+use axum::{
+    extract::{Path, State},
+    http::header,
+    response::IntoResponse,
+    Extension, Json,
+};
+use sea_orm::*;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Télécharge l'attestation de fin de niveau d'un plongeur. L'attestation est
+/// émise au premier téléchargement si tous les acquis du niveau sont validés.
+pub async fn download_completion_certificate(
+    Extension(auth): Extension<AuthUser>,
+    State((db, config)): State<(Arc<DatabaseConnection>, Arc<Config>)>,
+    Path((person_id, level)): Path<(Uuid, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let viewer = People::find()
+        .filter(people::Column::Email.eq(&auth.claims.email))
+        .one(db.as_ref())
+        .await?;
+
+    // Un plongeur peut télécharger ses propres attestations
+    if viewer.as_ref().map(|v| v.id) != Some(person_id) {
+        check_permission(&auth, Permission::CompetenciesValidate)?;
+    }
+
+    let person = People::find_by_id(person_id)
+        .one(db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Personne non trouvée".to_string()))?;
+
+    let certificate = CertificateService::issue(db.as_ref(), &person, &level, viewer.map(|v| v.id)).await?;
+    let level_name = CertificateService::level_name(db.as_ref(), &certificate.level).await?;
+    let verify_url = format!(
+        "{}/api/v1/certificates/verify/{}",
+        config.magic_link.base_url.trim_end_matches('/'),
+        certificate.verification_code
+    );
+    let pdf_data = CertificateService::render(&certificate, &level_name, &config.club, &verify_url)?;
+
+    let file_name = format!(
+        "Attestation_{}_{}_{}.pdf",
+        certificate.level, person.last_name, person.first_name
+    );
+
+    let headers = [
+        (header::CONTENT_TYPE, "application/pdf".to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", file_name),
+        ),
+    ];
+
+    Ok((headers, pdf_data))
+}
+
+/// Vérification publique d'une attestation à partir du code imprimé
+pub async fn verify_completion_certificate(
+    State((db, config)): State<(Arc<DatabaseConnection>, Arc<Config>)>,
+    Path(code): Path<String>,
+) -> Result<Json<CertificateVerificationResponse>, AppError> {
+    let certificate = CertificateService::find_by_code(db.as_ref(), &code)
+        .await?
+        .ok_or(AppError::NotFound("Aucune attestation ne correspond à ce code".to_string()))?;
+    let level_name = CertificateService::level_name(db.as_ref(), &certificate.level).await?;
+
+    Ok(Json(CertificateService::to_verification(certificate, level_name, &config.club)))
+}
@@ -0,0 +1,214 @@
+use crate::entities::prelude::*;
+use crate::entities::{certifications, competency_domains, competency_skills, diving_levels};
+use crate::errors::AppError;
+use crate::middleware::acl::{check_permission, AuthUser};
+use crate::models::{
+    is_valid_level_family, CreateDivingLevelRequest, DivingLevelResponse, Permission,
+    UpdateDivingLevelRequest, ValidatorRule,
+};
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use chrono::Utc;
+use sea_orm::*;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+fn level_response(level: diving_levels::Model) -> DivingLevelResponse {
+    DivingLevelResponse {
+        id: level.id,
+        code: level.code,
+        name: level.name,
+        family: level.family,
+        rank: level.rank,
+        parent_code: level.parent_code,
+        is_instructor: level.is_instructor,
+        recycling_months: level.recycling_months,
+    }
+}
+
+fn check_family(family: &str) -> Result<(), AppError> {
+    if !is_valid_level_family(family) {
+        return Err(AppError::Validation(format!("Famille de niveau invalide: {}", family)));
+    }
+    Ok(())
+}
+
+/// Vérifie que le niveau parent existe et n'est pas le niveau lui-même
+async fn check_parent(
+    db: &DatabaseConnection,
+    code: &str,
+    parent_code: &str,
+) -> Result<(), AppError> {
+    if parent_code.eq_ignore_ascii_case(code) {
+        return Err(AppError::Validation(
+            "Un niveau ne peut pas être son propre parent".to_string(),
+        ));
+    }
+
+    DivingLevels::find()
+        .filter(diving_levels::Column::Code.eq(parent_code))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::Validation(format!("Niveau parent inconnu: {}", parent_code)))?;
+
+    Ok(())
+}
+
+/// Liste le référentiel des niveaux, du plus bas au plus haut
+pub async fn list_diving_levels(
+    State(db): State<Arc<DatabaseConnection>>,
+) -> Result<Json<Vec<DivingLevelResponse>>, AppError> {
+    let levels = DivingLevels::find()
+        .order_by_asc(diving_levels::Column::Rank)
+        .order_by_asc(diving_levels::Column::Code)
+        .all(db.as_ref())
+        .await?;
+
+    Ok(Json(levels.into_iter().map(level_response).collect()))
+}
+
+/// Ajoute un niveau, une compétence ou une qualification au référentiel
+pub async fn create_diving_level(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Json(payload): Json<CreateDivingLevelRequest>,
+) -> Result<Json<DivingLevelResponse>, AppError> {
+    check_permission(&auth, Permission::CompetenciesCreate)?;
+    payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
+    check_family(&payload.family)?;
+
+    let code = payload.code.trim().to_uppercase();
+    if code.starts_with("PREPARING_") || code.contains(',') {
+        return Err(AppError::Validation(format!("Code de niveau invalide: {}", code)));
+    }
+
+    let existing = DivingLevels::find()
+        .filter(diving_levels::Column::Code.eq(&code))
+        .one(db.as_ref())
+        .await?;
+    if existing.is_some() {
+        return Err(AppError::Validation(format!("Le niveau {} existe déjà", code)));
+    }
+
+    if let Some(parent_code) = &payload.parent_code {
+        check_parent(db.as_ref(), &code, parent_code).await?;
+    }
+
+    let now = Utc::now().naive_utc();
+    let level = diving_levels::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        code: Set(code),
+        name: Set(payload.name),
+        family: Set(payload.family),
+        rank: Set(payload.rank),
+        parent_code: Set(payload.parent_code),
+        is_instructor: Set(payload.is_instructor.unwrap_or(false)),
+        recycling_months: Set(payload.recycling_months.filter(|m| *m > 0)),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db.as_ref())
+    .await?;
+
+    Ok(Json(level_response(level)))
+}
+
+/// Met à jour un niveau (le code n'est pas modifiable)
+pub async fn update_diving_level(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateDivingLevelRequest>,
+) -> Result<Json<DivingLevelResponse>, AppError> {
+    check_permission(&auth, Permission::CompetenciesEdit)?;
+    payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let level = DivingLevels::find_by_id(id)
+        .one(db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Niveau non trouvé".to_string()))?;
+
+    if let Some(parent_code) = &payload.parent_code {
+        check_parent(db.as_ref(), &level.code, parent_code).await?;
+    }
+
+    let mut active: diving_levels::ActiveModel = level.into();
+    if let Some(name) = payload.name {
+        active.name = Set(name);
+    }
+    if let Some(family) = payload.family {
+        check_family(&family)?;
+        active.family = Set(family);
+    }
+    if let Some(rank) = payload.rank {
+        active.rank = Set(rank);
+    }
+    if let Some(parent_code) = payload.parent_code {
+        active.parent_code = Set(Some(parent_code).filter(|p| !p.is_empty()));
+    }
+    if let Some(is_instructor) = payload.is_instructor {
+        active.is_instructor = Set(is_instructor);
+    }
+    // 0 supprime l'obligation de recyclage
+    if let Some(recycling_months) = payload.recycling_months {
+        active.recycling_months = Set(Some(recycling_months).filter(|m| *m > 0));
+    }
+    active.updated_at = Set(Utc::now().naive_utc());
+
+    Ok(Json(level_response(active.update(db.as_ref()).await?)))
+}
+
+/// Supprime un niveau qui n'est plus référencé
+pub async fn delete_diving_level(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_permission(&auth, Permission::CompetenciesDelete)?;
+
+    let level = DivingLevels::find_by_id(id)
+        .one(db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Niveau non trouvé".to_string()))?;
+
+    let certifications_count = Certifications::find()
+        .filter(certifications::Column::Level.eq(&level.code))
+        .count(db.as_ref())
+        .await?;
+    let domains_count = CompetencyDomains::find()
+        .filter(competency_domains::Column::DivingLevel.eq(&level.code))
+        .count(db.as_ref())
+        .await?;
+    let skills_count = CompetencySkills::find()
+        .filter(
+            Condition::any()
+                .add(competency_skills::Column::MinValidatorLevel.eq(&level.code))
+                .add(competency_skills::Column::ValidatorRule.is_not_null()),
+        )
+        .all(db.as_ref())
+        .await?
+        .iter()
+        .filter(|skill| {
+            skill.min_validator_level == level.code
+                || skill
+                    .validator_rule
+                    .as_deref()
+                    .and_then(|rule| ValidatorRule::parse(rule).ok())
+                    .is_some_and(|rule| rule.levels().contains(&level.code.as_str()))
+        })
+        .count();
+
+    if certifications_count + domains_count + skills_count as u64 > 0 {
+        return Err(AppError::Validation(format!(
+            "Le niveau {} est utilisé ({} brevets, {} domaines, {} acquis) et ne peut pas être supprimé",
+            level.code, certifications_count, domains_count, skills_count
+        )));
+    }
+
+    level.delete(db.as_ref()).await?;
+
+    Ok(Json(serde_json::json!({ "message": "Niveau supprimé" })))
+}
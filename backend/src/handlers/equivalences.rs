@@ -0,0 +1,284 @@
+use crate::entities::prelude::*;
+use crate::entities::{certification_equivalences, diving_levels, external_certifications};
+use crate::errors::AppError;
+use crate::middleware::acl::{check_permission, AuthUser};
+use crate::models::{
+    CertificationEquivalenceResponse, CreateCertificationEquivalenceRequest,
+    CreateExternalCertificationRequest, ExternalCertificationResponse, ListEquivalencesQuery,
+    Permission, UpdateCertificationEquivalenceRequest,
+};
+use crate::services::EquivalenceService;
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use chrono::{NaiveDate, Utc};
+use sea_orm::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+type EquivalencesMap = HashMap<(String, String), Vec<certification_equivalences::Model>>;
+
+fn equivalence_response(equivalence: certification_equivalences::Model) -> CertificationEquivalenceResponse {
+    CertificationEquivalenceResponse {
+        id: equivalence.id,
+        system: equivalence.system,
+        external_code: equivalence.external_code,
+        external_name: equivalence.external_name,
+        level: equivalence.level,
+        notes: equivalence.notes,
+    }
+}
+
+fn external_response(
+    cert: external_certifications::Model,
+    equivalences: &EquivalencesMap,
+) -> ExternalCertificationResponse {
+    let matches = equivalences
+        .get(&(
+            EquivalenceService::normalize(&cert.system),
+            EquivalenceService::normalize(&cert.external_code),
+        ))
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    ExternalCertificationResponse {
+        id: cert.id,
+        person_id: cert.person_id,
+        external_name: matches.first().map(|e| e.external_name.clone()),
+        equivalent_levels: matches.iter().map(|e| e.level.clone()).collect(),
+        system: cert.system,
+        external_code: cert.external_code,
+        obtained_at: cert.obtained_at.map(|d| d.format("%Y-%m-%d").to_string()),
+        certificate_number: cert.certificate_number,
+        issued_by: cert.issued_by,
+    }
+}
+
+/// Vérifie que l'aptitude FFESSM existe dans le référentiel et retourne son code
+async fn check_level(db: &DatabaseConnection, level: &str) -> Result<String, AppError> {
+    let code = level.trim().to_uppercase();
+    DivingLevels::find()
+        .filter(diving_levels::Column::Code.eq(&code))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::Validation(format!("Niveau inconnu: {}", code)))?;
+    Ok(code)
+}
+
+/// Liste la table d'équivalences, éventuellement pour un seul système
+pub async fn list_certification_equivalences(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Query(query): Query<ListEquivalencesQuery>,
+) -> Result<Json<Vec<CertificationEquivalenceResponse>>, AppError> {
+    check_permission(&auth, Permission::CompetenciesView)?;
+
+    let mut select = CertificationEquivalences::find();
+    if let Some(system) = &query.system {
+        select = select.filter(
+            certification_equivalences::Column::System.eq(EquivalenceService::normalize(system)),
+        );
+    }
+
+    let equivalences = select
+        .order_by_asc(certification_equivalences::Column::System)
+        .order_by_asc(certification_equivalences::Column::ExternalCode)
+        .order_by_asc(certification_equivalences::Column::Level)
+        .all(db.as_ref())
+        .await?;
+
+    Ok(Json(equivalences.into_iter().map(equivalence_response).collect()))
+}
+
+/// Ajoute une équivalence entre un brevet externe et une aptitude FFESSM
+pub async fn create_certification_equivalence(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Json(payload): Json<CreateCertificationEquivalenceRequest>,
+) -> Result<Json<CertificationEquivalenceResponse>, AppError> {
+    check_permission(&auth, Permission::CompetenciesEdit)?;
+    payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let system = EquivalenceService::normalize(&payload.system);
+    let external_code = EquivalenceService::normalize(&payload.external_code);
+    let level = check_level(db.as_ref(), &payload.level).await?;
+
+    let existing = CertificationEquivalences::find()
+        .filter(certification_equivalences::Column::System.eq(&system))
+        .filter(certification_equivalences::Column::ExternalCode.eq(&external_code))
+        .filter(certification_equivalences::Column::Level.eq(&level))
+        .one(db.as_ref())
+        .await?;
+    if existing.is_some() {
+        return Err(AppError::Validation(format!(
+            "L'équivalence {} {} -> {} existe déjà",
+            system, external_code, level
+        )));
+    }
+
+    let now = Utc::now().naive_utc();
+    let equivalence = certification_equivalences::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        system: Set(system),
+        external_code: Set(external_code),
+        external_name: Set(payload.external_name),
+        level: Set(level),
+        notes: Set(payload.notes),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db.as_ref())
+    .await?;
+
+    Ok(Json(equivalence_response(equivalence)))
+}
+
+/// Met à jour une équivalence (le système et le code du brevet ne sont pas modifiables)
+pub async fn update_certification_equivalence(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateCertificationEquivalenceRequest>,
+) -> Result<Json<CertificationEquivalenceResponse>, AppError> {
+    check_permission(&auth, Permission::CompetenciesEdit)?;
+    payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let equivalence = CertificationEquivalences::find_by_id(id)
+        .one(db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Équivalence non trouvée".to_string()))?;
+
+    let mut active: certification_equivalences::ActiveModel = equivalence.into();
+    if let Some(external_name) = payload.external_name {
+        active.external_name = Set(external_name);
+    }
+    if let Some(level) = payload.level {
+        active.level = Set(check_level(db.as_ref(), &level).await?);
+    }
+    if let Some(notes) = payload.notes {
+        active.notes = Set(Some(notes).filter(|n| !n.is_empty()));
+    }
+    active.updated_at = Set(Utc::now().naive_utc());
+
+    Ok(Json(equivalence_response(active.update(db.as_ref()).await?)))
+}
+
+/// Supprime une équivalence
+pub async fn delete_certification_equivalence(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_permission(&auth, Permission::CompetenciesDelete)?;
+
+    let result = CertificationEquivalences::delete_by_id(id)
+        .exec(db.as_ref())
+        .await?;
+    if result.rows_affected == 0 {
+        return Err(AppError::NotFound("Équivalence non trouvée".to_string()));
+    }
+
+    Ok(Json(serde_json::json!({ "message": "Équivalence supprimée" })))
+}
+
+/// Liste les brevets externes d'un plongeur avec les aptitudes reconnues
+pub async fn list_person_external_certifications(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(person_id): Path<Uuid>,
+) -> Result<Json<Vec<ExternalCertificationResponse>>, AppError> {
+    check_permission(&auth, Permission::UsersView)?;
+
+    let certs = ExternalCertifications::find()
+        .filter(external_certifications::Column::PersonId.eq(person_id))
+        .order_by_asc(external_certifications::Column::System)
+        .order_by_asc(external_certifications::Column::ExternalCode)
+        .all(db.as_ref())
+        .await?;
+    let equivalences = EquivalenceService::equivalences_map(db.as_ref()).await?;
+
+    Ok(Json(
+        certs
+            .into_iter()
+            .map(|c| external_response(c, &equivalences))
+            .collect(),
+    ))
+}
+
+/// Enregistre un brevet d'un autre système pour un plongeur
+pub async fn create_person_external_certification(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(person_id): Path<Uuid>,
+    Json(payload): Json<CreateExternalCertificationRequest>,
+) -> Result<Json<ExternalCertificationResponse>, AppError> {
+    check_permission(&auth, Permission::UsersEdit)?;
+    payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
+
+    People::find_by_id(person_id)
+        .one(db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Personne non trouvée".to_string()))?;
+
+    let system = EquivalenceService::normalize(&payload.system);
+    let external_code = EquivalenceService::normalize(&payload.external_code);
+
+    let existing = ExternalCertifications::find()
+        .filter(external_certifications::Column::PersonId.eq(person_id))
+        .filter(external_certifications::Column::System.eq(&system))
+        .filter(external_certifications::Column::ExternalCode.eq(&external_code))
+        .one(db.as_ref())
+        .await?;
+    if existing.is_some() {
+        return Err(AppError::Validation(format!(
+            "Le brevet {} {} est déjà enregistré",
+            system, external_code
+        )));
+    }
+
+    let obtained_at = payload
+        .obtained_at
+        .as_deref()
+        .map(|d| {
+            NaiveDate::parse_from_str(d, "%Y-%m-%d")
+                .map_err(|_| AppError::Validation("Format de date invalide (YYYY-MM-DD)".to_string()))
+        })
+        .transpose()?;
+
+    let cert = external_certifications::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        person_id: Set(person_id),
+        system: Set(system),
+        external_code: Set(external_code),
+        obtained_at: Set(obtained_at),
+        certificate_number: Set(payload.certificate_number),
+        issued_by: Set(payload.issued_by),
+        created_at: Set(Utc::now().naive_utc()),
+    }
+    .insert(db.as_ref())
+    .await?;
+    let equivalences = EquivalenceService::equivalences_map(db.as_ref()).await?;
+
+    Ok(Json(external_response(cert, &equivalences)))
+}
+
+/// Supprime un brevet externe
+pub async fn delete_external_certification(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_permission(&auth, Permission::UsersEdit)?;
+
+    let result = ExternalCertifications::delete_by_id(id)
+        .exec(db.as_ref())
+        .await?;
+    if result.rows_affected == 0 {
+        return Err(AppError::NotFound("Brevet externe non trouvé".to_string()));
+    }
+
+    Ok(Json(serde_json::json!({ "message": "Brevet externe supprimé" })))
+}
@@ -1,30 +1,58 @@
 use axum::{
-    extract::{Multipart, Path, State},
+    extract::{Multipart, Path, Query, State},
     http::{header, StatusCode},
     response::IntoResponse,
     Json,
 };
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, Set, TransactionTrait,
+};
+use std::collections::HashSet;
+use std::io::Write;
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::entities::{
-    level_documents::{self, LevelDocumentInfo},
-    skill_document_positions::{self, SkillPositionInput, SkillPositionWithInfo},
+    level_document_generations,
+    level_document_versions::{self, CopyPositionsRequest, CopyPositionsResult, LevelDocumentVersionInfo},
+    level_documents::{self, BatchFormat, BatchGenerateRequest, GenerateQuery, LevelDocumentInfo},
+    skill_document_fields::{self, SkillFieldInput, TemplateFieldInfo},
+    skill_document_positions::{
+        self, DetectedSkillPosition, PositionDetectionResult, SkillPositionInput,
+        SkillPositionWithInfo, UndetectedSkill,
+    },
+    certifications::{self, STATUS_PREPARING},
     competency_skills, competency_modules, competency_domains, people,
 };
 use crate::errors::AppError;
-use crate::services::PdfGenerator;
+use crate::services::{acroform, position_detection, LevelDocumentService, NewTemplate, PdfGenerator};
+
+// Nombre maximum de personnes par génération groupée (PDF construits en mémoire)
+const MAX_BATCH_SIZE: usize = 100;
+
+// Partie de nom de fichier sûre: lettres, chiffres, '-' et '_' uniquement
+fn file_name_part(value: &str) -> String {
+    value
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+        .collect()
+}
 
 // Liste tous les documents par niveau
 pub async fn list_level_documents(
     State(db): State<Arc<DatabaseConnection>>,
 ) -> Result<Json<Vec<LevelDocumentInfo>>, AppError> {
     let docs = level_documents::Entity::find()
+        .find_also_related(level_document_versions::Entity)
         .all(db.as_ref())
         .await?;
     
-    let infos: Vec<LevelDocumentInfo> = docs.into_iter().map(|d| d.into()).collect();
+    let infos: Vec<LevelDocumentInfo> = docs
+        .into_iter()
+        .filter_map(|(doc, version)| version.map(|v| LevelDocumentInfo::new(doc, &v)))
+        .collect();
     Ok(Json(infos))
 }
 
@@ -33,38 +61,46 @@ pub async fn get_level_document(
     State(db): State<Arc<DatabaseConnection>>,
     Path(level): Path<String>,
 ) -> Result<Json<LevelDocumentInfo>, AppError> {
-    let doc = level_documents::Entity::find()
-        .filter(level_documents::Column::Level.eq(&level))
-        .one(db.as_ref())
-        .await?
-        .ok_or_else(|| AppError::NotFound(format!("Document not found for level {}", level)))?;
+    let (doc, version) = LevelDocumentService::current(db.as_ref(), &level).await?;
     
-    Ok(Json(doc.into()))
+    Ok(Json(LevelDocumentInfo::new(doc, &version)))
 }
 
-// Télécharge le fichier PDF d'un niveau
+// Télécharge le fichier PDF actif d'un niveau
 pub async fn download_level_document(
     State(db): State<Arc<DatabaseConnection>>,
     Path(level): Path<String>,
 ) -> Result<impl IntoResponse, AppError> {
-    let doc = level_documents::Entity::find()
-        .filter(level_documents::Column::Level.eq(&level))
-        .one(db.as_ref())
-        .await?
-        .ok_or_else(|| AppError::NotFound(format!("Document not found for level {}", level)))?;
+    let (_, version) = LevelDocumentService::current(db.as_ref(), &level).await?;
+    
+    Ok(pdf_attachment(version))
+}
+
+// Télécharge une version donnée du modèle d'un niveau
+pub async fn download_level_document_version(
+    State(db): State<Arc<DatabaseConnection>>,
+    Path((level, version)): Path<(String, i32)>,
+) -> Result<impl IntoResponse, AppError> {
+    let version = LevelDocumentService::version(db.as_ref(), &level, version).await?;
     
+    Ok(pdf_attachment(version))
+}
+
+fn pdf_attachment(version: level_document_versions::Model) -> impl IntoResponse {
     let headers = [
         (header::CONTENT_TYPE, "application/pdf".to_string()),
         (
             header::CONTENT_DISPOSITION,
-            format!("attachment; filename=\"{}\"", doc.file_name),
+            format!("attachment; filename=\"{}\"", version.file_name),
         ),
     ];
     
-    Ok((headers, doc.file_data))
+    (headers, version.file_data)
 }
 
-// Upload un document pour un niveau
+// Upload une nouvelle version du document d'un niveau, qui devient active.
+// Champs multipart: "file", "notes" (optionnel), "copy_positions" ("true" pour
+// reprendre les positions de la version précédente)
 pub async fn upload_level_document(
     State(db): State<Arc<DatabaseConnection>>,
     Path(level): Path<String>,
@@ -72,17 +108,32 @@ pub async fn upload_level_document(
 ) -> Result<Json<LevelDocumentInfo>, AppError> {
     let mut file_name: Option<String> = None;
     let mut file_data: Option<Vec<u8>> = None;
+    let mut notes: Option<String> = None;
+    let mut copy_positions = false;
     
     while let Some(field) = multipart.next_field().await.map_err(|e| {
         AppError::Validation(format!("Failed to read multipart: {}", e))
     })? {
         let name = field.name().unwrap_or("").to_string();
         
-        if name == "file" {
-            file_name = field.file_name().map(|s| s.to_string());
-            file_data = Some(field.bytes().await.map_err(|e| {
-                AppError::Validation(format!("Failed to read file: {}", e))
-            })?.to_vec());
+        match name.as_str() {
+            "file" => {
+                file_name = field.file_name().map(|s| s.to_string());
+                file_data = Some(field.bytes().await.map_err(|e| {
+                    AppError::Validation(format!("Failed to read file: {}", e))
+                })?.to_vec());
+            }
+            "notes" | "copy_positions" => {
+                let value = field.text().await.map_err(|e| {
+                    AppError::Validation(format!("Failed to read field {}: {}", name, e))
+                })?;
+                if name == "notes" {
+                    notes = Some(value).filter(|n| !n.trim().is_empty());
+                } else {
+                    copy_positions = matches!(value.trim(), "true" | "1" | "on");
+                }
+            }
+            _ => {}
         }
     }
     
@@ -92,37 +143,86 @@ pub async fn upload_level_document(
     // Vérifier que c'est un PDF et compter les pages
     let page_count = count_pdf_pages(&file_data)?;
     
-    let now = chrono::Utc::now().naive_utc();
+    let (doc, version) = LevelDocumentService::add_version(
+        db.as_ref(),
+        &level,
+        NewTemplate {
+            file_name,
+            file_data,
+            page_count,
+            notes,
+        },
+        copy_positions,
+    )
+    .await?;
     
-    // Vérifier si un document existe déjà pour ce niveau
-    let existing = level_documents::Entity::find()
-        .filter(level_documents::Column::Level.eq(&level))
-        .one(db.as_ref())
+    Ok(Json(LevelDocumentInfo::new(doc, &version)))
+}
+
+// Liste les versions du document d'un niveau, de la plus récente à la plus ancienne
+pub async fn list_level_document_versions(
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(level): Path<String>,
+) -> Result<Json<Vec<LevelDocumentVersionInfo>>, AppError> {
+    let (doc, _) = LevelDocumentService::current(db.as_ref(), &level).await?;
+    
+    let versions = level_document_versions::Entity::find()
+        .filter(level_document_versions::Column::Level.eq(&level))
+        .order_by_desc(level_document_versions::Column::Version)
+        .all(db.as_ref())
         .await?;
     
-    let model = if let Some(existing) = existing {
-        // Mise à jour
-        let mut active: level_documents::ActiveModel = existing.into();
-        active.file_name = Set(file_name);
-        active.file_data = Set(file_data);
-        active.page_count = Set(page_count);
-        active.updated_at = Set(now);
-        active.update(db.as_ref()).await?
-    } else {
-        // Création
-        let active = level_documents::ActiveModel {
-            id: Set(Uuid::new_v4()),
-            level: Set(level),
-            file_name: Set(file_name),
-            file_data: Set(file_data),
-            page_count: Set(page_count),
-            created_at: Set(now),
-            updated_at: Set(now),
-        };
-        active.insert(db.as_ref()).await?
-    };
+    let mut result = Vec::new();
+    for version in versions {
+        let positions_count = skill_document_positions::Entity::find()
+            .filter(skill_document_positions::Column::VersionId.eq(version.id))
+            .count(db.as_ref())
+            .await?;
+        let generated_count = level_document_generations::Entity::find()
+            .filter(level_document_generations::Column::VersionId.eq(version.id))
+            .count(db.as_ref())
+            .await?;
+        
+        result.push(LevelDocumentVersionInfo {
+            id: version.id,
+            is_current: doc.current_version_id == Some(version.id),
+            level: version.level,
+            version: version.version,
+            file_name: version.file_name,
+            page_count: version.page_count,
+            notes: version.notes,
+            positions_count,
+            generated_count,
+            created_at: version.created_at,
+        });
+    }
+    
+    Ok(Json(result))
+}
+
+// Réactive une version (retour arrière) avec ses propres positions
+pub async fn activate_level_document_version(
+    State(db): State<Arc<DatabaseConnection>>,
+    Path((level, version)): Path<(String, i32)>,
+) -> Result<Json<LevelDocumentInfo>, AppError> {
+    let (doc, version) = LevelDocumentService::activate(db.as_ref(), &level, version).await?;
+    
+    Ok(Json(LevelDocumentInfo::new(doc, &version)))
+}
+
+// Recopie les positions d'une autre version vers la version indiquée
+pub async fn copy_level_document_positions(
+    State(db): State<Arc<DatabaseConnection>>,
+    Path((level, version)): Path<(String, i32)>,
+    Json(input): Json<CopyPositionsRequest>,
+) -> Result<Json<CopyPositionsResult>, AppError> {
+    let target = LevelDocumentService::version(db.as_ref(), &level, version).await?;
+    let source = LevelDocumentService::version(db.as_ref(), &level, input.from_version).await?;
+    
+    let result =
+        LevelDocumentService::copy_positions(db.as_ref(), &source, &target, input.overwrite).await?;
     
-    Ok(Json(model.into()))
+    Ok(Json(result))
 }
 
 // Supprime un document pour un niveau
@@ -138,22 +238,23 @@ pub async fn delete_level_document(
     
     level_documents::Entity::delete_by_id(doc.id).exec(db.as_ref()).await?;
     
-    // Supprimer aussi les positions associées
-    skill_document_positions::Entity::delete_many()
-        .filter(skill_document_positions::Column::Level.eq(&level))
+    // Supprimer aussi les versions (les positions et l'historique suivent en cascade)
+    level_document_versions::Entity::delete_many()
+        .filter(level_document_versions::Column::Level.eq(&level))
         .exec(db.as_ref())
         .await?;
     
     Ok(StatusCode::NO_CONTENT)
 }
 
-// Liste les positions des acquis pour un niveau
+// Liste les positions des acquis sur la version active d'un niveau
 pub async fn list_skill_positions(
     State(db): State<Arc<DatabaseConnection>>,
     Path(level): Path<String>,
 ) -> Result<Json<Vec<SkillPositionWithInfo>>, AppError> {
+    let (_, version) = LevelDocumentService::current(db.as_ref(), &level).await?;
     let positions = skill_document_positions::Entity::find()
-        .filter(skill_document_positions::Column::Level.eq(&level))
+        .filter(skill_document_positions::Column::VersionId.eq(version.id))
         .all(db.as_ref())
         .await?;
     
@@ -197,13 +298,14 @@ pub async fn list_skill_positions(
     Ok(Json(result))
 }
 
-// Définit ou met à jour la position d'un acquis
+// Définit ou met à jour la position d'un acquis sur la version active
 pub async fn set_skill_position(
     State(db): State<Arc<DatabaseConnection>>,
     Path(level): Path<String>,
     Json(input): Json<SkillPositionInput>,
 ) -> Result<Json<skill_document_positions::Model>, AppError> {
     let now = chrono::Utc::now().naive_utc();
+    let (_, version) = LevelDocumentService::current(db.as_ref(), &level).await?;
     
     // Vérifier que le skill existe
     competency_skills::Entity::find_by_id(input.skill_id)
@@ -214,7 +316,7 @@ pub async fn set_skill_position(
     // Vérifier si une position existe déjà
     let existing = skill_document_positions::Entity::find()
         .filter(skill_document_positions::Column::SkillId.eq(input.skill_id))
-        .filter(skill_document_positions::Column::Level.eq(&level))
+        .filter(skill_document_positions::Column::VersionId.eq(version.id))
         .one(db.as_ref())
         .await?;
     
@@ -233,6 +335,7 @@ pub async fn set_skill_position(
             id: Set(Uuid::new_v4()),
             skill_id: Set(input.skill_id),
             level: Set(level),
+            version_id: Set(version.id),
             page: Set(input.page),
             x: Set(input.x),
             y: Set(input.y),
@@ -248,26 +351,28 @@ pub async fn set_skill_position(
     Ok(Json(model))
 }
 
-// Supprime la position d'un acquis
+// Supprime la position d'un acquis sur la version active
 pub async fn delete_skill_position(
     State(db): State<Arc<DatabaseConnection>>,
     Path((level, skill_id)): Path<(String, Uuid)>,
 ) -> Result<StatusCode, AppError> {
+    let (_, version) = LevelDocumentService::current(db.as_ref(), &level).await?;
     skill_document_positions::Entity::delete_many()
         .filter(skill_document_positions::Column::SkillId.eq(skill_id))
-        .filter(skill_document_positions::Column::Level.eq(&level))
+        .filter(skill_document_positions::Column::VersionId.eq(version.id))
         .exec(db.as_ref())
         .await?;
     
     Ok(StatusCode::NO_CONTENT)
 }
 
-// Met à jour plusieurs positions en batch
+// Met à jour plusieurs positions en batch sur la version active
 pub async fn batch_update_positions(
     State(db): State<Arc<DatabaseConnection>>,
     Path(level): Path<String>,
     Json(positions): Json<Vec<SkillPositionInput>>,
 ) -> Result<Json<Vec<skill_document_positions::Model>>, AppError> {
+    let (_, version) = LevelDocumentService::current(db.as_ref(), &level).await?;
     let mut results = Vec::new();
     
     for input in positions {
@@ -275,7 +380,7 @@ pub async fn batch_update_positions(
         
         let existing = skill_document_positions::Entity::find()
             .filter(skill_document_positions::Column::SkillId.eq(input.skill_id))
-            .filter(skill_document_positions::Column::Level.eq(&level))
+            .filter(skill_document_positions::Column::VersionId.eq(version.id))
             .one(db.as_ref())
             .await?;
         
@@ -294,6 +399,7 @@ pub async fn batch_update_positions(
                 id: Set(Uuid::new_v4()),
                 skill_id: Set(input.skill_id),
                 level: Set(level.clone()),
+                version_id: Set(version.id),
                 page: Set(input.page),
                 x: Set(input.x),
                 y: Set(input.y),
@@ -312,10 +418,216 @@ pub async fn batch_update_positions(
     Ok(Json(results))
 }
 
+// Liste les champs de formulaire de la version active et les acquis associés
+pub async fn list_template_fields(
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(level): Path<String>,
+) -> Result<Json<Vec<TemplateFieldInfo>>, AppError> {
+    let (_, version) = LevelDocumentService::current(db.as_ref(), &level).await?;
+    let doc = lopdf::Document::load_mem(&version.file_data)
+        .map_err(|e| AppError::Validation(format!("Invalid PDF: {}", e)))?;
+    
+    let mappings: std::collections::HashMap<String, (skill_document_fields::Model, Option<competency_skills::Model>)> =
+        skill_document_fields::Entity::find()
+            .filter(skill_document_fields::Column::VersionId.eq(version.id))
+            .find_also_related(competency_skills::Entity)
+            .all(db.as_ref())
+            .await?
+            .into_iter()
+            .map(|(field, skill)| (field.field_name.clone(), (field, skill)))
+            .collect();
+    
+    let fields = acroform::list_fields(&doc)
+        .into_iter()
+        .map(|field| {
+            let mapping = mappings.get(&field.name);
+            TemplateFieldInfo {
+                skill_id: mapping.map(|(m, _)| m.skill_id),
+                skill_name: mapping.and_then(|(_, skill)| skill.as_ref().map(|s| s.name.clone())),
+                content: mapping.map(|(m, _)| m.content.clone()),
+                field_name: field.name,
+                field_type: field.field_type,
+                page: field.page,
+                rect: field.rect,
+            }
+        })
+        .collect();
+    
+    Ok(Json(fields))
+}
+
+// Remplace les associations champ/acquis de la version active
+pub async fn set_template_fields(
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(level): Path<String>,
+    Json(inputs): Json<Vec<SkillFieldInput>>,
+) -> Result<Json<Vec<skill_document_fields::Model>>, AppError> {
+    let (_, version) = LevelDocumentService::current(db.as_ref(), &level).await?;
+    let doc = lopdf::Document::load_mem(&version.file_data)
+        .map_err(|e| AppError::Validation(format!("Invalid PDF: {}", e)))?;
+    let text_fields: HashSet<String> = acroform::list_fields(&doc)
+        .into_iter()
+        .filter(|f| f.field_type.as_deref().is_none_or(|t| t == "Tx"))
+        .map(|f| f.name)
+        .collect();
+    
+    let mut seen = HashSet::new();
+    for input in &inputs {
+        if !text_fields.contains(&input.field_name) {
+            return Err(AppError::Validation(format!(
+                "Champ texte introuvable dans le modèle: {}",
+                input.field_name
+            )));
+        }
+        if !skill_document_fields::CONTENTS.contains(&input.content.as_str()) {
+            return Err(AppError::Validation(format!(
+                "Contenu de champ invalide: {} (attendu: {})",
+                input.content,
+                skill_document_fields::CONTENTS.join(", ")
+            )));
+        }
+        if !seen.insert(input.field_name.as_str()) {
+            return Err(AppError::Validation(format!(
+                "Champ associé plusieurs fois: {}",
+                input.field_name
+            )));
+        }
+    }
+    
+    let skill_ids: HashSet<Uuid> = inputs.iter().map(|i| i.skill_id).collect();
+    let found = competency_skills::Entity::find()
+        .filter(competency_skills::Column::Id.is_in(skill_ids.iter().copied()))
+        .count(db.as_ref())
+        .await?;
+    if found != skill_ids.len() as u64 {
+        return Err(AppError::NotFound("Skill not found".to_string()));
+    }
+    
+    let now = chrono::Utc::now().naive_utc();
+    let txn = db.begin().await?;
+    skill_document_fields::Entity::delete_many()
+        .filter(skill_document_fields::Column::VersionId.eq(version.id))
+        .exec(&txn)
+        .await?;
+    let mut results = Vec::new();
+    for input in inputs {
+        let model = skill_document_fields::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            version_id: Set(version.id),
+            skill_id: Set(input.skill_id),
+            level: Set(level.clone()),
+            field_name: Set(input.field_name),
+            content: Set(input.content),
+            created_at: Set(now),
+            updated_at: Set(now),
+        }
+        .insert(&txn)
+        .await?;
+        results.push(model);
+    }
+    txn.commit().await?;
+    
+    Ok(Json(results))
+}
+
+#[derive(serde::Deserialize)]
+pub struct DetectPositionsQuery {
+    /// Version du modèle à analyser (version active par défaut)
+    pub version: Option<i32>,
+}
+
+// Propose une position pour chaque acquis du niveau en cherchant son libellé
+// dans le texte du PDF. Rien n'est enregistré: les suggestions sont à
+// vérifier puis à envoyer à batch_update_positions.
+pub async fn detect_skill_positions(
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(level): Path<String>,
+    Query(query): Query<DetectPositionsQuery>,
+) -> Result<Json<PositionDetectionResult>, AppError> {
+    let version = match query.version {
+        Some(version) => LevelDocumentService::version(db.as_ref(), &level, version).await?,
+        None => LevelDocumentService::current(db.as_ref(), &level).await?.1,
+    };
+    
+    // Acquis du niveau dans l'ordre du référentiel
+    let domains = competency_domains::Entity::find()
+        .filter(competency_domains::Column::DivingLevel.eq(&level))
+        .order_by_asc(competency_domains::Column::SortOrder)
+        .all(db.as_ref())
+        .await?;
+    let mut skills = Vec::new();
+    for domain in &domains {
+        let modules = competency_modules::Entity::find()
+            .filter(competency_modules::Column::DomainId.eq(domain.id))
+            .order_by_asc(competency_modules::Column::SortOrder)
+            .all(db.as_ref())
+            .await?;
+        for module in modules {
+            let module_skills = competency_skills::Entity::find()
+                .filter(competency_skills::Column::ModuleId.eq(module.id))
+                .order_by_asc(competency_skills::Column::SortOrder)
+                .all(db.as_ref())
+                .await?;
+            for skill in module_skills {
+                skills.push((skill, module.name.clone(), domain.name.clone()));
+            }
+        }
+    }
+    
+    let placed: std::collections::HashSet<Uuid> = skill_document_positions::Entity::find()
+        .filter(skill_document_positions::Column::VersionId.eq(version.id))
+        .all(db.as_ref())
+        .await?
+        .into_iter()
+        .map(|p| p.skill_id)
+        .collect();
+    
+    let doc = lopdf::Document::load_mem(&version.file_data)
+        .map_err(|e| AppError::Validation(format!("Invalid PDF: {}", e)))?;
+    let labels: Vec<String> = skills.iter().map(|(skill, _, _)| skill.name.clone()).collect();
+    let detected = position_detection::detect_positions(&doc, &labels)?;
+    
+    let mut suggestions = Vec::new();
+    let mut unmatched = Vec::new();
+    for ((skill, module_name, domain_name), found) in skills.into_iter().zip(detected) {
+        match found {
+            Some(found) => suggestions.push(DetectedSkillPosition {
+                already_placed: placed.contains(&skill.id),
+                skill_id: skill.id,
+                skill_name: skill.name,
+                skill_number: skill.sort_order,
+                module_name,
+                domain_name,
+                page: found.page,
+                x: found.x,
+                y: found.y,
+                width: found.width,
+                height: found.height,
+                font_size: found.font_size,
+                matched_text: found.matched_text,
+                confidence: found.confidence,
+            }),
+            None => unmatched.push(UndetectedSkill {
+                skill_id: skill.id,
+                skill_name: skill.name,
+                module_name,
+                domain_name,
+            }),
+        }
+    }
+    
+    Ok(Json(PositionDetectionResult {
+        version: version.version,
+        suggestions,
+        unmatched,
+    }))
+}
+
 // Génère un PDF rempli pour une personne
 pub async fn generate_filled_document(
     State(db): State<Arc<DatabaseConnection>>,
     Path((level, person_id)): Path<(String, Uuid)>,
+    Query(query): Query<GenerateQuery>,
 ) -> Result<impl IntoResponse, AppError> {
     // Récupérer la personne pour le nom du fichier
     let person = people::Entity::find_by_id(person_id)
@@ -323,14 +635,17 @@ pub async fn generate_filled_document(
         .await?
         .ok_or_else(|| AppError::NotFound("Person not found".to_string()))?;
     
-    // Générer le PDF
-    let pdf_data = PdfGenerator::generate_filled_document(db.as_ref(), person_id, &level).await?;
+    // Générer le PDF avec la version active et garder trace de la version utilisée
+    let (_, version) = LevelDocumentService::current(db.as_ref(), &level).await?;
+    let pdf_data = PdfGenerator::generate_filled_document(db.as_ref(), person_id, &version, query.flatten).await?;
+    LevelDocumentService::record_generation(db.as_ref(), person_id, &version).await?;
     
     let file_name = format!(
-        "Competences_{}_{}_{}_{}.pdf",
+        "Competences_{}_v{}_{}_{}_{}.pdf",
         level,
-        person.last_name,
-        person.first_name,
+        version.version,
+        file_name_part(&person.last_name),
+        file_name_part(&person.first_name),
         chrono::Utc::now().format("%Y%m%d")
     );
     
@@ -345,18 +660,112 @@ pub async fn generate_filled_document(
     Ok((headers, pdf_data))
 }
 
+// Génère les livrets de plusieurs personnes (par exemple tous les candidats
+// d'un examen), en une archive ZIP ou en un PDF unique avec signets
+pub async fn generate_filled_documents_batch(
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(level): Path<String>,
+    Json(payload): Json<BatchGenerateRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let mut person_ids = payload.person_ids;
+    if payload.all_preparing {
+        let preparing: Vec<Uuid> = certifications::Entity::find()
+            .filter(certifications::Column::Level.eq(&level))
+            .filter(certifications::Column::Status.eq(STATUS_PREPARING))
+            .all(db.as_ref())
+            .await?
+            .into_iter()
+            .map(|c| c.person_id)
+            .collect();
+        // Candidats dans l'ordre alphabétique
+        let preparing = people::Entity::find()
+            .filter(people::Column::Id.is_in(preparing))
+            .order_by_asc(people::Column::LastName)
+            .order_by_asc(people::Column::FirstName)
+            .all(db.as_ref())
+            .await?;
+        person_ids.extend(preparing.into_iter().map(|p| p.id));
+    }
+    let mut seen = HashSet::new();
+    person_ids.retain(|id| seen.insert(*id));
+    if person_ids.is_empty() {
+        return Err(AppError::Validation("Aucune personne à traiter".to_string()));
+    }
+    if person_ids.len() > MAX_BATCH_SIZE {
+        return Err(AppError::Validation(format!(
+            "Trop de personnes à traiter ({}, maximum {})",
+            person_ids.len(),
+            MAX_BATCH_SIZE
+        )));
+    }
+    
+    let (_, version) = LevelDocumentService::current(db.as_ref(), &level).await?;
+    let documents = PdfGenerator::generate_filled_documents(db.as_ref(), &person_ids, &version, payload.flatten).await?;
+    LevelDocumentService::record_generations(db.as_ref(), &person_ids, &version).await?;
+    
+    let date = chrono::Utc::now().format("%Y%m%d");
+    let (content_type, file_name, data) = match payload.format {
+        BatchFormat::Pdf => {
+            let named: Vec<(String, Vec<u8>)> = documents
+                .into_iter()
+                .map(|(person, pdf)| (format!("{} {}", person.last_name, person.first_name), pdf))
+                .collect();
+            let merged = PdfGenerator::merge_documents(&named)?;
+            let file_name = format!("Competences_{}_v{}_{}.pdf", level, version.version, date);
+            ("application/pdf", file_name, merged)
+        }
+        BatchFormat::Zip => {
+            let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+            let options = zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Deflated);
+            let mut names = HashSet::new();
+            for (person, pdf) in documents {
+                let base = format!(
+                    "Competences_{}_v{}_{}_{}",
+                    level,
+                    version.version,
+                    file_name_part(&person.last_name),
+                    file_name_part(&person.first_name)
+                );
+                // Homonymes: suffixe numérique pour garder des noms uniques
+                let mut name = format!("{}.pdf", base);
+                let mut n = 2;
+                while !names.insert(name.clone()) {
+                    name = format!("{}_{}.pdf", base, n);
+                    n += 1;
+                }
+                zip.start_file(name, options)
+                    .and_then(|_| zip.write_all(&pdf).map_err(Into::into))
+                    .map_err(|e| AppError::Internal(format!("Failed to write ZIP: {}", e)))?;
+            }
+            let archive = zip
+                .finish()
+                .map_err(|e| AppError::Internal(format!("Failed to write ZIP: {}", e)))?
+                .into_inner();
+            let file_name = format!("Competences_{}_v{}_{}.zip", level, version.version, date);
+            ("application/zip", file_name, archive)
+        }
+    };
+    
+    let headers = [
+        (header::CONTENT_TYPE, content_type.to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", file_name),
+        ),
+    ];
+    
+    Ok((headers, data))
+}
+
 // Récupère les dimensions d'une page du document
 pub async fn get_document_page_info(
     State(db): State<Arc<DatabaseConnection>>,
     Path((level, page)): Path<(String, i32)>,
 ) -> Result<Json<PageInfo>, AppError> {
-    let doc = level_documents::Entity::find()
-        .filter(level_documents::Column::Level.eq(&level))
-        .one(db.as_ref())
-        .await?
-        .ok_or_else(|| AppError::NotFound(format!("Document not found for level {}", level)))?;
+    let (_, version) = LevelDocumentService::current(db.as_ref(), &level).await?;
     
-    let (width, height) = crate::services::pdf_generator::get_page_dimensions(&doc.file_data, page as usize)?;
+    let (width, height) = crate::services::pdf_generator::get_page_dimensions(&version.file_data, page as usize)?;
     
     Ok(Json(PageInfo { page, width, height }))
 }
@@ -0,0 +1,111 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::Utc;
+use sea_orm::DatabaseConnection;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::errors::AppError;
+use crate::handlers::competency_hierarchy::CompetencyState;
+use crate::middleware::acl::{check_permission, current_person, AuthUser};
+use crate::models::{
+    CreateMentorAssignmentRequest, DigestRunResponse, MentorAssignmentResponse,
+    MentorAssignmentsQuery, MentorDashboard, MentorDashboardQuery, Permission,
+    UpdateMentorAssignmentRequest,
+};
+use crate::services::MentorService;
+
+// ============================================================================
+// MENTOR ASSIGNMENT HANDLERS
+// ============================================================================
+
+pub async fn list_mentor_assignments(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Query(query): Query<MentorAssignmentsQuery>,
+) -> Result<Json<Vec<MentorAssignmentResponse>>, AppError> {
+    check_permission(&auth, Permission::CompetenciesView)?;
+
+    Ok(Json(MentorService::list(db.as_ref(), query).await?))
+}
+
+pub async fn create_mentor_assignment(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Json(payload): Json<CreateMentorAssignmentRequest>,
+) -> Result<Json<MentorAssignmentResponse>, AppError> {
+    check_permission(&auth, Permission::CompetenciesEdit)?;
+    payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
+
+    Ok(Json(MentorService::create(db.as_ref(), payload).await?))
+}
+
+pub async fn update_mentor_assignment(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateMentorAssignmentRequest>,
+) -> Result<Json<MentorAssignmentResponse>, AppError> {
+    check_permission(&auth, Permission::CompetenciesEdit)?;
+
+    Ok(Json(
+        MentorService::update(db.as_ref(), id, payload.mentor_id).await?,
+    ))
+}
+
+pub async fn delete_mentor_assignment(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    check_permission(&auth, Permission::CompetenciesEdit)?;
+
+    MentorService::delete(db.as_ref(), id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// MENTOR DASHBOARD HANDLERS
+// ============================================================================
+
+/// Progression et prochaines sessions des élèves dont l'utilisateur courant
+/// est le référent
+pub async fn get_my_mentees(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Query(query): Query<MentorDashboardQuery>,
+) -> Result<Json<MentorDashboard>, AppError> {
+    check_permission(&auth, Permission::CompetenciesValidate)?;
+    if query.inactive_weeks < 0 {
+        return Err(AppError::Validation(
+            "Le nombre de semaines doit être positif".to_string(),
+        ));
+    }
+
+    let mentor = current_person(db.as_ref(), &auth).await?;
+    Ok(Json(
+        MentorService::dashboard(db.as_ref(), mentor.id, query.inactive_weeks).await?,
+    ))
+}
+
+/// Envoie immédiatement les alertes de stagnation en attente aux référents
+pub async fn send_mentor_stall_alerts(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<Arc<CompetencyState>>,
+) -> Result<Json<DigestRunResponse>, AppError> {
+    check_permission(&auth, Permission::EmailsSend)?;
+
+    Ok(Json(
+        MentorService::send_stall_alerts(
+            state.db.as_ref(),
+            &state.email_service,
+            Utc::now().naive_utc(),
+            state.mentor_stall_weeks,
+        )
+        .await?,
+    ))
+}
@@ -10,6 +10,20 @@ pub mod groups;
 pub mod level_documents;
 pub mod palanquees;
 pub mod sorties;
+pub mod promotions;
+pub mod competency_frameworks;
+pub mod training_plans;
+pub mod certifications;
+pub mod diving_levels;
+pub mod equivalences;
+pub mod completion_certificates;
+pub mod cohort_progress;
+pub mod validation_evidence;
+pub mod notifications;
+pub mod quizzes;
+pub mod mentors;
+pub mod teaching_qualifications;
+pub mod validation_requests;
 
 pub use auth::*;
 pub use sessions::*;
@@ -23,4 +37,18 @@ pub use groups::*;
 pub use level_documents::*;
 pub use palanquees::*;
 pub use sorties::*;
+pub use promotions::*;
+pub use competency_frameworks::*;
+pub use training_plans::*;
+pub use certifications::*;
+pub use diving_levels::*;
+pub use equivalences::*;
+pub use completion_certificates::*;
+pub use cohort_progress::*;
+pub use validation_evidence::*;
+pub use notifications::*;
+pub use quizzes::*;
+pub use mentors::*;
+pub use teaching_qualifications::*;
+pub use validation_requests::*;
 
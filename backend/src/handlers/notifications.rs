@@ -0,0 +1,55 @@
+use axum::{extract::State, Extension, Json};
+use chrono::Utc;
+use sea_orm::*;
+use std::sync::Arc;
+
+use crate::errors::AppError;
+use crate::handlers::competency_hierarchy::CompetencyState;
+use crate::middleware::acl::{check_permission, current_person, AuthUser};
+use crate::models::{
+    DigestRunResponse, NotificationPreferencesResponse, Permission,
+    UpdateNotificationPreferencesRequest,
+};
+use crate::services::ProgressNotificationService;
+
+/// Préférences de notification de l'utilisateur courant
+pub async fn get_my_notification_preferences(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+) -> Result<Json<NotificationPreferencesResponse>, AppError> {
+    let person = current_person(db.as_ref(), &auth).await?;
+
+    Ok(Json(
+        ProgressNotificationService::preferences(db.as_ref(), person.id).await?,
+    ))
+}
+
+/// Active ou désactive les emails de progression de l'utilisateur courant
+pub async fn update_my_notification_preferences(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Json(payload): Json<UpdateNotificationPreferencesRequest>,
+) -> Result<Json<NotificationPreferencesResponse>, AppError> {
+    let person = current_person(db.as_ref(), &auth).await?;
+
+    Ok(Json(
+        ProgressNotificationService::update_preferences(db.as_ref(), person.id, payload).await?,
+    ))
+}
+
+/// Envoie immédiatement les résumés en attente (sans attendre l'heure prévue)
+pub async fn send_progress_digests(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<Arc<CompetencyState>>,
+) -> Result<Json<DigestRunResponse>, AppError> {
+    check_permission(&auth, Permission::EmailsSend)?;
+
+    Ok(Json(
+        ProgressNotificationService::send_digests(
+            state.db.as_ref(),
+            &state.email_service,
+            Utc::now().naive_utc(),
+        )
+        .await?,
+    ))
+}
@@ -5,7 +5,7 @@ use crate::models::{
     UpdatePalanqueeRequest, PalanqueeMemberResponse, AddMemberRequest, UpdateMemberRequest,
     SessionPalanqueesResponse, UnassignedParticipant, parse_time, format_time, DiverLevel,
 };
-use crate::services::{generate_fiche_securite, FicheSecuriteOptions};
+use crate::services::{generate_fiche_securite, FicheSecuriteOptions, LevelService, TeachingQualificationService};
 use axum::{
     extract::{Path, State, Query},
     Extension,
@@ -15,6 +15,7 @@ use axum::{
 };
 use chrono::Utc;
 use sea_orm::*;
+use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -256,6 +257,11 @@ pub async fn add_member(
         .await?
         .ok_or_else(|| AppError::NotFound("Person not found".to_string()))?;
 
+    // Un encadrant dont le titre a expiré ne peut plus être E
+    if payload.role == "E" {
+        TeachingQualificationService::check_can_supervise(db.as_ref(), person.id).await?;
+    }
+
     // Déterminer le type de gaz par défaut
     let gas_type = payload.gas_type.unwrap_or_else(|| {
         if questionnaire.wants_nitrox || questionnaire.nitrox_training {
@@ -277,10 +283,15 @@ pub async fn add_member(
 
     let member = member.insert(db.as_ref()).await?;
 
-    let preparing_level = person.diving_level.as_ref()
+    let catalog = LevelService::catalog(db.as_ref()).await?;
+    let effective = TeachingQualificationService::effective_level(db.as_ref(), person.id).await?;
+    let aptitude = effective.aptitude(&catalog);
+    let external_certifications = effective.external_certifications;
+    let diving_level = effective.native_level;
+    let preparing_level = effective.diving_level.as_ref()
         .and_then(|s| DiverLevel::extract_preparing_level(s));
-    let instructor_level = person.diving_level.as_ref()
-        .and_then(|s| DiverLevel::extract_instructor_level(s));
+    let instructor_level = diving_level.as_ref()
+        .and_then(|s| DiverLevel::extract_instructor_level(s, &catalog));
     
     Ok(Json(PalanqueeMemberResponse {
         id: member.id,
@@ -291,10 +302,12 @@ pub async fn add_member(
         person_id: person.id,
         first_name: person.first_name,
         last_name: person.last_name,
-        diving_level: person.diving_level,
+        diving_level,
         preparing_level,
         is_encadrant: questionnaire.is_encadrant,
         instructor_level,
+        aptitude,
+        external_certifications,
     }))
 }
 
@@ -309,6 +322,14 @@ pub async fn update_member(
         .await?
         .ok_or_else(|| AppError::NotFound("Member not found".to_string()))?;
 
+    if payload.role.as_deref() == Some("E") && member.role != "E" {
+        let questionnaire = Questionnaires::find_by_id(member.questionnaire_id)
+            .one(db.as_ref())
+            .await?
+            .ok_or_else(|| AppError::NotFound("Questionnaire not found".to_string()))?;
+        TeachingQualificationService::check_can_supervise(db.as_ref(), questionnaire.person_id).await?;
+    }
+
     let mut active_model: palanquee_members::ActiveModel = member.clone().into();
 
     if let Some(role) = payload.role {
@@ -331,10 +352,15 @@ pub async fn update_member(
         .await?
         .ok_or_else(|| AppError::NotFound("Person not found".to_string()))?;
 
-    let preparing_level = person.diving_level.as_ref()
+    let catalog = LevelService::catalog(db.as_ref()).await?;
+    let effective = TeachingQualificationService::effective_level(db.as_ref(), person.id).await?;
+    let aptitude = effective.aptitude(&catalog);
+    let external_certifications = effective.external_certifications;
+    let diving_level = effective.native_level;
+    let preparing_level = effective.diving_level.as_ref()
         .and_then(|s| DiverLevel::extract_preparing_level(s));
-    let instructor_level = person.diving_level.as_ref()
-        .and_then(|s| DiverLevel::extract_instructor_level(s));
+    let instructor_level = diving_level.as_ref()
+        .and_then(|s| DiverLevel::extract_instructor_level(s, &catalog));
 
     Ok(Json(PalanqueeMemberResponse {
         id: updated.id,
@@ -345,10 +371,12 @@ pub async fn update_member(
         person_id: person.id,
         first_name: person.first_name,
         last_name: person.last_name,
-        diving_level: person.diving_level,
+        diving_level,
         preparing_level,
         is_encadrant: questionnaire.is_encadrant,
         instructor_level,
+        aptitude,
+        external_certifications,
     }))
 }
 
@@ -469,6 +497,12 @@ pub async fn get_session_palanquees(
             .await?
     };
 
+    let catalog = LevelService::catalog(db.as_ref()).await?;
+    let mut effective_levels = TeachingQualificationService::effective_levels(
+        db.as_ref(),
+        all_questionnaires.iter().map(|q| q.person_id),
+    )
+    .await?;
     let mut unassigned_participants = vec![];
     for q in all_questionnaires {
         // Les encadrants restent toujours disponibles (ils peuvent faire plusieurs rotations)
@@ -480,24 +514,30 @@ pub async fn get_session_palanquees(
                 .await?
                 .ok_or_else(|| AppError::NotFound("Person not found".to_string()))?;
 
-            let preparing_level = person.diving_level.as_ref()
+            let effective = effective_levels.remove(&person.id).unwrap_or_default();
+            let aptitude = effective.aptitude(&catalog);
+            let external_certifications = effective.external_certifications;
+            let diving_level = effective.native_level;
+            let preparing_level = effective.diving_level.as_ref()
                 .and_then(|s| DiverLevel::extract_preparing_level(s));
 
-            let instructor_level = person.diving_level.as_ref()
-                .and_then(|s| DiverLevel::extract_instructor_level(s));
+            let instructor_level = diving_level.as_ref()
+                .and_then(|s| DiverLevel::extract_instructor_level(s, &catalog));
             
             unassigned_participants.push(UnassignedParticipant {
                 questionnaire_id: q.id,
                 person_id: person.id,
                 first_name: person.first_name,
                 last_name: person.last_name,
-                diving_level: person.diving_level,
+                diving_level,
                 preparing_level,
                 is_encadrant: q.is_encadrant,
                 wants_nitrox: q.wants_nitrox,
                 nitrox_training: q.nitrox_training,
                 nitrox_confirmed_formation: q.nitrox_confirmed_formation,
                 instructor_level,
+                aptitude,
+                external_certifications,
             });
         }
     }
@@ -559,22 +599,36 @@ async fn get_palanquee_members(
         .all(db)
         .await?;
 
+    let questionnaires: HashMap<Uuid, questionnaires::Model> = Questionnaires::find()
+        .filter(questionnaires::Column::Id.is_in(members.iter().map(|m| m.questionnaire_id)))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|q| (q.id, q))
+        .collect();
+
+    let catalog = LevelService::catalog(db).await?;
+    let mut effective_levels = TeachingQualificationService::effective_levels(
+        db,
+        questionnaires.values().map(|q| q.person_id),
+    )
+    .await?;
     let mut responses = vec![];
     for m in members {
-        let questionnaire = Questionnaires::find_by_id(m.questionnaire_id)
-            .one(db)
-            .await?;
-
-        if let Some(q) = questionnaire {
+        if let Some(q) = questionnaires.get(&m.questionnaire_id) {
             let person = People::find_by_id(q.person_id)
                 .one(db)
                 .await?;
 
             if let Some(p) = person {
-                let preparing_level = p.diving_level.as_ref()
+                let effective = effective_levels.remove(&p.id).unwrap_or_default();
+                let aptitude = effective.aptitude(&catalog);
+                let external_certifications = effective.external_certifications;
+                let diving_level = effective.native_level;
+                let preparing_level = effective.diving_level.as_ref()
                     .and_then(|s| DiverLevel::extract_preparing_level(s));
-                let instructor_level = p.diving_level.as_ref()
-                    .and_then(|s| DiverLevel::extract_instructor_level(s));
+                let instructor_level = diving_level.as_ref()
+                    .and_then(|s| DiverLevel::extract_instructor_level(s, &catalog));
                 
                 responses.push(PalanqueeMemberResponse {
                     id: m.id,
@@ -585,10 +639,12 @@ async fn get_palanquee_members(
                     person_id: p.id,
                     first_name: p.first_name,
                     last_name: p.last_name,
-                    diving_level: p.diving_level,
+                    diving_level,
                     preparing_level,
                     is_encadrant: q.is_encadrant,
                     instructor_level,
+                    aptitude,
+                    external_certifications,
                 });
             }
         }
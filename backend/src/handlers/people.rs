@@ -1,7 +1,8 @@
 use crate::entities::prelude::*;
 use crate::entities::people;
 use crate::errors::AppError;
-use crate::models::{CreatePersonRequest, UpdatePersonRequest, PersonResponse, DiverLevel};
+use crate::models::{CreatePersonRequest, UpdatePersonRequest, PersonResponse, DiverLevel, LevelCatalog};
+use crate::services::{CertificationService, LevelService, TeachingQualificationService};
 use axum::{
     extract::{Path, Query, State},
     Json,
@@ -16,16 +17,17 @@ use uuid::Uuid;
 use validator::Validate;
 
 /// Helper function pour calculer le display du niveau de plongée
-fn compute_diving_level_display(diving_level: &Option<String>) -> Option<String> {
+fn compute_diving_level_display(diving_level: &Option<String>, catalog: &LevelCatalog) -> Option<String> {
     diving_level.as_ref().and_then(|level_str| {
-        DiverLevel::from_string(level_str).map(|diver_level| diver_level.display())
+        DiverLevel::from_string(level_str, catalog).map(|diver_level| diver_level.display())
     })
 }
 
-/// Helper function pour vérifier si un plongeur est encadrant
-fn compute_is_instructor(diving_level: &Option<String>) -> bool {
+/// Helper function pour vérifier si un plongeur est encadrant, d'après ses
+/// niveaux sans les titres d'encadrement expirés
+fn compute_is_instructor(diving_level: &Option<String>, catalog: &LevelCatalog) -> bool {
     diving_level.as_ref()
-        .and_then(|level_str| DiverLevel::from_string(level_str))
+        .and_then(|level_str| DiverLevel::from_string(level_str, catalog))
         .map(|diver_level| diver_level.is_instructor())
         .unwrap_or(false)
 }
@@ -70,13 +72,19 @@ pub async fn list_people(
 
     // Get all groups to map group_id to group_name
     let groups_map = get_groups_map(db.as_ref()).await?;
+    let levels_map =
+        CertificationService::diving_levels(db.as_ref(), people_list.iter().map(|p| p.id)).await?;
+    let teaching_map =
+        TeachingQualificationService::teaching_levels(db.as_ref(), people_list.iter().map(|p| p.id)).await?;
+    let catalog = LevelService::catalog(db.as_ref()).await?;
 
     let response: Vec<PersonResponse> = people_list
         .into_iter()
         .map(|p| {
-            let diving_level_display = compute_diving_level_display(&p.diving_level);
-            let is_instructor = compute_is_instructor(&p.diving_level);
-            let preparing_level = compute_preparing_level(&p.diving_level);
+            let diving_level = levels_map.get(&p.id).cloned();
+            let diving_level_display = compute_diving_level_display(&diving_level, &catalog);
+            let is_instructor = compute_is_instructor(&teaching_map.get(&p.id).cloned(), &catalog);
+            let preparing_level = compute_preparing_level(&diving_level);
             let group_name = p.group_id.and_then(|gid| groups_map.get(&gid).cloned());
             PersonResponse {
                 id: p.id,
@@ -90,7 +98,7 @@ pub async fn list_people(
                 default_wants_2nd_reg: p.default_wants_2nd_reg,
                 default_wants_stab: p.default_wants_stab,
                 default_stab_size: p.default_stab_size.clone(),
-                diving_level: p.diving_level,
+                diving_level,
                 diving_level_display,
                 is_instructor,
                 preparing_level,
@@ -145,9 +153,12 @@ pub async fn get_person(
         .map_err(|_| AppError::Database(sea_orm::DbErr::Custom("Failed to query person".to_string())))?
         .ok_or(AppError::NotFound("Person not found".to_string()))?;
 
-    let diving_level_display = compute_diving_level_display(&person.diving_level);
-    let is_instructor = compute_is_instructor(&person.diving_level);
-    let preparing_level = compute_preparing_level(&person.diving_level);
+    let diving_level = CertificationService::diving_level(db.as_ref(), person.id).await?;
+    let teaching_level = TeachingQualificationService::teaching_level(db.as_ref(), person.id).await?;
+    let catalog = LevelService::catalog(db.as_ref()).await?;
+    let diving_level_display = compute_diving_level_display(&diving_level, &catalog);
+    let is_instructor = compute_is_instructor(&teaching_level, &catalog);
+    let preparing_level = compute_preparing_level(&diving_level);
     let group_name = get_group_name(db.as_ref(), person.group_id).await?;
 
     Ok(Json(PersonResponse {
@@ -162,7 +173,7 @@ pub async fn get_person(
         default_wants_2nd_reg: person.default_wants_2nd_reg,
         default_wants_stab: person.default_wants_stab,
         default_stab_size: person.default_stab_size,
-        diving_level: person.diving_level,
+        diving_level,
         diving_level_display,
         is_instructor,
         preparing_level,
@@ -194,7 +205,6 @@ pub async fn create_person(
         default_wants_2nd_reg: Set(payload.default_wants_2nd_reg.unwrap_or(false)),
         default_wants_stab: Set(payload.default_wants_stab.unwrap_or(true)),
         default_stab_size: Set(payload.default_stab_size),
-        diving_level: Set(payload.diving_level),
         group_id: Set(payload.group_id),
         password_hash: Set(None),
         temp_password: Set(None),
@@ -204,8 +214,9 @@ pub async fn create_person(
         updated_at: Set(now),
     };
 
+    let txn = db.begin().await?;
     let person = new_person
-        .insert(db.as_ref())
+        .insert(&txn)
         .await
         .map_err(|e| {
             // Check for unique constraint violation on email
@@ -221,9 +232,16 @@ pub async fn create_person(
             AppError::Database(sea_orm::DbErr::Custom(format!("Failed to create person: {}", e)))
         })?;
 
-    let diving_level_display = compute_diving_level_display(&person.diving_level);
-    let is_instructor = compute_is_instructor(&person.diving_level);
-    let preparing_level = compute_preparing_level(&person.diving_level);
+    if let Some(level) = payload.diving_level.as_deref() {
+        CertificationService::sync_from_level_string(&txn, person.id, level).await?;
+    }
+    let diving_level = CertificationService::diving_level(&txn, person.id).await?;
+    let catalog = LevelService::catalog(&txn).await?;
+    txn.commit().await?;
+
+    let diving_level_display = compute_diving_level_display(&diving_level, &catalog);
+    let is_instructor = compute_is_instructor(&diving_level, &catalog);
+    let preparing_level = compute_preparing_level(&diving_level);
     let group_name = get_group_name(db.as_ref(), person.group_id).await?;
 
     Ok(Json(PersonResponse {
@@ -238,7 +256,7 @@ pub async fn create_person(
         default_wants_2nd_reg: person.default_wants_2nd_reg,
         default_wants_stab: person.default_wants_stab,
         default_stab_size: person.default_stab_size,
-        diving_level: person.diving_level,
+        diving_level,
         diving_level_display,
         is_instructor,
         preparing_level,
@@ -293,17 +311,15 @@ pub async fn update_person(
     if let Some(val) = payload.default_stab_size {
         person.default_stab_size = Set(Some(val));
     }
-    if let Some(val) = payload.diving_level {
-        person.diving_level = Set(Some(val));
-    }
     if let Some(val) = payload.group_id {
         person.group_id = Set(Some(val));
     }
     
     person.updated_at = Set(Utc::now().naive_utc());
 
+    let txn = db.begin().await?;
     let updated = person
-        .update(db.as_ref())
+        .update(&txn)
         .await
         .map_err(|e| {
             // Check for unique constraint violation on email
@@ -319,9 +335,17 @@ pub async fn update_person(
             AppError::Database(sea_orm::DbErr::Custom(format!("Failed to update person: {}", e)))
         })?;
 
-    let diving_level_display = compute_diving_level_display(&updated.diving_level);
-    let is_instructor = compute_is_instructor(&updated.diving_level);
-    let preparing_level = compute_preparing_level(&updated.diving_level);
+    if let Some(level) = payload.diving_level.as_deref() {
+        CertificationService::sync_from_level_string(&txn, updated.id, level).await?;
+    }
+    let diving_level = CertificationService::diving_level(&txn, updated.id).await?;
+    let teaching_level = TeachingQualificationService::teaching_level(&txn, updated.id).await?;
+    let catalog = LevelService::catalog(&txn).await?;
+    txn.commit().await?;
+
+    let diving_level_display = compute_diving_level_display(&diving_level, &catalog);
+    let is_instructor = compute_is_instructor(&teaching_level, &catalog);
+    let preparing_level = compute_preparing_level(&diving_level);
     let group_name = get_group_name(db.as_ref(), updated.group_id).await?;
 
     Ok(Json(PersonResponse {
@@ -336,7 +360,7 @@ pub async fn update_person(
         default_wants_2nd_reg: updated.default_wants_2nd_reg,
         default_wants_stab: updated.default_wants_stab,
         default_stab_size: updated.default_stab_size,
-        diving_level: updated.diving_level,
+        diving_level,
         diving_level_display,
         is_instructor,
         preparing_level,
@@ -0,0 +1,129 @@
+use crate::entities::prelude::*;
+use crate::entities::{certifications, level_promotions, people};
+use crate::errors::AppError;
+use crate::handlers::competency_hierarchy::CompetencyState;
+use crate::middleware::acl::{check_permission, AuthUser};
+use crate::models::{LevelPromotionResponse, ListLevelPromotionsQuery, Permission};
+use crate::services::PromotionService;
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use sea_orm::*;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Liste les promotions de niveau (proposées, appliquées, refusées)
+pub async fn list_level_promotions(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<Arc<CompetencyState>>,
+    Query(query): Query<ListLevelPromotionsQuery>,
+) -> Result<Json<Vec<LevelPromotionResponse>>, AppError> {
+    check_permission(&auth, Permission::CompetenciesValidate)?;
+
+    let mut select = LevelPromotions::find();
+    if let Some(status) = query.status {
+        select = select.filter(level_promotions::Column::Status.eq(status));
+    }
+    if let Some(person_id) = query.person_id {
+        select = select.filter(level_promotions::Column::PersonId.eq(person_id));
+    }
+
+    let promotions = select
+        .order_by_desc(level_promotions::Column::CreatedAt)
+        .all(state.db.as_ref())
+        .await?;
+
+    let mut response = Vec::new();
+    for promotion in promotions {
+        response.push(PromotionService::to_response(state.db.as_ref(), promotion).await?);
+    }
+
+    Ok(Json(response))
+}
+
+/// Parcourt tous les plongeurs qui préparent un niveau et propose
+/// les promotions pour ceux dont tous les acquis sont validés
+pub async fn detect_level_promotions(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<Arc<CompetencyState>>,
+) -> Result<Json<Vec<LevelPromotionResponse>>, AppError> {
+    check_permission(&auth, Permission::UsersEdit)?;
+
+    let candidates = Certifications::find()
+        .filter(certifications::Column::Status.eq(certifications::STATUS_PREPARING))
+        .find_also_related(People)
+        .all(state.db.as_ref())
+        .await?;
+
+    let mut response = Vec::new();
+    for (certification, person) in candidates {
+        let Some(person) = person else {
+            continue;
+        };
+        let level = certification.level;
+
+        let Some(mut promotion) = PromotionService::propose(state.db.as_ref(), &person, &level).await? else {
+            continue;
+        };
+
+        if state.auto_promote {
+            promotion = PromotionService::apply(state.db.as_ref(), promotion, None).await?;
+        }
+
+        PromotionService::notify(
+            state.db.as_ref(),
+            &state.email_service,
+            &state.admin_emails,
+            &person,
+            &promotion,
+        )
+        .await;
+
+        response.push(PromotionService::to_response(state.db.as_ref(), promotion).await?);
+    }
+
+    Ok(Json(response))
+}
+
+/// Applique une promotion proposée: le niveau devient un brevet obtenu au club
+pub async fn apply_level_promotion(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<Arc<CompetencyState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<LevelPromotionResponse>, AppError> {
+    check_permission(&auth, Permission::UsersEdit)?;
+
+    let promotion = LevelPromotions::find_by_id(id)
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Promotion non trouvée".to_string()))?;
+
+    let admin_id = People::find()
+        .filter(people::Column::Email.eq(&auth.claims.email))
+        .one(state.db.as_ref())
+        .await?
+        .map(|p| p.id);
+
+    let applied = PromotionService::apply(state.db.as_ref(), promotion, admin_id).await?;
+
+    Ok(Json(PromotionService::to_response(state.db.as_ref(), applied).await?))
+}
+
+/// Refuse une promotion proposée (le niveau n'est pas modifié)
+pub async fn reject_level_promotion(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<Arc<CompetencyState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<LevelPromotionResponse>, AppError> {
+    check_permission(&auth, Permission::UsersEdit)?;
+
+    let promotion = LevelPromotions::find_by_id(id)
+        .one(state.db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Promotion non trouvée".to_string()))?;
+
+    let rejected = PromotionService::reject(state.db.as_ref(), promotion).await?;
+
+    Ok(Json(PromotionService::to_response(state.db.as_ref(), rejected).await?))
+}
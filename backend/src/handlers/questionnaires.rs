@@ -1,7 +1,7 @@
 use crate::config::Config;
 use crate::errors::AppError;
 use crate::models::{CreateQuestionnaireRequest, QuestionnaireDetailResponse, QuestionnaireResponse, QuestionnaireTokenData, SubmitQuestionnaireRequest, UpdateQuestionnaireRequest, SetDirecteurPlongeeRequest};
-use crate::services::QuestionnaireService;
+use crate::services::{EmailService, MentorService, QuestionnaireService};
 use axum::{
     extract::{Path, Query, State},
     Json,
@@ -26,16 +26,17 @@ pub async fn get_questionnaire_by_token(
 }
 
 pub async fn submit_questionnaire(
-    State(db): State<Arc<DatabaseConnection>>,
+    State((db, email_service)): State<(Arc<DatabaseConnection>, Arc<EmailService>)>,
     Json(payload): Json<SubmitQuestionnaireRequest>,
 ) -> Result<Json<QuestionnaireResponse>, AppError> {
     let response = QuestionnaireService::submit(db.as_ref(), payload).await?;
+    notify_mentors(db.as_ref(), &email_service, &response).await;
     Ok(Json(response))
 }
 
 /// Créer un questionnaire directement (auto-inscription)
 pub async fn create_questionnaire(
-    State(db): State<Arc<DatabaseConnection>>,
+    State((db, email_service)): State<(Arc<DatabaseConnection>, Arc<EmailService>)>,
     Json(payload): Json<CreateQuestionnaireRequest>,
 ) -> Result<Json<QuestionnaireResponse>, AppError> {
     payload
@@ -43,9 +44,29 @@ pub async fn create_questionnaire(
         .map_err(|e| AppError::Validation(e.to_string()))?;
     
     let response = QuestionnaireService::create_direct(db.as_ref(), payload).await?;
+    notify_mentors(db.as_ref(), &email_service, &response).await;
     Ok(Json(response))
 }
 
+/// Prévient les référents de l'inscrit (n'échoue jamais l'inscription)
+async fn notify_mentors(
+    db: &DatabaseConnection,
+    email_service: &EmailService,
+    questionnaire: &QuestionnaireResponse,
+) {
+    if let Err(e) = MentorService::notify_registration(
+        db,
+        email_service,
+        questionnaire.person_id,
+        questionnaire.session_id,
+        questionnaire.sortie_id,
+    )
+    .await
+    {
+        tracing::error!("Failed to notify mentors of registration: {}", e);
+    }
+}
+
 pub async fn list_questionnaires(
     State(db): State<Arc<DatabaseConnection>>,
     Query(query): Query<QuestionnaireQuery>,
@@ -0,0 +1,579 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{NaiveDate, Utc};
+use sea_orm::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::entities::prelude::*;
+use crate::entities::{competency_domains, people, quiz_attempts, quiz_questions, quizzes};
+use crate::errors::AppError;
+use crate::handlers::competency_hierarchy::{after_final_validation, CompetencyState};
+use crate::middleware::acl::{check_permission, current_person, AuthUser};
+use crate::models::{
+    CreateQuizQuestionRequest, CreateQuizRequest, Permission, QuizAttemptResponse,
+    QuizQuestionResponse, QuizQuestionsQuery, QuizResponse, RecordQuizResultRequest,
+    StudentQuizResponse, SubmitQuizAttemptRequest, UpdateQuizQuestionRequest, UpdateQuizRequest,
+};
+use crate::services::QuizService;
+
+// ============================================================================
+// QUESTION BANK HANDLERS
+// ============================================================================
+
+pub async fn list_quiz_questions(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Query(query): Query<QuizQuestionsQuery>,
+) -> Result<Json<Vec<QuizQuestionResponse>>, AppError> {
+    check_permission(&auth, Permission::CompetenciesValidate)?;
+
+    let mut select = QuizQuestions::find()
+        .order_by_asc(quiz_questions::Column::DivingLevel)
+        .order_by_asc(quiz_questions::Column::CreatedAt);
+    if let Some(level) = query.diving_level {
+        select = select.filter(quiz_questions::Column::DivingLevel.eq(level));
+    }
+    if let Some(domain_id) = query.domain_id {
+        select = select.filter(quiz_questions::Column::DomainId.eq(domain_id));
+    }
+    if !query.include_inactive {
+        select = select.filter(quiz_questions::Column::IsActive.eq(true));
+    }
+    let questions = select.all(db.as_ref()).await?;
+
+    let domain_names: HashMap<Uuid, String> = CompetencyDomains::find()
+        .filter(competency_domains::Column::Id.is_in(questions.iter().filter_map(|q| q.domain_id)))
+        .all(db.as_ref())
+        .await?
+        .into_iter()
+        .map(|d| (d.id, d.name))
+        .collect();
+
+    Ok(Json(
+        questions
+            .into_iter()
+            .map(|q| QuizService::question_response(q, &domain_names))
+            .collect(),
+    ))
+}
+
+pub async fn create_quiz_question(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Json(payload): Json<CreateQuizQuestionRequest>,
+) -> Result<Json<QuizQuestionResponse>, AppError> {
+    check_permission(&auth, Permission::CompetenciesCreate)?;
+    payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
+    QuizService::check_question(&payload.choices, &payload.correct_choices)?;
+
+    let domain_names = domain_name(db.as_ref(), payload.domain_id).await?;
+
+    let now = Utc::now().naive_utc();
+    let question = quiz_questions::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        diving_level: Set(payload.diving_level),
+        domain_id: Set(payload.domain_id),
+        question: Set(payload.question),
+        choices: Set(serde_json::json!(payload.choices)),
+        correct_choices: Set(serde_json::json!(QuizService::normalize_choices(&payload.correct_choices))),
+        explanation: Set(payload.explanation),
+        points: Set(payload.points.unwrap_or(1)),
+        is_active: Set(true),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db.as_ref())
+    .await?;
+
+    Ok(Json(QuizService::question_response(question, &domain_names)))
+}
+
+pub async fn update_quiz_question(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateQuizQuestionRequest>,
+) -> Result<Json<QuizQuestionResponse>, AppError> {
+    check_permission(&auth, Permission::CompetenciesEdit)?;
+    payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let question = QuizQuestions::find_by_id(id)
+        .one(db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Question non trouvée".to_string()))?;
+
+    let choices = payload.choices.clone().unwrap_or_else(|| question.choice_list());
+    let correct_choices = payload
+        .correct_choices
+        .clone()
+        .unwrap_or_else(|| question.correct_list());
+    QuizService::check_question(&choices, &correct_choices)?;
+
+    let mut active: quiz_questions::ActiveModel = question.into();
+    if let Some(diving_level) = payload.diving_level {
+        active.diving_level = Set(diving_level);
+    }
+    if let Some(domain_id) = payload.domain_id {
+        active.domain_id = Set(Some(domain_id));
+    }
+    if let Some(text) = payload.question {
+        active.question = Set(text);
+    }
+    if payload.explanation.is_some() {
+        active.explanation = Set(payload.explanation);
+    }
+    if let Some(points) = payload.points {
+        active.points = Set(points);
+    }
+    if let Some(is_active) = payload.is_active {
+        active.is_active = Set(is_active);
+    }
+    active.choices = Set(serde_json::json!(choices));
+    active.correct_choices = Set(serde_json::json!(QuizService::normalize_choices(&correct_choices)));
+    active.updated_at = Set(Utc::now().naive_utc());
+    let question = active.update(db.as_ref()).await?;
+
+    let domain_names = domain_name(db.as_ref(), question.domain_id).await?;
+    Ok(Json(QuizService::question_response(question, &domain_names)))
+}
+
+/// Supprime une question. Les passages déjà corrigés gardent leur note.
+pub async fn delete_quiz_question(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    check_permission(&auth, Permission::CompetenciesDelete)?;
+
+    let result = QuizQuestions::delete_by_id(id).exec(db.as_ref()).await?;
+    if result.rows_affected == 0 {
+        return Err(AppError::NotFound("Question non trouvée".to_string()));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn domain_name(
+    db: &DatabaseConnection,
+    domain_id: Option<Uuid>,
+) -> Result<HashMap<Uuid, String>, AppError> {
+    let Some(domain_id) = domain_id else {
+        return Ok(HashMap::new());
+    };
+    let domain = CompetencyDomains::find_by_id(domain_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::NotFound("Domaine non trouvé".to_string()))?;
+    Ok(HashMap::from([(domain.id, domain.name)]))
+}
+
+// ============================================================================
+// QUIZ HANDLERS
+// ============================================================================
+
+pub async fn list_quizzes(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+) -> Result<Json<Vec<QuizResponse>>, AppError> {
+    check_permission(&auth, Permission::CompetenciesValidate)?;
+
+    let quizzes = Quizzes::find()
+        .order_by_asc(quizzes::Column::DivingLevel)
+        .order_by_asc(quizzes::Column::Title)
+        .all(db.as_ref())
+        .await?;
+
+    let mut response = Vec::with_capacity(quizzes.len());
+    for quiz in quizzes {
+        response.push(QuizService::quiz_response(db.as_ref(), quiz).await?);
+    }
+    Ok(Json(response))
+}
+
+pub async fn get_quiz(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<QuizResponse>, AppError> {
+    check_permission(&auth, Permission::CompetenciesValidate)?;
+
+    let quiz = find_quiz(db.as_ref(), id).await?;
+    Ok(Json(QuizService::quiz_response(db.as_ref(), quiz).await?))
+}
+
+/// Crée un questionnaire; son auteur en est l'encadrant responsable
+pub async fn create_quiz(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Json(payload): Json<CreateQuizRequest>,
+) -> Result<Json<QuizResponse>, AppError> {
+    check_permission(&auth, Permission::CompetenciesCreate)?;
+    payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let author = current_person(db.as_ref(), &auth).await?;
+    QuizService::check_validation_target(db.as_ref(), &author, payload.skill_id, payload.stage_id)
+        .await?;
+
+    let now = Utc::now().naive_utc();
+    let quiz = quizzes::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        title: Set(payload.title),
+        description: Set(payload.description),
+        diving_level: Set(payload.diving_level),
+        domain_id: Set(payload.domain_id),
+        question_count: Set(payload.question_count),
+        pass_percentage: Set(payload.pass_percentage.unwrap_or(75)),
+        time_limit_minutes: Set(payload.time_limit_minutes),
+        max_attempts: Set(payload.max_attempts),
+        show_corrections: Set(payload.show_corrections),
+        skill_id: Set(payload.skill_id),
+        stage_id: Set(payload.stage_id),
+        is_published: Set(payload.is_published),
+        created_by_id: Set(Some(author.id)),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db.as_ref())
+    .await?;
+
+    Ok(Json(QuizService::quiz_response(db.as_ref(), quiz).await?))
+}
+
+pub async fn update_quiz(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateQuizRequest>,
+) -> Result<Json<QuizResponse>, AppError> {
+    check_permission(&auth, Permission::CompetenciesEdit)?;
+    payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let quiz = find_quiz(db.as_ref(), id).await?;
+
+    // Changer l'acquis validé fait de l'éditeur l'encadrant responsable
+    let target_changed = payload.skill_id.is_some() || payload.stage_id.is_some();
+    let responsible = if target_changed {
+        let editor = current_person(db.as_ref(), &auth).await?;
+        QuizService::check_validation_target(
+            db.as_ref(),
+            &editor,
+            payload.skill_id.or(quiz.skill_id),
+            payload.stage_id.or(quiz.stage_id),
+        )
+        .await?;
+        Some(editor.id)
+    } else {
+        quiz.created_by_id
+    };
+
+    let mut active: quizzes::ActiveModel = quiz.into();
+    if let Some(title) = payload.title {
+        active.title = Set(title);
+    }
+    if payload.description.is_some() {
+        active.description = Set(payload.description);
+    }
+    if let Some(diving_level) = payload.diving_level {
+        active.diving_level = Set(diving_level);
+    }
+    if payload.domain_id.is_some() {
+        active.domain_id = Set(payload.domain_id);
+    }
+    if payload.question_count.is_some() {
+        active.question_count = Set(payload.question_count);
+    }
+    if let Some(pass_percentage) = payload.pass_percentage {
+        active.pass_percentage = Set(pass_percentage);
+    }
+    if payload.time_limit_minutes.is_some() {
+        active.time_limit_minutes = Set(payload.time_limit_minutes);
+    }
+    if payload.max_attempts.is_some() {
+        active.max_attempts = Set(payload.max_attempts);
+    }
+    if let Some(show_corrections) = payload.show_corrections {
+        active.show_corrections = Set(show_corrections);
+    }
+    if payload.skill_id.is_some() {
+        active.skill_id = Set(payload.skill_id);
+    }
+    if payload.stage_id.is_some() {
+        active.stage_id = Set(payload.stage_id);
+    }
+    if let Some(is_published) = payload.is_published {
+        active.is_published = Set(is_published);
+    }
+    active.created_by_id = Set(responsible);
+    active.updated_at = Set(Utc::now().naive_utc());
+    let quiz = active.update(db.as_ref()).await?;
+
+    Ok(Json(QuizService::quiz_response(db.as_ref(), quiz).await?))
+}
+
+/// Supprime un questionnaire et ses passages (les acquis validés restent)
+pub async fn delete_quiz(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    check_permission(&auth, Permission::CompetenciesDelete)?;
+
+    let result = Quizzes::delete_by_id(id).exec(db.as_ref()).await?;
+    if result.rows_affected == 0 {
+        return Err(AppError::NotFound("Questionnaire non trouvé".to_string()));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn find_quiz(db: &DatabaseConnection, id: Uuid) -> Result<quizzes::Model, AppError> {
+    Quizzes::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or(AppError::NotFound("Questionnaire non trouvé".to_string()))
+}
+
+// ============================================================================
+// ATTEMPT HANDLERS
+// ============================================================================
+
+/// Résultats de tous les élèves pour un questionnaire
+pub async fn list_quiz_attempts(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<QuizAttemptResponse>>, AppError> {
+    check_permission(&auth, Permission::CompetenciesValidate)?;
+
+    let quiz = find_quiz(db.as_ref(), id).await?;
+    let attempts = QuizAttempts::find()
+        .filter(quiz_attempts::Column::QuizId.eq(quiz.id))
+        .order_by_desc(quiz_attempts::Column::StartedAt)
+        .all(db.as_ref())
+        .await?;
+
+    let names: HashMap<Uuid, String> = People::find()
+        .filter(people::Column::Id.is_in(attempts.iter().map(|a| a.person_id)))
+        .all(db.as_ref())
+        .await?
+        .into_iter()
+        .map(|p| (p.id, format!("{} {}", p.first_name, p.last_name)))
+        .collect();
+
+    let mut response = Vec::with_capacity(attempts.len());
+    for attempt in attempts {
+        let name = names.get(&attempt.person_id).cloned();
+        response.push(QuizService::attempt_response(db.as_ref(), &quiz, attempt, name).await?);
+    }
+    Ok(Json(response))
+}
+
+#[derive(Deserialize)]
+pub struct MyQuizzesQuery {
+    pub diving_level: Option<String>,
+}
+
+/// Questionnaires publiés, avec les résultats de l'utilisateur courant
+pub async fn list_my_quizzes(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Query(query): Query<MyQuizzesQuery>,
+) -> Result<Json<Vec<StudentQuizResponse>>, AppError> {
+    let person = current_person(db.as_ref(), &auth).await?;
+
+    let mut select = Quizzes::find()
+        .filter(quizzes::Column::IsPublished.eq(true))
+        .order_by_asc(quizzes::Column::DivingLevel)
+        .order_by_asc(quizzes::Column::Title);
+    if let Some(level) = query.diving_level {
+        select = select.filter(quizzes::Column::DivingLevel.eq(level));
+    }
+    let quizzes = select.all(db.as_ref()).await?;
+
+    let mut attempts: HashMap<Uuid, Vec<quiz_attempts::Model>> = HashMap::new();
+    for attempt in QuizAttempts::find()
+        .filter(quiz_attempts::Column::PersonId.eq(person.id))
+        .all(db.as_ref())
+        .await?
+    {
+        attempts.entry(attempt.quiz_id).or_default().push(attempt);
+    }
+
+    Ok(Json(
+        quizzes
+            .into_iter()
+            .map(|quiz| {
+                let mine = attempts.remove(&quiz.id).unwrap_or_default();
+                StudentQuizResponse {
+                    attempts_used: mine.len() as i32,
+                    best_percentage: mine
+                        .iter()
+                        .filter_map(|a| a.percentage)
+                        .max_by(|a, b| a.total_cmp(b)),
+                    passed: mine.iter().any(|a| a.passed == Some(true)),
+                    in_progress_attempt_id: mine
+                        .iter()
+                        .find(|a| a.submitted_at.is_none())
+                        .map(|a| a.id),
+                    id: quiz.id,
+                    title: quiz.title,
+                    description: quiz.description,
+                    diving_level: quiz.diving_level,
+                    pass_percentage: quiz.pass_percentage,
+                    time_limit_minutes: quiz.time_limit_minutes,
+                    max_attempts: quiz.max_attempts,
+                }
+            })
+            .collect(),
+    ))
+}
+
+/// Commence (ou reprend) un passage du questionnaire
+pub async fn start_quiz_attempt(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<QuizAttemptResponse>, AppError> {
+    let person = current_person(db.as_ref(), &auth).await?;
+    let quiz = find_quiz(db.as_ref(), id).await?;
+
+    let attempt = QuizService::start_attempt(db.as_ref(), &quiz, person.id).await?;
+    let name = Some(format!("{} {}", person.first_name, person.last_name));
+    Ok(Json(QuizService::attempt_response(db.as_ref(), &quiz, attempt, name).await?))
+}
+
+/// Consulte un passage (l'élève concerné ou les encadrants)
+pub async fn get_quiz_attempt(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<QuizAttemptResponse>, AppError> {
+    let (quiz, attempt, person) = find_attempt(db.as_ref(), &auth, id).await?;
+
+    let name = Some(format!("{} {}", person.first_name, person.last_name));
+    Ok(Json(QuizService::attempt_response(db.as_ref(), &quiz, attempt, name).await?))
+}
+
+/// Envoie les réponses d'un passage; s'il est réussi, l'acquis du
+/// questionnaire est validé
+pub async fn submit_quiz_attempt(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<Arc<CompetencyState>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<SubmitQuizAttemptRequest>,
+) -> Result<Json<QuizAttemptResponse>, AppError> {
+    let db = state.db.as_ref();
+    let me = current_person(db, &auth).await?;
+    let (quiz, attempt, person) = find_attempt(db, &auth, id).await?;
+
+    if attempt.person_id != me.id {
+        return Err(AppError::Forbidden(
+            "Seul l'élève peut envoyer ses réponses".to_string(),
+        ));
+    }
+
+    let attempt = QuizService::submit_attempt(db, &quiz, attempt, payload.answers).await?;
+    let attempt = record_quiz_validation(&state, &quiz, attempt).await;
+
+    let name = Some(format!("{} {}", person.first_name, person.last_name));
+    Ok(Json(QuizService::attempt_response(db, &quiz, attempt, name).await?))
+}
+
+/// Enregistre la note d'un examen passé sur papier (encadrants)
+pub async fn record_quiz_result(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<Arc<CompetencyState>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<RecordQuizResultRequest>,
+) -> Result<Json<QuizAttemptResponse>, AppError> {
+    check_permission(&auth, Permission::CompetenciesValidate)?;
+    payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let db = state.db.as_ref();
+    let quiz = find_quiz(db, id).await?;
+    let recorder = current_person(db, &auth).await?;
+    let student = People::find_by_id(payload.person_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::NotFound("Élève non trouvé".to_string()))?;
+
+    let taken_at = match payload.taken_at.as_deref() {
+        Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| AppError::Validation("Format de date invalide (YYYY-MM-DD)".to_string()))?,
+        None => Utc::now().naive_utc().date(),
+    };
+
+    let attempt = QuizService::record_result(
+        db,
+        &quiz,
+        student.id,
+        &recorder,
+        payload.score,
+        payload.max_score,
+        taken_at,
+    )
+    .await?;
+    let attempt = record_quiz_validation(&state, &quiz, attempt).await;
+
+    let name = Some(format!("{} {}", student.first_name, student.last_name));
+    Ok(Json(QuizService::attempt_response(db, &quiz, attempt, name).await?))
+}
+
+/// Valide l'acquis d'un passage réussi, puis détecte un passage de niveau
+/// comme une validation manuelle. Un échec est loggé sans annuler la note.
+async fn record_quiz_validation(
+    state: &CompetencyState,
+    quiz: &quizzes::Model,
+    mut attempt: quiz_attempts::Model,
+) -> quiz_attempts::Model {
+    let db = state.db.as_ref();
+    let (validation, is_final) = match QuizService::validate_skill(db, quiz, &attempt).await {
+        Ok(Some(result)) => result,
+        Ok(None) => return attempt,
+        Err(e) => {
+            tracing::warn!("Failed to validate skill for quiz attempt {}: {}", attempt.id, e);
+            return attempt;
+        }
+    };
+    attempt.validation_id = Some(validation.id);
+
+    if is_final {
+        after_final_validation(state, validation.person_id, validation.skill_id).await;
+    }
+
+    attempt
+}
+
+/// Charge un passage accessible à l'utilisateur courant (l'élève concerné ou
+/// un encadrant), avec son questionnaire et l'élève
+async fn find_attempt(
+    db: &DatabaseConnection,
+    auth: &AuthUser,
+    id: Uuid,
+) -> Result<(quizzes::Model, quiz_attempts::Model, people::Model), AppError> {
+    let attempt = QuizAttempts::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or(AppError::NotFound("Passage non trouvé".to_string()))?;
+
+    let viewer = current_person(db, auth).await?;
+    let student = if attempt.person_id == viewer.id {
+        viewer
+    } else {
+        check_permission(auth, Permission::CompetenciesValidate)?;
+        People::find_by_id(attempt.person_id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Élève non trouvé".to_string()))?
+    };
+
+    let quiz = find_quiz(db, attempt.quiz_id).await?;
+    Ok((quiz, attempt, student))
+}
@@ -2,6 +2,7 @@ use crate::entities::{prelude::*, people, questionnaires, sessions};
 use crate::errors::AppError;
 use crate::middleware::acl::AuthUser;
 use crate::models::{CreateSessionRequest, SessionResponse, SessionSummary, StabSize, ParticipantInfo, UpdateSessionRequest, Permission};
+use crate::services::{CertificationService, LevelService};
 use axum::{
     extract::{Path, State},
     Extension,
@@ -255,6 +256,10 @@ pub async fn get_session_summary(
         .map(|(size, count)| StabSize { size, count })
         .collect();
 
+    let levels_map =
+        CertificationService::diving_levels(db.as_ref(), persons_list.iter().map(|p| p.id)).await?;
+    let catalog = LevelService::catalog(db.as_ref()).await?;
+
     // Build participants list with magic links
     let mut participants = Vec::new();
     for q in &questionnaires_list {
@@ -270,11 +275,12 @@ pub async fn get_session_summary(
                 .unwrap_or_default();
 
             // Extract diving level display and preparing level
-            let diving_level_display = person.diving_level.as_ref()
-                .and_then(|s| DiverLevel::from_string(s))
+            let diving_level = levels_map.get(&person.id);
+            let diving_level_display = diving_level
+                .and_then(|s| DiverLevel::from_string(s, &catalog))
                 .map(|dl| dl.display())
                 .filter(|s| s != "Aucun niveau");
-            let preparing_level = person.diving_level.as_ref()
+            let preparing_level = diving_level
                 .and_then(|s| DiverLevel::extract_preparing_level(s));
 
             participants.push(ParticipantInfo {
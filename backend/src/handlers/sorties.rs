@@ -5,6 +5,7 @@ use crate::models::{
     CopyAttendeesRequest, CopyAttendeesResponse, SessionResponse, DiveDirectorRequest, DiveDirectorResponse,
 };
 use crate::models::DiverLevel;
+use crate::services::CertificationService;
 use axum::{
     extract::{Path, State},
     Json,
@@ -542,13 +543,17 @@ pub async fn get_sortie_questionnaires(
         .await
         .unwrap_or_default();
 
+    let levels_map =
+        CertificationService::diving_levels(db.as_ref(), people_list.iter().map(|p| p.id)).await?;
+
     let mut responses = Vec::new();
     for q in questionnaires_list {
         let person = people_list.iter().find(|p| p.id == q.person_id);
         if let Some(person) = person {
             let email_job = email_jobs_list.iter().find(|e| e.person_id == q.person_id);
+            let diving_level = levels_map.get(&person.id).cloned();
             
-            let preparing_level = person.diving_level.as_ref()
+            let preparing_level = diving_level.as_ref()
                 .and_then(|s| DiverLevel::extract_preparing_level(s));
             responses.push(QuestionnaireDetailResponse {
                 id: q.id,
@@ -575,7 +580,7 @@ pub async fn get_sortie_questionnaires(
                 submitted_at: q.submitted_at.map(|dt| dt.to_string()),
                 magic_link: email_job.map(|e| e.questionnaire_token.to_string()),
                 email_status: email_job.map(|e| e.status.clone()),
-                diving_level: person.diving_level.clone(),
+                diving_level,
                 preparing_level,
             });
         }
@@ -0,0 +1,110 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use sea_orm::DatabaseConnection;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::errors::AppError;
+use crate::middleware::acl::{check_permission, current_person, AuthUser};
+use crate::models::{
+    CreateTeachingQualificationRequest, Permission, RecordRecyclingRequest,
+    TeachingQualificationResponse, TeachingQualificationsQuery,
+    UpdateTeachingQualificationRequest,
+};
+use crate::services::TeachingQualificationService;
+
+// ============================================================================
+// TEACHING QUALIFICATION HANDLERS
+// ============================================================================
+
+/// Titres d'encadrement, filtrables par personne, niveau ou échéance proche
+pub async fn list_teaching_qualifications(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Query(query): Query<TeachingQualificationsQuery>,
+) -> Result<Json<Vec<TeachingQualificationResponse>>, AppError> {
+    check_permission(&auth, Permission::UsersView)?;
+
+    Ok(Json(TeachingQualificationService::list(db.as_ref(), query).await?))
+}
+
+pub async fn create_teaching_qualification(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Json(payload): Json<CreateTeachingQualificationRequest>,
+) -> Result<Json<TeachingQualificationResponse>, AppError> {
+    check_permission(&auth, Permission::UsersEdit)?;
+    payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
+
+    Ok(Json(TeachingQualificationService::create(db.as_ref(), payload).await?))
+}
+
+pub async fn update_teaching_qualification(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateTeachingQualificationRequest>,
+) -> Result<Json<TeachingQualificationResponse>, AppError> {
+    check_permission(&auth, Permission::UsersEdit)?;
+    payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
+
+    Ok(Json(
+        TeachingQualificationService::update(db.as_ref(), id, payload).await?,
+    ))
+}
+
+pub async fn delete_teaching_qualification(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    check_permission(&auth, Permission::UsersEdit)?;
+
+    TeachingQualificationService::delete(db.as_ref(), id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Enregistre un recyclage et prolonge la validité du titre
+pub async fn record_teaching_recycling(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<RecordRecyclingRequest>,
+) -> Result<Json<TeachingQualificationResponse>, AppError> {
+    check_permission(&auth, Permission::UsersEdit)?;
+    let recorder = current_person(db.as_ref(), &auth).await.ok();
+
+    Ok(Json(
+        TeachingQualificationService::record_recycling(
+            db.as_ref(),
+            id,
+            payload,
+            recorder.map(|p| p.id),
+        )
+        .await?,
+    ))
+}
+
+/// Titres d'encadrement de l'utilisateur courant et leurs échéances
+pub async fn get_my_teaching_qualifications(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+) -> Result<Json<Vec<TeachingQualificationResponse>>, AppError> {
+    let person = current_person(db.as_ref(), &auth).await?;
+
+    Ok(Json(
+        TeachingQualificationService::list(
+            db.as_ref(),
+            TeachingQualificationsQuery {
+                person_id: Some(person.id),
+                level: None,
+                expiring_within_days: None,
+            },
+        )
+        .await?,
+    ))
+}
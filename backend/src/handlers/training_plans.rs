@@ -0,0 +1,335 @@
+use crate::entities::prelude::*;
+use crate::entities::{
+    palanquee_members, palanquees, people, questionnaires, rotations, session_skill_plans,
+    validation_stages,
+};
+use crate::errors::AppError;
+use crate::handlers::competency_hierarchy::get_competency_hierarchy_for_person;
+use crate::middleware::acl::{check_permission, current_person, AuthUser};
+use crate::models::{
+    CompetencyHierarchyResponse, DiverLevel, Permission, PinSkillRequest, PlannedSkill,
+    SessionTrainingPlan, SkillPlanPin, StudentTrainingPlan, SuggestedEncadrant,
+    ValidationStageResponse,
+};
+use crate::services::{LevelService, SkillValidationService, TeachingQualificationService};
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use chrono::Utc;
+use sea_orm::*;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+fn stage_response(s: &validation_stages::Model) -> ValidationStageResponse {
+    ValidationStageResponse {
+        id: s.id,
+        code: s.code.clone(),
+        name: s.name.clone(),
+        description: s.description.clone(),
+        color: s.color.clone(),
+        icon: s.icon.clone(),
+        sort_order: s.sort_order,
+        is_final: s.is_final,
+        allowed_next_stage_ids: s.allowed_next(),
+        validator_rule: s.validator_rule.clone(),
+        self_assessable: s.self_assessable,
+    }
+}
+
+fn pin_response(
+    pin: &session_skill_plans::Model,
+    people_map: &HashMap<Uuid, people::Model>,
+) -> SkillPlanPin {
+    SkillPlanPin {
+        id: pin.id,
+        person_id: pin.person_id,
+        skill_id: pin.skill_id,
+        palanquee_id: pin.palanquee_id,
+        target_stage_id: pin.target_stage_id,
+        encadrant_id: pin.encadrant_id,
+        encadrant_name: pin
+            .encadrant_id
+            .and_then(|id| people_map.get(&id))
+            .map(|p| format!("{} {}", p.first_name, p.last_name)),
+        notes: pin.notes.clone(),
+    }
+}
+
+/// Plan de formation de la session: pour chaque élève des palanquées,
+/// les acquis restant à valider, l'étape suivante à atteindre et les
+/// encadrants de la palanquée habilités à les valider
+pub async fn get_session_training_plan(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<SessionTrainingPlan>, AppError> {
+    check_permission(&auth, Permission::CompetenciesValidate)?;
+
+    Sessions::find_by_id(session_id)
+        .one(db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Session non trouvée".to_string()))?;
+
+    let rotations_list = Rotations::find()
+        .filter(rotations::Column::SessionId.eq(session_id))
+        .order_by_asc(rotations::Column::Number)
+        .all(db.as_ref())
+        .await?;
+    let rotation_numbers: HashMap<Uuid, i32> =
+        rotations_list.iter().map(|r| (r.id, r.number)).collect();
+
+    let palanquees_list = Palanquees::find()
+        .filter(palanquees::Column::RotationId.is_in(rotation_numbers.keys().copied()))
+        .order_by_asc(palanquees::Column::Number)
+        .all(db.as_ref())
+        .await?;
+
+    let members = PalanqueeMembers::find()
+        .filter(palanquee_members::Column::PalanqueeId.is_in(palanquees_list.iter().map(|p| p.id)))
+        .all(db.as_ref())
+        .await?;
+
+    let questionnaires_map: HashMap<Uuid, questionnaires::Model> = Questionnaires::find()
+        .filter(questionnaires::Column::Id.is_in(members.iter().map(|m| m.questionnaire_id)))
+        .all(db.as_ref())
+        .await?
+        .into_iter()
+        .map(|q| (q.id, q))
+        .collect();
+
+    let pins = SessionSkillPlans::find()
+        .filter(session_skill_plans::Column::SessionId.eq(session_id))
+        .all(db.as_ref())
+        .await?;
+
+    let mut people_ids: Vec<Uuid> = questionnaires_map.values().map(|q| q.person_id).collect();
+    people_ids.extend(pins.iter().filter_map(|p| p.encadrant_id));
+    let people_map: HashMap<Uuid, people::Model> = People::find()
+        .filter(people::Column::Id.is_in(people_ids))
+        .all(db.as_ref())
+        .await?
+        .into_iter()
+        .map(|p| (p.id, p))
+        .collect();
+    // Titres d'encadrement expirés exclus: seuls les encadrants à jour sont suggérés
+    let levels_map =
+        TeachingQualificationService::teaching_levels(db.as_ref(), people_map.keys().copied()).await?;
+    let catalog = LevelService::catalog(db.as_ref()).await?;
+
+    let stages = ValidationStages::find()
+        .order_by_asc(validation_stages::Column::SortOrder)
+        .all(db.as_ref())
+        .await?;
+
+    let pins_map: HashMap<(Uuid, Uuid), &session_skill_plans::Model> =
+        pins.iter().map(|p| ((p.person_id, p.skill_id), p)).collect();
+
+    // La progression d'un élève présent dans plusieurs palanquées n'est chargée qu'une fois
+    let mut hierarchies: HashMap<Uuid, Option<CompetencyHierarchyResponse>> = HashMap::new();
+    let mut students = Vec::new();
+
+    for palanquee in &palanquees_list {
+        let palanquee_members: Vec<(&palanquee_members::Model, &questionnaires::Model)> = members
+            .iter()
+            .filter(|m| m.palanquee_id == palanquee.id)
+            .filter_map(|m| questionnaires_map.get(&m.questionnaire_id).map(|q| (m, q)))
+            .collect();
+
+        let encadrants: Vec<&people::Model> = palanquee_members
+            .iter()
+            .filter(|(m, q)| m.role != "P" || q.is_encadrant)
+            .filter_map(|(_, q)| people_map.get(&q.person_id))
+            .collect();
+
+        for (_, questionnaire) in palanquee_members
+            .iter()
+            .filter(|(m, q)| m.role == "P" && !q.is_encadrant)
+        {
+            let Some(person) = people_map.get(&questionnaire.person_id) else {
+                continue;
+            };
+            let target_level = levels_map
+                .get(&person.id)
+                .and_then(|level| DiverLevel::extract_preparing_level(level));
+
+            if let Entry::Vacant(entry) = hierarchies.entry(person.id) {
+                let hierarchy = match &target_level {
+                    Some(level) => Some(get_competency_hierarchy_for_person(&db, level, person.id).await?.0),
+                    None => None,
+                };
+                entry.insert(hierarchy);
+            }
+
+            let mut skills = Vec::new();
+            if let Some(hierarchy) = hierarchies.get(&person.id).and_then(|h| h.as_ref()) {
+                for domain in &hierarchy.domains {
+                    for module in &domain.modules {
+                        for skill in &module.skills {
+                            let expired = skill.validation.as_ref().is_some_and(|v| v.expired);
+                            if skill.validation.as_ref().is_some_and(|v| v.is_final) && !expired {
+                                continue;
+                            }
+
+                            let current_stage = skill
+                                .validation
+                                .as_ref()
+                                .and_then(|v| stages.iter().find(|s| s.id == v.stage_id));
+                            // Un acquis à revalider se revalide à son étape actuelle
+                            let next_stage = match current_stage {
+                                Some(stage) if expired => Some(stage_response(stage)),
+                                _ => SkillValidationService::next_stage(&stages, current_stage)
+                                    .map(stage_response),
+                            };
+
+                            let suggested_encadrants = encadrants
+                                .iter()
+                                .filter(|e| {
+                                    levels_map.get(&e.id).is_some_and(|level| {
+                                        SkillValidationService::can_validate(
+                                            &catalog,
+                                            level,
+                                            &skill.min_validator_level,
+                                            skill.validator_rule.as_deref(),
+                                        )
+                                    })
+                                })
+                                .map(|e| SuggestedEncadrant {
+                                    id: e.id,
+                                    name: format!("{} {}", e.first_name, e.last_name),
+                                    diving_level: levels_map.get(&e.id).cloned(),
+                                })
+                                .collect();
+
+                            skills.push(PlannedSkill {
+                                skill_id: skill.id,
+                                skill_name: skill.name.clone(),
+                                module_name: module.name.clone(),
+                                domain_name: domain.name.clone(),
+                                min_validator_level: skill.min_validator_level.clone(),
+                                validator_rule: skill.validator_rule.clone(),
+                                current_stage_name: skill.validation.as_ref().map(|v| v.stage_name.clone()),
+                                next_stage,
+                                suggested_encadrants,
+                                pin: pins_map
+                                    .get(&(person.id, skill.id))
+                                    .map(|p| pin_response(p, &people_map)),
+                            });
+                        }
+                    }
+                }
+            }
+
+            // Les acquis épinglés passent en premier (tri stable)
+            skills.sort_by_key(|s| s.pin.is_none());
+
+            students.push(StudentTrainingPlan {
+                person_id: person.id,
+                person_name: format!("{} {}", person.first_name, person.last_name),
+                palanquee_id: palanquee.id,
+                palanquee_number: palanquee.number,
+                rotation_number: rotation_numbers.get(&palanquee.rotation_id).copied().unwrap_or(0),
+                target_level,
+                skills,
+            });
+        }
+    }
+
+    Ok(Json(SessionTrainingPlan {
+        session_id,
+        students,
+    }))
+}
+
+/// Épingle un acquis au plan d'un élève pour la session (remplace l'épingle existante)
+pub async fn pin_session_skill(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(session_id): Path<Uuid>,
+    Json(payload): Json<PinSkillRequest>,
+) -> Result<Json<SkillPlanPin>, AppError> {
+    check_permission(&auth, Permission::CompetenciesValidate)?;
+
+    Sessions::find_by_id(session_id)
+        .one(db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Session non trouvée".to_string()))?;
+    CompetencySkills::find_by_id(payload.skill_id)
+        .one(db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Acquis non trouvé".to_string()))?;
+    People::find_by_id(payload.person_id)
+        .one(db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Élève non trouvé".to_string()))?;
+
+    let planner_id = current_person(db.as_ref(), &auth).await.ok().map(|p| p.id);
+
+    let existing = SessionSkillPlans::find()
+        .filter(session_skill_plans::Column::SessionId.eq(session_id))
+        .filter(session_skill_plans::Column::PersonId.eq(payload.person_id))
+        .filter(session_skill_plans::Column::SkillId.eq(payload.skill_id))
+        .one(db.as_ref())
+        .await?;
+
+    let pin = match existing {
+        Some(existing) => {
+            let mut active: session_skill_plans::ActiveModel = existing.into();
+            active.palanquee_id = Set(payload.palanquee_id);
+            active.target_stage_id = Set(payload.target_stage_id);
+            active.encadrant_id = Set(payload.encadrant_id);
+            active.planned_by_id = Set(planner_id);
+            active.notes = Set(payload.notes);
+            active.update(db.as_ref()).await?
+        }
+        None => {
+            session_skill_plans::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                session_id: Set(session_id),
+                person_id: Set(payload.person_id),
+                skill_id: Set(payload.skill_id),
+                palanquee_id: Set(payload.palanquee_id),
+                target_stage_id: Set(payload.target_stage_id),
+                encadrant_id: Set(payload.encadrant_id),
+                planned_by_id: Set(planner_id),
+                notes: Set(payload.notes),
+                created_at: Set(Utc::now().naive_utc()),
+            }
+            .insert(db.as_ref())
+            .await?
+        }
+    };
+
+    let people_map: HashMap<Uuid, people::Model> = match pin.encadrant_id {
+        Some(id) => People::find_by_id(id)
+            .one(db.as_ref())
+            .await?
+            .map(|p| (p.id, p))
+            .into_iter()
+            .collect(),
+        None => HashMap::new(),
+    };
+
+    Ok(Json(pin_response(&pin, &people_map)))
+}
+
+/// Retire un acquis épinglé
+pub async fn unpin_session_skill(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path((session_id, pin_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    check_permission(&auth, Permission::CompetenciesValidate)?;
+
+    let pin = SessionSkillPlans::find_by_id(pin_id)
+        .filter(session_skill_plans::Column::SessionId.eq(session_id))
+        .one(db.as_ref())
+        .await?
+        .ok_or(AppError::NotFound("Acquis planifié non trouvé".to_string()))?;
+
+    pin.delete(db.as_ref()).await?;
+
+    Ok(Json(serde_json::json!({ "message": "Acquis retiré du plan" })))
+}
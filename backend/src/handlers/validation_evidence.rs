@@ -0,0 +1,365 @@
+use axum::{
+    extract::{Multipart, Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::Utc;
+use sea_orm::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::entities::prelude::*;
+use crate::entities::skill_validation_evidence::{self, KIND_DIVE_LOG, KIND_FILE};
+use crate::entities::skill_validation_comments;
+use crate::errors::AppError;
+use crate::middleware::acl::AuthUser;
+use crate::models::{
+    CreateValidationCommentRequest, UpdateValidationCommentRequest, ValidationCommentResponse,
+    ValidationEvidenceResponse,
+};
+use crate::services::validation_evidence::EvidenceViewer;
+use crate::services::{Storage, ValidationEvidenceService};
+
+/// State des routes de preuves (base + stockage des fichiers)
+pub struct EvidenceState {
+    pub db: Arc<DatabaseConnection>,
+    pub storage: Storage,
+    pub max_upload_bytes: usize,
+}
+
+// ============================================================================
+// EVIDENCE HANDLERS
+// ============================================================================
+
+/// Liste les preuves d'une validation (élève concerné ou encadrants)
+pub async fn list_validation_evidence(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<Arc<EvidenceState>>,
+    Path(validation_id): Path<Uuid>,
+) -> Result<Json<Vec<ValidationEvidenceResponse>>, AppError> {
+    ValidationEvidenceService::authorize(state.db.as_ref(), &auth, validation_id).await?;
+
+    Ok(Json(
+        ValidationEvidenceService::list_evidence(state.db.as_ref(), validation_id).await?,
+    ))
+}
+
+// Ajoute une preuve à une validation (encadrants).
+// Champs multipart: "file" (photo, vidéo ou PDF) et/ou "reference" (carnet
+// de plongée), "caption" (optionnel)
+pub async fn upload_validation_evidence(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<Arc<EvidenceState>>,
+    Path(validation_id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<Json<ValidationEvidenceResponse>, AppError> {
+    let db = state.db.as_ref();
+    let (subject, viewer) = ValidationEvidenceService::authorize(db, &auth, validation_id).await?;
+
+    if !viewer.is_encadrant {
+        return Err(AppError::Forbidden(
+            "Seuls les encadrants peuvent joindre des preuves".to_string(),
+        ));
+    }
+    if !subject.live {
+        return Err(AppError::Validation("Cette validation a été supprimée".to_string()));
+    }
+
+    let mut file: Option<(String, String, Vec<u8>)> = None;
+    let mut reference: Option<String> = None;
+    let mut caption: Option<String> = None;
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        AppError::Validation(format!("Failed to read multipart: {}", e))
+    })? {
+        let name = field.name().unwrap_or("").to_string();
+
+        match name.as_str() {
+            "file" => {
+                let file_name = field.file_name().unwrap_or("preuve").to_string();
+                let content_type = field
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let data = field.bytes().await.map_err(|e| {
+                    AppError::Validation(format!("Failed to read file: {}", e))
+                })?;
+                file = Some((file_name, content_type, data.to_vec()));
+            }
+            "reference" | "caption" => {
+                let value = field.text().await.map_err(|e| {
+                    AppError::Validation(format!("Failed to read field {}: {}", name, e))
+                })?;
+                let value = Some(value.trim().to_string()).filter(|v| !v.is_empty());
+                if name == "reference" {
+                    reference = value;
+                } else {
+                    caption = value;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if file.is_none() && reference.is_none() {
+        return Err(AppError::Validation(
+            "Un fichier ou une référence de carnet est requis".to_string(),
+        ));
+    }
+    if reference.as_ref().is_some_and(|r| r.chars().count() > 255) {
+        return Err(AppError::Validation("Référence trop longue (255 caractères max)".to_string()));
+    }
+
+    let id = Uuid::new_v4();
+    let mut evidence = skill_validation_evidence::ActiveModel {
+        id: Set(id),
+        validation_id: Set(validation_id),
+        person_id: Set(subject.person_id),
+        skill_id: Set(subject.skill_id),
+        kind: Set(KIND_DIVE_LOG.to_string()),
+        file_name: Set(None),
+        content_type: Set(None),
+        size_bytes: Set(None),
+        storage_key: Set(None),
+        reference: Set(reference),
+        caption: Set(caption),
+        uploaded_by_id: Set(Some(viewer.person.id)),
+        created_at: Set(Utc::now().naive_utc()),
+    };
+
+    let mut stored_key = None;
+    if let Some((file_name, content_type, data)) = file {
+        if data.is_empty() {
+            return Err(AppError::Validation("Le fichier est vide".to_string()));
+        }
+        if data.len() > state.max_upload_bytes {
+            return Err(AppError::Validation(format!(
+                "Fichier trop volumineux ({} Mo max)",
+                state.max_upload_bytes / (1024 * 1024)
+            )));
+        }
+        if !ValidationEvidenceService::is_allowed_content_type(&content_type) {
+            return Err(AppError::Validation(format!(
+                "Type de fichier non accepté: {} (images, vidéos ou PDF)",
+                content_type
+            )));
+        }
+
+        let key = ValidationEvidenceService::storage_key(validation_id, id);
+        state.storage.put(&key, &data).await?;
+
+        evidence.kind = Set(KIND_FILE.to_string());
+        evidence.file_name = Set(Some(file_name.chars().take(255).collect()));
+        evidence.content_type = Set(Some(content_type));
+        evidence.size_bytes = Set(Some(data.len() as i64));
+        evidence.storage_key = Set(Some(key.clone()));
+        stored_key = Some(key);
+    }
+
+    let evidence = match evidence.insert(db).await {
+        Ok(evidence) => evidence,
+        Err(e) => {
+            // Pas de fichier orphelin si l'enregistrement échoue
+            if let Some(key) = stored_key {
+                let _ = state.storage.delete(&key).await;
+            }
+            return Err(e.into());
+        }
+    };
+
+    let names = HashMap::from([(
+        viewer.person.id,
+        format!("{} {}", viewer.person.first_name, viewer.person.last_name),
+    )]);
+    Ok(Json(ValidationEvidenceService::evidence_response(evidence, &names)))
+}
+
+/// Télécharge le fichier d'une preuve
+pub async fn download_validation_evidence(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<Arc<EvidenceState>>,
+    Path((validation_id, evidence_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, AppError> {
+    let evidence = find_evidence(&state, &auth, validation_id, evidence_id).await?.0;
+
+    let key = evidence
+        .storage_key
+        .ok_or(AppError::NotFound("Cette preuve n'a pas de fichier".to_string()))?;
+    let data = state.storage.get(&key).await?;
+
+    let content_type = evidence
+        .content_type
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+    // Photos et vidéos s'affichent directement dans le navigateur
+    let disposition = if content_type.starts_with("image/") || content_type.starts_with("video/") {
+        "inline"
+    } else {
+        "attachment"
+    };
+    let file_name = evidence
+        .file_name
+        .unwrap_or_else(|| "preuve".to_string())
+        .replace(['"', '\\', '\r', '\n'], "_");
+
+    let headers = [
+        (header::CONTENT_TYPE, content_type),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("{}; filename=\"{}\"", disposition, file_name),
+        ),
+    ];
+
+    Ok((headers, data))
+}
+
+/// Supprime une preuve (auteur de l'envoi ou admin)
+pub async fn delete_validation_evidence(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<Arc<EvidenceState>>,
+    Path((validation_id, evidence_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, AppError> {
+    let (evidence, viewer) = find_evidence(&state, &auth, validation_id, evidence_id).await?;
+
+    if !viewer.can_delete(evidence.uploaded_by_id) {
+        return Err(AppError::Forbidden(
+            "Seul l'auteur de la preuve peut la supprimer".to_string(),
+        ));
+    }
+
+    let storage_key = evidence.storage_key.clone();
+    evidence.delete(state.db.as_ref()).await?;
+
+    if let Some(key) = storage_key {
+        state.storage.delete(&key).await?;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn find_evidence(
+    state: &EvidenceState,
+    auth: &AuthUser,
+    validation_id: Uuid,
+    evidence_id: Uuid,
+) -> Result<(skill_validation_evidence::Model, EvidenceViewer), AppError> {
+    let (_, viewer) =
+        ValidationEvidenceService::authorize(state.db.as_ref(), auth, validation_id).await?;
+
+    let evidence = SkillValidationEvidence::find_by_id(evidence_id)
+        .one(state.db.as_ref())
+        .await?
+        .filter(|e| e.validation_id == validation_id)
+        .ok_or(AppError::NotFound("Preuve non trouvée".to_string()))?;
+
+    Ok((evidence, viewer))
+}
+
+// ============================================================================
+// COMMENT HANDLERS
+// ============================================================================
+
+/// Fil de commentaires d'une validation (élève concerné ou encadrants)
+pub async fn list_validation_comments(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<Arc<EvidenceState>>,
+    Path(validation_id): Path<Uuid>,
+) -> Result<Json<Vec<ValidationCommentResponse>>, AppError> {
+    ValidationEvidenceService::authorize(state.db.as_ref(), &auth, validation_id).await?;
+
+    Ok(Json(
+        ValidationEvidenceService::list_comments(state.db.as_ref(), validation_id).await?,
+    ))
+}
+
+/// Commente une validation; l'élève peut répondre aux encadrants
+pub async fn create_validation_comment(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<Arc<EvidenceState>>,
+    Path(validation_id): Path<Uuid>,
+    Json(payload): Json<CreateValidationCommentRequest>,
+) -> Result<Json<ValidationCommentResponse>, AppError> {
+    payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let db = state.db.as_ref();
+    let (subject, viewer) = ValidationEvidenceService::authorize(db, &auth, validation_id).await?;
+
+    if !subject.live {
+        return Err(AppError::Validation("Cette validation a été supprimée".to_string()));
+    }
+
+    let comment = ValidationEvidenceService::add_comment(
+        db,
+        &subject,
+        &viewer.person,
+        payload.body.trim().to_string(),
+        payload.parent_id,
+    )
+    .await?;
+
+    Ok(Json(comment))
+}
+
+/// Modifie un commentaire (auteur uniquement)
+pub async fn update_validation_comment(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<Arc<EvidenceState>>,
+    Path(comment_id): Path<Uuid>,
+    Json(payload): Json<UpdateValidationCommentRequest>,
+) -> Result<Json<ValidationCommentResponse>, AppError> {
+    payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let db = state.db.as_ref();
+    let comment = find_comment(db, comment_id).await?;
+    let (_, viewer) = ValidationEvidenceService::authorize(db, &auth, comment.validation_id).await?;
+
+    if comment.author_id != Some(viewer.person.id) {
+        return Err(AppError::Forbidden(
+            "Seul l'auteur du commentaire peut le modifier".to_string(),
+        ));
+    }
+
+    let mut active: skill_validation_comments::ActiveModel = comment.into();
+    active.body = Set(payload.body.trim().to_string());
+    active.updated_at = Set(Utc::now().naive_utc());
+    let comment = active.update(db).await?;
+
+    let names = HashMap::from([(
+        viewer.person.id,
+        format!("{} {}", viewer.person.first_name, viewer.person.last_name),
+    )]);
+    Ok(Json(ValidationEvidenceService::comment_response(comment, &names)))
+}
+
+/// Supprime un commentaire et ses réponses (auteur ou admin)
+pub async fn delete_validation_comment(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<Arc<EvidenceState>>,
+    Path(comment_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let db = state.db.as_ref();
+    let comment = find_comment(db, comment_id).await?;
+    let (_, viewer) = ValidationEvidenceService::authorize(db, &auth, comment.validation_id).await?;
+
+    if !viewer.can_delete(comment.author_id) {
+        return Err(AppError::Forbidden(
+            "Seul l'auteur du commentaire peut le supprimer".to_string(),
+        ));
+    }
+
+    comment.delete(db).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn find_comment(
+    db: &DatabaseConnection,
+    comment_id: Uuid,
+) -> Result<skill_validation_comments::Model, AppError> {
+    SkillValidationComments::find_by_id(comment_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::NotFound("Commentaire non trouvé".to_string()))
+}
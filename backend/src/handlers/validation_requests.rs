@@ -0,0 +1,121 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use sea_orm::DatabaseConnection;
+use std::sync::Arc;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::errors::AppError;
+use crate::handlers::competency_hierarchy::{after_final_validation, CompetencyState};
+use crate::middleware::acl::{check_permission, current_person, AuthUser};
+use crate::models::{
+    ApproveValidationRequestRequest, CreateValidationRequestRequest, Permission,
+    RejectValidationRequestRequest, ValidationRequestResponse, ValidationRequestsQuery,
+};
+use crate::services::ValidationRequestService;
+
+// ============================================================================
+// VALIDATION REQUEST HANDLERS
+// ============================================================================
+
+/// Demande de validation d'un acquis, par l'élève lui-même ou par un
+/// encadrant pour le compte d'un élève
+pub async fn create_validation_request(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Json(payload): Json<CreateValidationRequestRequest>,
+) -> Result<Json<ValidationRequestResponse>, AppError> {
+    payload.validate().map_err(|e| AppError::Validation(e.to_string()))?;
+    let requester = current_person(db.as_ref(), &auth).await?;
+    let person_id = payload.person_id.unwrap_or(requester.id);
+    if person_id != requester.id {
+        check_permission(&auth, Permission::CompetenciesValidate)?;
+    }
+
+    Ok(Json(
+        ValidationRequestService::create(db.as_ref(), &requester, person_id, payload).await?,
+    ))
+}
+
+/// Demandes de l'utilisateur courant
+pub async fn list_my_validation_requests(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Query(query): Query<ValidationRequestsQuery>,
+) -> Result<Json<Vec<ValidationRequestResponse>>, AppError> {
+    let person = current_person(db.as_ref(), &auth).await?;
+
+    Ok(Json(
+        ValidationRequestService::list_for_person(db.as_ref(), person.id, query.status).await?,
+    ))
+}
+
+/// File des demandes en attente que l'utilisateur courant peut traiter
+pub async fn get_validation_request_queue(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Query(query): Query<ValidationRequestsQuery>,
+) -> Result<Json<Vec<ValidationRequestResponse>>, AppError> {
+    check_permission(&auth, Permission::CompetenciesValidate)?;
+    let validator = current_person(db.as_ref(), &auth).await?;
+    let is_real_admin = auth.claims.is_admin && auth.claims.impersonating.is_none();
+
+    Ok(Json(
+        ValidationRequestService::queue(db.as_ref(), &validator, is_real_admin, query.person_id)
+            .await?,
+    ))
+}
+
+/// Accepte une demande en validation réelle de l'acquis
+pub async fn approve_validation_request(
+    Extension(auth): Extension<AuthUser>,
+    State(state): State<Arc<CompetencyState>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ApproveValidationRequestRequest>,
+) -> Result<Json<ValidationRequestResponse>, AppError> {
+    check_permission(&auth, Permission::CompetenciesValidate)?;
+    let validator = current_person(state.db.as_ref(), &auth).await?;
+    let is_real_admin = auth.claims.is_admin && auth.claims.impersonating.is_none();
+
+    let (request, validation, is_final) =
+        ValidationRequestService::approve(state.db.as_ref(), id, &validator, is_real_admin, payload)
+            .await?;
+
+    if is_final {
+        after_final_validation(&state, validation.person_id, validation.skill_id).await;
+    }
+
+    Ok(Json(request))
+}
+
+pub async fn reject_validation_request(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<RejectValidationRequestRequest>,
+) -> Result<Json<ValidationRequestResponse>, AppError> {
+    check_permission(&auth, Permission::CompetenciesValidate)?;
+    let reviewer = current_person(db.as_ref(), &auth).await?;
+    let is_real_admin = auth.claims.is_admin && auth.claims.impersonating.is_none();
+
+    Ok(Json(
+        ValidationRequestService::reject(db.as_ref(), id, &reviewer, is_real_admin, payload.comment)
+            .await?,
+    ))
+}
+
+/// Retire une demande encore en attente
+pub async fn delete_validation_request(
+    Extension(auth): Extension<AuthUser>,
+    State(db): State<Arc<DatabaseConnection>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let person = current_person(db.as_ref(), &auth).await?;
+    let is_real_admin = auth.claims.is_admin && auth.claims.impersonating.is_none();
+
+    ValidationRequestService::cancel(db.as_ref(), id, &person, is_real_admin).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
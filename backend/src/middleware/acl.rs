@@ -31,6 +31,25 @@ impl AuthUser {
     }
 }
 
+/// Profil de l'utilisateur courant (ou impersonnifié)
+pub async fn current_person<C: ConnectionTrait>(
+    db: &C,
+    auth: &AuthUser,
+) -> Result<people::Model, AppError> {
+    let email = auth
+        .claims
+        .impersonating
+        .as_ref()
+        .map(|imp| imp.user_email.as_str())
+        .unwrap_or(&auth.claims.email);
+
+    People::find()
+        .filter(people::Column::Email.eq(email))
+        .one(db)
+        .await?
+        .ok_or(AppError::NotFound("Profil non trouvé".to_string()))
+}
+
 /// State pour le middleware ACL
 #[derive(Clone)]
 pub struct AclState {
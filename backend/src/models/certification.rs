@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// ============================================================================
+// CERTIFICATIONS (Niveaux et brevets détenus)
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CertificationResponse {
+    pub id: Uuid,
+    pub person_id: Uuid,
+    pub level: String,
+    pub status: String,
+    pub obtained_at: Option<String>,
+    pub issued_by: Option<String>,
+    pub issued_by_id: Option<Uuid>,
+    pub issued_by_name: Option<String>,
+    pub certificate_number: Option<String>,
+    pub source: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateCertificationRequest {
+    #[validate(length(min = 1, max = 10))]
+    pub level: String,
+    pub status: Option<String>, // obtained (défaut), preparing
+    pub obtained_at: Option<String>, // ISO date
+    #[validate(length(max = 255))]
+    pub issued_by: Option<String>,
+    pub issued_by_id: Option<Uuid>,
+    #[validate(length(max = 100))]
+    pub certificate_number: Option<String>,
+    pub source: Option<String>, // external (défaut), internal
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateCertificationRequest {
+    pub status: Option<String>,
+    pub obtained_at: Option<String>,
+    #[validate(length(max = 255))]
+    pub issued_by: Option<String>,
+    pub issued_by_id: Option<Uuid>,
+    #[validate(length(max = 100))]
+    pub certificate_number: Option<String>,
+    pub source: Option<String>,
+}
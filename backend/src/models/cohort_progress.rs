@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::ValidationStageResponse;
+
+// ============================================================================
+// COHORT PROGRESS (Tableau de progression d'une promotion)
+// ============================================================================
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DashboardFormat {
+    #[default]
+    Json,
+    Csv,
+    Xlsx,
+}
+
+fn default_inactive_weeks() -> i64 {
+    4
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CohortProgressQuery {
+    #[serde(default)]
+    pub format: DashboardFormat,
+    /// Élèves sans progression depuis ce nombre de semaines
+    #[serde(default = "default_inactive_weeks")]
+    pub inactive_weeks: i64,
+}
+
+/// Vue matricielle de tous les élèves préparant un niveau
+#[derive(Debug, Serialize)]
+pub struct CohortProgress {
+    pub diving_level: String,
+    pub inactive_weeks: i64,
+    pub stages: Vec<ValidationStageResponse>,
+    pub domains: Vec<CohortDomain>,
+    pub students: Vec<CohortStudent>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CohortDomain {
+    pub id: Uuid,
+    pub name: String,
+    pub modules: Vec<CohortModule>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CohortModule {
+    pub id: Uuid,
+    pub name: String,
+    pub skills: Vec<CohortSkill>,
+}
+
+/// Répartition des élèves de la promotion par étape pour un acquis
+#[derive(Debug, Serialize)]
+pub struct CohortSkill {
+    pub id: Uuid,
+    pub name: String,
+    /// Nombre d'élèves par étape, dans l'ordre de `stages`
+    pub stage_counts: Vec<StageCount>,
+    pub not_started: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StageCount {
+    pub stage_id: Uuid,
+    pub count: i32,
+}
+
+/// Ligne de la matrice: progression d'un élève par domaine et module
+#[derive(Debug, Serialize)]
+pub struct CohortStudent {
+    pub person_id: Uuid,
+    pub person_name: String,
+    pub percentage: f32,
+    /// Pourcentages dans l'ordre de `domains`
+    pub domains: Vec<UnitProgress>,
+    /// Pourcentages dans l'ordre des modules de `domains`
+    pub modules: Vec<UnitProgress>,
+    /// Dernière progression enregistrée sur un acquis du niveau
+    pub last_progress_at: Option<String>,
+    /// Aucune progression depuis `inactive_weeks` semaines
+    pub inactive: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnitProgress {
+    pub id: Uuid,
+    pub validated: i32,
+    pub total: i32,
+    pub percentage: f32,
+}
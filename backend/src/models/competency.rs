@@ -15,6 +15,9 @@ pub struct ValidationStageResponse {
     pub icon: String,
     pub sort_order: i32,
     pub is_final: bool,
+    pub allowed_next_stage_ids: Option<Vec<uuid::Uuid>>,
+    pub validator_rule: Option<String>,
+    pub self_assessable: bool,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -30,6 +33,10 @@ pub struct CreateValidationStageRequest {
     pub icon: Option<String>,
     pub sort_order: Option<i32>,
     pub is_final: Option<bool>,
+    /// Étapes accessibles depuis celle-ci (absent: progression libre)
+    pub allowed_next_stage_ids: Option<Vec<uuid::Uuid>>,
+    pub validator_rule: Option<String>,
+    pub self_assessable: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -45,6 +52,11 @@ pub struct UpdateValidationStageRequest {
     pub icon: Option<String>,
     pub sort_order: Option<i32>,
     pub is_final: Option<bool>,
+    /// Une liste vide rétablit la progression libre
+    pub allowed_next_stage_ids: Option<Vec<uuid::Uuid>>,
+    /// Une chaîne vide supprime la règle
+    pub validator_rule: Option<String>,
+    pub self_assessable: Option<bool>,
 }
 
 // ============================================================================
@@ -57,6 +69,7 @@ pub struct CompetencyDomainResponse {
     pub diving_level: String,
     pub name: String,
     pub sort_order: i32,
+    pub validity_months: Option<i32>,
     pub modules: Option<Vec<CompetencyModuleResponse>>,
 }
 
@@ -67,6 +80,9 @@ pub struct CreateCompetencyDomainRequest {
     #[validate(length(min = 1, max = 255))]
     pub name: String,
     pub sort_order: Option<i32>,
+    /// Durée de validité des validations en mois (absent: illimitée)
+    #[validate(range(min = 1))]
+    pub validity_months: Option<i32>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -76,6 +92,9 @@ pub struct UpdateCompetencyDomainRequest {
     #[validate(length(min = 1, max = 255))]
     pub name: Option<String>,
     pub sort_order: Option<i32>,
+    /// 0 pour une validité illimitée
+    #[validate(range(min = 0))]
+    pub validity_months: Option<i32>,
 }
 
 // ============================================================================
@@ -120,6 +139,10 @@ pub struct CompetencySkillResponse {
     pub description: Option<String>,
     pub sort_order: i32,
     pub min_validator_level: String,
+    /// Règle d'éligibilité des validateurs, prioritaire sur le niveau minimum
+    pub validator_rule: Option<String>,
+    /// Durée de validité en mois, prioritaire sur celle du domaine
+    pub validity_months: Option<i32>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -132,6 +155,11 @@ pub struct CreateCompetencySkillRequest {
     pub sort_order: Option<i32>,
     #[validate(length(min = 1, max = 10))]
     pub min_validator_level: Option<String>, // Default: "E2"
+    #[validate(length(max = 500))]
+    pub validator_rule: Option<String>, // ex: "E2 OR (N4 AND E1)"
+    /// Durée de validité en mois (absent: celle du domaine)
+    #[validate(range(min = 1))]
+    pub validity_months: Option<i32>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -144,6 +172,12 @@ pub struct UpdateCompetencySkillRequest {
     pub sort_order: Option<i32>,
     #[validate(length(min = 1, max = 10))]
     pub min_validator_level: Option<String>,
+    /// Chaîne vide pour revenir au seul niveau minimum
+    #[validate(length(max = 500))]
+    pub validator_rule: Option<String>,
+    /// 0 pour revenir à la durée de validité du domaine
+    #[validate(range(min = 0))]
+    pub validity_months: Option<i32>,
 }
 
 // ============================================================================
@@ -163,6 +197,8 @@ pub struct SkillValidationResponse {
     pub validated_by_id: uuid::Uuid,
     pub validated_by_name: Option<String>,
     pub notes: Option<String>,
+    pub session_id: Option<uuid::Uuid>,
+    pub palanquee_id: Option<uuid::Uuid>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -172,6 +208,53 @@ pub struct CreateSkillValidationRequest {
     pub stage_id: uuid::Uuid,
     pub validated_at: Option<String>, // ISO date, default: today
     pub notes: Option<String>,
+    /// Plongée d'où provient la validation (optionnel)
+    pub session_id: Option<uuid::Uuid>,
+    pub palanquee_id: Option<uuid::Uuid>,
+}
+
+/// Validation groupée après une plongée: mêmes acquis pour tous les
+/// plongeurs d'une palanquée ou d'une session
+#[derive(Debug, Deserialize, Validate)]
+pub struct BulkSkillValidationRequest {
+    pub palanquee_id: Option<uuid::Uuid>,
+    pub session_id: Option<uuid::Uuid>,
+    /// Restreint aux élèves indiqués (par défaut: tous les plongeurs de la plongée)
+    pub person_ids: Option<Vec<uuid::Uuid>>,
+    #[validate(length(min = 1, message = "Au moins un acquis est requis"))]
+    pub items: Vec<BulkSkillValidationItem>,
+    pub validated_at: Option<String>, // ISO date, default: today
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkSkillValidationItem {
+    pub skill_id: uuid::Uuid,
+    pub stage_id: uuid::Uuid,
+    /// Remplace les notes communes pour cet acquis
+    pub notes: Option<String>,
+}
+
+/// Résultat pour un couple (élève, acquis)
+#[derive(Debug, Serialize)]
+pub struct BulkSkillValidationResult {
+    pub person_id: uuid::Uuid,
+    pub person_name: Option<String>,
+    pub skill_id: uuid::Uuid,
+    pub skill_name: Option<String>,
+    pub stage_id: uuid::Uuid,
+    pub success: bool,
+    pub validation_id: Option<uuid::Uuid>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkSkillValidationResponse {
+    pub session_id: uuid::Uuid,
+    pub palanquee_id: Option<uuid::Uuid>,
+    pub success_count: usize,
+    pub error_count: usize,
+    pub results: Vec<BulkSkillValidationResult>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -184,8 +267,13 @@ pub struct UpdateSkillValidationRequest {
 /// Log entry for validation history (admin view)
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ValidationLogEntry {
+    /// Identifiant de l'entrée d'historique
     pub id: uuid::Uuid,
+    pub validation_id: Option<uuid::Uuid>,
+    /// created, advanced, updated, rollback, deleted
+    pub action: String,
     pub validated_at: String,
+    pub recorded_at: String,
     pub student_name: String,
     pub student_email: String,
     pub instructor_name: String,
@@ -197,7 +285,25 @@ pub struct ValidationLogEntry {
     pub stage_name: String,
     pub stage_color: String,
     pub is_final: bool,
+    pub previous_stage_name: Option<String>,
+    pub performed_by_name: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// Entrée de la frise chronologique d'un élève (une transition d'étape)
+#[derive(Debug, Serialize)]
+pub struct ValidationHistoryEntry {
+    pub id: uuid::Uuid,
+    pub action: String,
+    pub skill_id: uuid::Uuid,
+    pub skill_name: Option<String>,
+    pub from_stage: Option<ValidationStageResponse>,
+    pub to_stage: Option<ValidationStageResponse>,
+    pub validated_at: Option<String>,
+    pub validated_by_name: Option<String>,
+    pub performed_by_name: Option<String>,
     pub notes: Option<String>,
+    pub recorded_at: String,
 }
 
 // ============================================================================
@@ -236,6 +342,9 @@ pub struct CompetencySkillWithValidation {
     pub description: Option<String>,
     pub sort_order: i32,
     pub min_validator_level: String,
+    pub validator_rule: Option<String>,
+    /// Durée de validité effective (acquis, sinon domaine)
+    pub validity_months: Option<i32>,
     pub validation: Option<SkillValidationInfo>,
 }
 
@@ -251,6 +360,9 @@ pub struct SkillValidationInfo {
     pub validated_at: String,
     pub validated_by_name: String,
     pub notes: Option<String>,
+    pub expires_at: Option<String>,
+    /// Validation expirée: l'acquis est "à revalider"
+    pub expired: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -259,47 +371,195 @@ pub struct ProgressStats {
     pub validated: i32,      // Final stage
     pub in_progress: i32,    // Any non-final stage
     pub not_started: i32,
+    pub to_revalidate: i32,  // Expired validation
     pub percentage: f32,
 }
 
 // ============================================================================
-// LEGACY (for backward compatibility) - Keep old simple model
+// FRAMEWORK IMPORT/EXPORT (Référentiel complet d'un niveau)
+// ============================================================================
+
+/// Référentiel complet d'un niveau (domaines > modules > acquis),
+/// format d'échange pour l'import/export JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompetencyFramework {
+    pub diving_level: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub domains: Vec<FrameworkDomain>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameworkDomain {
+    pub name: String,
+    #[serde(default)]
+    pub sort_order: Option<i32>,
+    #[serde(default)]
+    pub validity_months: Option<i32>,
+    #[serde(default)]
+    pub modules: Vec<FrameworkModule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameworkModule {
+    pub name: String,
+    #[serde(default)]
+    pub sort_order: Option<i32>,
+    #[serde(default)]
+    pub skills: Vec<FrameworkSkill>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameworkSkill {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub sort_order: Option<i32>,
+    #[serde(default)]
+    pub min_validator_level: Option<String>,
+    #[serde(default)]
+    pub validator_rule: Option<String>,
+    #[serde(default)]
+    pub validity_months: Option<i32>,
+}
+
+/// Ligne CSV du référentiel (une ligne par acquis)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FrameworkCsvRow {
+    pub domain: String,
+    pub domain_order: Option<i32>,
+    #[serde(default)]
+    pub domain_validity_months: Option<i32>,
+    pub module: String,
+    pub module_order: Option<i32>,
+    pub skill: String,
+    pub skill_order: Option<i32>,
+    pub description: Option<String>,
+    pub min_validator_level: Option<String>,
+    #[serde(default)]
+    pub validator_rule: Option<String>,
+    #[serde(default)]
+    pub validity_months: Option<i32>,
+}
+
+/// Mode d'import: remplacement complet ou fusion par nom
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameworkImportMode {
+    Replace,
+    #[default]
+    Merge,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameworkFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FrameworkExportQuery {
+    #[serde(default)]
+    pub format: FrameworkFormat,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FrameworkImportQuery {
+    #[serde(default)]
+    pub format: FrameworkFormat,
+    #[serde(default)]
+    pub mode: FrameworkImportMode,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct FrameworkImportResult {
+    pub diving_level: String,
+    pub domains_created: usize,
+    pub modules_created: usize,
+    pub skills_created: usize,
+    pub skills_updated: usize,
+    pub domains_deleted: usize,
+    pub modules_deleted: usize,
+    pub skills_deleted: usize,
+    /// Acquis absents du fichier mais conservés car ils ont des validations
+    /// ou une position sur le document PDF (mode replace uniquement)
+    pub skills_kept: Vec<String>,
+}
+
+/// Référentiel officiel embarqué dans le backend
+#[derive(Debug, Serialize)]
+pub struct FrameworkBundleInfo {
+    pub code: String,
+    pub diving_level: String,
+    pub name: Option<String>,
+    pub domain_count: usize,
+    pub skill_count: usize,
+}
+
+// ============================================================================
+// LEGACY (lecture seule, dérivé de la hiérarchie domaines > modules > acquis)
 // ============================================================================
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CompetencyResponse {
+    /// ID de l'acquis (ou de la compétence historique non encore migrée)
     pub id: uuid::Uuid,
     pub level: String,
     pub name: String,
     pub description: Option<String>,
     pub sort_order: i32,
+    pub domain_name: Option<String>,
+    pub module_name: Option<String>,
     pub created_at: String,
     pub updated_at: String,
 }
 
-#[derive(Debug, Deserialize, Validate)]
-pub struct CreateCompetencyRequest {
-    #[validate(length(min = 1, max = 10, message = "Le niveau doit faire entre 1 et 10 caractères"))]
+/// Grouped competencies by level
+#[derive(Debug, Serialize)]
+pub struct CompetenciesByLevel {
     pub level: String,
-    #[validate(length(min = 1, max = 255, message = "Le nom doit faire entre 1 et 255 caractères"))]
-    pub name: String,
-    pub description: Option<String>,
-    pub sort_order: Option<i32>,
+    pub competencies: Vec<CompetencyResponse>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
-pub struct UpdateCompetencyRequest {
-    #[validate(length(min = 1, max = 10, message = "Le niveau doit faire entre 1 et 10 caractères"))]
-    pub level: Option<String>,
-    #[validate(length(min = 1, max = 255, message = "Le nom doit faire entre 1 et 255 caractères"))]
-    pub name: Option<String>,
-    pub description: Option<String>,
-    pub sort_order: Option<i32>,
+/// Corrections manuelles du rapprochement proposé par défaut
+#[derive(Debug, Default, Deserialize)]
+pub struct LegacyMigrationRequest {
+    #[serde(default)]
+    pub overrides: Vec<LegacyMappingOverride>,
 }
 
-/// Grouped competencies by level
+#[derive(Debug, Deserialize)]
+pub struct LegacyMappingOverride {
+    pub competency_id: uuid::Uuid,
+    /// Rattacher à un acquis existant
+    pub skill_id: Option<uuid::Uuid>,
+    /// Sinon créer l'acquis dans ce domaine / module (créés si besoin)
+    pub domain_name: Option<String>,
+    pub module_name: Option<String>,
+    #[serde(default)]
+    pub skip: bool,
+}
+
+/// Rapprochement d'une compétence historique
 #[derive(Debug, Serialize)]
-pub struct CompetenciesByLevel {
+pub struct LegacyMappingEntry {
+    pub competency_id: uuid::Uuid,
     pub level: String,
-    pub competencies: Vec<CompetencyResponse>,
+    pub name: String,
+    /// link (acquis existant), create (nouvel acquis), skip, migrated (déjà fait)
+    pub action: String,
+    pub skill_id: Option<uuid::Uuid>,
+    pub domain_name: Option<String>,
+    pub module_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LegacyMigrationResult {
+    pub linked: usize,
+    pub created: usize,
+    pub skipped: usize,
+    pub mappings: Vec<LegacyMappingEntry>,
 }
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// COMPLETION CERTIFICATES (Attestations de fin de niveau)
+// ============================================================================
+
+/// Résultat public de la vérification d'une attestation par son code
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CertificateVerificationResponse {
+    pub valid: bool,
+    pub verification_code: String,
+    pub person_name: String,
+    pub level: String,
+    pub level_name: String,
+    pub completed_at: String,
+    pub validators: Vec<String>,
+    pub issued_at: String,
+    pub club: String,
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// ============================================================================
+// EQUIVALENCES (CMAS, PADI, SSI -> aptitudes FFESSM)
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CertificationEquivalenceResponse {
+    pub id: Uuid,
+    pub system: String,
+    pub external_code: String,
+    pub external_name: String,
+    pub level: String,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateCertificationEquivalenceRequest {
+    #[validate(length(min = 1, max = 20))]
+    pub system: String,
+    #[validate(length(min = 1, max = 50))]
+    pub external_code: String,
+    #[validate(length(min = 1, max = 255))]
+    pub external_name: String,
+    #[validate(length(min = 1, max = 10))]
+    pub level: String,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateCertificationEquivalenceRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub external_name: Option<String>,
+    #[validate(length(min = 1, max = 10))]
+    pub level: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListEquivalencesQuery {
+    pub system: Option<String>,
+}
+
+// ============================================================================
+// BREVETS EXTERNES D'UN PLONGEUR
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExternalCertificationResponse {
+    pub id: Uuid,
+    pub person_id: Uuid,
+    pub system: String,
+    pub external_code: String,
+    pub external_name: Option<String>,
+    pub obtained_at: Option<String>,
+    pub certificate_number: Option<String>,
+    pub issued_by: Option<String>,
+    /// Aptitudes FFESSM reconnues pour ce brevet
+    pub equivalent_levels: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateExternalCertificationRequest {
+    #[validate(length(min = 1, max = 20))]
+    pub system: String,
+    #[validate(length(min = 1, max = 50))]
+    pub external_code: String,
+    pub obtained_at: Option<String>, // ISO date
+    #[validate(length(max = 100))]
+    pub certificate_number: Option<String>,
+    #[validate(length(max = 255))]
+    pub issued_by: Option<String>,
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::CohortProgress;
+
+// ============================================================================
+// MENTORS (Encadrants référents des élèves)
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct MentorAssignmentsQuery {
+    pub student_id: Option<Uuid>,
+    pub mentor_id: Option<Uuid>,
+    pub diving_level: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MentorAssignmentResponse {
+    pub id: Uuid,
+    pub student_id: Uuid,
+    pub student_name: String,
+    pub mentor_id: Uuid,
+    pub mentor_name: String,
+    pub diving_level: String,
+    pub stall_notified_at: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateMentorAssignmentRequest {
+    pub student_id: Uuid,
+    pub mentor_id: Uuid,
+    #[validate(length(min = 1, max = 10))]
+    pub diving_level: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMentorAssignmentRequest {
+    pub mentor_id: Uuid,
+}
+
+fn default_inactive_weeks() -> i64 {
+    4
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MentorDashboardQuery {
+    /// Élèves sans progression depuis ce nombre de semaines
+    #[serde(default = "default_inactive_weeks")]
+    pub inactive_weeks: i64,
+}
+
+/// Tableau de bord d'un référent: progression de ses élèves par niveau et
+/// prochaines sessions auxquelles ils sont inscrits
+#[derive(Debug, Serialize)]
+pub struct MentorDashboard {
+    pub levels: Vec<CohortProgress>,
+    pub upcoming_sessions: Vec<MenteeSession>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MenteeSession {
+    pub person_id: Uuid,
+    pub person_name: String,
+    pub session_id: Option<Uuid>,
+    pub sortie_id: Option<Uuid>,
+    pub name: String,
+    pub start_date: String,
+    pub location: Option<String>,
+}
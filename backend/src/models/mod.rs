@@ -10,6 +10,19 @@ pub mod competency;
 pub mod acl;
 pub mod palanquee;
 pub mod sortie;
+pub mod promotion;
+pub mod training_plan;
+pub mod certification;
+pub mod equivalence;
+pub mod validator_rule;
+pub mod completion_certificate;
+pub mod cohort_progress;
+pub mod validation_evidence;
+pub mod notification;
+pub mod quiz;
+pub mod mentor;
+pub mod teaching_qualification;
+pub mod validation_request;
 
 pub use session::*;
 pub use person::*;
@@ -23,4 +36,17 @@ pub use competency::*;
 pub use acl::*;
 pub use palanquee::*;
 pub use sortie::*;
+pub use promotion::*;
+pub use training_plan::*;
+pub use certification::*;
+pub use equivalence::*;
+pub use validator_rule::*;
+pub use completion_certificate::*;
+pub use cohort_progress::*;
+pub use validation_evidence::*;
+pub use notification::*;
+pub use quiz::*;
+pub use mentor::*;
+pub use teaching_qualification::*;
+pub use validation_request::*;
 
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// NOTIFICATIONS (Préférences et résumés de progression)
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct NotificationPreferencesResponse {
+    /// Résumé quotidien des nouvelles validations
+    pub progress_digest: bool,
+    /// Email quand un module ou un domaine est complet
+    pub milestone_emails: bool,
+    pub last_digest_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateNotificationPreferencesRequest {
+    pub progress_digest: Option<bool>,
+    pub milestone_emails: Option<bool>,
+}
+
+/// Bilan d'un envoi des résumés quotidiens
+#[derive(Debug, Default, Serialize)]
+pub struct DigestRunResponse {
+    pub sent: i32,
+    pub failed: i32,
+}
@@ -75,6 +75,9 @@ pub struct PalanqueeMemberResponse {
     pub preparing_level: Option<String>,
     pub is_encadrant: bool,
     pub instructor_level: Option<String>,
+    /// Aptitude FFESSM effective, y compris par équivalence de brevets externes
+    pub aptitude: Option<String>,
+    pub external_certifications: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -113,6 +116,9 @@ pub struct UnassignedParticipant {
     pub nitrox_training: bool,
     pub nitrox_confirmed_formation: bool,
     pub instructor_level: Option<String>,
+    /// Aptitude FFESSM effective, y compris par équivalence de brevets externes
+    pub aptitude: Option<String>,
+    pub external_certifications: Vec<String>,
 }
 
 // Helper pour parser les heures
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+// ============================================================================
+// LEVEL PROMOTIONS (Passage de niveau automatique)
+// ============================================================================
+
+/// Encadrant ayant validé au moins un acquis du niveau
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PromotionValidator {
+    pub id: Uuid,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LevelPromotionResponse {
+    pub id: Uuid,
+    pub person_id: Uuid,
+    pub person_name: Option<String>,
+    pub level: String,
+    pub status: String,
+    pub completed_at: String,
+    pub validators: Vec<PromotionValidator>,
+    pub promoted_at: Option<String>,
+    pub promoted_by_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListLevelPromotionsQuery {
+    pub status: Option<String>,
+    pub person_id: Option<Uuid>,
+}
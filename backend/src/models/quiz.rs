@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+use validator::Validate;
+
+// ============================================================================
+// QUIZ QUESTIONS (Banque de questions théoriques)
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct QuizQuestionsQuery {
+    pub diving_level: Option<String>,
+    pub domain_id: Option<Uuid>,
+    /// Inclut les questions retirées
+    #[serde(default)]
+    pub include_inactive: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuizQuestionResponse {
+    pub id: Uuid,
+    pub diving_level: String,
+    pub domain_id: Option<Uuid>,
+    pub domain_name: Option<String>,
+    pub question: String,
+    pub choices: Vec<String>,
+    pub correct_choices: Vec<usize>,
+    pub explanation: Option<String>,
+    pub points: i32,
+    pub is_active: bool,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateQuizQuestionRequest {
+    #[validate(length(min = 1, max = 10))]
+    pub diving_level: String,
+    pub domain_id: Option<Uuid>,
+    #[validate(length(min = 1))]
+    pub question: String,
+    pub choices: Vec<String>,
+    /// Indices (à partir de 0) des bonnes propositions
+    pub correct_choices: Vec<usize>,
+    pub explanation: Option<String>,
+    #[validate(range(min = 1))]
+    pub points: Option<i32>, // Default: 1
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateQuizQuestionRequest {
+    #[validate(length(min = 1, max = 10))]
+    pub diving_level: Option<String>,
+    pub domain_id: Option<Uuid>,
+    #[validate(length(min = 1))]
+    pub question: Option<String>,
+    pub choices: Option<Vec<String>>,
+    pub correct_choices: Option<Vec<usize>>,
+    pub explanation: Option<String>,
+    #[validate(range(min = 1))]
+    pub points: Option<i32>,
+    pub is_active: Option<bool>,
+}
+
+// ============================================================================
+// QUIZZES (Questionnaires et examens théoriques)
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct QuizResponse {
+    pub id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub diving_level: String,
+    pub domain_id: Option<Uuid>,
+    pub question_count: Option<i32>,
+    /// Questions actives correspondant au niveau (et au domaine)
+    pub available_questions: i32,
+    pub pass_percentage: i32,
+    pub time_limit_minutes: Option<i32>,
+    pub max_attempts: Option<i32>,
+    pub show_corrections: bool,
+    pub skill_id: Option<Uuid>,
+    pub stage_id: Option<Uuid>,
+    pub is_published: bool,
+    pub created_by_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateQuizRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub title: String,
+    pub description: Option<String>,
+    #[validate(length(min = 1, max = 10))]
+    pub diving_level: String,
+    pub domain_id: Option<Uuid>,
+    #[validate(range(min = 1))]
+    pub question_count: Option<i32>,
+    #[validate(range(min = 0, max = 100))]
+    pub pass_percentage: Option<i32>, // Default: 75
+    #[validate(range(min = 1))]
+    pub time_limit_minutes: Option<i32>,
+    #[validate(range(min = 1))]
+    pub max_attempts: Option<i32>,
+    #[serde(default)]
+    pub show_corrections: bool,
+    /// Acquis validé à l'étape `stage_id` en cas de réussite
+    pub skill_id: Option<Uuid>,
+    pub stage_id: Option<Uuid>,
+    #[serde(default)]
+    pub is_published: bool,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateQuizRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub title: Option<String>,
+    pub description: Option<String>,
+    #[validate(length(min = 1, max = 10))]
+    pub diving_level: Option<String>,
+    pub domain_id: Option<Uuid>,
+    #[validate(range(min = 1))]
+    pub question_count: Option<i32>,
+    #[validate(range(min = 0, max = 100))]
+    pub pass_percentage: Option<i32>,
+    #[validate(range(min = 1))]
+    pub time_limit_minutes: Option<i32>,
+    #[validate(range(min = 1))]
+    pub max_attempts: Option<i32>,
+    pub show_corrections: Option<bool>,
+    pub skill_id: Option<Uuid>,
+    pub stage_id: Option<Uuid>,
+    pub is_published: Option<bool>,
+}
+
+/// Questionnaire proposé à l'élève, avec ses résultats
+#[derive(Debug, Serialize)]
+pub struct StudentQuizResponse {
+    pub id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub diving_level: String,
+    pub pass_percentage: i32,
+    pub time_limit_minutes: Option<i32>,
+    pub max_attempts: Option<i32>,
+    pub attempts_used: i32,
+    pub best_percentage: Option<f32>,
+    pub passed: bool,
+    /// Passage commencé et non envoyé
+    pub in_progress_attempt_id: Option<Uuid>,
+}
+
+// ============================================================================
+// QUIZ ATTEMPTS (Passages et résultats)
+// ============================================================================
+
+/// Question telle que présentée à l'élève (sans les réponses)
+#[derive(Debug, Serialize)]
+pub struct AttemptQuestion {
+    pub id: Uuid,
+    pub question: String,
+    pub choices: Vec<String>,
+    pub points: i32,
+}
+
+/// Correction d'une question après l'envoi
+#[derive(Debug, Serialize)]
+pub struct QuestionCorrection {
+    pub question_id: Uuid,
+    pub selected: Vec<usize>,
+    pub correct_choices: Vec<usize>,
+    pub is_correct: bool,
+    pub explanation: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuizAttemptResponse {
+    pub id: Uuid,
+    pub quiz_id: Uuid,
+    pub quiz_title: String,
+    pub person_id: Uuid,
+    pub person_name: Option<String>,
+    pub started_at: String,
+    /// Heure limite d'envoi si le questionnaire est chronométré
+    pub deadline: Option<String>,
+    pub submitted_at: Option<String>,
+    /// Questions à traiter (vide une fois le passage envoyé)
+    pub questions: Vec<AttemptQuestion>,
+    pub score: Option<i32>,
+    pub max_score: i32,
+    pub percentage: Option<f32>,
+    pub passed: Option<bool>,
+    /// Résultat d'examen papier saisi par un encadrant
+    pub recorded_by_id: Option<Uuid>,
+    pub validation_id: Option<Uuid>,
+    /// Corrections, si le questionnaire les montre
+    pub corrections: Option<Vec<QuestionCorrection>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubmitQuizAttemptRequest {
+    /// Indices des propositions choisies, par question
+    pub answers: HashMap<Uuid, Vec<usize>>,
+}
+
+/// Résultat d'un examen passé sur papier
+#[derive(Debug, Deserialize, Validate)]
+pub struct RecordQuizResultRequest {
+    pub person_id: Uuid,
+    #[validate(range(min = 0))]
+    pub score: i32,
+    #[validate(range(min = 1))]
+    pub max_score: i32,
+    pub taken_at: Option<String>, // ISO date, default: today
+}
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// ============================================================================
+// TEACHING QUALIFICATIONS (Titres d'encadrement et recyclages)
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct TeachingQualificationsQuery {
+    pub person_id: Option<Uuid>,
+    pub level: Option<String>,
+    /// Titres expirés ou expirant dans ce nombre de jours
+    pub expiring_within_days: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TeachingQualificationResponse {
+    pub id: Uuid,
+    pub person_id: Uuid,
+    pub person_name: String,
+    pub level: String,
+    pub level_name: String,
+    pub obtained_at: String,
+    pub expires_at: Option<String>,
+    /// Faux si la date de recyclage est dépassée
+    pub is_active: bool,
+    pub last_recycled_at: Option<String>,
+    pub certificate_number: Option<String>,
+    pub notes: Option<String>,
+    pub recyclings: Vec<TeachingRecyclingResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TeachingRecyclingResponse {
+    pub id: Uuid,
+    pub recycled_at: String,
+    pub notes: Option<String>,
+    pub recorded_by_id: Option<Uuid>,
+    pub recorded_by_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateTeachingQualificationRequest {
+    pub person_id: Uuid,
+    #[validate(length(min = 1, max = 10))]
+    pub level: String,
+    pub obtained_at: String, // YYYY-MM-DD
+    /// Par défaut: date d'obtention + périodicité de recyclage du niveau
+    pub expires_at: Option<String>,
+    #[validate(length(max = 50))]
+    pub certificate_number: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateTeachingQualificationRequest {
+    pub obtained_at: Option<String>,
+    /// Chaîne vide: validité illimitée
+    pub expires_at: Option<String>,
+    #[validate(length(max = 50))]
+    pub certificate_number: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecordRecyclingRequest {
+    pub recycled_at: String, // YYYY-MM-DD
+    /// Par défaut: date du recyclage + périodicité de recyclage du niveau
+    pub expires_at: Option<String>,
+    pub notes: Option<String>,
+}
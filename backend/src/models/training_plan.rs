@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::ValidationStageResponse;
+
+// ============================================================================
+// TRAINING PLANS (Plan de formation par session)
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct SessionTrainingPlan {
+    pub session_id: Uuid,
+    pub students: Vec<StudentTrainingPlan>,
+}
+
+/// Plan d'un élève dans une palanquée de la session
+#[derive(Debug, Serialize)]
+pub struct StudentTrainingPlan {
+    pub person_id: Uuid,
+    pub person_name: String,
+    pub palanquee_id: Uuid,
+    pub palanquee_number: i32,
+    pub rotation_number: i32,
+    /// Niveau en préparation (None si l'élève ne prépare aucun niveau)
+    pub target_level: Option<String>,
+    pub skills: Vec<PlannedSkill>,
+}
+
+/// Acquis restant à travailler
+#[derive(Debug, Serialize)]
+pub struct PlannedSkill {
+    pub skill_id: Uuid,
+    pub skill_name: String,
+    pub module_name: String,
+    pub domain_name: String,
+    pub min_validator_level: String,
+    pub validator_rule: Option<String>,
+    pub current_stage_name: Option<String>,
+    pub next_stage: Option<ValidationStageResponse>,
+    /// Encadrants de la palanquée ayant le niveau requis pour valider
+    pub suggested_encadrants: Vec<SuggestedEncadrant>,
+    pub pin: Option<SkillPlanPin>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SuggestedEncadrant {
+    pub id: Uuid,
+    pub name: String,
+    pub diving_level: Option<String>,
+}
+
+/// Acquis épinglé par un encadrant pour la session
+#[derive(Debug, Serialize)]
+pub struct SkillPlanPin {
+    pub id: Uuid,
+    pub person_id: Uuid,
+    pub skill_id: Uuid,
+    pub palanquee_id: Option<Uuid>,
+    pub target_stage_id: Option<Uuid>,
+    pub encadrant_id: Option<Uuid>,
+    pub encadrant_name: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PinSkillRequest {
+    pub person_id: Uuid,
+    pub skill_id: Uuid,
+    pub palanquee_id: Option<Uuid>,
+    pub target_stage_id: Option<Uuid>,
+    pub encadrant_id: Option<Uuid>,
+    pub notes: Option<String>,
+}
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// ============================================================================
+// VALIDATION EVIDENCE (Preuves et commentaires d'une validation)
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct ValidationEvidenceResponse {
+    pub id: Uuid,
+    pub validation_id: Uuid,
+    pub kind: String, // file, dive_log
+    pub file_name: Option<String>,
+    pub content_type: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub reference: Option<String>,
+    pub caption: Option<String>,
+    pub uploaded_by_id: Option<Uuid>,
+    pub uploaded_by_name: Option<String>,
+    pub created_at: String,
+}
+
+/// Commentaire avec ses réponses, dans l'ordre chronologique
+#[derive(Debug, Serialize)]
+pub struct ValidationCommentResponse {
+    pub id: Uuid,
+    pub validation_id: Uuid,
+    pub parent_id: Option<Uuid>,
+    pub author_id: Option<Uuid>,
+    pub author_name: Option<String>,
+    pub body: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub replies: Vec<ValidationCommentResponse>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateValidationCommentRequest {
+    #[validate(length(min = 1, max = 5000))]
+    pub body: String,
+    /// Commentaire auquel on répond
+    pub parent_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateValidationCommentRequest {
+    #[validate(length(min = 1, max = 5000))]
+    pub body: String,
+}
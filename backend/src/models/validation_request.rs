@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+// ============================================================================
+// VALIDATION REQUESTS (Demandes de validation d'acquis)
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ValidationRequestsQuery {
+    /// pending, approved, rejected
+    pub status: Option<String>,
+    pub person_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidationRequestResponse {
+    pub id: Uuid,
+    pub person_id: Uuid,
+    pub person_name: String,
+    pub skill_id: Uuid,
+    pub skill_name: String,
+    pub min_validator_level: String,
+    pub stage_id: Option<Uuid>,
+    pub stage_name: Option<String>,
+    pub requested_by_id: Option<Uuid>,
+    pub requested_by_name: Option<String>,
+    pub session_id: Option<Uuid>,
+    pub session_name: Option<String>,
+    pub demonstrated_at: Option<String>,
+    pub comment: Option<String>,
+    pub status: String,
+    pub reviewed_by_id: Option<Uuid>,
+    pub reviewed_by_name: Option<String>,
+    pub reviewed_at: Option<String>,
+    pub review_comment: Option<String>,
+    pub validation_id: Option<Uuid>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateValidationRequestRequest {
+    /// Élève concerné (par défaut: l'utilisateur courant)
+    pub person_id: Option<Uuid>,
+    pub skill_id: Uuid,
+    /// Étape demandée (par défaut: étape finale)
+    pub stage_id: Option<Uuid>,
+    pub session_id: Option<Uuid>,
+    pub demonstrated_at: Option<String>, // YYYY-MM-DD
+    #[validate(length(max = 2000))]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApproveValidationRequestRequest {
+    /// Étape attribuée (par défaut: celle demandée)
+    pub stage_id: Option<Uuid>,
+    /// Par défaut: date de démonstration, sinon aujourd'hui
+    pub validated_at: Option<String>,
+    /// Par défaut: commentaire de la demande
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RejectValidationRequestRequest {
+    pub comment: Option<String>,
+}
@@ -0,0 +1,266 @@
+use serde::Serialize;
+use std::fmt;
+
+use super::diving_level::{DivingLevel, LevelCatalog};
+
+/// Règle d'éligibilité pour valider un acquis, combinant niveaux et brevets.
+///
+/// Syntaxe: codes de niveau combinés par `AND` / `OR` (ou `ET` / `OU`),
+/// avec parenthèses. `AND` est prioritaire sur `OR`.
+/// - `E2`: E2 ou un niveau supérieur de la même famille (E3, E4)
+/// - `=E3`: exactement E3
+/// - `PNC`: les compétences et spécialités doivent être détenues telles quelles
+///
+/// Exemple: `E2 OR (N4 AND E1)`, `E1 AND PNC`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidatorRule {
+    AtLeast(String),
+    Exactly(String),
+    And(Vec<ValidatorRule>),
+    Or(Vec<ValidatorRule>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    And,
+    Or,
+    Level { code: String, exact: bool },
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let spaced = input.replace('(', " ( ").replace(')', " ) ");
+    spaced
+        .split_whitespace()
+        .map(|word| match word.to_uppercase().as_str() {
+            "(" => Ok(Token::Open),
+            ")" => Ok(Token::Close),
+            "AND" | "ET" | "&&" => Ok(Token::And),
+            "OR" | "OU" | "||" => Ok(Token::Or),
+            upper => {
+                let (code, exact) = match upper.strip_prefix('=') {
+                    Some(code) => (code, true),
+                    None => (upper, false),
+                };
+                if code.is_empty() || !code.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    return Err(format!("Code de niveau invalide: {}", word));
+                }
+                Ok(Token::Level {
+                    code: code.to_string(),
+                    exact,
+                })
+            }
+        })
+        .collect()
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<ValidatorRule, String> {
+        let mut terms = vec![self.and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            terms.push(self.and()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { ValidatorRule::Or(terms) })
+    }
+
+    fn and(&mut self) -> Result<ValidatorRule, String> {
+        let mut terms = vec![self.atom()?];
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            terms.push(self.atom()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { ValidatorRule::And(terms) })
+    }
+
+    fn atom(&mut self) -> Result<ValidatorRule, String> {
+        match self.next() {
+            Some(Token::Level { code, exact: false }) => Ok(ValidatorRule::AtLeast(code)),
+            Some(Token::Level { code, exact: true }) => Ok(ValidatorRule::Exactly(code)),
+            Some(Token::Open) => {
+                let rule = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(rule),
+                    _ => Err("Parenthèse fermante manquante".to_string()),
+                }
+            }
+            Some(_) => Err("Niveau attendu avant ou après un opérateur".to_string()),
+            None => Err("Règle incomplète".to_string()),
+        }
+    }
+}
+
+impl ValidatorRule {
+    /// Analyse une règle saisie par un administrateur
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            return Err("Règle vide".to_string());
+        }
+
+        let mut parser = Parser { tokens, pos: 0 };
+        let rule = parser.or()?;
+        if parser.pos < parser.tokens.len() {
+            return Err("Expression inattendue en fin de règle".to_string());
+        }
+        Ok(rule)
+    }
+
+    /// Codes de niveau cités par la règle
+    pub fn levels(&self) -> Vec<&str> {
+        match self {
+            ValidatorRule::AtLeast(code) | ValidatorRule::Exactly(code) => vec![code.as_str()],
+            ValidatorRule::And(terms) | ValidatorRule::Or(terms) => {
+                terms.iter().flat_map(|t| t.levels()).collect()
+            }
+        }
+    }
+
+    /// Codes cités par la règle qui n'existent pas dans le référentiel
+    pub fn unknown_levels(&self, catalog: &LevelCatalog) -> Vec<String> {
+        self.levels()
+            .into_iter()
+            .filter(|code| catalog.parse(code).is_none())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Indique si un plongeur (chaîne historique "N4,E1,preparing_E2")
+    /// satisfait la règle. Les niveaux en préparation ne comptent pas.
+    pub fn is_satisfied(&self, diving_level: &str, catalog: &LevelCatalog) -> bool {
+        let held: Vec<&DivingLevel> = diving_level
+            .split(',')
+            .map(str::trim)
+            .filter(|l| !l.starts_with("preparing_"))
+            .filter_map(|l| catalog.parse(l))
+            .collect();
+        self.matches(&held, catalog)
+    }
+
+    fn matches(&self, held: &[&DivingLevel], catalog: &LevelCatalog) -> bool {
+        match self {
+            ValidatorRule::Exactly(code) => held.iter().any(|l| l.code.eq_ignore_ascii_case(code)),
+            ValidatorRule::AtLeast(code) => match catalog.parse(code) {
+                // Niveaux et qualifications: rang supérieur dans la même famille
+                Some(required) if required.is_complete_level() => held
+                    .iter()
+                    .any(|l| l.family == required.family && l.rank >= required.rank),
+                Some(required) => held.iter().any(|l| l.code == required.code),
+                None => false,
+            },
+            ValidatorRule::And(terms) => terms.iter().all(|t| t.matches(held, catalog)),
+            ValidatorRule::Or(terms) => terms.iter().any(|t| t.matches(held, catalog)),
+        }
+    }
+}
+
+impl fmt::Display for ValidatorRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidatorRule::AtLeast(code) => write!(f, "{}", code),
+            ValidatorRule::Exactly(code) => write!(f, "={}", code),
+            ValidatorRule::And(terms) => {
+                let parts: Vec<String> = terms
+                    .iter()
+                    .map(|t| match t {
+                        ValidatorRule::Or(_) => format!("({})", t),
+                        _ => t.to_string(),
+                    })
+                    .collect();
+                write!(f, "{}", parts.join(" AND "))
+            }
+            ValidatorRule::Or(terms) => {
+                let parts: Vec<String> = terms.iter().map(|t| t.to_string()).collect();
+                write!(f, "{}", parts.join(" OR "))
+            }
+        }
+    }
+}
+
+/// Personne du club pouvant valider un acquis
+#[derive(Debug, Serialize)]
+pub struct EligibleValidatorResponse {
+    pub person_id: uuid::Uuid,
+    pub first_name: String,
+    pub last_name: String,
+    pub diving_level: Option<String>,
+}
+
+/// Validateurs éligibles pour un acquis et règle appliquée
+#[derive(Debug, Serialize)]
+pub struct EligibleValidatorsResponse {
+    pub skill_id: uuid::Uuid,
+    /// Règle effective ("E2" lorsque seul le niveau minimum est défini)
+    pub rule: String,
+    pub validators: Vec<EligibleValidatorResponse>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_precedence() {
+        let rule = ValidatorRule::parse("e2 or n4 and E1").unwrap();
+        assert_eq!(rule.to_string(), "E2 OR N4 AND E1");
+        assert_eq!(
+            rule,
+            ValidatorRule::Or(vec![
+                ValidatorRule::AtLeast("E2".to_string()),
+                ValidatorRule::And(vec![
+                    ValidatorRule::AtLeast("N4".to_string()),
+                    ValidatorRule::AtLeast("E1".to_string()),
+                ]),
+            ])
+        );
+
+        let rule = ValidatorRule::parse("(E2 OU =E1) ET PNC").unwrap();
+        assert_eq!(rule.to_string(), "(E2 OR =E1) AND PNC");
+
+        assert!(ValidatorRule::parse("").is_err());
+        assert!(ValidatorRule::parse("E2 OR").is_err());
+        assert!(ValidatorRule::parse("(E2 AND N4").is_err());
+        assert!(ValidatorRule::parse("E2 N4").is_err());
+    }
+
+    #[test]
+    fn test_rule_evaluation() {
+        let catalog = LevelCatalog::builtin();
+
+        let rule = ValidatorRule::parse("E2 OR (N4 AND E1)").unwrap();
+        assert!(rule.is_satisfied("N4,E2", &catalog));
+        assert!(rule.is_satisfied("N4,E1", &catalog));
+        assert!(rule.is_satisfied("N5,E1", &catalog));
+        // Un N4 sans qualification d'encadrement ne suffit pas
+        assert!(!rule.is_satisfied("N4", &catalog));
+        assert!(!rule.is_satisfied("N3,E1", &catalog));
+        assert!(!rule.is_satisfied("N4,preparing_E1", &catalog));
+
+        let rule = ValidatorRule::parse("=E3").unwrap();
+        assert!(rule.is_satisfied("N4,E3", &catalog));
+        assert!(!rule.is_satisfied("N5,E4", &catalog));
+
+        let rule = ValidatorRule::parse("E1 AND PNC").unwrap();
+        assert!(rule.is_satisfied("N2,E1,PNC", &catalog));
+        assert!(!rule.is_satisfied("N2,E1,PN", &catalog));
+
+        let rule = ValidatorRule::parse("E2 OR X9").unwrap();
+        assert_eq!(rule.unknown_levels(&catalog), vec!["X9".to_string()]);
+    }
+}
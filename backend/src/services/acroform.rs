@@ -0,0 +1,573 @@
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId, Stream};
+use std::collections::{HashMap, HashSet};
+
+use super::pdf_generator::inherited_page_attributes;
+use super::pdf_text::{FontStyle, TextLayer};
+use crate::errors::AppError;
+
+/// Taille de police quand le champ n'en impose pas (DA "0 Tf")
+const MAX_AUTO_FONT_SIZE: f32 = 10.0;
+const MIN_FONT_SIZE: f32 = 4.0;
+const PADDING: f32 = 2.0;
+
+/// Champ terminal du formulaire
+#[derive(Debug, Clone)]
+pub struct FormField {
+    /// Nom complet (noms des parents séparés par des points)
+    pub name: String,
+    pub field_type: Option<String>,
+    pub field_id: ObjectId,
+    /// Annotations Widget affichant le champ
+    pub widgets: Vec<ObjectId>,
+    /// Page (à partir de 1) du premier widget
+    pub page: Option<i32>,
+    pub rect: Option<[f32; 4]>,
+}
+
+fn resolve<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Object> {
+    doc.dereference(object).ok().map(|(_, o)| o)
+}
+
+fn rect_of(doc: &Document, dict: &Dictionary) -> Option<[f32; 4]> {
+    let values = resolve(doc, dict.get(b"Rect").ok()?)?.as_array().ok()?;
+    if values.len() != 4 {
+        return None;
+    }
+    let n: Vec<f32> = values.iter().filter_map(|v| v.as_float().ok()).collect();
+    if n.len() != 4 {
+        return None;
+    }
+    Some([n[0].min(n[2]), n[1].min(n[3]), n[0].max(n[2]), n[1].max(n[3])])
+}
+
+fn text_of(dict: &Dictionary, key: &[u8]) -> Option<String> {
+    dict.get(key).ok().and_then(|o| lopdf::decode_text_string(o).ok())
+}
+
+/// Champs de l'AcroForm du document (vide s'il n'a pas de formulaire)
+pub fn list_fields(doc: &Document) -> Vec<FormField> {
+    let Some(roots) = doc
+        .catalog()
+        .ok()
+        .and_then(|c| c.get(b"AcroForm").ok())
+        .and_then(|o| resolve(doc, o))
+        .and_then(|o| o.as_dict().ok())
+        .and_then(|form| form.get(b"Fields").ok())
+        .and_then(|o| resolve(doc, o))
+        .and_then(|o| o.as_array().ok())
+    else {
+        return Vec::new();
+    };
+
+    // Page de chaque widget
+    let mut widget_pages: HashMap<ObjectId, i32> = HashMap::new();
+    for (page_number, page_id) in doc.get_pages() {
+        let annots = doc
+            .get_dictionary(page_id)
+            .ok()
+            .and_then(|page| page.get(b"Annots").ok())
+            .and_then(|o| resolve(doc, o))
+            .and_then(|o| o.as_array().ok());
+        for annot in annots.into_iter().flatten() {
+            if let Ok(id) = annot.as_reference() {
+                widget_pages.insert(id, page_number as i32);
+            }
+        }
+    }
+
+    let mut fields = Vec::new();
+    let mut visited = HashSet::new();
+    for root in roots {
+        if let Ok(id) = root.as_reference() {
+            collect_fields(doc, id, None, None, &widget_pages, &mut visited, &mut fields);
+        }
+    }
+    fields
+}
+
+fn collect_fields(
+    doc: &Document,
+    id: ObjectId,
+    parent_name: Option<&str>,
+    parent_type: Option<&str>,
+    widget_pages: &HashMap<ObjectId, i32>,
+    visited: &mut HashSet<ObjectId>,
+    fields: &mut Vec<FormField>,
+) {
+    if !visited.insert(id) {
+        return;
+    }
+    let Ok(dict) = doc.get_dictionary(id) else {
+        return;
+    };
+
+    let name = match (parent_name, text_of(dict, b"T")) {
+        (Some(parent), Some(own)) => Some(format!("{}.{}", parent, own)),
+        (None, Some(own)) => Some(own),
+        (parent, None) => parent.map(str::to_string),
+    };
+    let field_type = dict
+        .get(b"FT")
+        .ok()
+        .and_then(|o| o.as_name_str().ok())
+        .or(parent_type);
+
+    let kids: Vec<ObjectId> = dict
+        .get(b"Kids")
+        .ok()
+        .and_then(|o| resolve(doc, o))
+        .and_then(|o| o.as_array().ok())
+        .map(|kids| kids.iter().filter_map(|k| k.as_reference().ok()).collect())
+        .unwrap_or_default();
+    // Les enfants nommés sont des sous-champs, les autres des widgets
+    let (child_fields, widgets): (Vec<ObjectId>, Vec<ObjectId>) = kids
+        .into_iter()
+        .partition(|kid| doc.get_dictionary(*kid).map(|d| d.has(b"T")).unwrap_or(false));
+
+    for child in child_fields {
+        collect_fields(doc, child, name.as_deref(), field_type, widget_pages, visited, fields);
+    }
+
+    let widgets = if widgets.is_empty() && dict.has(b"Rect") {
+        vec![id]
+    } else {
+        widgets
+    };
+    let Some(name) = name else {
+        return;
+    };
+    if widgets.is_empty() {
+        return;
+    }
+
+    let first = widgets[0];
+    fields.push(FormField {
+        name,
+        field_type: field_type.map(str::to_string),
+        field_id: id,
+        page: widget_pages.get(&first).copied(),
+        rect: doc.get_dictionary(first).ok().and_then(|w| rect_of(doc, w)),
+        widgets,
+    });
+}
+
+/// Taille de police imposée par l'apparence par défaut ("/Helv 9 Tf 0 g")
+fn default_font_size(doc: &Document, field: &Dictionary, widget: &Dictionary) -> Option<f32> {
+    let da = [widget, field]
+        .into_iter()
+        .find_map(|d| d.get(b"DA").ok().and_then(|o| o.as_str().ok()))
+        .or_else(|| {
+            doc.catalog()
+                .ok()?
+                .get(b"AcroForm")
+                .ok()
+                .and_then(|o| resolve(doc, o))?
+                .as_dict()
+                .ok()?
+                .get(b"DA")
+                .ok()?
+                .as_str()
+                .ok()
+        })?;
+    let da = String::from_utf8_lossy(da);
+    let words: Vec<&str> = da.split_whitespace().collect();
+    let tf = words.iter().position(|w| *w == "Tf")?;
+    let size: f32 = words.get(tf.checked_sub(1)?)?.parse().ok()?;
+    (size > 0.0).then_some(size)
+}
+
+/// Renseigne les champs texte nommés et génère leur apparence.
+/// Les noms absents du formulaire sont ignorés.
+pub fn fill_fields(doc: &mut Document, values: &HashMap<String, String>) -> Result<(), AppError> {
+    if values.is_empty() {
+        return Ok(());
+    }
+
+    let fields = list_fields(doc);
+    let mut text = TextLayer::new();
+    let font_id = text.font_id(doc, FontStyle::Regular);
+
+    for field in fields {
+        let Some(value) = values.get(&field.name) else {
+            continue;
+        };
+        if field.field_type.as_deref().is_some_and(|t| t != "Tx") {
+            continue;
+        }
+
+        let field_dict = doc
+            .get_dictionary(field.field_id)
+            .map_err(|e| AppError::Internal(format!("Failed to get field: {}", e)))?
+            .clone();
+
+        for widget_id in &field.widgets {
+            let widget = doc
+                .get_dictionary(*widget_id)
+                .map_err(|e| AppError::Internal(format!("Failed to get widget: {}", e)))?;
+            let Some(rect) = rect_of(doc, widget) else {
+                continue;
+            };
+            let width = rect[2] - rect[0];
+            let height = rect[3] - rect[1];
+
+            let font_size = default_font_size(doc, &field_dict, widget)
+                .unwrap_or_else(|| {
+                    // Taille réduite pour que la valeur tienne dans la largeur
+                    let unit_width = TextLayer::width(FontStyle::Regular, value, 1.0).max(1.0);
+                    let fit = (width - 2.0 * PADDING) / unit_width;
+                    (height * 0.7).min(MAX_AUTO_FONT_SIZE).min(fit)
+                })
+                .max(MIN_FONT_SIZE);
+            let baseline = (height - font_size) / 2.0 + font_size * 0.22;
+
+            let content = format!(
+                "/Tx BMC\nq\nBT\n0 g\n/Helv {:.2} Tf\n{:.2} {:.2} Td\n{} Tj\nET\nQ\nEMC\n",
+                font_size,
+                PADDING,
+                baseline,
+                text.encode(FontStyle::Regular, value)
+            );
+            let appearance = Stream::new(
+                dictionary! {
+                    "Type" => "XObject",
+                    "Subtype" => "Form",
+                    "BBox" => vec![0.into(), 0.into(), width.into(), height.into()],
+                    "Resources" => dictionary! { "Font" => dictionary! { "Helv" => font_id } },
+                },
+                content.into_bytes(),
+            );
+            let appearance_id = doc.add_object(appearance);
+
+            let widget = doc
+                .get_dictionary_mut(*widget_id)
+                .map_err(|e| AppError::Internal(format!("Failed to get widget: {}", e)))?;
+            widget.set("AP", dictionary! { "N" => appearance_id });
+        }
+
+        let field_dict = doc
+            .get_dictionary_mut(field.field_id)
+            .map_err(|e| AppError::Internal(format!("Failed to get field: {}", e)))?;
+        field_dict.set("V", lopdf::text_string(value));
+    }
+
+    text.embed(doc)
+}
+
+/// Apparence normale d'un widget (selon son état /AS pour les cases)
+fn normal_appearance(doc: &Document, widget: &Dictionary) -> Option<ObjectId> {
+    let ap = resolve(doc, widget.get(b"AP").ok()?)?.as_dict().ok()?;
+    let normal = ap.get(b"N").ok()?;
+    if let Ok(id) = normal.as_reference() {
+        if doc.get_object(id).ok()?.as_stream().is_ok() {
+            return Some(id);
+        }
+    }
+    let states = resolve(doc, normal)?.as_dict().ok()?;
+    let state = widget.get(b"AS").ok()?.as_name().ok()?;
+    states.get(state).ok()?.as_reference().ok()
+}
+
+/// Dessine les widgets du formulaire dans le contenu des pages puis
+/// supprime le formulaire. Les widgets cachés ou sans apparence disparaissent.
+pub fn flatten(doc: &mut Document) -> Result<(), AppError> {
+    let widgets: HashSet<ObjectId> = list_fields(doc)
+        .into_iter()
+        .flat_map(|f| f.widgets)
+        .collect();
+    if widgets.is_empty() {
+        if let Ok(catalog) = doc.catalog_mut() {
+            catalog.remove(b"AcroForm");
+        }
+        return Ok(());
+    }
+
+    for (_, page_id) in doc.get_pages() {
+        let Ok(page) = doc.get_dictionary(page_id) else {
+            continue;
+        };
+        let annots: Vec<Object> = page
+            .get(b"Annots")
+            .ok()
+            .and_then(|o| resolve(doc, o))
+            .and_then(|o| o.as_array().ok())
+            .cloned()
+            .unwrap_or_default();
+        let (flattened, kept): (Vec<Object>, Vec<Object>) = annots.into_iter().partition(|a| {
+            a.as_reference().map(|id| widgets.contains(&id)).unwrap_or(false)
+        });
+        if flattened.is_empty() {
+            continue;
+        }
+
+        let mut drawing = String::new();
+        let mut xobjects: Vec<(String, ObjectId)> = Vec::new();
+        for annot in flattened {
+            let Ok(widget_id) = annot.as_reference() else {
+                continue;
+            };
+            let Ok(widget) = doc.get_dictionary(widget_id) else {
+                continue;
+            };
+            // Widget caché (F bit 2)
+            let flags = widget.get(b"F").and_then(Object::as_i64).unwrap_or(0);
+            if flags & 2 != 0 {
+                continue;
+            }
+            let (Some(rect), Some(appearance_id)) = (rect_of(doc, widget), normal_appearance(doc, widget))
+            else {
+                continue;
+            };
+
+            let Ok(appearance) = doc.get_object_mut(appearance_id).and_then(Object::as_stream_mut) else {
+                continue;
+            };
+            appearance.dict.set("Type", "XObject");
+            appearance.dict.set("Subtype", "Form");
+            let bbox: Vec<f32> = appearance
+                .dict
+                .get(b"BBox")
+                .and_then(Object::as_array)
+                .map(|b| b.iter().filter_map(|v| v.as_float().ok()).collect())
+                .unwrap_or_default();
+            let [bx1, by1, bx2, by2] = match bbox.as_slice() {
+                &[a, b, c, d] => [a.min(c), b.min(d), a.max(c), b.max(d)],
+                _ => [0.0, 0.0, rect[2] - rect[0], rect[3] - rect[1]],
+            };
+
+            // Ramène la BBox de l'apparence sur le rectangle du widget
+            let sx = if bx2 > bx1 { (rect[2] - rect[0]) / (bx2 - bx1) } else { 1.0 };
+            let sy = if by2 > by1 { (rect[3] - rect[1]) / (by2 - by1) } else { 1.0 };
+            let name = format!("Flat{}_{}", appearance_id.0, appearance_id.1);
+            drawing.push_str(&format!(
+                "q {:.4} 0 0 {:.4} {:.4} {:.4} cm /{} Do Q\n",
+                sx,
+                sy,
+                rect[0] - bx1 * sx,
+                rect[1] - by1 * sy,
+                name
+            ));
+            xobjects.push((name, appearance_id));
+        }
+
+        add_page_xobjects(doc, page_id, &xobjects)?;
+        append_page_content(doc, page_id, drawing.into_bytes())?;
+
+        let page = doc
+            .get_dictionary_mut(page_id)
+            .map_err(|e| AppError::Internal(format!("Failed to get page: {}", e)))?;
+        if kept.is_empty() {
+            page.remove(b"Annots");
+        } else {
+            page.set("Annots", Object::Array(kept));
+        }
+    }
+
+    if let Ok(catalog) = doc.catalog_mut() {
+        catalog.remove(b"AcroForm");
+    }
+    Ok(())
+}
+
+/// Déclare des XObjects dans les ressources propres de la page
+fn add_page_xobjects(
+    doc: &mut Document,
+    page_id: ObjectId,
+    xobjects: &[(String, ObjectId)],
+) -> Result<(), AppError> {
+    let page = doc
+        .get_dictionary(page_id)
+        .map_err(|e| AppError::Internal(format!("Failed to get page: {}", e)))?;
+
+    // Ressources de la page, héritées ou partagées: copiées sur la page
+    let mut resources = match page.get(b"Resources") {
+        Ok(resources) => resolve(doc, resources).and_then(|o| o.as_dict().ok()).cloned(),
+        Err(_) => inherited_page_attributes(doc, page_id)
+            .into_iter()
+            .find(|(key, _)| *key == "Resources")
+            .and_then(|(_, value)| resolve(doc, &value).and_then(|o| o.as_dict().ok()).cloned()),
+    }
+    .unwrap_or_default();
+
+    let mut names = resources
+        .get(b"XObject")
+        .ok()
+        .and_then(|o| resolve(doc, o))
+        .and_then(|o| o.as_dict().ok())
+        .cloned()
+        .unwrap_or_default();
+    for (name, id) in xobjects {
+        names.set(name.as_bytes(), Object::Reference(*id));
+    }
+    resources.set("XObject", Object::Dictionary(names));
+
+    let page = doc
+        .get_dictionary_mut(page_id)
+        .map_err(|e| AppError::Internal(format!("Failed to get page: {}", e)))?;
+    page.set("Resources", Object::Dictionary(resources));
+    Ok(())
+}
+
+/// Ajoute des opérations après le contenu existant, dans un état graphique propre
+fn append_page_content(doc: &mut Document, page_id: ObjectId, content: Vec<u8>) -> Result<(), AppError> {
+    let existing: Vec<Object> = {
+        let page = doc
+            .get_dictionary(page_id)
+            .map_err(|e| AppError::Internal(format!("Failed to get page: {}", e)))?;
+        match page.get(b"Contents") {
+            Ok(Object::Reference(id)) => match doc.get_object(*id) {
+                Ok(Object::Array(items)) => items.clone(),
+                _ => vec![Object::Reference(*id)],
+            },
+            Ok(Object::Array(items)) => items.clone(),
+            _ => Vec::new(),
+        }
+    };
+
+    let open_id = doc.add_object(Stream::new(Dictionary::new(), b"q\n".to_vec()));
+    let mut closing = b"Q\n".to_vec();
+    closing.extend(content);
+    let close_id = doc.add_object(Stream::new(Dictionary::new(), closing));
+
+    let mut contents = vec![Object::Reference(open_id)];
+    contents.extend(existing);
+    contents.push(Object::Reference(close_id));
+
+    let page = doc
+        .get_dictionary_mut(page_id)
+        .map_err(|e| AppError::Internal(format!("Failed to get page: {}", e)))?;
+    page.set("Contents", Object::Array(contents));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Page A4 avec un groupe "acquis" contenant deux champs texte
+    fn form_pdf() -> Document {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let page_id = doc.new_object_id();
+        let group_id = doc.new_object_id();
+
+        let date_id = doc.add_object(dictionary! {
+            "T" => Object::string_literal("date"),
+            "Parent" => group_id,
+            "Type" => "Annot",
+            "Subtype" => "Widget",
+            "Rect" => vec![100.into(), 700.into(), 200.into(), 720.into()],
+            "P" => page_id,
+        });
+        let validator_id = doc.add_object(dictionary! {
+            "T" => Object::string_literal("validateur"),
+            "Parent" => group_id,
+            "Type" => "Annot",
+            "Subtype" => "Widget",
+            "Rect" => vec![100.into(), 670.into(), 300.into(), 690.into()],
+            "DA" => Object::string_literal("/Helv 9 Tf 0 g"),
+            "P" => page_id,
+        });
+        doc.objects.insert(
+            group_id,
+            Object::Dictionary(dictionary! {
+                "T" => Object::string_literal("acquis"),
+                "FT" => "Tx",
+                "Kids" => vec![date_id.into(), validator_id.into()],
+            }),
+        );
+        doc.objects.insert(
+            page_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+                "Annots" => vec![date_id.into(), validator_id.into()],
+            }),
+        );
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+            "AcroForm" => dictionary! { "Fields" => vec![group_id.into()] },
+        });
+        doc.trailer.set("Root", catalog_id);
+        doc
+    }
+
+    #[test]
+    fn test_list_and_fill_fields() {
+        let mut doc = form_pdf();
+        let fields = list_fields(&doc);
+        let names: Vec<&str> = fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["acquis.date", "acquis.validateur"]);
+        assert_eq!(fields[0].field_type.as_deref(), Some("Tx"));
+        assert_eq!(fields[0].page, Some(1));
+        assert_eq!(fields[1].rect, Some([100.0, 670.0, 300.0, 690.0]));
+
+        let values = HashMap::from([
+            ("acquis.validateur".to_string(), "Jean Dupré (E3)".to_string()),
+            ("inconnu".to_string(), "ignoré".to_string()),
+        ]);
+        fill_fields(&mut doc, &values).unwrap();
+
+        let validator = doc.get_dictionary(fields[1].field_id).unwrap();
+        assert_eq!(
+            lopdf::decode_text_string(validator.get(b"V").unwrap()).unwrap(),
+            "Jean Dupré (E3)"
+        );
+        let appearance_id = normal_appearance(&doc, validator).unwrap();
+        let appearance = doc.get_object(appearance_id).unwrap().as_stream().unwrap();
+        let content = String::from_utf8_lossy(&appearance.content);
+        // Taille imposée par le DA, texte en identifiants de glyphes
+        assert!(content.contains("/Helv 9.00 Tf"));
+        assert!(content.contains("> Tj"));
+        let font = appearance
+            .dict
+            .get(b"Resources")
+            .and_then(Object::as_dict)
+            .and_then(|r| r.get(b"Font"))
+            .and_then(Object::as_dict)
+            .and_then(|f| f.get(b"Helv"))
+            .and_then(Object::as_reference)
+            .unwrap();
+        let font = doc.get_dictionary(font).unwrap();
+        assert_eq!(font.get(b"Subtype").unwrap().as_name().unwrap(), b"Type0");
+
+        // Champ non renseigné: pas d'apparence
+        let date = doc.get_dictionary(fields[0].field_id).unwrap();
+        assert!(!date.has(b"AP"));
+    }
+
+    #[test]
+    fn test_flatten() {
+        let mut doc = form_pdf();
+        let values = HashMap::from([("acquis.date".to_string(), "12/06/2026".to_string())]);
+        fill_fields(&mut doc, &values).unwrap();
+        flatten(&mut doc).unwrap();
+
+        assert!(!doc.catalog().unwrap().has(b"AcroForm"));
+        assert!(list_fields(&doc).is_empty());
+
+        let page_id = *doc.get_pages().get(&1).unwrap();
+        let page = doc.get_dictionary(page_id).unwrap();
+        assert!(!page.has(b"Annots"));
+        let xobjects = page
+            .get(b"Resources")
+            .and_then(Object::as_dict)
+            .and_then(|r| r.get(b"XObject"))
+            .and_then(Object::as_dict)
+            .unwrap();
+        assert_eq!(xobjects.len(), 1);
+
+        let content = String::from_utf8_lossy(&doc.get_page_content(page_id).unwrap()).to_string();
+        assert!(content.contains("1.0000 0 0 1.0000 100.0000 700.0000 cm"));
+        assert!(content.contains(" Do Q"));
+    }
+}
@@ -0,0 +1,261 @@
+use chrono::{NaiveDate, Utc};
+use sea_orm::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::entities::certifications::{self, SOURCE_EXTERNAL, SOURCE_INTERNAL, STATUS_OBTAINED, STATUS_PREPARING};
+use crate::entities::prelude::*;
+use crate::errors::AppResult;
+use crate::models::LevelCatalog;
+use crate::services::LevelService;
+
+/// Niveaux des plongeurs, stockés une ligne par niveau dans `certifications`.
+///
+/// Les réponses de l'API exposent toujours la chaîne historique
+/// ("N1,N2,preparing_N3"), reconstruite depuis la table.
+pub struct CertificationService;
+
+impl CertificationService {
+    /// Code de niveau tel qu'enregistré: sans espaces, en majuscules
+    pub fn normalize_level(level: &str) -> String {
+        level.trim().to_uppercase()
+    }
+
+    /// Découpe une chaîne historique en (niveau, statut)
+    pub fn parse_level_string(diving_level: &str) -> Vec<(String, &'static str)> {
+        let mut levels: Vec<(String, &'static str)> = Vec::new();
+
+        for part in diving_level.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (level, status) = match part.strip_prefix("preparing_") {
+                Some(level) => (level, STATUS_PREPARING),
+                None => (part, STATUS_OBTAINED),
+            };
+
+            match levels.iter_mut().find(|(l, _)| l.eq_ignore_ascii_case(level)) {
+                // Un niveau obtenu l'emporte sur le même niveau en préparation
+                Some(existing) if status == STATUS_OBTAINED => existing.1 = STATUS_OBTAINED,
+                Some(_) => {}
+                None => levels.push((Self::normalize_level(level), status)),
+            }
+        }
+
+        levels
+    }
+
+    /// Reconstruit la chaîne historique depuis les lignes d'un plongeur:
+    /// niveaux obtenus par ordre hiérarchique, puis niveaux en préparation
+    pub fn level_string(certs: &[certifications::Model], catalog: &LevelCatalog) -> Option<String> {
+        if certs.is_empty() {
+            return None;
+        }
+
+        let rank = |level: &str| catalog.rank(level).unwrap_or(i32::MAX);
+        let mut sorted: Vec<&certifications::Model> = certs.iter().collect();
+        sorted.sort_by(|a, b| {
+            (a.status == STATUS_PREPARING, rank(&a.level), &a.level)
+                .cmp(&(b.status == STATUS_PREPARING, rank(&b.level), &b.level))
+        });
+
+        Some(
+            sorted
+                .iter()
+                .map(|c| {
+                    if c.status == STATUS_PREPARING {
+                        format!("preparing_{}", c.level)
+                    } else {
+                        c.level.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+        )
+    }
+
+    /// Niveaux d'un plongeur au format historique
+    pub async fn diving_level<C: ConnectionTrait>(
+        conn: &C,
+        person_id: Uuid,
+    ) -> AppResult<Option<String>> {
+        let certs = Certifications::find()
+            .filter(certifications::Column::PersonId.eq(person_id))
+            .all(conn)
+            .await?;
+        let catalog = LevelService::catalog(conn).await?;
+
+        Ok(Self::level_string(&certs, &catalog))
+    }
+
+    /// Niveaux de plusieurs plongeurs au format historique (absents si aucun niveau)
+    pub async fn diving_levels<C: ConnectionTrait>(
+        conn: &C,
+        person_ids: impl IntoIterator<Item = Uuid>,
+    ) -> AppResult<HashMap<Uuid, String>> {
+        let person_ids: Vec<Uuid> = person_ids.into_iter().collect();
+        if person_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let catalog = LevelService::catalog(conn).await?;
+        let mut by_person: HashMap<Uuid, Vec<certifications::Model>> = HashMap::new();
+        for cert in Certifications::find()
+            .filter(certifications::Column::PersonId.is_in(person_ids))
+            .all(conn)
+            .await?
+        {
+            by_person.entry(cert.person_id).or_default().push(cert);
+        }
+
+        Ok(by_person
+            .into_iter()
+            .filter_map(|(person_id, certs)| {
+                Self::level_string(&certs, &catalog).map(|s| (person_id, s))
+            })
+            .collect())
+    }
+
+    /// Aligne les lignes d'un plongeur sur une chaîne historique envoyée par
+    /// les formulaires ou l'import. Les informations des brevets conservés
+    /// (date, numéro, délivrance) ne sont pas modifiées.
+    pub async fn sync_from_level_string<C: ConnectionTrait>(
+        conn: &C,
+        person_id: Uuid,
+        diving_level: &str,
+    ) -> AppResult<()> {
+        let wanted = Self::parse_level_string(diving_level);
+        let existing = Certifications::find()
+            .filter(certifications::Column::PersonId.eq(person_id))
+            .all(conn)
+            .await?;
+        let now = Utc::now().naive_utc();
+
+        for cert in &existing {
+            match wanted.iter().find(|(l, _)| l.eq_ignore_ascii_case(&cert.level)) {
+                None => {
+                    Certifications::delete_by_id(cert.id).exec(conn).await?;
+                }
+                Some((_, status)) if cert.status != *status => {
+                    let mut active: certifications::ActiveModel = cert.clone().into();
+                    active.status = Set((*status).to_owned());
+                    active.updated_at = Set(now);
+                    active.update(conn).await?;
+                }
+                Some(_) => {}
+            }
+        }
+
+        for (level, status) in wanted {
+            if existing.iter().any(|c| c.level.eq_ignore_ascii_case(&level)) {
+                continue;
+            }
+
+            certifications::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                person_id: Set(person_id),
+                level: Set(level),
+                status: Set(status.to_string()),
+                obtained_at: Set(None),
+                issued_by: Set(None),
+                issued_by_id: Set(None),
+                certificate_number: Set(None),
+                source: Set(SOURCE_EXTERNAL.to_string()),
+                created_at: Set(now),
+                updated_at: Set(now),
+            }
+            .insert(conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Enregistre un niveau obtenu au club (promotion appliquée). Remplace
+    /// la ligne "en préparation" du même niveau si elle existe.
+    pub async fn record_promotion<C: ConnectionTrait>(
+        conn: &C,
+        person_id: Uuid,
+        level: &str,
+        obtained_at: NaiveDate,
+        issued_by_id: Option<Uuid>,
+    ) -> AppResult<certifications::Model> {
+        let now = Utc::now().naive_utc();
+        let level = Self::normalize_level(level);
+        let existing = Certifications::find()
+            .filter(certifications::Column::PersonId.eq(person_id))
+            .filter(certifications::Column::Level.eq(&level))
+            .one(conn)
+            .await?;
+
+        let cert = match existing {
+            Some(existing) => {
+                let mut active: certifications::ActiveModel = existing.into();
+                active.status = Set(STATUS_OBTAINED.to_string());
+                active.obtained_at = Set(Some(obtained_at));
+                active.issued_by_id = Set(issued_by_id);
+                active.source = Set(SOURCE_INTERNAL.to_string());
+                active.updated_at = Set(now);
+                active.update(conn).await?
+            }
+            None => {
+                certifications::ActiveModel {
+                    id: Set(Uuid::new_v4()),
+                    person_id: Set(person_id),
+                    level: Set(level),
+                    status: Set(STATUS_OBTAINED.to_string()),
+                    obtained_at: Set(Some(obtained_at)),
+                    issued_by: Set(None),
+                    issued_by_id: Set(issued_by_id),
+                    certificate_number: Set(None),
+                    source: Set(SOURCE_INTERNAL.to_string()),
+                    created_at: Set(now),
+                    updated_at: Set(now),
+                }
+                .insert(conn)
+                .await?
+            }
+        };
+
+        Ok(cert)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cert(level: &str, status: &str) -> certifications::Model {
+        let now = Utc::now().naive_utc();
+        certifications::Model {
+            id: Uuid::new_v4(),
+            person_id: Uuid::nil(),
+            level: level.to_string(),
+            status: status.to_string(),
+            obtained_at: None,
+            issued_by: None,
+            issued_by_id: None,
+            certificate_number: None,
+            source: SOURCE_EXTERNAL.to_string(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_level_string_roundtrip() {
+        let parsed = CertificationService::parse_level_string("N2, preparing_n3,N1,preparing_N2,n2");
+        assert_eq!(
+            parsed,
+            vec![
+                ("N2".to_string(), STATUS_OBTAINED),
+                ("N3".to_string(), STATUS_PREPARING),
+                ("N1".to_string(), STATUS_OBTAINED),
+            ]
+        );
+
+        let certs: Vec<_> = parsed.iter().map(|(l, s)| cert(l, s)).collect();
+        assert_eq!(
+            CertificationService::level_string(&certs, &LevelCatalog::builtin()).as_deref(),
+            Some("N1,N2,preparing_N3")
+        );
+        assert_eq!(CertificationService::level_string(&[], &LevelCatalog::builtin()), None);
+    }
+}
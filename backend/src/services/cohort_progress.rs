@@ -0,0 +1,603 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use csv::WriterBuilder;
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+use sea_orm::*;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::entities::certifications::STATUS_PREPARING;
+use crate::entities::prelude::*;
+use crate::entities::{
+    certifications, competency_domains, competency_modules, competency_skills, people,
+    skill_validation_history, skill_validations, validation_stages,
+};
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    CohortDomain, CohortModule, CohortProgress, CohortSkill, CohortStudent, StageCount,
+    UnitProgress, ValidationStageResponse,
+};
+use crate::services::SkillValidationService;
+
+/// Référentiel d'un niveau: domaines, leurs modules et leurs acquis
+type Framework = Vec<(
+    competency_domains::Model,
+    Vec<(competency_modules::Model, Vec<competency_skills::Model>)>,
+)>;
+
+/// Élève de la promotion et date de début de préparation du niveau
+struct Student {
+    person: people::Model,
+    preparing_since: NaiveDateTime,
+}
+
+fn percentage(validated: i32, total: i32) -> f32 {
+    if total > 0 {
+        (validated as f32 / total as f32) * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// Construction et export du tableau de progression d'une promotion
+pub struct CohortProgressService;
+
+impl CohortProgressService {
+    /// Progression de tous les élèves préparant `level`
+    pub async fn build(
+        db: &DatabaseConnection,
+        level: &str,
+        inactive_weeks: i64,
+    ) -> AppResult<CohortProgress> {
+        // Promotion: plongeurs ayant le niveau en préparation
+        let preparing = Certifications::find()
+            .filter(certifications::Column::Level.eq(level))
+            .filter(certifications::Column::Status.eq(STATUS_PREPARING))
+            .all(db)
+            .await?;
+        let since: HashMap<Uuid, NaiveDateTime> =
+            preparing.iter().map(|c| (c.person_id, c.created_at)).collect();
+
+        Self::build_for(db, level, inactive_weeks, since).await
+    }
+
+    /// Progression sur `level` des élèves donnés, avec la date de début de
+    /// suivi servant de référence quand ils n'ont encore aucune progression
+    pub async fn build_for(
+        db: &DatabaseConnection,
+        level: &str,
+        inactive_weeks: i64,
+        since: HashMap<Uuid, NaiveDateTime>,
+    ) -> AppResult<CohortProgress> {
+        let stages = ValidationStages::find()
+            .order_by_asc(validation_stages::Column::SortOrder)
+            .all(db)
+            .await?;
+
+        let domains = CompetencyDomains::find()
+            .filter(competency_domains::Column::DivingLevel.eq(level))
+            .order_by_asc(competency_domains::Column::SortOrder)
+            .all(db)
+            .await?;
+        let mut modules: HashMap<Uuid, Vec<competency_modules::Model>> = HashMap::new();
+        for module in CompetencyModules::find()
+            .filter(competency_modules::Column::DomainId.is_in(domains.iter().map(|d| d.id)))
+            .order_by_asc(competency_modules::Column::SortOrder)
+            .all(db)
+            .await?
+        {
+            modules.entry(module.domain_id).or_default().push(module);
+        }
+        let mut skills: HashMap<Uuid, Vec<competency_skills::Model>> = HashMap::new();
+        for skill in CompetencySkills::find()
+            .filter(
+                competency_skills::Column::ModuleId
+                    .is_in(modules.values().flatten().map(|m| m.id)),
+            )
+            .order_by_asc(competency_skills::Column::SortOrder)
+            .all(db)
+            .await?
+        {
+            skills.entry(skill.module_id).or_default().push(skill);
+        }
+        let framework: Framework = domains
+            .into_iter()
+            .map(|domain| {
+                let domain_modules = modules
+                    .remove(&domain.id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|module| {
+                        let module_skills = skills.remove(&module.id).unwrap_or_default();
+                        (module, module_skills)
+                    })
+                    .collect();
+                (domain, domain_modules)
+            })
+            .collect();
+
+        let mut students: Vec<Student> = People::find()
+            .filter(people::Column::Id.is_in(since.keys().copied()))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|person| Student {
+                preparing_since: since[&person.id],
+                person,
+            })
+            .collect();
+        students.sort_by(|a, b| {
+            (&a.person.last_name, &a.person.first_name)
+                .cmp(&(&b.person.last_name, &b.person.first_name))
+        });
+
+        let skill_ids: Vec<Uuid> = framework
+            .iter()
+            .flat_map(|(_, modules)| modules.iter().flat_map(|(_, skills)| skills.iter().map(|s| s.id)))
+            .collect();
+        let person_ids: Vec<Uuid> = students.iter().map(|s| s.person.id).collect();
+
+        let validations = SkillValidations::find()
+            .filter(skill_validations::Column::PersonId.is_in(person_ids.clone()))
+            .filter(skill_validations::Column::SkillId.is_in(skill_ids.clone()))
+            .all(db)
+            .await?;
+
+        // Dernière progression: historique des transitions, complété par la
+        // date de mise à jour des validations antérieures à l'historique
+        let mut last_progress: HashMap<Uuid, NaiveDateTime> = HashMap::new();
+        let history = SkillValidationHistory::find()
+            .filter(skill_validation_history::Column::PersonId.is_in(person_ids))
+            .filter(skill_validation_history::Column::SkillId.is_in(skill_ids.clone()))
+            .all(db)
+            .await?;
+        for at in history
+            .iter()
+            .map(|h| (h.person_id, h.created_at))
+            .chain(validations.iter().map(|v| (v.person_id, v.updated_at)))
+        {
+            let entry = last_progress.entry(at.0).or_insert(at.1);
+            if at.1 > *entry {
+                *entry = at.1;
+            }
+        }
+
+        // Les validations expirées (à revalider) ne comptent pas comme acquises
+        let periods = SkillValidationService::validity_periods(db, skill_ids.iter().copied()).await?;
+        let today = Utc::now().naive_utc().date();
+        let expired: HashSet<(Uuid, Uuid)> = validations
+            .iter()
+            .filter(|v| SkillValidationService::is_validation_expired(v, &periods, today))
+            .map(|v| (v.person_id, v.skill_id))
+            .collect();
+
+        let threshold = Utc::now().naive_utc() - Duration::weeks(inactive_weeks);
+
+        Ok(Self::compute(
+            level,
+            inactive_weeks,
+            &stages,
+            &framework,
+            &students,
+            &validations,
+            &expired,
+            &last_progress,
+            threshold,
+        ))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn compute(
+        level: &str,
+        inactive_weeks: i64,
+        stages: &[validation_stages::Model],
+        framework: &Framework,
+        students: &[Student],
+        validations: &[skill_validations::Model],
+        expired: &HashSet<(Uuid, Uuid)>,
+        last_progress: &HashMap<Uuid, NaiveDateTime>,
+        threshold: NaiveDateTime,
+    ) -> CohortProgress {
+        let stage_map: HashMap<Uuid, &validation_stages::Model> =
+            stages.iter().map(|s| (s.id, s)).collect();
+        // Étape de chaque (élève, acquis), les étapes inconnues comptant comme non commencées
+        let reached: HashMap<(Uuid, Uuid), &validation_stages::Model> = validations
+            .iter()
+            .filter_map(|v| stage_map.get(&v.stage_id).map(|s| ((v.person_id, v.skill_id), *s)))
+            .collect();
+        let is_validated = |person_id: Uuid, skill_id: Uuid| {
+            !expired.contains(&(person_id, skill_id))
+                && reached
+                    .get(&(person_id, skill_id))
+                    .map(|s| s.is_final)
+                    .unwrap_or(false)
+        };
+
+        let domains = framework
+            .iter()
+            .map(|(domain, modules)| CohortDomain {
+                id: domain.id,
+                name: domain.name.clone(),
+                modules: modules
+                    .iter()
+                    .map(|(module, skills)| CohortModule {
+                        id: module.id,
+                        name: module.name.clone(),
+                        skills: skills
+                            .iter()
+                            .map(|skill| {
+                                let mut counts: HashMap<Uuid, i32> = HashMap::new();
+                                let mut not_started = 0;
+                                for student in students {
+                                    match reached.get(&(student.person.id, skill.id)) {
+                                        Some(stage) => *counts.entry(stage.id).or_default() += 1,
+                                        None => not_started += 1,
+                                    }
+                                }
+                                CohortSkill {
+                                    id: skill.id,
+                                    name: skill.name.clone(),
+                                    stage_counts: stages
+                                        .iter()
+                                        .map(|s| StageCount {
+                                            stage_id: s.id,
+                                            count: counts.get(&s.id).copied().unwrap_or(0),
+                                        })
+                                        .collect(),
+                                    not_started,
+                                }
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let students = students
+            .iter()
+            .map(|student| {
+                let person_id = student.person.id;
+                let progress = |id: Uuid, skills: &mut dyn Iterator<Item = &competency_skills::Model>| {
+                    let (mut validated, mut total) = (0, 0);
+                    for skill in skills {
+                        total += 1;
+                        if is_validated(person_id, skill.id) {
+                            validated += 1;
+                        }
+                    }
+                    UnitProgress {
+                        id,
+                        validated,
+                        total,
+                        percentage: percentage(validated, total),
+                    }
+                };
+
+                let domain_progress: Vec<UnitProgress> = framework
+                    .iter()
+                    .map(|(domain, modules)| {
+                        progress(domain.id, &mut modules.iter().flat_map(|(_, skills)| skills.iter()))
+                    })
+                    .collect();
+                let module_progress: Vec<UnitProgress> = framework
+                    .iter()
+                    .flat_map(|(_, modules)| modules.iter())
+                    .map(|(module, skills)| progress(module.id, &mut skills.iter()))
+                    .collect();
+                let validated: i32 = domain_progress.iter().map(|d| d.validated).sum();
+                let total: i32 = domain_progress.iter().map(|d| d.total).sum();
+
+                let last = last_progress.get(&person_id).copied();
+                CohortStudent {
+                    person_id,
+                    person_name: format!("{} {}", student.person.first_name, student.person.last_name),
+                    percentage: percentage(validated, total),
+                    domains: domain_progress,
+                    modules: module_progress,
+                    last_progress_at: last.map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string()),
+                    // Sans progression: depuis le début de la préparation à défaut
+                    inactive: last.unwrap_or(student.preparing_since) < threshold,
+                }
+            })
+            .collect();
+
+        CohortProgress {
+            diving_level: level.to_string(),
+            inactive_weeks,
+            stages: stages
+                .iter()
+                .map(|s| ValidationStageResponse {
+                    id: s.id,
+                    code: s.code.clone(),
+                    name: s.name.clone(),
+                    description: s.description.clone(),
+                    color: s.color.clone(),
+                    icon: s.icon.clone(),
+                    sort_order: s.sort_order,
+                    is_final: s.is_final,
+                    allowed_next_stage_ids: s.allowed_next(),
+                    validator_rule: s.validator_rule.clone(),
+                    self_assessable: s.self_assessable,
+                })
+                .collect(),
+            domains,
+            students,
+        }
+    }
+
+    /// En-têtes de la matrice: domaines puis modules (préfixés par leur domaine)
+    fn matrix_headers(progress: &CohortProgress) -> Vec<String> {
+        let mut headers = vec!["Élève".to_string(), "Global %".to_string()];
+        headers.extend(progress.domains.iter().map(|d| format!("{} %", d.name)));
+        headers.extend(progress.domains.iter().flat_map(|d| {
+            d.modules.iter().map(move |m| format!("{} / {} %", d.name, m.name))
+        }));
+        headers.push("Dernière progression".to_string());
+        headers.push("Sans progression".to_string());
+        headers
+    }
+
+    /// Matrice élèves × domaines/modules au format CSV
+    pub fn to_csv(progress: &CohortProgress) -> AppResult<String> {
+        let mut writer = WriterBuilder::new().from_writer(vec![]);
+        let map_err = |e: csv::Error| AppError::Internal(format!("CSV export failed: {}", e));
+
+        writer.write_record(Self::matrix_headers(progress)).map_err(map_err)?;
+        for student in &progress.students {
+            let mut record = vec![
+                student.person_name.clone(),
+                format!("{:.1}", student.percentage),
+            ];
+            record.extend(student.domains.iter().map(|d| format!("{:.1}", d.percentage)));
+            record.extend(student.modules.iter().map(|m| format!("{:.1}", m.percentage)));
+            record.push(student.last_progress_at.clone().unwrap_or_default());
+            record.push(if student.inactive { "oui" } else { "non" }.to_string());
+            writer.write_record(record).map_err(map_err)?;
+        }
+
+        let bytes = writer
+            .into_inner()
+            .map_err(|e| AppError::Internal(format!("CSV export failed: {}", e)))?;
+        String::from_utf8(bytes).map_err(|e| AppError::Internal(e.to_string()))
+    }
+
+    /// Classeur XLSX: matrice, répartition par acquis et élèves sans progression
+    pub fn to_xlsx(progress: &CohortProgress) -> AppResult<Vec<u8>> {
+        Self::write_xlsx(progress)
+            .map_err(|e| AppError::Internal(format!("XLSX export failed: {}", e)))
+    }
+
+    fn write_xlsx(progress: &CohortProgress) -> Result<Vec<u8>, XlsxError> {
+        let bold = Format::new().set_bold();
+        let percent = Format::new().set_num_format("0%");
+
+        let mut workbook = Workbook::new();
+
+        let sheet = workbook.add_worksheet().set_name("Progression")?;
+        write_headers(sheet, &Self::matrix_headers(progress), &bold)?;
+        for (i, student) in progress.students.iter().enumerate() {
+            let row = i as u32 + 1;
+            sheet.write_string(row, 0, &student.person_name)?;
+            let percentages = std::iter::once(student.percentage)
+                .chain(student.domains.iter().map(|d| d.percentage))
+                .chain(student.modules.iter().map(|m| m.percentage));
+            let mut col = 1;
+            for value in percentages {
+                sheet.write_number_with_format(row, col, value as f64 / 100.0, &percent)?;
+                col += 1;
+            }
+            sheet.write_string(row, col, student.last_progress_at.as_deref().unwrap_or(""))?;
+            sheet.write_string(row, col + 1, if student.inactive { "oui" } else { "non" })?;
+        }
+        sheet.set_column_width(0, 30)?;
+        sheet.set_freeze_panes(1, 1)?;
+
+        let sheet = workbook.add_worksheet().set_name("Acquis")?;
+        let mut headers = vec!["Domaine".to_string(), "Module".to_string(), "Acquis".to_string()];
+        headers.extend(progress.stages.iter().map(|s| s.name.clone()));
+        headers.push("Non commencé".to_string());
+        write_headers(sheet, &headers, &bold)?;
+        let mut row = 1;
+        for domain in &progress.domains {
+            for module in &domain.modules {
+                for skill in &module.skills {
+                    sheet.write_string(row, 0, &domain.name)?;
+                    sheet.write_string(row, 1, &module.name)?;
+                    sheet.write_string(row, 2, &skill.name)?;
+                    let mut col = 3;
+                    for count in &skill.stage_counts {
+                        sheet.write_number(row, col, count.count)?;
+                        col += 1;
+                    }
+                    sheet.write_number(row, col, skill.not_started)?;
+                    row += 1;
+                }
+            }
+        }
+        sheet.set_column_width(2, 50)?;
+        sheet.set_freeze_panes(1, 3)?;
+
+        let sheet = workbook.add_worksheet().set_name("Sans progression")?;
+        write_headers(
+            sheet,
+            &[
+                "Élève".to_string(),
+                "Global %".to_string(),
+                "Dernière progression".to_string(),
+            ],
+            &bold,
+        )?;
+        for (i, student) in progress.students.iter().filter(|s| s.inactive).enumerate() {
+            let row = i as u32 + 1;
+            sheet.write_string(row, 0, &student.person_name)?;
+            sheet.write_number_with_format(row, 1, student.percentage as f64 / 100.0, &percent)?;
+            sheet.write_string(row, 2, student.last_progress_at.as_deref().unwrap_or("jamais"))?;
+        }
+        sheet.set_column_width(0, 30)?;
+
+        workbook.save_to_buffer()
+    }
+}
+
+fn write_headers(sheet: &mut Worksheet, headers: &[String], bold: &Format) -> Result<(), XlsxError> {
+    for (col, header) in headers.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, header, bold)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap().and_hms_opt(12, 0, 0).unwrap()
+    }
+
+    fn stage(code: &str, sort_order: i32, is_final: bool) -> validation_stages::Model {
+        validation_stages::Model {
+            id: Uuid::new_v4(),
+            code: code.to_string(),
+            name: code.to_string(),
+            description: None,
+            color: "#000000".to_string(),
+            icon: "".to_string(),
+            sort_order,
+            is_final,
+            allowed_next_stage_ids: None,
+            validator_rule: None,
+            self_assessable: false,
+            created_at: at(1),
+            updated_at: at(1),
+        }
+    }
+
+    fn skill(module_id: Uuid, name: &str) -> competency_skills::Model {
+        competency_skills::Model {
+            id: Uuid::new_v4(),
+            module_id,
+            name: name.to_string(),
+            description: None,
+            sort_order: 0,
+            min_validator_level: "E2".to_string(),
+            validator_rule: None,
+            validity_months: None,
+            created_at: at(1),
+            updated_at: at(1),
+        }
+    }
+
+    fn student(first_name: &str, preparing_since: NaiveDateTime) -> Student {
+        Student {
+            person: people::Model {
+                id: Uuid::new_v4(),
+                first_name: first_name.to_string(),
+                last_name: "Martin".to_string(),
+                email: format!("{}@example.com", first_name),
+                phone: None,
+                default_is_encadrant: false,
+                default_wants_regulator: false,
+                default_wants_nitrox: false,
+                default_wants_2nd_reg: false,
+                default_wants_stab: false,
+                default_stab_size: None,
+                group_id: None,
+                password_hash: None,
+                temp_password: None,
+                temp_password_expires_at: None,
+                must_change_password: false,
+                created_at: at(1),
+                updated_at: at(1),
+            },
+            preparing_since,
+        }
+    }
+
+    fn validation(person_id: Uuid, skill_id: Uuid, stage_id: Uuid) -> skill_validations::Model {
+        skill_validations::Model {
+            id: Uuid::new_v4(),
+            person_id,
+            skill_id,
+            stage_id,
+            validated_at: at(10).date(),
+            validated_by_id: Uuid::new_v4(),
+            notes: None,
+            session_id: None,
+            palanquee_id: None,
+            created_at: at(10),
+            updated_at: at(10),
+        }
+    }
+
+    #[test]
+    fn test_compute_and_export() {
+        let started = stage("started", 1, false);
+        let validated = stage("validated", 2, true);
+        let stages = vec![started.clone(), validated.clone()];
+
+        let domain = competency_domains::Model {
+            id: Uuid::new_v4(),
+            diving_level: "N2".to_string(),
+            name: "Pratique".to_string(),
+            sort_order: 0,
+            validity_months: None,
+            created_at: at(1),
+            updated_at: at(1),
+        };
+        let module = competency_modules::Model {
+            id: Uuid::new_v4(),
+            domain_id: domain.id,
+            name: "Immersion".to_string(),
+            sort_order: 0,
+            created_at: at(1),
+            updated_at: at(1),
+        };
+        let vdm = skill(module.id, "Vidage de masque");
+        let remontee = skill(module.id, "Remontée assistée");
+        let framework: Framework = vec![(domain, vec![(module, vec![vdm.clone(), remontee.clone()])])];
+
+        let alice = student("Alice", at(1));
+        let bob = student("Bob", at(1));
+        let validations = vec![
+            validation(alice.person.id, vdm.id, validated.id),
+            validation(alice.person.id, remontee.id, started.id),
+            validation(bob.person.id, vdm.id, started.id),
+        ];
+        let last_progress = HashMap::from([(alice.person.id, at(20)), (bob.person.id, at(5))]);
+
+        let progress = CohortProgressService::compute(
+            "N2",
+            2,
+            &stages,
+            &framework,
+            &[alice, bob],
+            &validations,
+            &HashSet::new(),
+            &last_progress,
+            at(15),
+        );
+
+        let alice = &progress.students[0];
+        assert_eq!(alice.percentage, 50.0);
+        assert_eq!(alice.modules[0].validated, 1);
+        assert!(!alice.inactive);
+        assert!(progress.students[1].inactive);
+
+        let skill = &progress.domains[0].modules[0].skills[0];
+        assert_eq!(skill.stage_counts[0].count, 1);
+        assert_eq!(skill.stage_counts[1].count, 1);
+        assert_eq!(progress.domains[0].modules[0].skills[1].not_started, 1);
+
+        let csv = CohortProgressService::to_csv(&progress).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("Élève,Global %,Pratique %,Pratique / Immersion %,Dernière progression,Sans progression")
+        );
+        assert_eq!(lines.next(), Some("Alice Martin,50.0,50.0,50.0,2024-03-20 12:00:00,non"));
+        assert_eq!(lines.next(), Some("Bob Martin,0.0,0.0,0.0,2024-03-05 12:00:00,oui"));
+
+        let xlsx = CohortProgressService::to_xlsx(&progress).unwrap();
+        assert_eq!(&xlsx[0..2], b"PK");
+    }
+}
@@ -0,0 +1,630 @@
+use chrono::Utc;
+use csv::{ReaderBuilder, WriterBuilder};
+use sea_orm::*;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::entities::prelude::*;
+use crate::entities::{
+    competency_domains, competency_modules, competency_skills, skill_document_positions,
+    skill_validations,
+};
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    CompetencyFramework, FrameworkBundleInfo, FrameworkCsvRow, FrameworkDomain,
+    FrameworkImportMode, FrameworkImportResult, FrameworkModule, FrameworkSkill, LevelCatalog,
+};
+use crate::services::{LevelService, SkillValidationService};
+
+/// Référentiels officiels FFESSM embarqués (code, contenu JSON)
+const BUNDLES: &[(&str, &str)] = &[
+    ("ffessm-n1", include_str!("../../frameworks/ffessm/n1.json")),
+    ("ffessm-n2", include_str!("../../frameworks/ffessm/n2.json")),
+    ("ffessm-n3", include_str!("../../frameworks/ffessm/n3.json")),
+    ("ffessm-n4", include_str!("../../frameworks/ffessm/n4.json")),
+    ("ffessm-e1", include_str!("../../frameworks/ffessm/e1.json")),
+    ("ffessm-e2", include_str!("../../frameworks/ffessm/e2.json")),
+    ("ffessm-e3", include_str!("../../frameworks/ffessm/e3.json")),
+];
+
+const DEFAULT_MIN_VALIDATOR_LEVEL: &str = "E2";
+
+/// Clé de rapprochement par nom (insensible à la casse et aux espaces)
+fn key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Import/export du référentiel de compétences d'un niveau
+pub struct CompetencyFrameworkService;
+
+impl CompetencyFrameworkService {
+    /// Liste les référentiels officiels disponibles
+    pub fn bundles() -> Vec<FrameworkBundleInfo> {
+        BUNDLES
+            .iter()
+            .filter_map(|(code, _)| {
+                let framework = Self::bundle(code)?;
+                Some(FrameworkBundleInfo {
+                    code: (*code).to_owned(),
+                    diving_level: framework.diving_level.clone(),
+                    name: framework.name.clone(),
+                    domain_count: framework.domains.len(),
+                    skill_count: framework
+                        .domains
+                        .iter()
+                        .flat_map(|d| &d.modules)
+                        .map(|m| m.skills.len())
+                        .sum(),
+                })
+            })
+            .collect()
+    }
+
+    /// Charge un référentiel officiel par son code (ex: "ffessm-n2")
+    pub fn bundle(code: &str) -> Option<CompetencyFramework> {
+        BUNDLES
+            .iter()
+            .find(|(c, _)| *c == code)
+            .and_then(|(_, content)| serde_json::from_str(content).ok())
+    }
+
+    /// Exporte le référentiel complet d'un niveau
+    pub async fn export(db: &DatabaseConnection, level: &str) -> AppResult<CompetencyFramework> {
+        let domains = CompetencyDomains::find()
+            .filter(competency_domains::Column::DivingLevel.eq(level))
+            .order_by_asc(competency_domains::Column::SortOrder)
+            .all(db)
+            .await?;
+
+        let domain_ids: Vec<Uuid> = domains.iter().map(|d| d.id).collect();
+        let modules = CompetencyModules::find()
+            .filter(competency_modules::Column::DomainId.is_in(domain_ids))
+            .order_by_asc(competency_modules::Column::SortOrder)
+            .all(db)
+            .await?;
+
+        let module_ids: Vec<Uuid> = modules.iter().map(|m| m.id).collect();
+        let skills = CompetencySkills::find()
+            .filter(competency_skills::Column::ModuleId.is_in(module_ids))
+            .order_by_asc(competency_skills::Column::SortOrder)
+            .all(db)
+            .await?;
+
+        let domains = domains
+            .into_iter()
+            .map(|domain| FrameworkDomain {
+                modules: modules
+                    .iter()
+                    .filter(|m| m.domain_id == domain.id)
+                    .map(|module| FrameworkModule {
+                        skills: skills
+                            .iter()
+                            .filter(|s| s.module_id == module.id)
+                            .map(|s| FrameworkSkill {
+                                name: s.name.clone(),
+                                description: s.description.clone(),
+                                sort_order: Some(s.sort_order),
+                                min_validator_level: Some(s.min_validator_level.clone()),
+                                validator_rule: s.validator_rule.clone(),
+                                validity_months: s.validity_months,
+                            })
+                            .collect(),
+                        name: module.name.clone(),
+                        sort_order: Some(module.sort_order),
+                    })
+                    .collect(),
+                name: domain.name,
+                sort_order: Some(domain.sort_order),
+                validity_months: domain.validity_months,
+            })
+            .collect();
+
+        Ok(CompetencyFramework {
+            diving_level: level.to_string(),
+            name: None,
+            domains,
+        })
+    }
+
+    /// Sérialise un référentiel en CSV (une ligne par acquis)
+    pub fn to_csv(framework: &CompetencyFramework) -> AppResult<String> {
+        let mut writer = WriterBuilder::new().has_headers(true).from_writer(vec![]);
+
+        for domain in &framework.domains {
+            for module in &domain.modules {
+                for skill in &module.skills {
+                    writer
+                        .serialize(FrameworkCsvRow {
+                            domain: domain.name.clone(),
+                            domain_order: domain.sort_order,
+                            domain_validity_months: domain.validity_months,
+                            module: module.name.clone(),
+                            module_order: module.sort_order,
+                            skill: skill.name.clone(),
+                            skill_order: skill.sort_order,
+                            description: skill.description.clone(),
+                            min_validator_level: skill.min_validator_level.clone(),
+                            validator_rule: skill.validator_rule.clone(),
+                            validity_months: skill.validity_months,
+                        })
+                        .map_err(|e| AppError::Internal(format!("CSV export failed: {}", e)))?;
+                }
+            }
+        }
+
+        let bytes = writer
+            .into_inner()
+            .map_err(|e| AppError::Internal(format!("CSV export failed: {}", e)))?;
+        String::from_utf8(bytes).map_err(|e| AppError::Internal(e.to_string()))
+    }
+
+    /// Reconstruit un référentiel à partir d'un CSV, dans l'ordre des lignes
+    pub fn from_csv(level: &str, content: &str) -> AppResult<CompetencyFramework> {
+        let mut reader = ReaderBuilder::new()
+            .has_headers(true)
+            .from_reader(content.as_bytes());
+
+        let mut framework = CompetencyFramework {
+            diving_level: level.to_string(),
+            name: None,
+            domains: vec![],
+        };
+
+        for (index, row) in reader.deserialize::<FrameworkCsvRow>().enumerate() {
+            let row = row.map_err(|e| {
+                AppError::Validation(format!("Ligne {} invalide: {}", index + 2, e))
+            })?;
+
+            let domain_pos = match framework.domains.iter().position(|d| key(&d.name) == key(&row.domain)) {
+                Some(pos) => pos,
+                None => {
+                    framework.domains.push(FrameworkDomain {
+                        name: row.domain.clone(),
+                        sort_order: row.domain_order,
+                        validity_months: row.domain_validity_months,
+                        modules: vec![],
+                    });
+                    framework.domains.len() - 1
+                }
+            };
+            let domain = &mut framework.domains[domain_pos];
+
+            let module_pos = match domain.modules.iter().position(|m| key(&m.name) == key(&row.module)) {
+                Some(pos) => pos,
+                None => {
+                    domain.modules.push(FrameworkModule {
+                        name: row.module.clone(),
+                        sort_order: row.module_order,
+                        skills: vec![],
+                    });
+                    domain.modules.len() - 1
+                }
+            };
+
+            domain.modules[module_pos].skills.push(FrameworkSkill {
+                name: row.skill,
+                description: row.description.filter(|d| !d.trim().is_empty()),
+                sort_order: row.skill_order,
+                min_validator_level: row.min_validator_level.filter(|l| !l.trim().is_empty()),
+                validator_rule: row.validator_rule.filter(|r| !r.trim().is_empty()),
+                validity_months: row.validity_months,
+            });
+        }
+
+        Ok(framework)
+    }
+
+    /// Vérifie la cohérence d'un référentiel avant import
+    fn validate(framework: &CompetencyFramework, catalog: &LevelCatalog) -> AppResult<()> {
+        for domain in &framework.domains {
+            if domain.name.trim().is_empty() || domain.name.len() > 100 {
+                return Err(AppError::Validation(
+                    "Le nom d'un domaine doit faire entre 1 et 100 caractères".to_string(),
+                ));
+            }
+            if domain.validity_months.is_some_and(|m| m <= 0) {
+                return Err(AppError::Validation(format!(
+                    "Durée de validité invalide pour le domaine \"{}\"",
+                    domain.name
+                )));
+            }
+            for module in &domain.modules {
+                if module.name.trim().is_empty() || module.name.len() > 255 {
+                    return Err(AppError::Validation(format!(
+                        "Module invalide dans le domaine \"{}\"",
+                        domain.name
+                    )));
+                }
+                for skill in &module.skills {
+                    if skill.name.trim().is_empty() || skill.name.len() > 500 {
+                        return Err(AppError::Validation(format!(
+                            "Acquis invalide dans le module \"{}\"",
+                            module.name
+                        )));
+                    }
+                    if let Some(level) = &skill.min_validator_level {
+                        if catalog.parse(level).is_none() {
+                            return Err(AppError::Validation(format!(
+                                "Niveau validateur inconnu: {}",
+                                level
+                            )));
+                        }
+                    }
+                    Self::validator_rule(skill, catalog)?;
+                    if skill.validity_months.is_some_and(|m| m <= 0) {
+                        return Err(AppError::Validation(format!(
+                            "Durée de validité invalide pour l'acquis \"{}\"",
+                            skill.name
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Règle de validation d'un acquis importé, mise en forme comme à la
+    /// saisie (None si absente ou vide)
+    fn validator_rule(skill: &FrameworkSkill, catalog: &LevelCatalog) -> AppResult<Option<String>> {
+        match &skill.validator_rule {
+            Some(rule) => SkillValidationService::normalize_rule(catalog, rule),
+            None => Ok(None),
+        }
+    }
+
+    /// Importe un référentiel pour un niveau.
+    ///
+    /// Les domaines, modules et acquis sont rapprochés par nom afin de
+    /// conserver leurs IDs, et donc les validations et positions PDF
+    /// existantes. Un acquis déplacé vers un autre module est retrouvé
+    /// par son nom dans le niveau. En mode `Replace`, les éléments absents
+    /// du fichier sont supprimés, sauf les acquis déjà validés ou positionnés.
+    pub async fn import(
+        db: &DatabaseConnection,
+        level: &str,
+        framework: &CompetencyFramework,
+        mode: FrameworkImportMode,
+    ) -> AppResult<FrameworkImportResult> {
+        let catalog = LevelService::catalog(db).await?;
+        Self::validate(framework, &catalog)?;
+
+        let txn = db.begin().await?;
+        let now = Utc::now().naive_utc();
+        let mut result = FrameworkImportResult {
+            diving_level: level.to_string(),
+            ..Default::default()
+        };
+
+        let existing_domains = CompetencyDomains::find()
+            .filter(competency_domains::Column::DivingLevel.eq(level))
+            .all(&txn)
+            .await?;
+        let domain_ids: Vec<Uuid> = existing_domains.iter().map(|d| d.id).collect();
+        let existing_modules = CompetencyModules::find()
+            .filter(competency_modules::Column::DomainId.is_in(domain_ids))
+            .all(&txn)
+            .await?;
+        let module_ids: Vec<Uuid> = existing_modules.iter().map(|m| m.id).collect();
+        let existing_skills = CompetencySkills::find()
+            .filter(competency_skills::Column::ModuleId.is_in(module_ids))
+            .all(&txn)
+            .await?;
+
+        let mut seen_domains: HashSet<Uuid> = HashSet::new();
+        let mut seen_modules: HashSet<Uuid> = HashSet::new();
+        let mut seen_skills: HashSet<Uuid> = HashSet::new();
+
+        for (d_index, domain) in framework.domains.iter().enumerate() {
+            let domain_order = domain.sort_order.unwrap_or(d_index as i32 + 1);
+            let domain_id = match existing_domains.iter().find(|d| key(&d.name) == key(&domain.name)) {
+                Some(existing) => {
+                    let mut active: competency_domains::ActiveModel = existing.clone().into();
+                    active.sort_order = Set(domain_order);
+                    if mode == FrameworkImportMode::Replace || domain.validity_months.is_some() {
+                        active.validity_months = Set(domain.validity_months);
+                    }
+                    if active.is_changed() {
+                        active.updated_at = Set(now);
+                        active.update(&txn).await?;
+                    }
+                    existing.id
+                }
+                None => {
+                    let created = competency_domains::ActiveModel {
+                        id: Set(Uuid::new_v4()),
+                        diving_level: Set(level.to_string()),
+                        name: Set(domain.name.trim().to_string()),
+                        sort_order: Set(domain_order),
+                        validity_months: Set(domain.validity_months),
+                        created_at: Set(now),
+                        updated_at: Set(now),
+                    }
+                    .insert(&txn)
+                    .await?;
+                    result.domains_created += 1;
+                    created.id
+                }
+            };
+            seen_domains.insert(domain_id);
+
+            for (m_index, module) in domain.modules.iter().enumerate() {
+                let module_order = module.sort_order.unwrap_or(m_index as i32 + 1);
+                let existing_module = existing_modules
+                    .iter()
+                    .find(|m| m.domain_id == domain_id && key(&m.name) == key(&module.name));
+                let module_id = match existing_module {
+                    Some(existing) => {
+                        if existing.sort_order != module_order {
+                            let mut active: competency_modules::ActiveModel = existing.clone().into();
+                            active.sort_order = Set(module_order);
+                            active.updated_at = Set(now);
+                            active.update(&txn).await?;
+                        }
+                        existing.id
+                    }
+                    None => {
+                        let created = competency_modules::ActiveModel {
+                            id: Set(Uuid::new_v4()),
+                            domain_id: Set(domain_id),
+                            name: Set(module.name.trim().to_string()),
+                            sort_order: Set(module_order),
+                            created_at: Set(now),
+                            updated_at: Set(now),
+                        }
+                        .insert(&txn)
+                        .await?;
+                        result.modules_created += 1;
+                        created.id
+                    }
+                };
+                seen_modules.insert(module_id);
+
+                for (s_index, skill) in module.skills.iter().enumerate() {
+                    let skill_order = skill.sort_order.unwrap_or(s_index as i32 + 1);
+                    // D'abord dans le même module, sinon n'importe où dans le niveau
+                    let existing_skill = existing_skills
+                        .iter()
+                        .filter(|s| !seen_skills.contains(&s.id) && key(&s.name) == key(&skill.name))
+                        .min_by_key(|s| s.module_id != module_id);
+
+                    let validator_rule = Self::validator_rule(skill, &catalog)?;
+                    match existing_skill {
+                        Some(existing) => {
+                            let mut active: competency_skills::ActiveModel = existing.clone().into();
+                            active.module_id = Set(module_id);
+                            active.sort_order = Set(skill_order);
+                            if mode == FrameworkImportMode::Replace || skill.description.is_some() {
+                                active.description = Set(skill.description.clone());
+                            }
+                            if let Some(min_level) = &skill.min_validator_level {
+                                active.min_validator_level = Set(min_level.clone());
+                            }
+                            if mode == FrameworkImportMode::Replace || skill.validator_rule.is_some() {
+                                active.validator_rule = Set(validator_rule);
+                            }
+                            if mode == FrameworkImportMode::Replace || skill.validity_months.is_some() {
+                                active.validity_months = Set(skill.validity_months);
+                            }
+                            if active.is_changed() {
+                                active.updated_at = Set(now);
+                                active.update(&txn).await?;
+                                result.skills_updated += 1;
+                            }
+                            seen_skills.insert(existing.id);
+                        }
+                        None => {
+                            competency_skills::ActiveModel {
+                                id: Set(Uuid::new_v4()),
+                                module_id: Set(module_id),
+                                name: Set(skill.name.trim().to_string()),
+                                description: Set(skill.description.clone()),
+                                sort_order: Set(skill_order),
+                                min_validator_level: Set(skill
+                                    .min_validator_level
+                                    .clone()
+                                    .unwrap_or_else(|| DEFAULT_MIN_VALIDATOR_LEVEL.to_string())),
+                                validator_rule: Set(validator_rule),
+                                validity_months: Set(skill.validity_months),
+                                created_at: Set(now),
+                                updated_at: Set(now),
+                            }
+                            .insert(&txn)
+                            .await?;
+                            result.skills_created += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        if mode == FrameworkImportMode::Replace {
+            Self::remove_unlisted(
+                &txn,
+                &existing_domains,
+                &existing_modules,
+                &existing_skills,
+                &seen_domains,
+                &seen_modules,
+                &seen_skills,
+                &mut result,
+            )
+            .await?;
+        }
+
+        txn.commit().await?;
+        Ok(result)
+    }
+
+    /// Nombre d'acquis restant par module, hors acquis supprimés
+    fn remaining_by_module(
+        skills: &[competency_skills::Model],
+        deleted: &HashSet<Uuid>,
+    ) -> HashMap<Uuid, usize> {
+        let mut remaining: HashMap<Uuid, usize> = HashMap::new();
+        for skill in skills.iter().filter(|s| !deleted.contains(&s.id)) {
+            *remaining.entry(skill.module_id).or_default() += 1;
+        }
+        remaining
+    }
+
+    /// Supprime les éléments absents du fichier importé (mode replace).
+    /// Les acquis ayant des validations ou une position PDF sont conservés,
+    /// ainsi que les modules et domaines qui les contiennent.
+    #[allow(clippy::too_many_arguments)]
+    async fn remove_unlisted(
+        txn: &DatabaseTransaction,
+        existing_domains: &[competency_domains::Model],
+        existing_modules: &[competency_modules::Model],
+        existing_skills: &[competency_skills::Model],
+        seen_domains: &HashSet<Uuid>,
+        seen_modules: &HashSet<Uuid>,
+        seen_skills: &HashSet<Uuid>,
+        result: &mut FrameworkImportResult,
+    ) -> AppResult<()> {
+        let unlisted: Vec<&competency_skills::Model> = existing_skills
+            .iter()
+            .filter(|s| !seen_skills.contains(&s.id))
+            .collect();
+        let unlisted_ids: Vec<Uuid> = unlisted.iter().map(|s| s.id).collect();
+
+        let mut in_use: HashSet<Uuid> = SkillValidations::find()
+            .filter(skill_validations::Column::SkillId.is_in(unlisted_ids.clone()))
+            .all(txn)
+            .await?
+            .into_iter()
+            .map(|v| v.skill_id)
+            .collect();
+        in_use.extend(
+            skill_document_positions::Entity::find()
+                .filter(skill_document_positions::Column::SkillId.is_in(unlisted_ids))
+                .all(txn)
+                .await?
+                .into_iter()
+                .map(|p| p.skill_id),
+        );
+
+        // Modules relus après l'import: un acquis déplacé compte dans son
+        // nouveau module, plus dans l'ancien
+        let current_skills = CompetencySkills::find()
+            .filter(
+                competency_skills::Column::ModuleId.is_in(existing_modules.iter().map(|m| m.id)),
+            )
+            .all(txn)
+            .await?;
+        let deleted: HashSet<Uuid> = unlisted
+            .iter()
+            .map(|s| s.id)
+            .filter(|id| !in_use.contains(id))
+            .collect();
+        let remaining = Self::remaining_by_module(&current_skills, &deleted);
+
+        for skill in unlisted {
+            if in_use.contains(&skill.id) {
+                result.skills_kept.push(skill.name.clone());
+            } else {
+                CompetencySkills::delete_by_id(skill.id).exec(txn).await?;
+                result.skills_deleted += 1;
+            }
+        }
+
+        let mut kept_domains: HashSet<Uuid> = HashSet::new();
+        for module in existing_modules {
+            let has_skills = remaining.get(&module.id).copied().unwrap_or(0) > 0;
+            if seen_modules.contains(&module.id) || has_skills {
+                kept_domains.insert(module.domain_id);
+                continue;
+            }
+            CompetencyModules::delete_by_id(module.id).exec(txn).await?;
+            result.modules_deleted += 1;
+        }
+
+        for domain in existing_domains {
+            if seen_domains.contains(&domain.id) || kept_domains.contains(&domain.id) {
+                continue;
+            }
+            CompetencyDomains::delete_by_id(domain.id).exec(txn).await?;
+            result.domains_deleted += 1;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundles_are_valid() {
+        let catalog = LevelCatalog::builtin();
+        let bundles = CompetencyFrameworkService::bundles();
+        assert_eq!(bundles.len(), BUNDLES.len());
+
+        for (code, _) in BUNDLES {
+            let framework = CompetencyFrameworkService::bundle(code).unwrap();
+            assert!(catalog.parse(&framework.diving_level).is_some());
+            assert!(CompetencyFrameworkService::validate(&framework, &catalog).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_moved_skill_leaves_old_module_empty() {
+        let now = chrono::Utc::now().naive_utc();
+        let (old_module, new_module) = (Uuid::new_v4(), Uuid::new_v4());
+        let skill = |module_id| competency_skills::Model {
+            id: Uuid::new_v4(),
+            module_id,
+            name: "Remontée gilet".to_string(),
+            description: None,
+            sort_order: 0,
+            min_validator_level: "E2".to_string(),
+            validator_rule: None,
+            validity_months: None,
+            created_at: now,
+            updated_at: now,
+        };
+        // Acquis déplacé par l'import, acquis non listé et supprimé
+        let moved = skill(new_module);
+        let dropped = skill(old_module);
+        let deleted = HashSet::from([dropped.id]);
+
+        let remaining =
+            CompetencyFrameworkService::remaining_by_module(&[moved, dropped], &deleted);
+
+        assert_eq!(remaining.get(&new_module), Some(&1));
+        assert_eq!(remaining.get(&old_module), None);
+    }
+
+    #[test]
+    fn test_imported_rules_are_normalized() {
+        let catalog = LevelCatalog::builtin();
+        let skill = |rule: &str| FrameworkSkill {
+            name: "Lâcher et reprise d'embout".to_string(),
+            description: None,
+            sort_order: None,
+            min_validator_level: None,
+            validator_rule: Some(rule.to_string()),
+            validity_months: None,
+        };
+
+        let normalized = CompetencyFrameworkService::validator_rule(&skill(" e2 "), &catalog).unwrap();
+        assert_eq!(normalized.as_deref(), Some("E2"));
+        assert_eq!(CompetencyFrameworkService::validator_rule(&skill("  "), &catalog).unwrap(), None);
+    }
+
+    #[test]
+    fn test_csv_roundtrip() {
+        let framework = CompetencyFrameworkService::bundle("ffessm-n2").unwrap();
+        let csv = CompetencyFrameworkService::to_csv(&framework).unwrap();
+        let parsed = CompetencyFrameworkService::from_csv("N2", &csv).unwrap();
+
+        assert_eq!(parsed.domains.len(), framework.domains.len());
+        for (a, b) in parsed.domains.iter().zip(&framework.domains) {
+            assert_eq!(a.name, b.name);
+            assert_eq!(a.modules.len(), b.modules.len());
+            for (ma, mb) in a.modules.iter().zip(&b.modules) {
+                assert_eq!(ma.skills.len(), mb.skills.len());
+            }
+        }
+    }
+}
@@ -0,0 +1,523 @@
+use chrono::Utc;
+use lopdf::{Dictionary, Document, Object, Stream};
+use rand::Rng;
+use sea_orm::*;
+use std::collections::HashMap;
+use std::fmt::Write;
+use uuid::Uuid;
+
+use crate::config::ClubConfig;
+use crate::entities::prelude::*;
+use crate::entities::{completion_certificates, diving_levels, people};
+use crate::errors::{AppError, AppResult};
+use crate::models::CertificateVerificationResponse;
+use crate::services::pdf_text::{FontStyle, TextLayer};
+use crate::services::PromotionService;
+
+/// Caractères du code de vérification (sans 0/O ni 1/I/L)
+const CODE_CHARSET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const CODE_GROUPS: usize = 3;
+const CODE_GROUP_LEN: usize = 4;
+
+// A4 paysage
+const PAGE_WIDTH: f32 = 842.0;
+const PAGE_HEIGHT: f32 = 595.0;
+const MARGIN: f32 = 40.0;
+
+/// Émission, vérification et rendu des attestations de fin de niveau
+pub struct CertificateService;
+
+impl CertificateService {
+    /// Génère un code de vérification aléatoire (XXXX-XXXX-XXXX)
+    pub fn generate_code() -> String {
+        let mut rng = rand::thread_rng();
+        (0..CODE_GROUPS)
+            .map(|_| {
+                (0..CODE_GROUP_LEN)
+                    .map(|_| CODE_CHARSET[rng.gen_range(0..CODE_CHARSET.len())] as char)
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    /// Remet un code saisi à la main dans sa forme canonique (majuscules,
+    /// tirets), ou `None` s'il ne peut pas être un code valide
+    pub fn normalize_code(input: &str) -> Option<String> {
+        let chars: Vec<char> = input
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-')
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+
+        if chars.len() != CODE_GROUPS * CODE_GROUP_LEN
+            || chars.iter().any(|c| !c.is_ascii() || !CODE_CHARSET.contains(&(*c as u8)))
+        {
+            return None;
+        }
+
+        Some(
+            chars
+                .chunks(CODE_GROUP_LEN)
+                .map(|group| group.iter().collect::<String>())
+                .collect::<Vec<_>>()
+                .join("-"),
+        )
+    }
+
+    /// Retourne l'attestation d'un plongeur pour un niveau, en l'émettant si
+    /// elle n'existe pas encore. Échoue si le niveau n'est pas complet.
+    pub async fn issue(
+        db: &DatabaseConnection,
+        person: &people::Model,
+        level: &str,
+        issued_by_id: Option<Uuid>,
+    ) -> AppResult<completion_certificates::Model> {
+        let existing = CompletionCertificates::find()
+            .filter(completion_certificates::Column::PersonId.eq(person.id))
+            .filter(completion_certificates::Column::Level.eq(level))
+            .one(db)
+            .await?;
+        if let Some(certificate) = existing {
+            return Ok(certificate);
+        }
+
+        let completed = PromotionService::check_level_completion(db, person.id, level)
+            .await?
+            .ok_or_else(|| {
+                AppError::Validation(format!(
+                    "Tous les acquis du niveau {} ne sont pas validés",
+                    level
+                ))
+            })?;
+
+        let validators: HashMap<Uuid, people::Model> = People::find()
+            .filter(people::Column::Id.is_in(completed.validator_ids.clone()))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|p| (p.id, p))
+            .collect();
+        let validator_names: Vec<String> = completed
+            .validator_ids
+            .iter()
+            .filter_map(|id| validators.get(id))
+            .map(|p| format!("{} {}", p.first_name, p.last_name))
+            .collect();
+
+        // Collision très improbable, mais le code doit rester unique
+        let mut code = Self::generate_code();
+        while CompletionCertificates::find()
+            .filter(completion_certificates::Column::VerificationCode.eq(&code))
+            .one(db)
+            .await?
+            .is_some()
+        {
+            code = Self::generate_code();
+        }
+
+        let certificate = completion_certificates::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            person_id: Set(person.id),
+            level: Set(completed.level),
+            verification_code: Set(code),
+            person_name: Set(format!("{} {}", person.first_name, person.last_name)),
+            completed_at: Set(completed.completed_at),
+            validator_names: Set(serde_json::json!(validator_names)),
+            issued_at: Set(Utc::now().naive_utc()),
+            issued_by_id: Set(issued_by_id),
+        };
+
+        Ok(certificate.insert(db).await?)
+    }
+
+    /// Recherche une attestation par son code de vérification
+    pub async fn find_by_code(
+        db: &DatabaseConnection,
+        code: &str,
+    ) -> AppResult<Option<completion_certificates::Model>> {
+        let Some(code) = Self::normalize_code(code) else {
+            return Ok(None);
+        };
+
+        Ok(CompletionCertificates::find()
+            .filter(completion_certificates::Column::VerificationCode.eq(code))
+            .one(db)
+            .await?)
+    }
+
+    /// Nom complet du niveau (référentiel), ou son code à défaut
+    pub async fn level_name(db: &DatabaseConnection, level: &str) -> AppResult<String> {
+        Ok(DivingLevels::find()
+            .filter(diving_levels::Column::Code.eq(level))
+            .one(db)
+            .await?
+            .map(|l| l.name)
+            .unwrap_or_else(|| level.to_string()))
+    }
+
+    /// Construit la réponse publique de vérification
+    pub fn to_verification(
+        certificate: completion_certificates::Model,
+        level_name: String,
+        club: &ClubConfig,
+    ) -> CertificateVerificationResponse {
+        CertificateVerificationResponse {
+            valid: true,
+            validators: certificate.validators(),
+            verification_code: certificate.verification_code,
+            person_name: certificate.person_name,
+            level: certificate.level,
+            level_name,
+            completed_at: certificate.completed_at.to_string(),
+            issued_at: certificate.issued_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            club: club.name.clone(),
+        }
+    }
+
+    /// Génère le PDF de l'attestation (A4 paysage)
+    pub fn render(
+        certificate: &completion_certificates::Model,
+        level_name: &str,
+        club: &ClubConfig,
+        verify_url: &str,
+    ) -> AppResult<Vec<u8>> {
+        let mut doc = Document::with_version("1.5");
+
+        let mut fonts = TextLayer::new();
+        let mut font_dict = Dictionary::new();
+        font_dict.set("F1", Object::Reference(fonts.font_id(&mut doc, FontStyle::Regular)));
+        font_dict.set("F2", Object::Reference(fonts.font_id(&mut doc, FontStyle::Bold)));
+        font_dict.set("F3", Object::Reference(fonts.font_id(&mut doc, FontStyle::Oblique)));
+
+        let mut resources = Dictionary::new();
+        resources.set("Font", Object::Dictionary(font_dict));
+
+        let mut content = String::new();
+
+        // Logo en haut à gauche, ignoré s'il est illisible
+        if let Some(path) = &club.logo_path {
+            match load_logo(path) {
+                Ok(logo) => {
+                    let height = 70.0;
+                    let width = height * logo.width as f32 / logo.height as f32;
+                    let mut xobjects = Dictionary::new();
+                    xobjects.set("Logo", Object::Reference(doc.add_object(logo.stream)));
+                    resources.set("XObject", Object::Dictionary(xobjects));
+                    writeln!(
+                        content,
+                        "q {} 0 0 {} {} {} cm /Logo Do Q",
+                        width,
+                        height,
+                        MARGIN + 20.0,
+                        PAGE_HEIGHT - MARGIN - 20.0 - height
+                    )
+                    .unwrap();
+                }
+                Err(e) => tracing::warn!("Failed to load club logo {}: {}", path, e),
+            }
+        }
+
+        draw_body(&mut content, &mut fonts, certificate, level_name, club, verify_url);
+        fonts.embed(&mut doc)?;
+
+        let resources_id = doc.add_object(resources);
+        let content_id = doc.add_object(Stream::new(Dictionary::new(), content.into_bytes()));
+
+        let mut page_dict = Dictionary::new();
+        page_dict.set("Type", Object::Name(b"Page".to_vec()));
+        page_dict.set("MediaBox", Object::Array(vec![
+            Object::Integer(0),
+            Object::Integer(0),
+            Object::Real(PAGE_WIDTH),
+            Object::Real(PAGE_HEIGHT),
+        ]));
+        page_dict.set("Resources", Object::Reference(resources_id));
+        page_dict.set("Contents", Object::Reference(content_id));
+        let page_id = doc.add_object(page_dict);
+
+        let mut pages_dict = Dictionary::new();
+        pages_dict.set("Type", Object::Name(b"Pages".to_vec()));
+        pages_dict.set("Kids", Object::Array(vec![Object::Reference(page_id)]));
+        pages_dict.set("Count", Object::Integer(1));
+        let pages_id = doc.add_object(pages_dict);
+
+        if let Ok(Object::Dictionary(ref mut page)) = doc.get_object_mut(page_id) {
+            page.set("Parent", Object::Reference(pages_id));
+        }
+
+        let mut info = Dictionary::new();
+        info.set("Title", lopdf::text_string(&format!("Attestation {} - {}", certificate.level, certificate.person_name)));
+        let info_id = doc.add_object(info);
+
+        let mut catalog_dict = Dictionary::new();
+        catalog_dict.set("Type", Object::Name(b"Catalog".to_vec()));
+        catalog_dict.set("Pages", Object::Reference(pages_id));
+        let catalog_id = doc.add_object(catalog_dict);
+
+        doc.trailer.set("Root", Object::Reference(catalog_id));
+        doc.trailer.set("Info", Object::Reference(info_id));
+
+        let mut output = Vec::new();
+        doc.save_to(&mut output)
+            .map_err(|e| AppError::Internal(format!("Failed to generate PDF: {}", e)))?;
+
+        Ok(output)
+    }
+}
+
+/// Écrit une ligne centrée horizontalement
+fn centered(content: &mut String, fonts: &mut TextLayer, style: FontStyle, size: f32, y: f32, text: &str) {
+    let font = match style {
+        FontStyle::Regular => "F1",
+        FontStyle::Bold => "F2",
+        FontStyle::Oblique => "F3",
+    };
+    let x = (PAGE_WIDTH - TextLayer::width(style, text, size)) / 2.0;
+    writeln!(content, "BT /{} {} Tf {} {} Td {} Tj ET", font, size, x, y, fonts.encode(style, text)).unwrap();
+}
+
+/// Découpe un texte en lignes ne dépassant pas `max_width`
+fn wrap(text: &str, style: FontStyle, size: f32, max_width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+        if !line.is_empty() && TextLayer::width(style, &candidate, size) > max_width {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        } else {
+            line = candidate;
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn draw_body(
+    content: &mut String,
+    fonts: &mut TextLayer,
+    certificate: &completion_certificates::Model,
+    level_name: &str,
+    club: &ClubConfig,
+    verify_url: &str,
+) {
+    // Double cadre
+    writeln!(content, "0.2 0.4 0.7 RG 3 w {} {} {} {} re S", MARGIN, MARGIN, PAGE_WIDTH - 2.0 * MARGIN, PAGE_HEIGHT - 2.0 * MARGIN).unwrap();
+    writeln!(content, "0.5 w {} {} {} {} re S", MARGIN + 8.0, MARGIN + 8.0, PAGE_WIDTH - 2.0 * MARGIN - 16.0, PAGE_HEIGHT - 2.0 * MARGIN - 16.0).unwrap();
+
+    // En-tête du club
+    let mut y = PAGE_HEIGHT - MARGIN - 45.0;
+    writeln!(content, "0.2 0.4 0.7 rg").unwrap();
+    if !club.name.is_empty() {
+        centered(content, fonts, FontStyle::Bold, 16.0, y, &club.name);
+        y -= 18.0;
+    }
+    writeln!(content, "0.4 0.4 0.4 rg").unwrap();
+    if let Some(affiliation) = &club.affiliation {
+        centered(content, fonts, FontStyle::Regular, 10.0, y, &format!("Club affilié FFESSM n° {}", affiliation));
+    }
+
+    writeln!(content, "0.2 0.4 0.7 rg").unwrap();
+    centered(content, fonts, FontStyle::Bold, 28.0, PAGE_HEIGHT - 170.0, "ATTESTATION DE FIN DE FORMATION");
+
+    writeln!(content, "0 g").unwrap();
+    let intro = if club.name.is_empty() {
+        "Nous attestons que".to_string()
+    } else {
+        format!("Le club {} atteste que", club.name)
+    };
+    centered(content, fonts, FontStyle::Regular, 13.0, PAGE_HEIGHT - 215.0, &intro);
+    centered(content, fonts, FontStyle::Bold, 26.0, PAGE_HEIGHT - 255.0, &certificate.person_name);
+    centered(content, fonts, FontStyle::Regular, 13.0, PAGE_HEIGHT - 290.0, "a validé l'ensemble des compétences de la formation");
+
+    let level = if level_name == certificate.level {
+        level_name.to_string()
+    } else {
+        format!("{} ({})", level_name, certificate.level)
+    };
+    centered(content, fonts, FontStyle::Bold, 20.0, PAGE_HEIGHT - 325.0, &level);
+    centered(
+        content,
+        fonts,
+        FontStyle::Regular,
+        12.0,
+        PAGE_HEIGHT - 350.0,
+        &format!("Formation achevée le {}", certificate.completed_at.format("%d/%m/%Y")),
+    );
+
+    // Encadrants validateurs
+    let validators = certificate.validators();
+    if !validators.is_empty() {
+        let mut y = PAGE_HEIGHT - 385.0;
+        writeln!(content, "0.3 0.3 0.3 rg").unwrap();
+        centered(content, fonts, FontStyle::Bold, 10.0, y, "Compétences validées par");
+        for line in wrap(&validators.join(", "), FontStyle::Regular, 10.0, PAGE_WIDTH - 2.0 * MARGIN - 120.0) {
+            y -= 14.0;
+            centered(content, fonts, FontStyle::Regular, 10.0, y, &line);
+        }
+    }
+
+    // Lieu et date d'émission
+    writeln!(content, "0 g").unwrap();
+    let issued = certificate.issued_at.format("%d/%m/%Y").to_string();
+    let place = match &club.city {
+        Some(city) => format!("Fait à {}, le {}", city, issued),
+        None => format!("Fait le {}", issued),
+    };
+    writeln!(
+        content,
+        "BT /F1 11 Tf {} {} Td {} Tj ET",
+        PAGE_WIDTH - MARGIN - 40.0 - TextLayer::width(FontStyle::Regular, &place, 11.0),
+        MARGIN + 70.0,
+        fonts.encode(FontStyle::Regular, &place)
+    )
+    .unwrap();
+
+    // Code de vérification
+    writeln!(content, "0.4 0.4 0.4 rg").unwrap();
+    writeln!(
+        content,
+        "BT /F2 10 Tf {} {} Td {} Tj ET",
+        MARGIN + 30.0,
+        MARGIN + 40.0,
+        fonts.encode(FontStyle::Bold, &format!("Code de vérification : {}", certificate.verification_code))
+    )
+    .unwrap();
+    writeln!(
+        content,
+        "BT /F3 8 Tf {} {} Td {} Tj ET",
+        MARGIN + 30.0,
+        MARGIN + 26.0,
+        fonts.encode(FontStyle::Oblique, &format!("Vérifiable sur {}", verify_url))
+    )
+    .unwrap();
+    writeln!(content, "0 g").unwrap();
+}
+
+/// Logo JPEG prêt à être affiché (DCTDecode, sans décodage)
+struct Logo {
+    stream: Stream,
+    width: u32,
+    height: u32,
+}
+
+fn load_logo(path: &str) -> Result<Logo, String> {
+    let data = std::fs::read(path).map_err(|e| e.to_string())?;
+    let (width, height, components) = jpeg_info(&data).ok_or("not a baseline or progressive JPEG")?;
+    let color_space = match components {
+        1 => "DeviceGray",
+        4 => "DeviceCMYK",
+        _ => "DeviceRGB",
+    };
+
+    let mut dict = Dictionary::new();
+    dict.set("Type", Object::Name(b"XObject".to_vec()));
+    dict.set("Subtype", Object::Name(b"Image".to_vec()));
+    dict.set("Width", Object::Integer(width as i64));
+    dict.set("Height", Object::Integer(height as i64));
+    dict.set("ColorSpace", Object::Name(color_space.as_bytes().to_vec()));
+    dict.set("BitsPerComponent", Object::Integer(8));
+    dict.set("Filter", Object::Name(b"DCTDecode".to_vec()));
+    if components == 4 {
+        // Les JPEG CMJN (Adobe) sont stockés inversés
+        dict.set("Decode", Object::Array((0..4).flat_map(|_| [Object::Integer(1), Object::Integer(0)]).collect()));
+    }
+
+    Ok(Logo {
+        stream: Stream::new(dict, data).with_compression(false),
+        width,
+        height,
+    })
+}
+
+/// Largeur, hauteur et nombre de composantes lus dans le segment SOF
+fn jpeg_info(data: &[u8]) -> Option<(u32, u32, u8)> {
+    if data.get(0..2)? != [0xFF, 0xD8] {
+        return None;
+    }
+
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+        let marker = data[pos + 1];
+        let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        // SOF0..SOF15 sauf DHT (C4), JPG (C8) et DAC (CC)
+        if (0xC0..=0xCF).contains(&marker) && ![0xC4, 0xC8, 0xCC].contains(&marker) {
+            let segment = data.get(pos + 4..pos + 2 + length)?;
+            let height = u16::from_be_bytes([*segment.get(1)?, *segment.get(2)?]) as u32;
+            let width = u16::from_be_bytes([*segment.get(3)?, *segment.get(4)?]) as u32;
+            let components = *segment.get(5)?;
+            if width == 0 || height == 0 {
+                return None;
+            }
+            return Some((width, height, components));
+        }
+        pos += 2 + length;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_generation_and_normalization() {
+        let code = CertificateService::generate_code();
+        assert_eq!(code.len(), 14);
+        assert_eq!(CertificateService::normalize_code(&code), Some(code.clone()));
+
+        // Saisie manuelle: minuscules, espaces, sans tirets
+        let typed = code.replace('-', " ").to_lowercase();
+        assert_eq!(CertificateService::normalize_code(&typed), Some(code));
+
+        // Caractères ambigus exclus, longueur incorrecte
+        assert_eq!(CertificateService::normalize_code("ABCD-EFGH-IJK0"), None);
+        assert_eq!(CertificateService::normalize_code("ABCD-EFGH"), None);
+    }
+
+    #[test]
+    fn test_render() {
+        let certificate = completion_certificates::Model {
+            id: Uuid::new_v4(),
+            person_id: Uuid::new_v4(),
+            level: "N2".to_string(),
+            verification_code: "ABCD-EFGH-JK23".to_string(),
+            person_name: "Élodie Łukasiewicz".to_string(),
+            completed_at: chrono::NaiveDate::from_ymd_opt(2024, 6, 15).unwrap(),
+            validator_names: serde_json::json!(["Jean Dupont", "Anne Martin"]),
+            issued_at: chrono::NaiveDate::from_ymd_opt(2024, 6, 20).unwrap().and_hms_opt(10, 0, 0).unwrap(),
+            issued_by_id: None,
+        };
+        let club = ClubConfig {
+            name: "USI".to_string(),
+            affiliation: Some("00 00 0000".to_string()),
+            city: Some("Paris".to_string()),
+            logo_path: None,
+        };
+
+        let pdf = CertificateService::render(&certificate, "Niveau 2", &club, "http://localhost/verify").unwrap();
+        let doc = Document::load_mem(&pdf).unwrap();
+        let text = doc.extract_text(&[1]).unwrap();
+        assert!(text.contains("Élodie Łukasiewicz"));
+        assert!(text.contains("Niveau 2 (N2)"));
+        assert!(text.contains("15/06/2024"));
+        assert!(text.contains("Jean Dupont, Anne Martin"));
+        assert!(text.contains("ABCD-EFGH-JK23"));
+    }
+
+    #[test]
+    fn test_jpeg_info() {
+        // SOI, APP0 minimal, SOF0 64x32 en 3 composantes
+        let jpeg = [
+            0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xC0, 0x00, 0x11, 0x08, 0x00, 0x20,
+            0x00, 0x40, 0x03, 0x01, 0x22, 0x00, 0x02, 0x11, 0x01, 0x03, 0x11, 0x01,
+        ];
+        assert_eq!(jpeg_info(&jpeg), Some((64, 32, 3)));
+        assert_eq!(jpeg_info(b"not a jpeg"), None);
+    }
+}
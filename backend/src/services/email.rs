@@ -88,13 +88,13 @@ impl EmailService {
         let email_job = EmailJobs::find_by_id(email_job_id)
             .one(db)
             .await
-            .map_err(|e| AppError::Database(e))?
+            .map_err(AppError::Database)?
             .ok_or(AppError::NotFound("Email job not found".to_string()))?;
 
         let person = People::find_by_id(email_job.person_id)
             .one(db)
             .await
-            .map_err(|e| AppError::Database(e))?
+            .map_err(AppError::Database)?
             .ok_or(AppError::NotFound("Person not found".to_string()))?;
 
         let subject = email_job.email_subject.as_deref().unwrap_or("Questionnaire");
@@ -123,7 +123,7 @@ impl EmailService {
             .find_also_related(People)
             .all(db)
             .await
-            .map_err(|e| AppError::Database(e))?;
+            .map_err(AppError::Database)?;
 
         let mut sent_count = 0;
         let mut errors = Vec::new();
@@ -170,7 +170,7 @@ impl EmailService {
         let email_job = EmailJobs::find_by_id(email_job_id)
             .one(db)
             .await
-            .map_err(|e| AppError::Database(e))?
+            .map_err(AppError::Database)?
             .ok_or(AppError::NotFound("Email job not found".to_string()))?;
 
         let mut active: email_jobs::ActiveModel = email_job.into();
@@ -182,7 +182,7 @@ impl EmailService {
         active
             .update(db)
             .await
-            .map_err(|e| AppError::Database(e))?;
+            .map_err(AppError::Database)?;
 
         Ok(())
     }
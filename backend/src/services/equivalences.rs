@@ -0,0 +1,179 @@
+use sea_orm::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::entities::prelude::*;
+use crate::entities::{certification_equivalences, external_certifications};
+use crate::errors::AppResult;
+use crate::models::{DiverLevel, LevelCatalog};
+use crate::services::CertificationService;
+
+/// Niveaux d'un plongeur en tenant compte de ses brevets externes
+#[derive(Debug, Clone, Default)]
+pub struct EffectiveLevel {
+    /// Niveaux FFESSM enregistrés au club, sans équivalence
+    pub native_level: Option<String>,
+    /// Niveaux FFESSM détenus et aptitudes reconnues par équivalence,
+    /// au format historique ("N1,PE40,PA20,preparing_N2")
+    pub diving_level: Option<String>,
+    /// Brevets externes présentés (ex: "PADI AOWD")
+    pub external_certifications: Vec<String>,
+}
+
+impl EffectiveLevel {
+    /// Aptitude FFESSM retenue pour la sécurité (ex: "N1 (PA20/PE40)")
+    pub fn aptitude(&self, catalog: &LevelCatalog) -> Option<String> {
+        self.diving_level
+            .as_deref()
+            .and_then(|s| DiverLevel::from_string(s, catalog))
+            .map(|diver| diver.aptitude())
+    }
+}
+
+/// Équivalences entre systèmes de brevets (CMAS, PADI, SSI) et aptitudes FFESSM
+pub struct EquivalenceService;
+
+impl EquivalenceService {
+    /// Normalise un système ou un code de brevet pour la comparaison
+    pub fn normalize(value: &str) -> String {
+        value.trim().to_uppercase()
+    }
+
+    /// Ajoute les aptitudes obtenues par équivalence aux niveaux FFESSM
+    /// détenus, sans doublon. Un niveau en préparation obtenu par
+    /// équivalence est considéré comme acquis.
+    pub fn merge_levels(native: Option<&str>, equivalents: &[String]) -> Option<String> {
+        let mut parts: Vec<String> = native
+            .unwrap_or("")
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        for level in equivalents {
+            if parts.iter().any(|p| p.eq_ignore_ascii_case(level)) {
+                continue;
+            }
+            let preparing_marker = format!("preparing_{}", level);
+            parts.retain(|p| !p.eq_ignore_ascii_case(&preparing_marker));
+            parts.push(level.clone());
+        }
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(","))
+        }
+    }
+
+    /// Aptitudes FFESSM reconnues pour chaque brevet (système, code)
+    pub async fn equivalences_map<C: ConnectionTrait>(
+        conn: &C,
+    ) -> AppResult<HashMap<(String, String), Vec<certification_equivalences::Model>>> {
+        let mut map: HashMap<(String, String), Vec<certification_equivalences::Model>> =
+            HashMap::new();
+        for equivalence in CertificationEquivalences::find().all(conn).await? {
+            map.entry((
+                Self::normalize(&equivalence.system),
+                Self::normalize(&equivalence.external_code),
+            ))
+            .or_default()
+            .push(equivalence);
+        }
+        Ok(map)
+    }
+
+    /// Niveaux effectifs de plusieurs plongeurs
+    pub async fn effective_levels<C: ConnectionTrait>(
+        conn: &C,
+        person_ids: impl IntoIterator<Item = Uuid>,
+    ) -> AppResult<HashMap<Uuid, EffectiveLevel>> {
+        let person_ids: Vec<Uuid> = person_ids.into_iter().collect();
+        if person_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let mut native = CertificationService::diving_levels(conn, person_ids.iter().copied()).await?;
+        let externals = ExternalCertifications::find()
+            .filter(external_certifications::Column::PersonId.is_in(person_ids.iter().copied()))
+            .all(conn)
+            .await?;
+        let equivalences = if externals.is_empty() {
+            HashMap::new()
+        } else {
+            Self::equivalences_map(conn).await?
+        };
+
+        let mut equivalent_levels: HashMap<Uuid, Vec<String>> = HashMap::new();
+        let mut labels: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for external in &externals {
+            labels
+                .entry(external.person_id)
+                .or_default()
+                .push(format!("{} {}", external.system, external.external_code));
+
+            let key = (Self::normalize(&external.system), Self::normalize(&external.external_code));
+            for equivalence in equivalences.get(&key).into_iter().flatten() {
+                let levels = equivalent_levels.entry(external.person_id).or_default();
+                if !levels.contains(&equivalence.level) {
+                    levels.push(equivalence.level.clone());
+                }
+            }
+        }
+
+        Ok(person_ids
+            .into_iter()
+            .map(|person_id| {
+                let native_level = native.remove(&person_id);
+                let diving_level = Self::merge_levels(
+                    native_level.as_deref(),
+                    equivalent_levels.get(&person_id).map(Vec::as_slice).unwrap_or_default(),
+                );
+                let external_certifications = labels.remove(&person_id).unwrap_or_default();
+                (
+                    person_id,
+                    EffectiveLevel {
+                        native_level,
+                        diving_level,
+                        external_certifications,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    /// Niveaux effectifs d'un plongeur
+    pub async fn effective_level<C: ConnectionTrait>(
+        conn: &C,
+        person_id: Uuid,
+    ) -> AppResult<EffectiveLevel> {
+        Ok(Self::effective_levels(conn, [person_id])
+            .await?
+            .remove(&person_id)
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_levels() {
+        let equivalents = vec!["PE40".to_string(), "PA20".to_string()];
+        assert_eq!(
+            EquivalenceService::merge_levels(Some("N1,preparing_PA20"), &equivalents).as_deref(),
+            Some("N1,PE40,PA20")
+        );
+        assert_eq!(
+            EquivalenceService::merge_levels(None, &equivalents).as_deref(),
+            Some("PE40,PA20")
+        );
+        assert_eq!(
+            EquivalenceService::merge_levels(Some("N2,PE40"), &["PE40".to_string()]).as_deref(),
+            Some("N2,PE40")
+        );
+        assert_eq!(EquivalenceService::merge_levels(None, &[]), None);
+    }
+}
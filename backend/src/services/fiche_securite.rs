@@ -7,6 +7,8 @@ use std::collections::HashSet;
 use crate::entities::{sessions, rotations, palanquees, palanquee_members, questionnaires, people};
 use crate::errors::AppError;
 use crate::models::DiverLevel;
+use crate::services::pdf_text::{FontStyle, TextLayer};
+use crate::services::{EquivalenceService, LevelService};
 
 /// Données pour générer une fiche de sécurité
 #[derive(Debug)]
@@ -62,6 +64,7 @@ pub async fn generate_fiche_securite(
         .all(db)
         .await?;
 
+    let catalog = LevelService::catalog(db).await?;
     let mut rotations_data = vec![];
     let mut unique_questionnaire_ids: HashSet<Uuid> = HashSet::new();
 
@@ -94,12 +97,11 @@ pub async fn generate_fiche_securite(
                         .await?;
 
                     if let Some(p) = person {
-                        let aptitude = p.diving_level.as_ref()
-                            .and_then(|s| DiverLevel::from_string(s))
-                            .map(|dl| dl.display())
-                            .unwrap_or_default();
+                        // Aptitude effective: brevets FFESSM et équivalences des brevets externes
+                        let effective = EquivalenceService::effective_level(db, p.id).await?;
+                        let aptitude = effective.aptitude(&catalog).unwrap_or_default();
                         
-                        let preparing = p.diving_level.as_ref()
+                        let preparing = effective.diving_level.as_ref()
                             .and_then(|s| DiverLevel::extract_preparing_level(s));
 
                         members_data.push(MemberData {
@@ -192,19 +194,22 @@ const MIN_Y: f32 = 40.0; // Marge basse minimum
 fn generate_pdf(data: &FicheSecuriteData) -> Result<Vec<u8>, AppError> {
     let mut doc = Document::with_version("1.5");
     
-    let font_helvetica = create_font(&mut doc, "Helvetica");
-    let font_helvetica_bold = create_font(&mut doc, "Helvetica-Bold");
+    // Polices embarquées, écrites une fois toutes les pages générées
+    let mut fonts = TextLayer::new();
+    let font_regular = fonts.font_id(&mut doc, FontStyle::Regular);
+    let font_bold = fonts.font_id(&mut doc, FontStyle::Bold);
     
     let mut font_dict = Dictionary::new();
-    font_dict.set("F1", Object::Reference(font_helvetica));
-    font_dict.set("F2", Object::Reference(font_helvetica_bold));
+    font_dict.set("F1", Object::Reference(font_regular));
+    font_dict.set("F2", Object::Reference(font_bold));
     
     let mut resources = Dictionary::new();
     resources.set("Font", Object::Dictionary(font_dict));
     let resources_id = doc.add_object(resources);
     
     // Générer toutes les pages
-    let page_contents = generate_all_pages(data);
+    let page_contents = generate_all_pages(data, &mut fonts);
+    fonts.embed(&mut doc)?;
     
     let mut page_ids = vec![];
     for content in page_contents {
@@ -255,16 +260,6 @@ fn generate_pdf(data: &FicheSecuriteData) -> Result<Vec<u8>, AppError> {
     Ok(output)
 }
 
-fn create_font(doc: &mut Document, name: &str) -> lopdf::ObjectId {
-    let font_dict = Dictionary::from_iter(vec![
-        ("Type", Object::Name(b"Font".to_vec())),
-        ("Subtype", Object::Name(b"Type1".to_vec())),
-        ("BaseFont", Object::Name(name.as_bytes().to_vec())),
-        ("Encoding", Object::Name(b"WinAnsiEncoding".to_vec())),
-    ]);
-    doc.add_object(font_dict)
-}
-
 /// Calcule la hauteur nécessaire pour une rotation
 fn calculate_rotation_height(rotation: &RotationData) -> f32 {
     let mut height = ROTATION_HEADER_HEIGHT + HEADER_HEIGHT; // Header rotation + header tableau
@@ -275,7 +270,7 @@ fn calculate_rotation_height(rotation: &RotationData) -> f32 {
 }
 
 /// Génère toutes les pages du PDF
-fn generate_all_pages(data: &FicheSecuriteData) -> Vec<String> {
+fn generate_all_pages(data: &FicheSecuriteData, fonts: &mut TextLayer) -> Vec<String> {
     let mut pages = vec![];
     let mut current_page = String::new();
     let mut y = PAGE_HEIGHT - MARGIN;
@@ -283,7 +278,7 @@ fn generate_all_pages(data: &FicheSecuriteData) -> Vec<String> {
     let mut page_num = 1;
     
     // En-tête sur la première page
-    y = draw_header(&mut current_page, data, y);
+    y = draw_header(&mut current_page, fonts, data, y);
     
     for rotation in &data.rotations {
         let rotation_height = calculate_rotation_height(rotation);
@@ -291,38 +286,38 @@ fn generate_all_pages(data: &FicheSecuriteData) -> Vec<String> {
         // Vérifier si la rotation rentre sur la page actuelle
         if y - rotation_height < MIN_Y && !is_first_page {
             // Nouvelle page nécessaire
-            draw_page_footer(&mut current_page, page_num);
+            draw_page_footer(&mut current_page, fonts, page_num);
             pages.push(current_page);
             current_page = String::new();
             y = PAGE_HEIGHT - MARGIN;
             page_num += 1;
             
             // En-tête simplifié sur les pages suivantes
-            y = draw_continuation_header(&mut current_page, data, y, page_num);
+            y = draw_continuation_header(&mut current_page, fonts, data, y, page_num);
         }
         
         // Dessiner la rotation
-        y = draw_rotation(&mut current_page, rotation, y);
+        y = draw_rotation(&mut current_page, fonts, rotation, y);
         is_first_page = false;
     }
     
     // Légende et footer sur la dernière page
-    draw_legend(&mut current_page, y - 10.0);
-    draw_page_footer(&mut current_page, page_num);
+    draw_legend(&mut current_page, fonts, y - 10.0);
+    draw_page_footer(&mut current_page, fonts, page_num);
     pages.push(current_page);
     
     pages
 }
 
 /// Dessine l'en-tête complet (première page)
-fn draw_header(content: &mut String, data: &FicheSecuriteData, mut y: f32) -> f32 {
+fn draw_header(content: &mut String, fonts: &mut TextLayer, data: &FicheSecuriteData, mut y: f32) -> f32 {
     let width = PAGE_WIDTH - 2.0 * MARGIN;
     
     // Titre avec fond bleu
     let title_height = 28.0;
     writeln!(content, "0.2 0.4 0.7 rg {} {} {} {} re f", MARGIN, y - title_height, width, title_height).unwrap();
     writeln!(content, "1 1 1 rg").unwrap(); // Texte blanc
-    writeln!(content, "BT /F2 16 Tf {} {} Td (FICHE DE SECURITE) Tj ET", PAGE_WIDTH / 2.0 - 75.0, y - 19.0).unwrap();
+    writeln!(content, "BT /F2 16 Tf {} {} Td {} Tj ET", PAGE_WIDTH / 2.0 - 75.0, y - 19.0, fonts.encode(FontStyle::Bold, "FICHE DE SECURITE")).unwrap();
     writeln!(content, "0 g").unwrap();
     y -= title_height + 8.0;
     
@@ -342,68 +337,67 @@ fn draw_header(content: &mut String, data: &FicheSecuriteData, mut y: f32) -> f3
     
     // Ligne 1
     writeln!(content, "0 0 0 rg").unwrap(); // Noir
-    writeln!(content, "BT /F2 10 Tf {} {} Td (Date:) Tj ET", col1, y - 14.0).unwrap();
-    writeln!(content, "BT /F1 10 Tf {} {} Td ({}) Tj ET", col1 + 35.0, y - 14.0, escape_pdf(&data.date)).unwrap();
+    writeln!(content, "BT /F2 10 Tf {} {} Td {} Tj ET", col1, y - 14.0, fonts.encode(FontStyle::Bold, "Date:")).unwrap();
+    writeln!(content, "BT /F1 10 Tf {} {} Td {} Tj ET", col1 + 35.0, y - 14.0, fonts.encode(FontStyle::Regular, &data.date)).unwrap();
     
-    writeln!(content, "BT /F2 10 Tf {} {} Td (Club:) Tj ET", col2, y - 14.0).unwrap();
-    writeln!(content, "BT /F1 10 Tf {} {} Td ({}) Tj ET", col2 + 35.0, y - 14.0, escape_pdf(&data.club)).unwrap();
+    writeln!(content, "BT /F2 10 Tf {} {} Td {} Tj ET", col2, y - 14.0, fonts.encode(FontStyle::Bold, "Club:")).unwrap();
+    writeln!(content, "BT /F1 10 Tf {} {} Td {} Tj ET", col2 + 35.0, y - 14.0, fonts.encode(FontStyle::Regular, &data.club)).unwrap();
     
-    writeln!(content, "BT /F2 10 Tf {} {} Td (Effectif:) Tj ET", col4, y - 14.0).unwrap();
+    writeln!(content, "BT /F2 10 Tf {} {} Td {} Tj ET", col4, y - 14.0, fonts.encode(FontStyle::Bold, "Effectif:")).unwrap();
     writeln!(content, "0.2 0.5 0.2 rg").unwrap(); // Vert
-    writeln!(content, "BT /F2 14 Tf {} {} Td ({}) Tj ET", col4 + 55.0, y - 14.0, data.effectif_unique).unwrap();
+    writeln!(content, "BT /F2 14 Tf {} {} Td {} Tj ET", col4 + 55.0, y - 14.0, fonts.encode(FontStyle::Bold, &data.effectif_unique.to_string())).unwrap();
     writeln!(content, "0 0 0 rg").unwrap(); // Remettre en noir
     
     // Ligne 2
-    writeln!(content, "BT /F2 10 Tf {} {} Td (Site:) Tj ET", col1, y - 30.0).unwrap();
-    writeln!(content, "BT /F1 10 Tf {} {} Td ({}) Tj ET", col1 + 35.0, y - 30.0, escape_pdf(&data.site)).unwrap();
+    writeln!(content, "BT /F2 10 Tf {} {} Td {} Tj ET", col1, y - 30.0, fonts.encode(FontStyle::Bold, "Site:")).unwrap();
+    writeln!(content, "BT /F1 10 Tf {} {} Td {} Tj ET", col1 + 35.0, y - 30.0, fonts.encode(FontStyle::Regular, &data.site)).unwrap();
     
-    writeln!(content, "BT /F2 10 Tf {} {} Td (DP:) Tj ET", col2, y - 30.0).unwrap();
-    writeln!(content, "BT /F1 10 Tf {} {} Td ({}) Tj ET", col2 + 25.0, y - 30.0, escape_pdf(&data.directeur_plongee)).unwrap();
+    writeln!(content, "BT /F2 10 Tf {} {} Td {} Tj ET", col2, y - 30.0, fonts.encode(FontStyle::Bold, "DP:")).unwrap();
+    writeln!(content, "BT /F1 10 Tf {} {} Td {} Tj ET", col2 + 25.0, y - 30.0, fonts.encode(FontStyle::Regular, &data.directeur_plongee)).unwrap();
     
-    writeln!(content, "BT /F2 10 Tf {} {} Td (Position:) Tj ET", col3, y - 30.0).unwrap();
-    writeln!(content, "BT /F1 9 Tf {} {} Td ({}) Tj ET", col3 + 55.0, y - 30.0, escape_pdf(&data.position)).unwrap();
+    writeln!(content, "BT /F2 10 Tf {} {} Td {} Tj ET", col3, y - 30.0, fonts.encode(FontStyle::Bold, "Position:")).unwrap();
+    writeln!(content, "BT /F1 9 Tf {} {} Td {} Tj ET", col3 + 55.0, y - 30.0, fonts.encode(FontStyle::Regular, &data.position)).unwrap();
     
     // Ligne 3
-    writeln!(content, "BT /F2 10 Tf {} {} Td (S\\351curit\\351 surface:) Tj ET", col1, y - 46.0).unwrap();
-    writeln!(content, "BT /F1 10 Tf {} {} Td ({}) Tj ET", col1 + 100.0, y - 46.0, escape_pdf(&data.securite_surface)).unwrap();
+    writeln!(content, "BT /F2 10 Tf {} {} Td {} Tj ET", col1, y - 46.0, fonts.encode(FontStyle::Bold, "Sécurité surface:")).unwrap();
+    writeln!(content, "BT /F1 10 Tf {} {} Td {} Tj ET", col1 + 100.0, y - 46.0, fonts.encode(FontStyle::Regular, &data.securite_surface)).unwrap();
     
     if !data.observations.is_empty() {
-        writeln!(content, "BT /F2 9 Tf {} {} Td (Obs:) Tj ET", col3, y - 46.0).unwrap();
-        writeln!(content, "BT /F1 9 Tf {} {} Td ({}) Tj ET", col3 + 30.0, y - 46.0, escape_pdf(&data.observations)).unwrap();
+        writeln!(content, "BT /F2 9 Tf {} {} Td {} Tj ET", col3, y - 46.0, fonts.encode(FontStyle::Bold, "Obs:")).unwrap();
+        writeln!(content, "BT /F1 9 Tf {} {} Td {} Tj ET", col3 + 30.0, y - 46.0, fonts.encode(FontStyle::Regular, &data.observations)).unwrap();
     }
     
     y - info_height - 12.0
 }
 
 /// En-tête simplifié pour les pages de continuation
-fn draw_continuation_header(content: &mut String, data: &FicheSecuriteData, y: f32, page: i32) -> f32 {
+fn draw_continuation_header(content: &mut String, fonts: &mut TextLayer, data: &FicheSecuriteData, y: f32, page: i32) -> f32 {
     let width = PAGE_WIDTH - 2.0 * MARGIN;
     
     // Bandeau simple
     let header_height = 22.0;
     writeln!(content, "0.2 0.4 0.7 rg {} {} {} {} re f", MARGIN, y - header_height, width, header_height).unwrap();
     writeln!(content, "1 1 1 rg").unwrap();
-    writeln!(content, "BT /F2 12 Tf {} {} Td (FICHE DE SECURITE - {} - Page {}) Tj ET", 
-        MARGIN + 10.0, y - 15.0, escape_pdf(&data.date), page).unwrap();
+    writeln!(content, "BT /F2 12 Tf {} {} Td {} Tj ET", MARGIN + 10.0, y - 15.0, fonts.encode(FontStyle::Bold, &format!("FICHE DE SECURITE - {} - Page {}", data.date, page))).unwrap();
     writeln!(content, "0 g").unwrap();
     
     y - header_height - 10.0
 }
 
 /// Dessine une rotation complète
-fn draw_rotation(content: &mut String, rotation: &RotationData, mut y: f32) -> f32 {
+fn draw_rotation(content: &mut String, fonts: &mut TextLayer, rotation: &RotationData, mut y: f32) -> f32 {
     let width = PAGE_WIDTH - 2.0 * MARGIN;
     
     // Bandeau de rotation - fond vert foncé avec texte blanc
     writeln!(content, "0.15 0.45 0.25 rg {} {} {} {} re f", MARGIN, y - ROTATION_HEADER_HEIGHT, width, ROTATION_HEADER_HEIGHT).unwrap();
     writeln!(content, "1 1 1 rg").unwrap(); // Texte blanc
-    writeln!(content, "BT /F2 12 Tf {} {} Td (ROTATION {}) Tj ET", MARGIN + 15.0, y - 15.0, rotation.numero).unwrap();
+    writeln!(content, "BT /F2 12 Tf {} {} Td {} Tj ET", MARGIN + 15.0, y - 15.0, fonts.encode(FontStyle::Bold, &format!("ROTATION {}", rotation.numero))).unwrap();
     writeln!(content, "0 g").unwrap();
     y -= ROTATION_HEADER_HEIGHT;
     
     // En-tête du tableau - fond bleu très clair
     let cols = [160.0, 55.0, 75.0, 70.0, 55.0, 185.0, 182.0];
-    let col_headers = ["NOM Prenom", "Gaz", "Aptitude", "Prepa", "Fonction", "Params Prevus", "Params Realises"];
+    let col_headers = ["NOM Prénom", "Gaz", "Aptitude", "Prépa", "Fonction", "Params Prévus", "Params Réalisés"];
     
     writeln!(content, "0.85 0.9 0.95 rg {} {} {} {} re f", MARGIN, y - HEADER_HEIGHT, width, HEADER_HEIGHT).unwrap();
     
@@ -411,7 +405,7 @@ fn draw_rotation(content: &mut String, rotation: &RotationData, mut y: f32) -> f
     let mut col_x = MARGIN;
     writeln!(content, "0.1 0.1 0.3 rg").unwrap(); // Texte bleu foncé
     for (i, &col_w) in cols.iter().enumerate() {
-        writeln!(content, "BT /F2 8 Tf {} {} Td ({}) Tj ET", col_x + 3.0, y - 12.0, col_headers[i]).unwrap();
+        writeln!(content, "BT /F2 8 Tf {} {} Td {} Tj ET", col_x + 3.0, y - 12.0, fonts.encode(FontStyle::Bold, col_headers[i])).unwrap();
         col_x += col_w;
     }
     writeln!(content, "0 g").unwrap();
@@ -441,7 +435,7 @@ fn draw_rotation(content: &mut String, rotation: &RotationData, mut y: f32) -> f
         // Badge palanquée sur le côté - fond violet
         writeln!(content, "0.4 0.3 0.6 rg {} {} {} {} re f", MARGIN - 22.0, y - pal_height, 20.0, pal_height).unwrap();
         writeln!(content, "1 1 1 rg").unwrap(); // Texte blanc
-        writeln!(content, "BT /F2 9 Tf {} {} Td (P{}) Tj ET", MARGIN - 19.0, y - pal_height / 2.0 - 3.0, palanquee.numero).unwrap();
+        writeln!(content, "BT /F2 9 Tf {} {} Td {} Tj ET", MARGIN - 19.0, y - pal_height / 2.0 - 3.0, fonts.encode(FontStyle::Bold, &format!("P{}", palanquee.numero))).unwrap();
         writeln!(content, "0 g").unwrap();
         
         // Membres
@@ -450,7 +444,7 @@ fn draw_rotation(content: &mut String, rotation: &RotationData, mut y: f32) -> f
             col_x = MARGIN;
             
             // Nom
-            writeln!(content, "BT /F1 9 Tf {} {} Td ({}) Tj ET", col_x + 5.0, member_y, escape_pdf(&member.name)).unwrap();
+            writeln!(content, "BT /F1 9 Tf {} {} Td {} Tj ET", col_x + 5.0, member_y, fonts.encode(FontStyle::Regular, &member.name)).unwrap();
             col_x += cols[0];
             
             // Gaz avec couleur
@@ -459,18 +453,18 @@ fn draw_rotation(content: &mut String, rotation: &RotationData, mut y: f32) -> f
             } else {
                 writeln!(content, "0.2 0.4 0.6 rg").unwrap(); // Bleu
             }
-            writeln!(content, "BT /F2 9 Tf {} {} Td ({}) Tj ET", col_x + 5.0, member_y, escape_pdf(&member.gas)).unwrap();
+            writeln!(content, "BT /F2 9 Tf {} {} Td {} Tj ET", col_x + 5.0, member_y, fonts.encode(FontStyle::Bold, &member.gas)).unwrap();
             writeln!(content, "0 g").unwrap();
             col_x += cols[1];
             
             // Aptitude
-            writeln!(content, "BT /F1 9 Tf {} {} Td ({}) Tj ET", col_x + 5.0, member_y, escape_pdf(&member.aptitude)).unwrap();
+            writeln!(content, "BT /F1 9 Tf {} {} Td {} Tj ET", col_x + 5.0, member_y, fonts.encode(FontStyle::Regular, &member.aptitude)).unwrap();
             col_x += cols[2];
             
             // Prépa
             if let Some(ref prep) = member.preparing {
                 writeln!(content, "0.6 0.3 0 rg").unwrap(); // Orange foncé
-                writeln!(content, "BT /F2 9 Tf {} {} Td ({}) Tj ET", col_x + 5.0, member_y, escape_pdf(prep)).unwrap();
+                writeln!(content, "BT /F2 9 Tf {} {} Td {} Tj ET", col_x + 5.0, member_y, fonts.encode(FontStyle::Bold, prep)).unwrap();
                 writeln!(content, "0 g").unwrap();
             }
             col_x += cols[3];
@@ -479,11 +473,11 @@ fn draw_rotation(content: &mut String, rotation: &RotationData, mut y: f32) -> f
             match member.role.as_str() {
                 "E" | "GP" => {
                     writeln!(content, "0.5 0.2 0.5 rg").unwrap(); // Violet
-                    writeln!(content, "BT /F2 10 Tf {} {} Td ({}) Tj ET", col_x + 15.0, member_y, escape_pdf(&member.role)).unwrap();
+                    writeln!(content, "BT /F2 10 Tf {} {} Td {} Tj ET", col_x + 15.0, member_y, fonts.encode(FontStyle::Bold, &member.role)).unwrap();
                 }
                 _ => {
                     writeln!(content, "0.3 0.3 0.3 rg").unwrap(); // Gris
-                    writeln!(content, "BT /F1 9 Tf {} {} Td ({}) Tj ET", col_x + 18.0, member_y, escape_pdf(&member.role)).unwrap();
+                    writeln!(content, "BT /F1 9 Tf {} {} Td {} Tj ET", col_x + 18.0, member_y, fonts.encode(FontStyle::Regular, &member.role)).unwrap();
                 }
             }
             writeln!(content, "0 g").unwrap();
@@ -501,14 +495,12 @@ fn draw_rotation(content: &mut String, rotation: &RotationData, mut y: f32) -> f
             palanquee.planned_time.map_or("__".to_string(), |t| t.to_string()),
             palanquee.planned_depth.map_or("__".to_string(), |d| d.to_string())
         );
-        writeln!(content, "BT /F1 9 Tf {} {} Td ({}) Tj ET", col_x + 10.0, params_y, escape_pdf(&planned)).unwrap();
+        writeln!(content, "BT /F1 9 Tf {} {} Td {} Tj ET", col_x + 10.0, params_y, fonts.encode(FontStyle::Regular, &planned)).unwrap();
         col_x += cols[5];
         
         // Réalisés (durée et profondeur uniquement, plus d'espace pour écrire à la main)
-        let actual = format!(
-            "______' / ______m"
-        );
-        writeln!(content, "BT /F1 9 Tf {} {} Td ({}) Tj ET", col_x + 10.0, params_y, escape_pdf(&actual)).unwrap();
+        let actual = "______' / ______m".to_string();
+        writeln!(content, "BT /F1 9 Tf {} {} Td {} Tj ET", col_x + 10.0, params_y, fonts.encode(FontStyle::Regular, &actual)).unwrap();
         
         // Lignes verticales
         writeln!(content, "0.8 0.8 0.85 RG 0.3 w").unwrap();
@@ -533,47 +525,15 @@ fn draw_rotation(content: &mut String, rotation: &RotationData, mut y: f32) -> f
 }
 
 /// Dessine la légende
-fn draw_legend(content: &mut String, y: f32) {
+fn draw_legend(content: &mut String, fonts: &mut TextLayer, y: f32) {
     writeln!(content, "0.4 0.4 0.4 rg").unwrap();
-    writeln!(content, "BT /F1 8 Tf {} {} Td (L\\351gende: E = Encadrant    GP = Guide de Palanqu\\351e    P = Plongeur) Tj ET", MARGIN, y).unwrap();
+    writeln!(content, "BT /F1 8 Tf {} {} Td {} Tj ET", MARGIN, y, fonts.encode(FontStyle::Regular, "Légende: E = Encadrant    GP = Guide de Palanquée    P = Plongeur")).unwrap();
     writeln!(content, "0 g").unwrap();
 }
 
 /// Dessine le pied de page
-fn draw_page_footer(content: &mut String, page: i32) {
+fn draw_page_footer(content: &mut String, fonts: &mut TextLayer, page: i32) {
     writeln!(content, "0.5 0.5 0.5 rg").unwrap();
-    writeln!(content, "BT /F1 8 Tf {} {} Td (Page {}) Tj ET", PAGE_WIDTH - 60.0, 20.0, page).unwrap();
+    writeln!(content, "BT /F1 8 Tf {} {} Td {} Tj ET", PAGE_WIDTH - 60.0, 20.0, fonts.encode(FontStyle::Regular, &format!("Page {}", page))).unwrap();
     writeln!(content, "0 g").unwrap();
 }
-
-/// Échappe une chaîne pour PDF (WinAnsi)
-fn escape_pdf(s: &str) -> String {
-    let mut result = String::new();
-    for c in s.chars() {
-        match c {
-            '\\' => result.push_str("\\\\"),
-            '(' => result.push_str("\\("),
-            ')' => result.push_str("\\)"),
-            'é' => result.push_str("\\351"),
-            'è' => result.push_str("\\350"),
-            'ê' => result.push_str("\\352"),
-            'à' => result.push_str("\\340"),
-            'â' => result.push_str("\\342"),
-            'ù' => result.push_str("\\371"),
-            'û' => result.push_str("\\373"),
-            'î' => result.push_str("\\356"),
-            'ï' => result.push_str("\\357"),
-            'ô' => result.push_str("\\364"),
-            'ç' => result.push_str("\\347"),
-            'É' => result.push_str("\\311"),
-            'È' => result.push_str("\\310"),
-            'Ê' => result.push_str("\\312"),
-            'À' => result.push_str("\\300"),
-            '°' => result.push_str("\\260"),
-            '\'' => result.push_str("'"),
-            _ if c.is_ascii() => result.push(c),
-            _ => result.push('?'),
-        }
-    }
-    result
-}
@@ -1,7 +1,7 @@
 use crate::entities::{import_jobs, people, prelude::*, questionnaires};
 use crate::errors::{AppError, AppResult};
 use crate::models::{CsvImportRow, ImportError};
-use crate::services::EmailService;
+use crate::services::{EmailService, TeachingQualificationService};
 use chrono::Utc;
 use csv::ReaderBuilder;
 use sea_orm::*;
@@ -156,7 +156,6 @@ impl ImportService {
                 default_wants_2nd_reg: Set(false),
                 default_wants_stab: Set(false),
                 default_stab_size: Set(None),
-                diving_level: Set(None),
                 group_id: Set(None),
                 password_hash: Set(None),
                 temp_password: Set(None),
@@ -186,11 +185,8 @@ impl ImportService {
 
         // Create questionnaire record pre-filled with user's default preferences
         let now = Utc::now().naive_utc();
-        // Compute is_instructor from diving_level
-        let is_instructor = person.diving_level.as_ref()
-            .and_then(|level_str| crate::models::DiverLevel::from_string(level_str))
-            .map(|diver_level| diver_level.is_instructor())
-            .unwrap_or(false);
+        // Compute is_instructor from diving_level and teaching qualifications
+        let is_instructor = TeachingQualificationService::is_instructor(db, person.id).await?;
         let questionnaire = questionnaires::ActiveModel {
             id: Set(Uuid::new_v4()),
             session_id: Set(Some(session_id)),
@@ -0,0 +1,397 @@
+use chrono::Utc;
+use sea_orm::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::entities::prelude::*;
+use crate::entities::{competencies, competency_domains, competency_modules, competency_skills};
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    CompetencyResponse, LegacyMappingEntry, LegacyMigrationRequest, LegacyMigrationResult,
+};
+
+/// Domaine et module par défaut des compétences sans équivalent dans la hiérarchie
+const DEFAULT_DOMAIN: &str = "Compétences générales";
+const DEFAULT_MODULE: &str = "Ancien référentiel";
+const DEFAULT_MIN_VALIDATOR_LEVEL: &str = "E2";
+
+pub const ACTION_LINK: &str = "link";
+pub const ACTION_CREATE: &str = "create";
+pub const ACTION_SKIP: &str = "skip";
+pub const ACTION_MIGRATED: &str = "migrated";
+
+fn key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+fn format_datetime(dt: &chrono::NaiveDateTime) -> String {
+    dt.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Migration des compétences historiques (table `competencies`) vers la
+/// hiérarchie domaines > modules > acquis, et vue historique dérivée
+pub struct LegacyCompetencyService;
+
+impl LegacyCompetencyService {
+    /// Calcule le rapprochement de chaque compétence historique sans rien modifier.
+    /// Par défaut une compétence est rattachée à l'acquis du même niveau portant
+    /// le même nom, sinon un acquis est créé dans le domaine/module par défaut.
+    pub async fn plan<C: ConnectionTrait>(
+        conn: &C,
+        request: &LegacyMigrationRequest,
+    ) -> AppResult<Vec<LegacyMappingEntry>> {
+        let legacy = Competencies::find()
+            .order_by_asc(competencies::Column::Level)
+            .order_by_asc(competencies::Column::SortOrder)
+            .all(conn)
+            .await?;
+
+        let domains: HashMap<Uuid, competency_domains::Model> = CompetencyDomains::find()
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|d| (d.id, d))
+            .collect();
+        let modules: HashMap<Uuid, competency_modules::Model> = CompetencyModules::find()
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|m| (m.id, m))
+            .collect();
+        let skills = CompetencySkills::find().all(conn).await?;
+
+        // Niveau, domaine et module de chaque acquis
+        let location = |skill: &competency_skills::Model| {
+            let module = modules.get(&skill.module_id)?;
+            let domain = domains.get(&module.domain_id)?;
+            Some((domain.diving_level.clone(), domain.name.clone(), module.name.clone()))
+        };
+
+        let mut entries = Vec::new();
+        for competency in legacy {
+            let correction = request
+                .overrides
+                .iter()
+                .find(|o| o.competency_id == competency.id);
+
+            let mut entry = LegacyMappingEntry {
+                competency_id: competency.id,
+                level: competency.level.clone(),
+                name: competency.name.clone(),
+                action: ACTION_CREATE.to_string(),
+                skill_id: None,
+                domain_name: None,
+                module_name: None,
+            };
+
+            let linked_skill = if let Some(skill_id) = competency.skill_id {
+                entry.action = ACTION_MIGRATED.to_string();
+                skills.iter().find(|s| s.id == skill_id)
+            } else if correction.is_some_and(|o| o.skip) {
+                entry.action = ACTION_SKIP.to_string();
+                None
+            } else if let Some(skill_id) = correction.and_then(|o| o.skill_id) {
+                entry.action = ACTION_LINK.to_string();
+                Some(skills.iter().find(|s| s.id == skill_id).ok_or_else(|| {
+                    AppError::Validation(format!("Acquis {} introuvable", skill_id))
+                })?)
+            } else {
+                let same_name = skills.iter().find(|s| {
+                    key(&s.name) == key(&competency.name)
+                        && location(s).is_some_and(|(level, _, _)| level == competency.level)
+                });
+                if same_name.is_some() {
+                    entry.action = ACTION_LINK.to_string();
+                }
+                same_name
+            };
+
+            if let Some(skill) = linked_skill {
+                entry.skill_id = Some(skill.id);
+                if let Some((_, domain_name, module_name)) = location(skill) {
+                    entry.domain_name = Some(domain_name);
+                    entry.module_name = Some(module_name);
+                }
+            } else if entry.action == ACTION_CREATE {
+                entry.domain_name = Some(
+                    correction
+                        .and_then(|o| o.domain_name.clone())
+                        .unwrap_or_else(|| DEFAULT_DOMAIN.to_string()),
+                );
+                entry.module_name = Some(
+                    correction
+                        .and_then(|o| o.module_name.clone())
+                        .unwrap_or_else(|| DEFAULT_MODULE.to_string()),
+                );
+            }
+
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+
+    /// Applique le rapprochement dans une transaction
+    pub async fn migrate(
+        db: &DatabaseConnection,
+        request: &LegacyMigrationRequest,
+    ) -> AppResult<LegacyMigrationResult> {
+        let txn = db.begin().await?;
+        let now = Utc::now().naive_utc();
+        let mut mappings = Self::plan(&txn, request).await?;
+        let mut legacy: HashMap<Uuid, competencies::Model> = Competencies::find()
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|c| (c.id, c))
+            .collect();
+        let (mut linked, mut created, mut skipped) = (0, 0, 0);
+
+        for entry in mappings.iter_mut() {
+            let skill_id = match entry.action.as_str() {
+                ACTION_LINK => {
+                    linked += 1;
+                    entry.skill_id
+                }
+                ACTION_CREATE => {
+                    let competency = legacy
+                        .get(&entry.competency_id)
+                        .ok_or(AppError::NotFound("Compétence non trouvée".to_string()))?;
+                    let module_id = Self::find_or_create_module(
+                        &txn,
+                        &entry.level,
+                        entry.domain_name.as_deref().unwrap_or(DEFAULT_DOMAIN),
+                        entry.module_name.as_deref().unwrap_or(DEFAULT_MODULE),
+                    )
+                    .await?;
+
+                    let skill = competency_skills::ActiveModel {
+                        id: Set(Uuid::new_v4()),
+                        module_id: Set(module_id),
+                        name: Set(competency.name.clone()),
+                        description: Set(competency.description.clone()),
+                        sort_order: Set(competency.sort_order),
+                        min_validator_level: Set(DEFAULT_MIN_VALIDATOR_LEVEL.to_string()),
+                        validator_rule: Set(None),
+                        validity_months: Set(None),
+                        created_at: Set(now),
+                        updated_at: Set(now),
+                    }
+                    .insert(&txn)
+                    .await?;
+
+                    created += 1;
+                    entry.skill_id = Some(skill.id);
+                    Some(skill.id)
+                }
+                ACTION_SKIP => {
+                    skipped += 1;
+                    None
+                }
+                _ => None,
+            };
+
+            if let Some(skill_id) = skill_id {
+                let competency = legacy
+                    .remove(&entry.competency_id)
+                    .ok_or(AppError::NotFound("Compétence non trouvée".to_string()))?;
+                let mut active: competencies::ActiveModel = competency.into();
+                active.skill_id = Set(Some(skill_id));
+                active.migrated_at = Set(Some(now));
+                active.updated_at = Set(now);
+                active.update(&txn).await?;
+            }
+        }
+
+        txn.commit().await?;
+
+        Ok(LegacyMigrationResult {
+            linked,
+            created,
+            skipped,
+            mappings,
+        })
+    }
+
+    async fn find_or_create_module(
+        txn: &DatabaseTransaction,
+        level: &str,
+        domain_name: &str,
+        module_name: &str,
+    ) -> AppResult<Uuid> {
+        let now = Utc::now().naive_utc();
+
+        let domain = CompetencyDomains::find()
+            .filter(competency_domains::Column::DivingLevel.eq(level))
+            .all(txn)
+            .await?
+            .into_iter()
+            .find(|d| key(&d.name) == key(domain_name));
+        let domain_id = match domain {
+            Some(domain) => domain.id,
+            None => {
+                let max_order = CompetencyDomains::find()
+                    .filter(competency_domains::Column::DivingLevel.eq(level))
+                    .order_by_desc(competency_domains::Column::SortOrder)
+                    .one(txn)
+                    .await?
+                    .map(|d| d.sort_order)
+                    .unwrap_or(0);
+                competency_domains::ActiveModel {
+                    id: Set(Uuid::new_v4()),
+                    diving_level: Set(level.to_string()),
+                    name: Set(domain_name.to_string()),
+                    sort_order: Set(max_order + 1),
+                    validity_months: Set(None),
+                    created_at: Set(now),
+                    updated_at: Set(now),
+                }
+                .insert(txn)
+                .await?
+                .id
+            }
+        };
+
+        let module = CompetencyModules::find()
+            .filter(competency_modules::Column::DomainId.eq(domain_id))
+            .all(txn)
+            .await?
+            .into_iter()
+            .find(|m| key(&m.name) == key(module_name));
+        match module {
+            Some(module) => Ok(module.id),
+            None => {
+                let max_order = CompetencyModules::find()
+                    .filter(competency_modules::Column::DomainId.eq(domain_id))
+                    .order_by_desc(competency_modules::Column::SortOrder)
+                    .one(txn)
+                    .await?
+                    .map(|m| m.sort_order)
+                    .unwrap_or(0);
+                Ok(competency_modules::ActiveModel {
+                    id: Set(Uuid::new_v4()),
+                    domain_id: Set(domain_id),
+                    name: Set(module_name.to_string()),
+                    sort_order: Set(max_order + 1),
+                    created_at: Set(now),
+                    updated_at: Set(now),
+                }
+                .insert(txn)
+                .await?
+                .id)
+            }
+        }
+    }
+
+    /// Vue historique à plat dérivée de la hiérarchie: un élément par acquis,
+    /// suivi des compétences historiques pas encore migrées
+    pub async fn derived_list(
+        db: &DatabaseConnection,
+        level: Option<&str>,
+    ) -> AppResult<Vec<CompetencyResponse>> {
+        let mut domain_select = CompetencyDomains::find();
+        if let Some(level) = level {
+            domain_select = domain_select.filter(competency_domains::Column::DivingLevel.eq(level));
+        }
+        let domains = domain_select
+            .order_by_asc(competency_domains::Column::DivingLevel)
+            .order_by_asc(competency_domains::Column::SortOrder)
+            .all(db)
+            .await?;
+        let modules = CompetencyModules::find()
+            .filter(competency_modules::Column::DomainId.is_in(domains.iter().map(|d| d.id)))
+            .order_by_asc(competency_modules::Column::SortOrder)
+            .all(db)
+            .await?;
+        let skills = CompetencySkills::find()
+            .filter(competency_skills::Column::ModuleId.is_in(modules.iter().map(|m| m.id)))
+            .order_by_asc(competency_skills::Column::SortOrder)
+            .all(db)
+            .await?;
+
+        let mut next_order: HashMap<String, i32> = HashMap::new();
+        let mut response = Vec::new();
+
+        for domain in &domains {
+            for module in modules.iter().filter(|m| m.domain_id == domain.id) {
+                for skill in skills.iter().filter(|s| s.module_id == module.id) {
+                    let order = next_order.entry(domain.diving_level.clone()).or_insert(0);
+                    response.push(CompetencyResponse {
+                        id: skill.id,
+                        level: domain.diving_level.clone(),
+                        name: skill.name.clone(),
+                        description: skill.description.clone(),
+                        sort_order: *order,
+                        domain_name: Some(domain.name.clone()),
+                        module_name: Some(module.name.clone()),
+                        created_at: format_datetime(&skill.created_at),
+                        updated_at: format_datetime(&skill.updated_at),
+                    });
+                    *order += 1;
+                }
+            }
+        }
+
+        let mut legacy_select = Competencies::find().filter(competencies::Column::SkillId.is_null());
+        if let Some(level) = level {
+            legacy_select = legacy_select.filter(competencies::Column::Level.eq(level));
+        }
+        for competency in legacy_select
+            .order_by_asc(competencies::Column::Level)
+            .order_by_asc(competencies::Column::SortOrder)
+            .all(db)
+            .await?
+        {
+            let order = next_order.entry(competency.level.clone()).or_insert(0);
+            response.push(CompetencyResponse {
+                id: competency.id,
+                level: competency.level,
+                name: competency.name,
+                description: competency.description,
+                sort_order: *order,
+                domain_name: None,
+                module_name: None,
+                created_at: format_datetime(&competency.created_at),
+                updated_at: format_datetime(&competency.updated_at),
+            });
+            *order += 1;
+        }
+
+        Ok(response)
+    }
+
+    /// Retrouve un élément de la vue historique par ID d'acquis ou par
+    /// ID de compétence historique (redirigé vers l'acquis migré)
+    pub async fn derived_get(db: &DatabaseConnection, id: Uuid) -> AppResult<CompetencyResponse> {
+        let legacy = Competencies::find_by_id(id).one(db).await?;
+        let (target_id, level) = match legacy {
+            Some(competency) => match competency.skill_id {
+                Some(skill_id) => (skill_id, Self::skill_level(db, skill_id).await?),
+                None => (competency.id, Some(competency.level)),
+            },
+            None => (id, Self::skill_level(db, id).await?),
+        };
+        let level = level.ok_or(AppError::NotFound("Compétence non trouvée".to_string()))?;
+
+        // Seul le niveau de l'élément est reconstruit (numérotation par niveau)
+        Self::derived_list(db, Some(&level))
+            .await?
+            .into_iter()
+            .find(|c| c.id == target_id)
+            .ok_or(AppError::NotFound("Compétence non trouvée".to_string()))
+    }
+
+    /// Niveau d'un acquis, via son module et son domaine
+    async fn skill_level(db: &DatabaseConnection, skill_id: Uuid) -> AppResult<Option<String>> {
+        let Some(skill) = CompetencySkills::find_by_id(skill_id).one(db).await? else {
+            return Ok(None);
+        };
+        let Some(module) = CompetencyModules::find_by_id(skill.module_id).one(db).await? else {
+            return Ok(None);
+        };
+        Ok(CompetencyDomains::find_by_id(module.domain_id)
+            .one(db)
+            .await?
+            .map(|d| d.diving_level))
+    }
+}
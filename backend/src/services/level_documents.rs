@@ -0,0 +1,307 @@
+use chrono::Utc;
+use sea_orm::*;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::entities::level_document_versions::CopyPositionsResult;
+use crate::entities::{
+    level_document_generations, level_document_versions, level_documents,
+    skill_document_fields, skill_document_positions,
+};
+use crate::errors::{AppError, AppResult};
+use crate::services::acroform;
+
+/// Fichier PDF téléversé pour une nouvelle version
+pub struct NewTemplate {
+    pub file_name: String,
+    pub file_data: Vec<u8>,
+    pub page_count: i32,
+    pub notes: Option<String>,
+}
+
+/// Versions des modèles PDF de niveau et positions associées
+pub struct LevelDocumentService;
+
+impl LevelDocumentService {
+    /// Document d'un niveau et sa version active
+    pub async fn current<C: ConnectionTrait>(
+        conn: &C,
+        level: &str,
+    ) -> AppResult<(level_documents::Model, level_document_versions::Model)> {
+        let doc = level_documents::Entity::find()
+            .filter(level_documents::Column::Level.eq(level))
+            .one(conn)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Document not found for level {}", level)))?;
+
+        let version = match doc.current_version_id {
+            Some(version_id) => level_document_versions::Entity::find_by_id(version_id)
+                .one(conn)
+                .await?,
+            None => None,
+        }
+        .ok_or_else(|| AppError::NotFound(format!("No active template version for level {}", level)))?;
+
+        Ok((doc, version))
+    }
+
+    /// Version donnée du modèle d'un niveau
+    pub async fn version<C: ConnectionTrait>(
+        conn: &C,
+        level: &str,
+        version: i32,
+    ) -> AppResult<level_document_versions::Model> {
+        level_document_versions::Entity::find()
+            .filter(level_document_versions::Column::Level.eq(level))
+            .filter(level_document_versions::Column::Version.eq(version))
+            .one(conn)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Version {} not found for level {}", version, level))
+            })
+    }
+
+    /// Ajoute une version et l'active. Les positions de la version
+    /// précédente sont recopiées si `copy_positions`.
+    pub async fn add_version(
+        db: &DatabaseConnection,
+        level: &str,
+        template: NewTemplate,
+        copy_positions: bool,
+    ) -> AppResult<(level_documents::Model, level_document_versions::Model)> {
+        let now = Utc::now().naive_utc();
+        let txn = db.begin().await?;
+
+        let existing = level_documents::Entity::find()
+            .filter(level_documents::Column::Level.eq(level))
+            .one(&txn)
+            .await?;
+        let previous = match existing.as_ref().and_then(|d| d.current_version_id) {
+            Some(version_id) => level_document_versions::Entity::find_by_id(version_id)
+                .one(&txn)
+                .await?,
+            None => None,
+        };
+
+        let last_number = level_document_versions::Entity::find()
+            .filter(level_document_versions::Column::Level.eq(level))
+            .order_by_desc(level_document_versions::Column::Version)
+            .one(&txn)
+            .await?
+            .map(|v| v.version)
+            .unwrap_or(0);
+
+        let version = level_document_versions::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            level: Set(level.to_string()),
+            version: Set(last_number + 1),
+            file_name: Set(template.file_name),
+            file_data: Set(template.file_data),
+            page_count: Set(template.page_count),
+            notes: Set(template.notes),
+            created_at: Set(now),
+        }
+        .insert(&txn)
+        .await?;
+
+        let doc = match existing {
+            Some(existing) => {
+                let mut active: level_documents::ActiveModel = existing.into();
+                active.current_version_id = Set(Some(version.id));
+                active.updated_at = Set(now);
+                active.update(&txn).await?
+            }
+            None => {
+                level_documents::ActiveModel {
+                    id: Set(Uuid::new_v4()),
+                    level: Set(level.to_string()),
+                    current_version_id: Set(Some(version.id)),
+                    created_at: Set(now),
+                    updated_at: Set(now),
+                }
+                .insert(&txn)
+                .await?
+            }
+        };
+
+        if copy_positions {
+            if let Some(previous) = previous {
+                Self::copy_positions(&txn, &previous, &version, false).await?;
+            }
+        }
+
+        txn.commit().await?;
+        Ok((doc, version))
+    }
+
+    /// Revient à une version antérieure (ou réactive une version plus récente)
+    pub async fn activate(
+        db: &DatabaseConnection,
+        level: &str,
+        version: i32,
+    ) -> AppResult<(level_documents::Model, level_document_versions::Model)> {
+        let (doc, _) = Self::current(db, level).await?;
+        let target = Self::version(db, level, version).await?;
+
+        let mut active: level_documents::ActiveModel = doc.into();
+        active.current_version_id = Set(Some(target.id));
+        active.updated_at = Set(Utc::now().naive_utc());
+        let doc = active.update(db).await?;
+
+        Ok((doc, target))
+    }
+
+    /// Recopie les positions et les champs de formulaire associés d'une
+    /// version vers une autre. Les positions sur une page absente de la
+    /// cible sont ignorées, comme les champs absents du PDF cible et ce qui
+    /// est déjà placé sur la cible sauf si `overwrite`.
+    pub async fn copy_positions<C: ConnectionTrait>(
+        conn: &C,
+        from: &level_document_versions::Model,
+        to: &level_document_versions::Model,
+        overwrite: bool,
+    ) -> AppResult<CopyPositionsResult> {
+        if from.id == to.id {
+            return Err(AppError::Validation(
+                "Les versions source et cible doivent être différentes".to_string(),
+            ));
+        }
+
+        let source = skill_document_positions::Entity::find()
+            .filter(skill_document_positions::Column::VersionId.eq(from.id))
+            .all(conn)
+            .await?;
+        let target = skill_document_positions::Entity::find()
+            .filter(skill_document_positions::Column::VersionId.eq(to.id))
+            .all(conn)
+            .await?;
+        let placed: HashSet<Uuid> = target.iter().map(|p| p.skill_id).collect();
+
+        let now = Utc::now().naive_utc();
+        let mut result = CopyPositionsResult { copied: 0, skipped: 0 };
+        for position in source {
+            if position.page < 1 || position.page > to.page_count {
+                result.skipped += 1;
+                continue;
+            }
+
+            if placed.contains(&position.skill_id) {
+                if !overwrite {
+                    result.skipped += 1;
+                    continue;
+                }
+                skill_document_positions::Entity::delete_many()
+                    .filter(skill_document_positions::Column::VersionId.eq(to.id))
+                    .filter(skill_document_positions::Column::SkillId.eq(position.skill_id))
+                    .exec(conn)
+                    .await?;
+            }
+
+            skill_document_positions::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                skill_id: Set(position.skill_id),
+                level: Set(to.level.clone()),
+                version_id: Set(to.id),
+                page: Set(position.page),
+                x: Set(position.x),
+                y: Set(position.y),
+                width: Set(position.width),
+                height: Set(position.height),
+                font_size: Set(position.font_size),
+                created_at: Set(now),
+                updated_at: Set(now),
+            }
+            .insert(conn)
+            .await?;
+            result.copied += 1;
+        }
+
+        let source_fields = skill_document_fields::Entity::find()
+            .filter(skill_document_fields::Column::VersionId.eq(from.id))
+            .all(conn)
+            .await?;
+        if source_fields.is_empty() {
+            return Ok(result);
+        }
+        let target_fields: HashSet<String> = lopdf::Document::load_mem(&to.file_data)
+            .map(|doc| acroform::list_fields(&doc).into_iter().map(|f| f.name).collect())
+            .unwrap_or_default();
+        let mapped: HashSet<String> = skill_document_fields::Entity::find()
+            .filter(skill_document_fields::Column::VersionId.eq(to.id))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|f| f.field_name)
+            .collect();
+
+        for field in source_fields {
+            if !target_fields.contains(&field.field_name) {
+                result.skipped += 1;
+                continue;
+            }
+
+            if mapped.contains(&field.field_name) {
+                if !overwrite {
+                    result.skipped += 1;
+                    continue;
+                }
+                skill_document_fields::Entity::delete_many()
+                    .filter(skill_document_fields::Column::VersionId.eq(to.id))
+                    .filter(skill_document_fields::Column::FieldName.eq(&field.field_name))
+                    .exec(conn)
+                    .await?;
+            }
+
+            skill_document_fields::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                version_id: Set(to.id),
+                skill_id: Set(field.skill_id),
+                level: Set(to.level.clone()),
+                field_name: Set(field.field_name),
+                content: Set(field.content),
+                created_at: Set(now),
+                updated_at: Set(now),
+            }
+            .insert(conn)
+            .await?;
+            result.copied += 1;
+        }
+
+        Ok(result)
+    }
+
+    /// Garde la trace d'un document généré et de la version utilisée
+    pub async fn record_generation<C: ConnectionTrait>(
+        conn: &C,
+        person_id: Uuid,
+        version: &level_document_versions::Model,
+    ) -> AppResult<()> {
+        Self::record_generations(conn, &[person_id], version).await
+    }
+
+    /// Garde la trace d'une génération groupée en une seule requête
+    pub async fn record_generations<C: ConnectionTrait>(
+        conn: &C,
+        person_ids: &[Uuid],
+        version: &level_document_versions::Model,
+    ) -> AppResult<()> {
+        if person_ids.is_empty() {
+            return Ok(());
+        }
+
+        let now = Utc::now().naive_utc();
+        level_document_generations::Entity::insert_many(person_ids.iter().map(|&person_id| {
+            level_document_generations::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                person_id: Set(person_id),
+                level: Set(version.level.clone()),
+                version_id: Set(version.id),
+                version: Set(version.version),
+                created_at: Set(now),
+            }
+        }))
+        .exec(conn)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use sea_orm::*;
+
+use crate::entities::diving_levels;
+use crate::entities::prelude::*;
+use crate::errors::AppResult;
+use crate::models::{DivingLevel, LevelCatalog};
+
+/// Accès au référentiel des niveaux (table diving_levels)
+pub struct LevelService;
+
+impl LevelService {
+    pub fn to_level(model: diving_levels::Model) -> DivingLevel {
+        DivingLevel {
+            code: model.code,
+            name: model.name,
+            family: model.family,
+            rank: model.rank,
+            parent_code: model.parent_code,
+            is_instructor: model.is_instructor,
+        }
+    }
+
+    /// Charge le catalogue des niveaux connus
+    pub async fn catalog<C: ConnectionTrait>(conn: &C) -> AppResult<LevelCatalog> {
+        let levels = DivingLevels::find()
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(Self::to_level)
+            .collect();
+
+        Ok(LevelCatalog::new(levels))
+    }
+}
@@ -0,0 +1,661 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use sea_orm::*;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::entities::prelude::*;
+use crate::entities::{
+    competency_domains, competency_modules, competency_skills, mentor_assignments, people,
+    questionnaires, sessions, skill_validation_history, skill_validations, sorties,
+    validation_stages,
+};
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    CreateMentorAssignmentRequest, DigestRunResponse, MenteeSession, MentorAssignmentResponse,
+    MentorAssignmentsQuery, MentorDashboard,
+};
+use crate::services::progress_notification::escape_html;
+use crate::services::{CohortProgressService, EmailService, LevelService, SkillValidationService};
+
+/// Module commencé sur lequel un élève ne progresse plus
+#[derive(Debug, Clone, PartialEq)]
+pub struct StalledModule {
+    pub name: String,
+    pub last_progress_at: NaiveDateTime,
+}
+
+/// Encadrants référents: suivi des élèves, inscriptions et alertes de stagnation
+pub struct MentorService;
+
+impl MentorService {
+    // ------------------------------------------------------------------------
+    // Affectations
+    // ------------------------------------------------------------------------
+
+    pub async fn list(
+        db: &DatabaseConnection,
+        query: MentorAssignmentsQuery,
+    ) -> AppResult<Vec<MentorAssignmentResponse>> {
+        let mut select = MentorAssignments::find()
+            .order_by_asc(mentor_assignments::Column::DivingLevel)
+            .order_by_asc(mentor_assignments::Column::CreatedAt);
+        if let Some(student_id) = query.student_id {
+            select = select.filter(mentor_assignments::Column::StudentId.eq(student_id));
+        }
+        if let Some(mentor_id) = query.mentor_id {
+            select = select.filter(mentor_assignments::Column::MentorId.eq(mentor_id));
+        }
+        if let Some(level) = query.diving_level {
+            select = select.filter(mentor_assignments::Column::DivingLevel.eq(level));
+        }
+        let assignments = select.all(db).await?;
+
+        Self::responses(db, assignments).await
+    }
+
+    /// Désigne le référent d'un élève pour un niveau (un seul par niveau)
+    pub async fn create(
+        db: &DatabaseConnection,
+        request: CreateMentorAssignmentRequest,
+    ) -> AppResult<MentorAssignmentResponse> {
+        let level = LevelService::catalog(db)
+            .await?
+            .parse(&request.diving_level)
+            .map(|l| l.code.clone())
+            .ok_or_else(|| {
+                AppError::Validation(format!("Niveau inconnu: {}", request.diving_level))
+            })?;
+        Self::check_people(db, request.student_id, request.mentor_id).await?;
+
+        let existing = MentorAssignments::find()
+            .filter(mentor_assignments::Column::StudentId.eq(request.student_id))
+            .filter(mentor_assignments::Column::DivingLevel.eq(&level))
+            .one(db)
+            .await?;
+        if existing.is_some() {
+            return Err(AppError::Validation(format!(
+                "L'élève a déjà un référent pour le niveau {}",
+                level
+            )));
+        }
+
+        let now = Utc::now().naive_utc();
+        let assignment = mentor_assignments::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            student_id: Set(request.student_id),
+            mentor_id: Set(request.mentor_id),
+            diving_level: Set(level),
+            stall_notified_at: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+        }
+        .insert(db)
+        .await?;
+
+        Self::response(db, assignment).await
+    }
+
+    /// Change de référent. Le nouveau référent sera prévenu d'une stagnation
+    /// en cours même si l'ancien l'a déjà été.
+    pub async fn update(
+        db: &DatabaseConnection,
+        id: Uuid,
+        mentor_id: Uuid,
+    ) -> AppResult<MentorAssignmentResponse> {
+        let assignment = MentorAssignments::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Référent non trouvé".to_string()))?;
+        Self::check_people(db, assignment.student_id, mentor_id).await?;
+
+        let mut active: mentor_assignments::ActiveModel = assignment.into();
+        active.mentor_id = Set(mentor_id);
+        active.stall_notified_at = Set(None);
+        active.updated_at = Set(Utc::now().naive_utc());
+        let assignment = active.update(db).await?;
+
+        Self::response(db, assignment).await
+    }
+
+    pub async fn delete(db: &DatabaseConnection, id: Uuid) -> AppResult<()> {
+        let result = MentorAssignments::delete_by_id(id).exec(db).await?;
+        if result.rows_affected == 0 {
+            return Err(AppError::NotFound("Référent non trouvé".to_string()));
+        }
+        Ok(())
+    }
+
+    async fn check_people(db: &DatabaseConnection, student_id: Uuid, mentor_id: Uuid) -> AppResult<()> {
+        if student_id == mentor_id {
+            return Err(AppError::Validation(
+                "Un élève ne peut pas être son propre référent".to_string(),
+            ));
+        }
+        People::find_by_id(student_id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Élève non trouvé".to_string()))?;
+        People::find_by_id(mentor_id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Référent non trouvé".to_string()))?;
+        Ok(())
+    }
+
+    async fn response(
+        db: &DatabaseConnection,
+        assignment: mentor_assignments::Model,
+    ) -> AppResult<MentorAssignmentResponse> {
+        Self::responses(db, vec![assignment])
+            .await?
+            .pop()
+            .ok_or_else(|| AppError::Internal("Référent introuvable".to_string()))
+    }
+
+    async fn responses(
+        db: &DatabaseConnection,
+        assignments: Vec<mentor_assignments::Model>,
+    ) -> AppResult<Vec<MentorAssignmentResponse>> {
+        let names = Self::names(
+            db,
+            assignments.iter().flat_map(|a| [a.student_id, a.mentor_id]),
+        )
+        .await?;
+
+        Ok(assignments
+            .into_iter()
+            .map(|a| MentorAssignmentResponse {
+                id: a.id,
+                student_id: a.student_id,
+                student_name: names.get(&a.student_id).cloned().unwrap_or_default(),
+                mentor_id: a.mentor_id,
+                mentor_name: names.get(&a.mentor_id).cloned().unwrap_or_default(),
+                diving_level: a.diving_level,
+                stall_notified_at: a
+                    .stall_notified_at
+                    .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string()),
+                created_at: a.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            })
+            .collect())
+    }
+
+    async fn names(
+        db: &DatabaseConnection,
+        ids: impl IntoIterator<Item = Uuid>,
+    ) -> AppResult<HashMap<Uuid, String>> {
+        Ok(People::find()
+            .filter(people::Column::Id.is_in(ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|p| (p.id, format!("{} {}", p.first_name, p.last_name)))
+            .collect())
+    }
+
+    // ------------------------------------------------------------------------
+    // Tableau de bord du référent
+    // ------------------------------------------------------------------------
+
+    /// Progression des élèves suivis, par niveau, et leurs inscriptions à
+    /// venir. Un élève sans progression compte comme inactif à partir de la
+    /// date où le référent lui a été affecté.
+    pub async fn dashboard(
+        db: &DatabaseConnection,
+        mentor_id: Uuid,
+        inactive_weeks: i64,
+    ) -> AppResult<MentorDashboard> {
+        let assignments = MentorAssignments::find()
+            .filter(mentor_assignments::Column::MentorId.eq(mentor_id))
+            .order_by_asc(mentor_assignments::Column::DivingLevel)
+            .all(db)
+            .await?;
+
+        let mut by_level: Vec<(String, HashMap<Uuid, NaiveDateTime>)> = Vec::new();
+        for assignment in &assignments {
+            match by_level.iter_mut().find(|(level, _)| *level == assignment.diving_level) {
+                Some((_, since)) => {
+                    since.insert(assignment.student_id, assignment.created_at);
+                }
+                None => by_level.push((
+                    assignment.diving_level.clone(),
+                    HashMap::from([(assignment.student_id, assignment.created_at)]),
+                )),
+            }
+        }
+
+        let mut levels = Vec::with_capacity(by_level.len());
+        for (level, since) in by_level {
+            levels.push(CohortProgressService::build_for(db, &level, inactive_weeks, since).await?);
+        }
+
+        let student_ids: HashSet<Uuid> = assignments.iter().map(|a| a.student_id).collect();
+        let upcoming_sessions = Self::upcoming_sessions(db, &student_ids).await?;
+
+        Ok(MentorDashboard {
+            levels,
+            upcoming_sessions,
+        })
+    }
+
+    /// Sessions et sorties à venir auxquelles les élèves sont inscrits
+    async fn upcoming_sessions(
+        db: &DatabaseConnection,
+        student_ids: &HashSet<Uuid>,
+    ) -> AppResult<Vec<MenteeSession>> {
+        let today = Utc::now().naive_utc().date();
+        let registrations = Questionnaires::find()
+            .filter(questionnaires::Column::PersonId.is_in(student_ids.iter().copied()))
+            .all(db)
+            .await?;
+
+        let sessions: HashMap<Uuid, sessions::Model> = Sessions::find()
+            .filter(sessions::Column::Id.is_in(registrations.iter().filter_map(|q| q.session_id)))
+            .filter(sessions::Column::StartDate.gte(today))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|s| (s.id, s))
+            .collect();
+        let sorties: HashMap<Uuid, sorties::Model> = Sorties::find()
+            .filter(sorties::Column::Id.is_in(registrations.iter().filter_map(|q| q.sortie_id)))
+            .filter(sorties::Column::EndDate.gte(today))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|s| (s.id, s))
+            .collect();
+        let names = Self::names(db, student_ids.iter().copied()).await?;
+
+        let mut upcoming: Vec<MenteeSession> = registrations
+            .into_iter()
+            .filter_map(|q| {
+                let person_name = names.get(&q.person_id).cloned().unwrap_or_default();
+                if let Some(sortie) = q.sortie_id.and_then(|id| sorties.get(&id)) {
+                    Some(MenteeSession {
+                        person_id: q.person_id,
+                        person_name,
+                        session_id: None,
+                        sortie_id: Some(sortie.id),
+                        name: sortie.name.clone(),
+                        start_date: sortie.start_date.format("%Y-%m-%d").to_string(),
+                        location: Some(sortie.location.clone()),
+                    })
+                } else {
+                    q.session_id.and_then(|id| sessions.get(&id)).map(|session| MenteeSession {
+                        person_id: q.person_id,
+                        person_name,
+                        session_id: Some(session.id),
+                        sortie_id: None,
+                        name: session.name.clone(),
+                        start_date: session.start_date.format("%Y-%m-%d").to_string(),
+                        location: session.location.clone(),
+                    })
+                }
+            })
+            .collect();
+        upcoming.sort_by(|a, b| {
+            (&a.start_date, &a.person_name).cmp(&(&b.start_date, &b.person_name))
+        });
+
+        Ok(upcoming)
+    }
+
+    // ------------------------------------------------------------------------
+    // Notifications
+    // ------------------------------------------------------------------------
+
+    /// Prévient les référents d'un élève qui vient de s'inscrire à une
+    /// session ou une sortie. Les erreurs d'envoi sont seulement loggées.
+    pub async fn notify_registration(
+        db: &DatabaseConnection,
+        email_service: &EmailService,
+        student_id: Uuid,
+        session_id: Option<Uuid>,
+        sortie_id: Option<Uuid>,
+    ) -> AppResult<()> {
+        let mentor_ids: HashSet<Uuid> = MentorAssignments::find()
+            .filter(mentor_assignments::Column::StudentId.eq(student_id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|a| a.mentor_id)
+            .collect();
+        if mentor_ids.is_empty() {
+            return Ok(());
+        }
+
+        let event = match (sortie_id, session_id) {
+            (Some(sortie_id), _) => Sorties::find_by_id(sortie_id)
+                .one(db)
+                .await?
+                .map(|s| (s.name, s.start_date)),
+            (None, Some(session_id)) => Sessions::find_by_id(session_id)
+                .one(db)
+                .await?
+                .map(|s| (s.name, s.start_date)),
+            (None, None) => None,
+        };
+        let Some((event_name, start_date)) = event else {
+            return Ok(());
+        };
+        let Some(student) = People::find_by_id(student_id).one(db).await? else {
+            return Ok(());
+        };
+        let student_name = format!("{} {}", student.first_name, student.last_name);
+
+        let subject = format!("Inscription de {} à {}", student_name, event_name);
+        for mentor in People::find()
+            .filter(people::Column::Id.is_in(mentor_ids))
+            .all(db)
+            .await?
+        {
+            let body = format!(
+                "<p>Bonjour {},</p>\
+                 <p><strong>{}</strong>, dont tu es le référent, s'est inscrit à \
+                 <strong>{}</strong> le {}.</p>",
+                escape_html(&mentor.first_name),
+                escape_html(&student_name),
+                escape_html(&event_name),
+                start_date.format("%d/%m/%Y"),
+            );
+            let to_name = format!("{} {}", mentor.first_name, mentor.last_name);
+            if let Err(e) = email_service
+                .send_email(&mentor.email, &to_name, &subject, &body)
+                .await
+            {
+                tracing::warn!("Failed to notify mentor {}: {}", mentor.email, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Envoie à chaque référent la liste de ses élèves bloqués sur un module
+    /// commencé depuis `stall_weeks` semaines. Un échec d'envoi est retenté
+    /// au passage suivant.
+    pub async fn send_stall_alerts(
+        db: &DatabaseConnection,
+        email_service: &EmailService,
+        now: NaiveDateTime,
+        stall_weeks: i64,
+    ) -> AppResult<DigestRunResponse> {
+        let assignments = MentorAssignments::find().all(db).await?;
+        let threshold = now - Duration::weeks(stall_weeks);
+
+        // Alertes à envoyer, regroupées par référent
+        let mut alerts: HashMap<Uuid, Vec<(mentor_assignments::Model, Vec<StalledModule>)>> =
+            HashMap::new();
+        let levels: HashSet<String> = assignments.iter().map(|a| a.diving_level.clone()).collect();
+        for level in levels {
+            let level_assignments: Vec<&mentor_assignments::Model> =
+                assignments.iter().filter(|a| a.diving_level == level).collect();
+            let stalled = Self::stalled_modules_by_student(
+                db,
+                &level,
+                level_assignments.iter().map(|a| a.student_id).collect(),
+                threshold,
+            )
+            .await?;
+
+            for assignment in level_assignments {
+                let modules = stalled.get(&assignment.student_id).cloned().unwrap_or_default();
+                if Self::should_alert(&modules, assignment.stall_notified_at) {
+                    alerts
+                        .entry(assignment.mentor_id)
+                        .or_default()
+                        .push((assignment.clone(), modules));
+                }
+            }
+        }
+
+        let mut run = DigestRunResponse::default();
+        if alerts.is_empty() {
+            return Ok(run);
+        }
+        let names = Self::names(
+            db,
+            alerts.values().flatten().map(|(a, _)| a.student_id),
+        )
+        .await?;
+
+        for (mentor_id, students) in alerts {
+            let Some(mentor) = People::find_by_id(mentor_id).one(db).await? else {
+                continue;
+            };
+            let subject = if students.len() == 1 {
+                "Un de tes élèves ne progresse plus".to_string()
+            } else {
+                format!("{} de tes élèves ne progressent plus", students.len())
+            };
+            let body = Self::stall_body(&mentor.first_name, stall_weeks, &students, &names);
+            let to_name = format!("{} {}", mentor.first_name, mentor.last_name);
+
+            if let Err(e) = email_service
+                .send_email(&mentor.email, &to_name, &subject, &body)
+                .await
+            {
+                tracing::warn!("Failed to send stall alert to {}: {}", mentor.email, e);
+                run.failed += 1;
+                continue;
+            }
+            run.sent += 1;
+
+            for (assignment, _) in students {
+                let mut active: mentor_assignments::ActiveModel = assignment.into();
+                active.stall_notified_at = Set(Some(now));
+                active.update(db).await?;
+            }
+        }
+
+        Ok(run)
+    }
+
+    /// Modules commencés mais incomplets sans progression depuis `threshold`,
+    /// par élève
+    async fn stalled_modules_by_student(
+        db: &DatabaseConnection,
+        level: &str,
+        student_ids: Vec<Uuid>,
+        threshold: NaiveDateTime,
+    ) -> AppResult<HashMap<Uuid, Vec<StalledModule>>> {
+        let domain_ids: Vec<Uuid> = CompetencyDomains::find()
+            .filter(competency_domains::Column::DivingLevel.eq(level))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|d| d.id)
+            .collect();
+        let modules = CompetencyModules::find()
+            .filter(competency_modules::Column::DomainId.is_in(domain_ids))
+            .order_by_asc(competency_modules::Column::SortOrder)
+            .all(db)
+            .await?;
+        let mut module_skills: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for skill in CompetencySkills::find()
+            .filter(competency_skills::Column::ModuleId.is_in(modules.iter().map(|m| m.id)))
+            .all(db)
+            .await?
+        {
+            module_skills.entry(skill.module_id).or_default().push(skill.id);
+        }
+        let skill_ids: Vec<Uuid> = module_skills.values().flatten().copied().collect();
+
+        let final_stages: HashSet<Uuid> = ValidationStages::find()
+            .filter(validation_stages::Column::IsFinal.eq(true))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        let validations = SkillValidations::find()
+            .filter(skill_validations::Column::PersonId.is_in(student_ids.clone()))
+            .filter(skill_validations::Column::SkillId.is_in(skill_ids.clone()))
+            .all(db)
+            .await?;
+        let history = SkillValidationHistory::find()
+            .filter(skill_validation_history::Column::PersonId.is_in(student_ids.clone()))
+            .filter(skill_validation_history::Column::SkillId.is_in(skill_ids.clone()))
+            .all(db)
+            .await?;
+
+        // Acquis validés (hors validations expirées) et dernière progression
+        // par (élève, acquis)
+        let periods = SkillValidationService::validity_periods(db, skill_ids).await?;
+        let today = Utc::now().naive_utc().date();
+        let validated: HashSet<(Uuid, Uuid)> = validations
+            .iter()
+            .filter(|v| final_stages.contains(&v.stage_id))
+            .filter(|v| !SkillValidationService::is_validation_expired(v, &periods, today))
+            .map(|v| (v.person_id, v.skill_id))
+            .collect();
+        let mut last_progress: HashMap<(Uuid, Uuid), NaiveDateTime> = HashMap::new();
+        for (key, at) in history
+            .iter()
+            .map(|h| ((h.person_id, h.skill_id), h.created_at))
+            .chain(validations.iter().map(|v| ((v.person_id, v.skill_id), v.updated_at)))
+        {
+            let entry = last_progress.entry(key).or_insert(at);
+            if at > *entry {
+                *entry = at;
+            }
+        }
+
+        Ok(student_ids
+            .into_iter()
+            .map(|student_id| {
+                let stalled = modules
+                    .iter()
+                    .filter_map(|module| {
+                        let skills = module_skills.get(&module.id)?;
+                        Self::module_stall(
+                            skills.iter().map(|skill_id| {
+                                let key = (student_id, *skill_id);
+                                (validated.contains(&key), last_progress.get(&key).copied())
+                            }),
+                            threshold,
+                        )
+                        .map(|last_progress_at| StalledModule {
+                            name: module.name.clone(),
+                            last_progress_at,
+                        })
+                    })
+                    .collect();
+                (student_id, stalled)
+            })
+            .collect())
+    }
+
+    /// Dernière progression d'un module commencé, incomplet et sans
+    /// progression depuis `threshold`. Chaque acquis est décrit par
+    /// (validé, dernière progression).
+    fn module_stall(
+        skills: impl Iterator<Item = (bool, Option<NaiveDateTime>)>,
+        threshold: NaiveDateTime,
+    ) -> Option<NaiveDateTime> {
+        let mut complete = true;
+        let mut last: Option<NaiveDateTime> = None;
+        for (validated, progress) in skills {
+            complete &= validated;
+            last = last.max(progress);
+        }
+        last.filter(|last| !complete && *last < threshold)
+    }
+
+    /// Une alerte est due si l'élève stagne et a progressé depuis la
+    /// précédente alerte (sinon le référent est déjà au courant)
+    fn should_alert(stalled: &[StalledModule], notified_at: Option<NaiveDateTime>) -> bool {
+        match notified_at {
+            None => !stalled.is_empty(),
+            Some(notified_at) => stalled.iter().any(|m| m.last_progress_at > notified_at),
+        }
+    }
+
+    fn stall_body(
+        first_name: &str,
+        stall_weeks: i64,
+        students: &[(mentor_assignments::Model, Vec<StalledModule>)],
+        names: &HashMap<Uuid, String>,
+    ) -> String {
+        let rows: String = students
+            .iter()
+            .flat_map(|(assignment, modules)| {
+                let name = names.get(&assignment.student_id).cloned().unwrap_or_default();
+                modules.iter().map(move |m| {
+                    format!(
+                        "<tr><td><strong>{}</strong></td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                        escape_html(&name),
+                        escape_html(&assignment.diving_level),
+                        escape_html(&m.name),
+                        m.last_progress_at.format("%d/%m/%Y"),
+                    )
+                })
+            })
+            .collect();
+
+        format!(
+            "<p>Bonjour {},</p>\
+             <p>Ces élèves dont tu es le référent n'ont pas progressé depuis au moins \
+             {} semaines sur un module commencé:</p>\
+             <table border=\"1\" cellpadding=\"4\" cellspacing=\"0\">\
+             <tr><th>Élève</th><th>Niveau</th><th>Module</th><th>Dernière progression</th></tr>\
+             {}</table>",
+            escape_html(first_name),
+            stall_weeks,
+            rows,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn day(d: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 5, d)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn module_stall_requires_started_incomplete_and_old_progress() {
+        let threshold = day(15);
+
+        // Jamais commencé: pas de stagnation
+        assert_eq!(
+            MentorService::module_stall([(false, None), (false, None)].into_iter(), threshold),
+            None
+        );
+        // Commencé puis abandonné
+        assert_eq!(
+            MentorService::module_stall([(true, Some(day(2))), (false, Some(day(5)))].into_iter(), threshold),
+            Some(day(5))
+        );
+        // Progression récente
+        assert_eq!(
+            MentorService::module_stall([(false, Some(day(2))), (false, Some(day(20)))].into_iter(), threshold),
+            None
+        );
+        // Module complet
+        assert_eq!(
+            MentorService::module_stall([(true, Some(day(2))), (true, Some(day(5)))].into_iter(), threshold),
+            None
+        );
+    }
+
+    #[test]
+    fn should_alert_once_per_stall() {
+        let stalled = vec![StalledModule {
+            name: "Remontées".to_string(),
+            last_progress_at: day(5),
+        }];
+
+        assert!(!MentorService::should_alert(&[], None));
+        assert!(MentorService::should_alert(&stalled, None));
+        // Déjà prévenu pour cette stagnation
+        assert!(!MentorService::should_alert(&stalled, Some(day(20))));
+        // L'élève a progressé depuis la dernière alerte puis stagne à nouveau
+        assert!(MentorService::should_alert(&stalled, Some(day(3))));
+    }
+}
@@ -4,6 +4,27 @@ pub mod import;
 pub mod auth;
 pub mod pdf_generator;
 pub mod fiche_securite;
+pub mod promotion;
+pub mod competency_framework;
+pub mod validation_history;
+pub mod skill_validation;
+pub mod legacy_competencies;
+pub mod certification;
+pub mod levels;
+pub mod equivalences;
+pub mod level_documents;
+pub mod position_detection;
+pub mod acroform;
+pub mod pdf_text;
+pub mod completion_certificate;
+pub mod cohort_progress;
+pub mod storage;
+pub mod validation_evidence;
+pub mod progress_notification;
+pub mod quiz;
+pub mod mentor;
+pub mod teaching_qualification;
+pub mod validation_request;
 
 pub use questionnaire::QuestionnaireService;
 pub use email::EmailService;
@@ -11,4 +32,22 @@ pub use import::ImportService;
 pub use auth::AuthService;
 pub use pdf_generator::PdfGenerator;
 pub use fiche_securite::{generate_fiche_securite, FicheSecuriteOptions};
+pub use promotion::PromotionService;
+pub use competency_framework::CompetencyFrameworkService;
+pub use validation_history::ValidationHistoryService;
+pub use skill_validation::{SkillValidationService, ValidationSource};
+pub use legacy_competencies::LegacyCompetencyService;
+pub use certification::CertificationService;
+pub use levels::LevelService;
+pub use equivalences::EquivalenceService;
+pub use level_documents::{LevelDocumentService, NewTemplate};
+pub use completion_certificate::CertificateService;
+pub use cohort_progress::CohortProgressService;
+pub use storage::Storage;
+pub use validation_evidence::ValidationEvidenceService;
+pub use progress_notification::ProgressNotificationService;
+pub use quiz::QuizService;
+pub use mentor::MentorService;
+pub use teaching_qualification::TeachingQualificationService;
+pub use validation_request::ValidationRequestService;
 
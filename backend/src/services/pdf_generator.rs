@@ -1,14 +1,17 @@
+use chrono::Utc;
 use lopdf::{Document, Object, Dictionary, ObjectId, Stream};
 use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
 use uuid::Uuid;
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
 
 use crate::entities::{
-    level_documents, skill_document_positions, skill_validations,
+    level_document_versions, skill_document_fields, skill_document_positions, skill_validations,
     validation_stages, people,
 };
 use crate::errors::AppError;
-use crate::models::diving_level::DivingLevel;
+use crate::services::pdf_text::{FontStyle, TextLayer};
+use crate::services::{acroform, CertificationService, LevelService, SkillValidationService};
 
 pub struct PdfGenerator;
 
@@ -24,38 +27,67 @@ struct TextPosition {
     font_size: f32,
 }
 
+/// Validateur affiché dans les cases du document
+struct ValidatorInfo {
+    name: String,
+    level: String,
+}
+
 impl PdfGenerator {
-    /// Génère un PDF rempli avec les validations d'une personne
+    /// Génère un PDF rempli avec les validations d'une personne, à partir
+    /// d'une version du modèle du niveau et des positions ou champs de
+    /// formulaire de cette version. `flatten` aplatit le formulaire.
     pub async fn generate_filled_document(
         db: &DatabaseConnection,
         person_id: Uuid,
-        level: &str,
+        template: &level_document_versions::Model,
+        flatten: bool,
     ) -> Result<Vec<u8>, AppError> {
-        // Récupérer la personne
-        let _person = people::Entity::find_by_id(person_id)
-            .one(db)
-            .await?
-            .ok_or_else(|| AppError::NotFound("Person not found".to_string()))?;
-        
-        // Récupérer le document template
-        let doc_entity = level_documents::Entity::find()
-            .filter(level_documents::Column::Level.eq(level))
-            .one(db)
+        let mut documents = Self::generate_filled_documents(db, &[person_id], template, flatten).await?;
+        Ok(documents.remove(0).1)
+    }
+    
+    /// Génère les PDF remplis de plusieurs personnes, dans l'ordre demandé.
+    /// Le nombre de requêtes ne dépend ni du nombre de personnes ni du
+    /// nombre de validateurs, et le modèle n'est analysé qu'une fois.
+    pub async fn generate_filled_documents(
+        db: &DatabaseConnection,
+        person_ids: &[Uuid],
+        template: &level_document_versions::Model,
+        flatten: bool,
+    ) -> Result<Vec<(people::Model, Vec<u8>)>, AppError> {
+        // Récupérer les personnes
+        let mut persons: HashMap<Uuid, people::Model> = people::Entity::find()
+            .filter(people::Column::Id.is_in(person_ids.iter().copied()))
+            .all(db)
             .await?
-            .ok_or_else(|| AppError::NotFound(format!("No document template for level {}", level)))?;
+            .into_iter()
+            .map(|p| (p.id, p))
+            .collect();
+        if person_ids.iter().any(|id| !persons.contains_key(id)) {
+            return Err(AppError::NotFound("Person not found".to_string()));
+        }
         
         // Charger le PDF
-        let mut doc = Document::load_mem(&doc_entity.file_data)
+        let template_doc = Document::load_mem(&template.file_data)
             .map_err(|e| AppError::Internal(format!("Failed to load PDF: {}", e)))?;
         
+        let catalog = LevelService::catalog(db).await?;
+
         // Récupérer les positions des skills
         let positions = skill_document_positions::Entity::find()
-            .filter(skill_document_positions::Column::Level.eq(level))
+            .filter(skill_document_positions::Column::VersionId.eq(template.id))
+            .all(db)
+            .await?;
+        
+        // Récupérer les champs de formulaire associés aux skills
+        let fields = skill_document_fields::Entity::find()
+            .filter(skill_document_fields::Column::VersionId.eq(template.id))
             .all(db)
             .await?;
         
         // Récupérer les étapes "validé en mer" (is_final = true) pour n'afficher que ces validations dans le PDF
-        let final_stage_ids: std::collections::HashSet<Uuid> = validation_stages::Entity::find()
+        let final_stage_ids: Vec<Uuid> = validation_stages::Entity::find()
             .filter(validation_stages::Column::IsFinal.eq(true))
             .all(db)
             .await?
@@ -63,93 +95,293 @@ impl PdfGenerator {
             .map(|s| s.id)
             .collect();
         
-        // Récupérer les validations de la personne (uniquement celles validées en mer)
+        // Récupérer les validations des personnes (uniquement celles validées en mer
+        // et toujours valides: les acquis à revalider restent vides)
         let validations = skill_validations::Entity::find()
-            .filter(skill_validations::Column::PersonId.eq(person_id))
+            .filter(skill_validations::Column::PersonId.is_in(person_ids.iter().copied()))
+            .filter(skill_validations::Column::StageId.is_in(final_stage_ids))
             .all(db)
             .await?;
-        
-        let validations_validées_mer: Vec<_> = validations
+        let periods =
+            SkillValidationService::validity_periods(db, validations.iter().map(|v| v.skill_id)).await?;
+        let today = Utc::now().naive_utc().date();
+        let validations: Vec<skill_validations::Model> = validations
             .into_iter()
-            .filter(|v| final_stage_ids.contains(&v.stage_id))
+            .filter(|v| !SkillValidationService::is_validation_expired(v, &periods, today))
             .collect();
         
-        // Créer un map des validations par skill_id
-        let validation_map: std::collections::HashMap<Uuid, _> = validations_validées_mer
+        // Récupérer les validateurs avec leur niveau le plus élevé
+        let validator_ids: HashSet<Uuid> = validations.iter().map(|v| v.validated_by_id).collect();
+        let validator_levels = CertificationService::diving_levels(db, validator_ids.iter().copied()).await?;
+        let validators: HashMap<Uuid, ValidatorInfo> = people::Entity::find()
+            .filter(people::Column::Id.is_in(validator_ids))
+            .all(db)
+            .await?
             .into_iter()
-            .map(|v| (v.skill_id, v))
+            .map(|validator| {
+                let level = validator_levels
+                    .get(&validator.id)
+                    .and_then(|levels| catalog.highest_level(levels))
+                    .map(|l| l.code.clone())
+                    .unwrap_or_default();
+                let name = format!("{} {}", validator.first_name, validator.last_name);
+                (validator.id, ValidatorInfo { name, level })
+            })
             .collect();
         
-        // Grouper les textes par page
-        let mut texts_by_page: std::collections::HashMap<u32, Vec<TextPosition>> = 
-            std::collections::HashMap::new();
+        // Créer un map des validations par personne puis par skill_id
+        let mut validations_by_person: HashMap<Uuid, HashMap<Uuid, skill_validations::Model>> =
+            HashMap::new();
+        for validation in validations {
+            validations_by_person
+                .entry(validation.person_id)
+                .or_default()
+                .insert(validation.skill_id, validation);
+        }
         
-        for position in positions {
-            if let Some(validation) = validation_map.get(&position.skill_id) {
-                // Récupérer le validateur avec son niveau le plus élevé
-                let (validator_name, validator_level) = if let Some(validator) = people::Entity::find_by_id(validation.validated_by_id)
-                    .one(db)
-                    .await?
-                {
-                    let name = format!("{} {}", validator.first_name, validator.last_name);
-                    let level = validator.diving_level
-                        .as_ref()
-                        .map(|levels| get_highest_level(levels))
-                        .unwrap_or_default();
-                    (name, level)
-                } else {
-                    ("?".to_string(), String::new())
-                };
-                
-                // Formater la date
-                let date_str = validation.validated_at.format("%d/%m/%Y").to_string();
-                
-                // Déterminer si on peut afficher sur 2 lignes
-                let can_use_two_lines = position.height >= position.font_size * 2.5;
-                
-                let (line1, line2) = if can_use_two_lines {
-                    let second_line = if validator_level.is_empty() {
-                        validator_name
-                    } else {
-                        format!("{} ({})", validator_name, validator_level)
-                    };
-                    (date_str, Some(second_line))
-                } else {
-                    (format!("{} - {}", date_str, validator_name), None)
-                };
-                
-                texts_by_page
-                    .entry(position.page as u32)
-                    .or_default()
-                    .push(TextPosition {
-                        x: position.x,
-                        y: position.y,
-                        width: position.width,
-                        height: position.height,
-                        line1,
-                        line2,
-                        font_size: position.font_size,
-                    });
+        let mut documents = Vec::with_capacity(person_ids.len());
+        for person_id in person_ids {
+            let Some(person) = persons.remove(person_id) else {
+                // Personne demandée deux fois
+                continue;
+            };
+            let empty = HashMap::new();
+            let validation_map = validations_by_person.get(person_id).unwrap_or(&empty);
+            let mut doc = template_doc.clone();
+            fill_form_fields(&mut doc, &fields, validation_map, &validators, flatten)?;
+            let pdf = fill_document(doc, &positions, validation_map, &validators)?;
+            documents.push((person, pdf));
+        }
+        
+        Ok(documents)
+    }
+    
+    /// Assemble plusieurs PDF en un seul, avec un signet par document
+    /// pointant sur sa première page
+    pub fn merge_documents(documents: &[(String, Vec<u8>)]) -> Result<Vec<u8>, AppError> {
+        let mut merged = Document::with_version("1.5");
+        let pages_id = merged.new_object_id();
+        let outlines_id = merged.new_object_id();
+        
+        let mut kids: Vec<Object> = Vec::new();
+        let mut bookmarks: Vec<(String, ObjectId)> = Vec::new();
+        
+        for (title, data) in documents {
+            let mut doc = Document::load_mem(data)
+                .map_err(|e| AppError::Internal(format!("Failed to load PDF: {}", e)))?;
+            doc.renumber_objects_with(merged.max_id + 1);
+            
+            let pages: Vec<ObjectId> = doc.get_pages().into_values().collect();
+            for &page_id in &pages {
+                let inherited = inherited_page_attributes(&doc, page_id);
+                if let Ok(Object::Dictionary(page)) = doc.get_object_mut(page_id) {
+                    for (key, value) in inherited {
+                        page.set(key, value);
+                    }
+                    page.set("Parent", Object::Reference(pages_id));
+                }
+                kids.push(Object::Reference(page_id));
+            }
+            if let Some(&first_page) = pages.first() {
+                bookmarks.push((title.clone(), first_page));
             }
+            
+            // Les catalogues et arbres de pages d'origine sont remplacés
+            merged.max_id = merged.max_id.max(doc.max_id);
+            merged.objects.extend(doc.objects.into_iter().filter(|(_, object)| {
+                !matches!(
+                    object.as_dict().and_then(|d| d.get(b"Type")).and_then(Object::as_name),
+                    Ok(b"Catalog") | Ok(b"Pages")
+                )
+            }));
         }
         
-        // Ajouter les annotations à chaque page
-        for (page_num, texts) in texts_by_page {
-            add_freetext_annotations(&mut doc, page_num, &texts)?;
+        let count = kids.len() as i64;
+        merged.objects.insert(pages_id, Object::Dictionary(Dictionary::from_iter(vec![
+            ("Type", Object::Name(b"Pages".to_vec())),
+            ("Kids", Object::Array(kids)),
+            ("Count", Object::Integer(count)),
+        ])));
+        
+        // Signets: une entrée par document, chaînée par Prev/Next
+        let item_ids: Vec<ObjectId> = bookmarks.iter().map(|_| merged.new_object_id()).collect();
+        for (i, (title, page_id)) in bookmarks.iter().enumerate() {
+            let mut item = Dictionary::from_iter(vec![
+                ("Title", lopdf::text_string(title)),
+                ("Parent", Object::Reference(outlines_id)),
+                ("Dest", Object::Array(vec![Object::Reference(*page_id), Object::Name(b"Fit".to_vec())])),
+            ]);
+            if i > 0 {
+                item.set("Prev", Object::Reference(item_ids[i - 1]));
+            }
+            if let Some(next) = item_ids.get(i + 1) {
+                item.set("Next", Object::Reference(*next));
+            }
+            merged.objects.insert(item_ids[i], Object::Dictionary(item));
         }
+        let mut outlines = Dictionary::from_iter(vec![
+            ("Type", Object::Name(b"Outlines".to_vec())),
+            ("Count", Object::Integer(item_ids.len() as i64)),
+        ]);
+        if let (Some(first), Some(last)) = (item_ids.first(), item_ids.last()) {
+            outlines.set("First", Object::Reference(*first));
+            outlines.set("Last", Object::Reference(*last));
+        }
+        merged.objects.insert(outlines_id, Object::Dictionary(outlines));
+        
+        let catalog_id = merged.add_object(Dictionary::from_iter(vec![
+            ("Type", Object::Name(b"Catalog".to_vec())),
+            ("Pages", Object::Reference(pages_id)),
+            ("Outlines", Object::Reference(outlines_id)),
+            ("PageMode", Object::Name(b"UseOutlines".to_vec())),
+        ]));
+        merged.trailer.set("Root", Object::Reference(catalog_id));
+        merged.prune_objects();
         
-        // Sauvegarder le PDF modifié
         let mut output = Vec::new();
-        doc.save_to(&mut output)
+        merged.save_to(&mut output)
             .map_err(|e| AppError::Internal(format!("Failed to save PDF: {}", e)))?;
         
         Ok(output)
     }
 }
 
+/// Date, nom et niveau du validateur affichés pour une validation
+fn validation_texts(
+    validation: &skill_validations::Model,
+    validators: &HashMap<Uuid, ValidatorInfo>,
+) -> (String, String, String) {
+    let (validator_name, validator_level) = match validators.get(&validation.validated_by_id) {
+        Some(validator) => (validator.name.clone(), validator.level.clone()),
+        None => ("?".to_string(), String::new()),
+    };
+    
+    // Formater la date
+    let date_str = validation.validated_at.format("%d/%m/%Y").to_string();
+    (date_str, validator_name, validator_level)
+}
+
+/// Renseigne les champs AcroForm associés aux skills validés, puis aplatit
+/// le formulaire si demandé
+fn fill_form_fields(
+    doc: &mut Document,
+    fields: &[skill_document_fields::Model],
+    validation_map: &HashMap<Uuid, skill_validations::Model>,
+    validators: &HashMap<Uuid, ValidatorInfo>,
+    flatten: bool,
+) -> Result<(), AppError> {
+    let mut values = HashMap::new();
+    for field in fields {
+        let Some(validation) = validation_map.get(&field.skill_id) else {
+            continue;
+        };
+        let (date_str, validator_name, validator_level) = validation_texts(validation, validators);
+        let value = match field.content.as_str() {
+            skill_document_fields::CONTENT_DATE => date_str,
+            skill_document_fields::CONTENT_VALIDATOR => validator_name,
+            skill_document_fields::CONTENT_LEVEL => validator_level,
+            _ if validator_level.is_empty() => format!("{} - {}", date_str, validator_name),
+            _ => format!("{} - {} ({})", date_str, validator_name, validator_level),
+        };
+        values.insert(field.field_name.clone(), value);
+    }
+    
+    acroform::fill_fields(doc, &values)?;
+    if flatten {
+        acroform::flatten(doc)?;
+    }
+    Ok(())
+}
+
+/// Place les validations d'une personne dans une copie du modèle
+fn fill_document(
+    mut doc: Document,
+    positions: &[skill_document_positions::Model],
+    validation_map: &HashMap<Uuid, skill_validations::Model>,
+    validators: &HashMap<Uuid, ValidatorInfo>,
+) -> Result<Vec<u8>, AppError> {
+    // Grouper les textes par page
+    let mut texts_by_page: HashMap<u32, Vec<TextPosition>> = HashMap::new();
+    
+    for position in positions {
+        if let Some(validation) = validation_map.get(&position.skill_id) {
+            let (date_str, validator_name, validator_level) = validation_texts(validation, validators);
+            
+            // Déterminer si on peut afficher sur 2 lignes
+            let can_use_two_lines = position.height >= position.font_size * 2.5;
+            
+            let (line1, line2) = if can_use_two_lines {
+                let second_line = if validator_level.is_empty() {
+                    validator_name
+                } else {
+                    format!("{} ({})", validator_name, validator_level)
+                };
+                (date_str, Some(second_line))
+            } else {
+                (format!("{} - {}", date_str, validator_name), None)
+            };
+            
+            texts_by_page
+                .entry(position.page as u32)
+                .or_default()
+                .push(TextPosition {
+                    x: position.x,
+                    y: position.y,
+                    width: position.width,
+                    height: position.height,
+                    line1,
+                    line2,
+                    font_size: position.font_size,
+                });
+        }
+    }
+    
+    // Ajouter les annotations à chaque page
+    let mut text = TextLayer::new();
+    for (page_num, texts) in texts_by_page {
+        add_freetext_annotations(&mut doc, &mut text, page_num, &texts)?;
+    }
+    text.embed(&mut doc)?;
+    
+    // Sauvegarder le PDF modifié
+    let mut output = Vec::new();
+    doc.save_to(&mut output)
+        .map_err(|e| AppError::Internal(format!("Failed to save PDF: {}", e)))?;
+    
+    Ok(output)
+}
+
+/// Attributs hérités de l'arbre des pages, à recopier sur la page
+/// lorsqu'elle change de parent
+pub fn inherited_page_attributes(doc: &Document, page_id: ObjectId) -> Vec<(&'static str, Object)> {
+    let Ok(page) = doc.get_dictionary(page_id) else {
+        return Vec::new();
+    };
+    
+    let mut inherited = Vec::new();
+    for key in ["Resources", "MediaBox", "CropBox", "Rotate"] {
+        if page.has(key.as_bytes()) {
+            continue;
+        }
+        let mut parent = page.get(b"Parent").and_then(Object::as_reference).ok();
+        while let Some(parent_id) = parent {
+            let Ok(node) = doc.get_dictionary(parent_id) else {
+                break;
+            };
+            if let Ok(value) = node.get(key.as_bytes()) {
+                inherited.push((key, value.clone()));
+                break;
+            }
+            parent = node.get(b"Parent").and_then(Object::as_reference).ok();
+        }
+    }
+    inherited
+}
+
 /// Ajoute des annotations FreeText à une page PDF (ne modifie pas le contenu existant)
 fn add_freetext_annotations(
     doc: &mut Document,
+    text: &mut TextLayer,
     page_num: u32,
     texts: &[TextPosition],
 ) -> Result<(), AppError> {
@@ -157,14 +389,8 @@ fn add_freetext_annotations(
     let page_id = *pages.get(&page_num)
         .ok_or_else(|| AppError::Validation(format!("Page {} not found", page_num)))?;
     
-    // Créer une police Helvetica-Oblique avec encodage WinAnsi pour les accents
-    let font_dict = Dictionary::from_iter(vec![
-        ("Type", Object::Name(b"Font".to_vec())),
-        ("Subtype", Object::Name(b"Type1".to_vec())),
-        ("BaseFont", Object::Name(b"Helvetica-Oblique".to_vec())),
-        ("Encoding", Object::Name(b"WinAnsiEncoding".to_vec())),
-    ]);
-    let font_id = doc.add_object(font_dict);
+    // Police oblique embarquée, commune à toutes les pages
+    let font_id = text.font_id(doc, FontStyle::Oblique);
     
     // Créer les annotations
     let mut annotation_refs: Vec<Object> = Vec::new();
@@ -175,7 +401,7 @@ fn add_freetext_annotations(
         if let Some(ref line2) = pos.line2 {
             // Mode 2 lignes : une seule annotation avec appearance stream personnalisé
             let annot_id = create_styled_annotation(
-                doc,
+                doc, text,
                 pos.x, pos.y, pos.width, pos.height,
                 &pos.line1, Some(line2),
                 font_size, font_id,
@@ -184,7 +410,7 @@ fn add_freetext_annotations(
         } else {
             // Mode 1 ligne
             let annot_id = create_styled_annotation(
-                doc,
+                doc, text,
                 pos.x, pos.y, pos.width, pos.height,
                 &pos.line1, None,
                 font_size, font_id,
@@ -237,8 +463,10 @@ fn add_freetext_annotations(
 }
 
 /// Crée une annotation FreeText stylée avec coins arrondis
+#[allow(clippy::too_many_arguments)]
 fn create_styled_annotation(
     doc: &mut Document,
+    text: &mut TextLayer,
     x: f32, y: f32, width: f32, height: f32,
     line1: &str, line2: Option<&str>,
     font_size: f32, font_id: ObjectId,
@@ -292,14 +520,14 @@ fn create_styled_annotation(
         let text_y2 = text_y1 - line_spacing;
         
         writeln!(content, "{:.2} {:.2} Td", padding, text_y1).unwrap();
-        writeln!(content, "({}) Tj", escape_pdf_string(line1)).unwrap();
+        writeln!(content, "{} Tj", text.encode(FontStyle::Oblique, line1)).unwrap();
         writeln!(content, "{:.2} {:.2} Td", 0.0, text_y2 - text_y1).unwrap();
-        writeln!(content, "({}) Tj", escape_pdf_string(l2)).unwrap();
+        writeln!(content, "{} Tj", text.encode(FontStyle::Oblique, l2)).unwrap();
     } else {
         // 1 ligne centrée verticalement
         let text_y = (h - font_size) / 2.0 + font_size * 0.25;
         writeln!(content, "{:.2} {:.2} Td", padding, text_y).unwrap();
-        writeln!(content, "({}) Tj", escape_pdf_string(line1)).unwrap();
+        writeln!(content, "{} Tj", text.encode(FontStyle::Oblique, line1)).unwrap();
     }
     
     writeln!(content, "ET").unwrap();
@@ -351,8 +579,7 @@ fn create_styled_annotation(
 }
 
 /// Obtient les dimensions d'une page à partir du document
-#[allow(dead_code)]
-fn get_page_dimensions_from_doc(doc: &Document, page_id: ObjectId) -> Result<(f32, f32), AppError> {
+pub fn get_page_dimensions_from_doc(doc: &Document, page_id: ObjectId) -> Result<(f32, f32), AppError> {
     let page = doc.get_object(page_id)
         .map_err(|e| AppError::Internal(format!("Failed to get page: {}", e)))?
         .as_dict()
@@ -367,97 +594,12 @@ fn get_page_dimensions_from_doc(doc: &Document, page_id: ObjectId) -> Result<(f3
     if media_box.len() >= 4 {
         let width = media_box[2].as_float().unwrap_or(612.0);
         let height = media_box[3].as_float().unwrap_or(792.0);
-        Ok((width as f32, height as f32))
+        Ok((width, height))
     } else {
         Ok((612.0, 792.0))
     }
 }
 
-/// Convertit UTF-8 en WinAnsi et échappe pour PDF (avec octales pour accents)
-fn escape_pdf_string(s: &str) -> String {
-    let mut result = String::new();
-    for c in s.chars() {
-        let byte = utf8_to_winansi(c);
-        match byte {
-            b'\\' => result.push_str("\\\\"),
-            b'(' => result.push_str("\\("),
-            b')' => result.push_str("\\)"),
-            // Pour les bytes > 127, utiliser l'escape octale PDF
-            b if b > 127 => result.push_str(&format!("\\{:03o}", b)),
-            b => result.push(b as char),
-        }
-    }
-    result
-}
-
-/// Convertit un caractère UTF-8 en byte WinAnsi (Windows-1252)
-fn utf8_to_winansi(c: char) -> u8 {
-    match c {
-        // ASCII standard (0-127)
-        c if c.is_ascii() => c as u8,
-        // Caractères accentués français courants
-        'é' => 233,
-        'è' => 232,
-        'ê' => 234,
-        'ë' => 235,
-        'à' => 224,
-        'â' => 226,
-        'ä' => 228,
-        'ù' => 249,
-        'û' => 251,
-        'ü' => 252,
-        'î' => 238,
-        'ï' => 239,
-        'ô' => 244,
-        'ö' => 246,
-        'ç' => 231,
-        'É' => 201,
-        'È' => 200,
-        'Ê' => 202,
-        'Ë' => 203,
-        'À' => 192,
-        'Â' => 194,
-        'Ä' => 196,
-        'Ù' => 217,
-        'Û' => 219,
-        'Ü' => 220,
-        'Î' => 206,
-        'Ï' => 207,
-        'Ô' => 212,
-        'Ö' => 214,
-        'Ç' => 199,
-        'œ' => 156,
-        'Œ' => 140,
-        'æ' => 230,
-        'Æ' => 198,
-        'ñ' => 241,
-        'Ñ' => 209,
-        '°' => 176,
-        '€' => 128,
-        // Caractère non supporté -> ?
-        _ => b'?',
-    }
-}
-
-/// Extrait le niveau le plus élevé d'une chaîne de niveaux séparés par des virgules
-fn get_highest_level(levels_str: &str) -> String {
-    let mut highest: Option<DivingLevel> = None;
-    let mut highest_hierarchy: u8 = 0;
-    
-    for level_str in levels_str.split(',') {
-        let trimmed = level_str.trim();
-        if let Some(level) = DivingLevel::parse(trimmed) {
-            let h = level.hierarchy();
-            if h > highest_hierarchy {
-                highest_hierarchy = h;
-                highest = Some(level);
-            }
-        }
-    }
-    
-    highest.map(|l| l.to_string()).unwrap_or_default()
-}
-
 #[allow(dead_code)]
 /// Obtient les dimensions d'une page PDF
 pub fn get_page_dimensions(data: &[u8], page_num: usize) -> Result<(f32, f32), AppError> {
@@ -470,3 +612,67 @@ pub fn get_page_dimensions(data: &[u8], page_num: usize) -> Result<(f32, f32), A
     
     get_page_dimensions_from_doc(&doc, *page_id)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::dictionary;
+
+    /// PDF vide de `pages` pages, la MediaBox étant portée par l'arbre des pages
+    fn blank_pdf(pages: usize) -> Vec<u8> {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let kids: Vec<Object> = (0..pages)
+            .map(|_| {
+                doc.add_object(dictionary! { "Type" => "Page", "Parent" => pages_id })
+                    .into()
+            })
+            .collect();
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => pages as i64,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+
+        let mut output = Vec::new();
+        doc.save_to(&mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn test_merge_documents() {
+        let merged = PdfGenerator::merge_documents(&[
+            ("Dupont Léa".to_string(), blank_pdf(2)),
+            ("Martin Paul".to_string(), blank_pdf(1)),
+        ])
+        .unwrap();
+
+        let doc = Document::load_mem(&merged).unwrap();
+        let pages: Vec<ObjectId> = doc.get_pages().into_values().collect();
+        assert_eq!(pages.len(), 3);
+        // La MediaBox héritée est recopiée sur chaque page
+        for page_id in &pages {
+            assert!(doc.get_dictionary(*page_id).unwrap().has(b"MediaBox"));
+        }
+
+        let catalog = doc.catalog().unwrap();
+        let outlines_id = catalog.get(b"Outlines").and_then(Object::as_reference).unwrap();
+        let outlines = doc.get_dictionary(outlines_id).unwrap();
+        assert_eq!(outlines.get(b"Count").and_then(Object::as_i64).unwrap(), 2);
+
+        let first_id = outlines.get(b"First").and_then(Object::as_reference).unwrap();
+        let first = doc.get_dictionary(first_id).unwrap();
+        assert_eq!(lopdf::decode_text_string(first.get(b"Title").unwrap()).unwrap(), "Dupont Léa");
+        let next_id = first.get(b"Next").and_then(Object::as_reference).unwrap();
+        let second = doc.get_dictionary(next_id).unwrap();
+        // Le second signet pointe sur la première page du second livret
+        let dest = second.get(b"Dest").and_then(Object::as_array).unwrap();
+        assert_eq!(dest[0].as_reference().unwrap(), pages[2]);
+    }
+}
@@ -0,0 +1,277 @@
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId, Stream, StringFormat};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use crate::errors::AppError;
+
+const REGULAR: &[u8] = include_bytes!("../../assets/fonts/DejaVuSansCondensed.ttf");
+const BOLD: &[u8] = include_bytes!("../../assets/fonts/DejaVuSansCondensed-Bold.ttf");
+const OBLIQUE: &[u8] = include_bytes!("../../assets/fonts/DejaVuSansCondensed-Oblique.ttf");
+
+/// Caractère affiché quand la police n'a pas de glyphe
+const REPLACEMENT: char = '?';
+
+/// Graisse ou style de la police
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FontStyle {
+    Regular,
+    Bold,
+    Oblique,
+}
+
+impl FontStyle {
+    fn data(self) -> &'static [u8] {
+        match self {
+            FontStyle::Regular => REGULAR,
+            FontStyle::Bold => BOLD,
+            FontStyle::Oblique => OBLIQUE,
+        }
+    }
+
+    fn postscript_name(self) -> &'static str {
+        match self {
+            FontStyle::Regular => "DejaVuSansCondensed",
+            FontStyle::Bold => "DejaVuSansCondensed-Bold",
+            FontStyle::Oblique => "DejaVuSansCondensed-Oblique",
+        }
+    }
+
+    fn face(self) -> ttf_parser::Face<'static> {
+        ttf_parser::Face::parse(self.data(), 0).expect("bundled font must be valid")
+    }
+}
+
+/// Glyphe utilisé et texte qu'il représente (pour ToUnicode)
+#[derive(Default)]
+struct UsedFont {
+    id: Option<ObjectId>,
+    glyphs: BTreeMap<u16, char>,
+}
+
+/// Polices utilisées par un document en cours de génération.
+///
+/// `font_id` réserve l'objet de la police, `encode` produit les chaînes à
+/// passer à `Tj` et `embed` écrit les sous-ensembles une fois tout le texte
+/// encodé.
+#[derive(Default)]
+pub struct TextLayer {
+    fonts: HashMap<FontStyle, UsedFont>,
+}
+
+impl TextLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Objet de la police (réservé au premier appel), à référencer dans les
+    /// ressources des pages ou des apparences
+    pub fn font_id(&mut self, doc: &mut Document, style: FontStyle) -> ObjectId {
+        let font = self.fonts.entry(style).or_default();
+        *font.id.get_or_insert_with(|| doc.new_object_id())
+    }
+
+    /// Chaîne hexadécimale (identifiants de glyphes sur 2 octets) à afficher
+    /// avec `Tj` dans la police `style`
+    pub fn encode(&mut self, style: FontStyle, text: &str) -> String {
+        let face = style.face();
+        let font = self.fonts.entry(style).or_default();
+
+        let mut hex = String::with_capacity(text.len() * 4 + 2);
+        hex.push('<');
+        for c in text.chars() {
+            let (c, glyph) = match face.glyph_index(c) {
+                Some(glyph) => (c, glyph),
+                None => (REPLACEMENT, face.glyph_index(REPLACEMENT).unwrap_or_default()),
+            };
+            font.glyphs.entry(glyph.0).or_insert(c);
+            write!(hex, "{:04X}", glyph.0).unwrap();
+        }
+        hex.push('>');
+        hex
+    }
+
+    /// Largeur du texte en points pour une taille de police donnée
+    pub fn width(style: FontStyle, text: &str, font_size: f32) -> f32 {
+        let face = style.face();
+        let units = face.units_per_em() as f32;
+        let advance: f32 = text
+            .chars()
+            .map(|c| {
+                face.glyph_index(c)
+                    .or_else(|| face.glyph_index(REPLACEMENT))
+                    .and_then(|g| face.glyph_hor_advance(g))
+                    .unwrap_or(0) as f32
+            })
+            .sum();
+        advance / units * font_size
+    }
+
+    /// Écrit dans le document les polices réservées, réduites aux glyphes
+    /// encodés
+    pub fn embed(self, doc: &mut Document) -> Result<(), AppError> {
+        for (style, font) in self.fonts {
+            if let Some(id) = font.id {
+                embed_font(doc, id, style, &font.glyphs)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Préfixe de sous-ensemble (6 majuscules) dérivé des glyphes embarqués
+fn subset_tag(glyphs: &BTreeMap<u16, char>) -> String {
+    let mut hash: u32 = 2166136261;
+    for glyph in glyphs.keys() {
+        for byte in glyph.to_be_bytes() {
+            hash = (hash ^ byte as u32).wrapping_mul(16777619);
+        }
+    }
+    (0..6)
+        .map(|i| (b'A' + ((hash >> (i * 5)) % 26) as u8) as char)
+        .collect()
+}
+
+fn embed_font(
+    doc: &mut Document,
+    id: ObjectId,
+    style: FontStyle,
+    glyphs: &BTreeMap<u16, char>,
+) -> Result<(), AppError> {
+    let face = style.face();
+    let scale = 1000.0 / face.units_per_em() as f32;
+    let to_pdf = |v: i16| Object::Integer((v as f32 * scale).round() as i64);
+
+    // Le glyphe 0 (.notdef) est toujours conservé
+    let mut kept: Vec<u16> = vec![0];
+    kept.extend(glyphs.keys().copied().filter(|g| *g != 0));
+    let subset = subsetter::subset(style.data(), 0, subsetter::Profile::pdf(&kept))
+        .map_err(|e| AppError::Internal(format!("Failed to subset font: {}", e)))?;
+
+    let base_font = format!("{}+{}", subset_tag(glyphs), style.postscript_name());
+
+    let mut font_file = Stream::new(dictionary! { "Length1" => subset.len() as i64 }, subset);
+    // Sans compression en cas d'échec: le PDF reste valide
+    let _ = font_file.compress();
+    let font_file_id = doc.add_object(font_file);
+
+    let bbox = face.global_bounding_box();
+    let italic_angle = face.italic_angle();
+    let mut flags = 32; // Non symbolique
+    if italic_angle != 0.0 {
+        flags |= 64;
+    }
+    let descriptor_id = doc.add_object(dictionary! {
+        "Type" => "FontDescriptor",
+        "FontName" => Object::Name(base_font.clone().into_bytes()),
+        "Flags" => flags,
+        "FontBBox" => vec![to_pdf(bbox.x_min), to_pdf(bbox.y_min), to_pdf(bbox.x_max), to_pdf(bbox.y_max)],
+        "ItalicAngle" => italic_angle,
+        "Ascent" => to_pdf(face.ascender()),
+        "Descent" => to_pdf(face.descender()),
+        "CapHeight" => to_pdf(face.capital_height().unwrap_or(face.ascender())),
+        "StemV" => if style == FontStyle::Bold { 120 } else { 80 },
+        "FontFile2" => font_file_id,
+    });
+
+    // Largeurs des glyphes utilisés: "gid [largeur]"
+    let mut widths = Vec::with_capacity(glyphs.len() * 2);
+    for glyph in glyphs.keys() {
+        let advance = face.glyph_hor_advance(ttf_parser::GlyphId(*glyph)).unwrap_or(0);
+        widths.push(Object::Integer(*glyph as i64));
+        widths.push(Object::Array(vec![Object::Integer(
+            (advance as f32 * scale).round() as i64,
+        )]));
+    }
+
+    let cid_font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "CIDFontType2",
+        "BaseFont" => Object::Name(base_font.clone().into_bytes()),
+        "CIDSystemInfo" => dictionary! {
+            "Registry" => Object::String(b"Adobe".to_vec(), StringFormat::Literal),
+            "Ordering" => Object::String(b"Identity".to_vec(), StringFormat::Literal),
+            "Supplement" => 0,
+        },
+        "FontDescriptor" => descriptor_id,
+        "DW" => 1000,
+        "W" => widths,
+        "CIDToGIDMap" => "Identity",
+    });
+
+    let to_unicode_id = doc.add_object(Stream::new(Dictionary::new(), to_unicode_cmap(glyphs).into_bytes()));
+
+    doc.objects.insert(
+        id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type0",
+            "BaseFont" => Object::Name(base_font.into_bytes()),
+            "Encoding" => "Identity-H",
+            "DescendantFonts" => vec![cid_font_id.into()],
+            "ToUnicode" => to_unicode_id,
+        }),
+    );
+    Ok(())
+}
+
+/// CMap ToUnicode associant chaque glyphe à son caractère
+fn to_unicode_cmap(glyphs: &BTreeMap<u16, char>) -> String {
+    let mut cmap = String::from(
+        "/CIDInit /ProcSet findresource begin\n\
+         12 dict begin\n\
+         begincmap\n\
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (UCS) /Supplement 0 >> def\n\
+         /CMapName /Adobe-Identity-UCS def\n\
+         /CMapType 2 def\n\
+         1 begincodespacerange\n<0000> <FFFF>\nendcodespacerange\n",
+    );
+
+    let entries: Vec<(&u16, &char)> = glyphs.iter().collect();
+    // 100 entrées au plus par bloc
+    for chunk in entries.chunks(100) {
+        writeln!(cmap, "{} beginbfchar", chunk.len()).unwrap();
+        for (glyph, c) in chunk {
+            let mut units = [0u16; 2];
+            let hex: String = c.encode_utf16(&mut units).iter().map(|u| format!("{:04X}", u)).collect();
+            writeln!(cmap, "<{:04X}> <{}>", glyph, hex).unwrap();
+        }
+        cmap.push_str("endbfchar\n");
+    }
+
+    cmap.push_str("endcmap\nCMapName currentdict /CMap defineresource pop\nend\nend\n");
+    cmap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_and_embed() {
+        let mut doc = Document::with_version("1.5");
+        let mut layer = TextLayer::new();
+        let font_id = layer.font_id(&mut doc, FontStyle::Regular);
+        // Le même style réutilise la même police
+        assert_eq!(layer.font_id(&mut doc, FontStyle::Regular), font_id);
+
+        // Caractères hors Latin-1 (polonais, grec)
+        let hex = layer.encode(FontStyle::Regular, "Łukasz Ωmega");
+        assert_eq!(hex.len(), 2 + 12 * 4);
+        assert!(!hex.contains(&format!("{:04X}", 0)));
+        assert!(TextLayer::width(FontStyle::Bold, "Łukasz", 10.0) > TextLayer::width(FontStyle::Regular, "Ł", 10.0));
+
+        layer.embed(&mut doc).unwrap();
+
+        let font = doc.get_dictionary(font_id).unwrap();
+        assert_eq!(font.get(b"Subtype").unwrap().as_name().unwrap(), b"Type0");
+        let base_font = font.get(b"BaseFont").unwrap().as_name_str().unwrap();
+        assert!(base_font.ends_with("+DejaVuSansCondensed"));
+
+        let to_unicode = font.get(b"ToUnicode").and_then(Object::as_reference).unwrap();
+        let cmap = doc.get_object(to_unicode).unwrap().as_stream().unwrap();
+        let cmap = String::from_utf8_lossy(&cmap.content);
+        // Ł = U+0141, Ω = U+03A9
+        assert!(cmap.contains("<0141>"));
+        assert!(cmap.contains("<03A9>"));
+    }
+}
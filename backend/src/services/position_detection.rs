@@ -0,0 +1,596 @@
+use lopdf::content::Operation;
+use lopdf::{Dictionary, Document, Encoding, Object};
+use std::collections::BTreeMap;
+
+use crate::errors::AppError;
+
+/// Largeur moyenne d'un glyphe (en millièmes d'em) quand la police ne
+/// fournit pas de table /Widths
+const DEFAULT_GLYPH_WIDTH: f32 = 500.0;
+/// Écart entre la fin du libellé et la case de validation proposée
+const BOX_GAP: f32 = 6.0;
+/// Dimensions par défaut de la case proposée (2 lignes en police 8)
+const BOX_WIDTH: f32 = 110.0;
+const BOX_MIN_HEIGHT: f32 = 20.0;
+const PAGE_MARGIN: f32 = 10.0;
+
+/// Fragment de texte affiché par un opérateur Tj/TJ/'/", en coordonnées de page
+#[derive(Debug, Clone)]
+pub struct TextRun {
+    pub page: u32,
+    pub text: String,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub font_size: f32,
+}
+
+/// Ligne de texte reconstituée (fragments de même ligne de base)
+#[derive(Debug, Clone)]
+struct TextLine {
+    page: u32,
+    /// Texte normalisé de la ligne
+    text: String,
+    /// Abscisse de chaque caractère de `text`
+    char_x: Vec<f32>,
+    x_end: f32,
+    y: f32,
+    font_size: f32,
+}
+
+/// Position proposée pour la case de validation d'un libellé
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectedBox {
+    pub page: i32,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub font_size: f32,
+    /// Texte du document reconnu comme le libellé
+    pub matched_text: String,
+    /// 1.0 pour une correspondance exacte sur une ligne, moins pour un
+    /// libellé coupé sur plusieurs lignes ou reconnu par son début
+    pub confidence: f32,
+}
+
+type Matrix = [f32; 6];
+
+const IDENTITY: Matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+fn multiply(m1: &Matrix, m2: &Matrix) -> Matrix {
+    [
+        m1[0] * m2[0] + m1[1] * m2[2],
+        m1[0] * m2[1] + m1[1] * m2[3],
+        m1[2] * m2[0] + m1[3] * m2[2],
+        m1[2] * m2[1] + m1[3] * m2[3],
+        m1[4] * m2[0] + m1[5] * m2[2] + m2[4],
+        m1[4] * m2[1] + m1[5] * m2[3] + m2[5],
+    ]
+}
+
+fn translate(tx: f32, ty: f32) -> Matrix {
+    [1.0, 0.0, 0.0, 1.0, tx, ty]
+}
+
+fn number(obj: &Object) -> f32 {
+    obj.as_float().unwrap_or(0.0)
+}
+
+fn operand(op: &Operation, index: usize) -> f32 {
+    op.operands.get(index).map(number).unwrap_or(0.0)
+}
+
+/// Police courante: encodage et largeurs des glyphes
+struct FontInfo<'a> {
+    encoding: Option<Encoding<'a>>,
+    first_char: i64,
+    widths: Vec<f32>,
+}
+
+impl FontInfo<'_> {
+    fn decode(&self, bytes: &[u8]) -> String {
+        self.encoding
+            .as_ref()
+            .and_then(|e| Document::decode_text(e, bytes).ok())
+            .unwrap_or_else(|| bytes.iter().map(|&b| b as char).collect())
+    }
+
+    /// Largeur du texte en unités de texte (avant mise à l'échelle par la taille)
+    fn width(&self, bytes: &[u8]) -> f32 {
+        bytes
+            .iter()
+            .map(|&b| {
+                let index = b as i64 - self.first_char;
+                if index >= 0 {
+                    self.widths.get(index as usize).copied().unwrap_or(DEFAULT_GLYPH_WIDTH)
+                } else {
+                    DEFAULT_GLYPH_WIDTH
+                }
+            })
+            .sum::<f32>()
+            / 1000.0
+    }
+}
+
+fn font_info<'a>(doc: &'a Document, font: &'a Dictionary) -> FontInfo<'a> {
+    let widths = font
+        .get_deref(b"Widths", doc)
+        .and_then(Object::as_array)
+        .map(|w| w.iter().map(number).collect())
+        .unwrap_or_default();
+    FontInfo {
+        encoding: font.get_font_encoding(doc).ok(),
+        first_char: font.get(b"FirstChar").and_then(Object::as_i64).unwrap_or(0),
+        widths,
+    }
+}
+
+/// Extrait les fragments de texte positionnés de toutes les pages.
+/// Le texte des XObjects de formulaire n'est pas parcouru.
+pub fn extract_text_runs(doc: &Document) -> Result<Vec<TextRun>, AppError> {
+    let mut runs = Vec::new();
+
+    for (page_number, page_id) in doc.get_pages() {
+        let fonts: BTreeMap<Vec<u8>, FontInfo> = doc
+            .get_page_fonts(page_id)
+            .map_err(|e| AppError::Validation(format!("Invalid PDF fonts: {}", e)))?
+            .into_iter()
+            .map(|(name, font)| (name, font_info(doc, font)))
+            .collect();
+        let content = doc
+            .get_and_decode_page_content(page_id)
+            .map_err(|e| AppError::Validation(format!("Invalid PDF content: {}", e)))?;
+
+        let mut ctm_stack: Vec<Matrix> = Vec::new();
+        let mut ctm = IDENTITY;
+        let mut tm = IDENTITY;
+        let mut lm = IDENTITY;
+        let mut font: Option<&FontInfo> = None;
+        let mut font_size = 0.0_f32;
+        let mut leading = 0.0_f32;
+        let mut char_spacing = 0.0_f32;
+        let mut word_spacing = 0.0_f32;
+        let mut h_scale = 1.0_f32;
+
+        for op in &content.operations {
+            match op.operator.as_str() {
+                "q" => ctm_stack.push(ctm),
+                "Q" => ctm = ctm_stack.pop().unwrap_or(IDENTITY),
+                "cm" => {
+                    let m = [
+                        operand(op, 0), operand(op, 1), operand(op, 2),
+                        operand(op, 3), operand(op, 4), operand(op, 5),
+                    ];
+                    ctm = multiply(&m, &ctm);
+                }
+                "BT" => {
+                    tm = IDENTITY;
+                    lm = IDENTITY;
+                }
+                "Tf" => {
+                    font = op
+                        .operands
+                        .first()
+                        .and_then(|o| o.as_name().ok())
+                        .and_then(|name| fonts.get(name));
+                    font_size = operand(op, 1);
+                }
+                "TL" => leading = operand(op, 0),
+                "Tc" => char_spacing = operand(op, 0),
+                "Tw" => word_spacing = operand(op, 0),
+                "Tz" => h_scale = operand(op, 0) / 100.0,
+                "Td" | "TD" => {
+                    if op.operator == "TD" {
+                        leading = -operand(op, 1);
+                    }
+                    lm = multiply(&translate(operand(op, 0), operand(op, 1)), &lm);
+                    tm = lm;
+                }
+                "Tm" => {
+                    lm = [
+                        operand(op, 0), operand(op, 1), operand(op, 2),
+                        operand(op, 3), operand(op, 4), operand(op, 5),
+                    ];
+                    tm = lm;
+                }
+                "T*" | "'" | "\"" | "Tj" | "TJ" => {
+                    if op.operator == "\"" {
+                        word_spacing = operand(op, 0);
+                        char_spacing = operand(op, 1);
+                    }
+                    if matches!(op.operator.as_str(), "T*" | "'" | "\"") {
+                        lm = multiply(&translate(0.0, -leading), &lm);
+                        tm = lm;
+                    }
+                    if op.operator == "T*" {
+                        continue;
+                    }
+
+                    let Some(font) = font else { continue };
+                    let items: Vec<&Object> = match op.operator.as_str() {
+                        "TJ" => op
+                            .operands
+                            .first()
+                            .and_then(|o| o.as_array().ok())
+                            .map(|a| a.iter().collect())
+                            .unwrap_or_default(),
+                        _ => op.operands.last().into_iter().collect(),
+                    };
+
+                    let rendering = multiply(&tm, &ctm);
+                    let scale = (rendering[2] * rendering[2] + rendering[3] * rendering[3]).sqrt();
+                    let mut text = String::new();
+                    let mut advance = 0.0_f32;
+
+                    for item in items {
+                        match item {
+                            Object::String(bytes, _) => {
+                                let decoded = font.decode(bytes);
+                                let spaces = bytes.iter().filter(|&&b| b == b' ').count() as f32;
+                                advance += font.width(bytes) * font_size * h_scale
+                                    + (char_spacing * bytes.len() as f32 + word_spacing * spaces) * h_scale;
+                                text.push_str(&decoded);
+                            }
+                            other => {
+                                let adjustment = number(other);
+                                advance -= adjustment / 1000.0 * font_size * h_scale;
+                                // Un grand décalage négatif sépare souvent deux mots
+                                if adjustment < -200.0 && !text.ends_with(' ') {
+                                    text.push(' ');
+                                }
+                            }
+                        }
+                    }
+
+                    if !text.trim().is_empty() {
+                        runs.push(TextRun {
+                            page: page_number,
+                            text,
+                            x: rendering[4],
+                            y: rendering[5],
+                            // L'avance est en espace texte: mise à l'échelle vers la page
+                            width: advance
+                                * (rendering[0] * rendering[0] + rendering[1] * rendering[1]).sqrt(),
+                            font_size: font_size * scale,
+                        });
+                    }
+                    tm = multiply(&translate(advance, 0.0), &tm);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(runs)
+}
+
+/// Normalise un libellé pour la comparaison: minuscules, sans accents ni
+/// ponctuation, espaces simples
+pub fn normalize(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut pending_space = false;
+    for c in text.chars().flat_map(char::to_lowercase) {
+        let c = match c {
+            'à' | 'â' | 'ä' | 'á' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'î' | 'ï' | 'í' => 'i',
+            'ô' | 'ö' | 'ó' => 'o',
+            'ù' | 'û' | 'ü' | 'ú' => 'u',
+            'ç' => 'c',
+            'œ' => 'o',
+            'æ' => 'a',
+            '’' => '\'',
+            c => c,
+        };
+        if c.is_alphanumeric() {
+            if pending_space && !result.is_empty() {
+                result.push(' ');
+            }
+            pending_space = false;
+            result.push(c);
+        } else {
+            pending_space = true;
+        }
+    }
+    result
+}
+
+/// Regroupe les fragments par ligne de base, de haut en bas puis de gauche à droite
+fn build_lines(runs: &[TextRun]) -> Vec<TextLine> {
+    let mut sorted: Vec<&TextRun> = runs.iter().collect();
+    sorted.sort_by(|a, b| {
+        a.page
+            .cmp(&b.page)
+            .then(b.y.partial_cmp(&a.y).unwrap_or(std::cmp::Ordering::Equal))
+            .then(a.x.partial_cmp(&b.x).unwrap_or(std::cmp::Ordering::Equal))
+    });
+
+    let mut lines: Vec<TextLine> = Vec::new();
+    for run in sorted {
+        let normalized = normalize(&run.text);
+        if normalized.is_empty() {
+            continue;
+        }
+
+        let tolerance = run.font_size.max(1.0) * 0.3;
+        let line = lines.last_mut().filter(|l| {
+            l.page == run.page
+                && (l.y - run.y).abs() <= tolerance
+                // Deux colonnes d'un tableau restent des lignes distinctes
+                && run.x - l.x_end <= run.font_size.max(1.0) * 1.5
+        });
+
+        let char_count = normalized.chars().count().max(1) as f32;
+        let char_width = run.width / char_count;
+        match line {
+            Some(line) => {
+                line.text.push(' ');
+                line.char_x.push(line.x_end);
+                for (i, _) in normalized.chars().enumerate() {
+                    line.char_x.push(run.x + i as f32 * char_width);
+                }
+                line.text.push_str(&normalized);
+                line.x_end = line.x_end.max(run.x + run.width);
+                line.font_size = line.font_size.max(run.font_size);
+            }
+            None => lines.push(TextLine {
+                page: run.page,
+                char_x: (0..normalized.chars().count())
+                    .map(|i| run.x + i as f32 * char_width)
+                    .collect(),
+                text: normalized,
+                x_end: run.x + run.width,
+                y: run.y,
+                font_size: run.font_size,
+            }),
+        }
+    }
+    lines
+}
+
+/// Libellé reconnu: lignes couvertes et abscisse de fin du texte
+struct TextMatch {
+    first: usize,
+    last: usize,
+    x_end: f32,
+    confidence: f32,
+}
+
+fn find_in_lines(lines: &[TextLine], needle: &str) -> Option<TextMatch> {
+    // Sur une seule ligne
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(byte_pos) = line.text.find(needle) {
+            let start = line.text[..byte_pos].chars().count();
+            let end = start + needle.chars().count();
+            let x_end = line.char_x.get(end).copied().unwrap_or(line.x_end);
+            return Some(TextMatch {
+                first: i,
+                last: i,
+                x_end,
+                confidence: 1.0,
+            });
+        }
+    }
+
+    // Libellé coupé sur plusieurs lignes d'une même cellule
+    for i in 0..lines.len() {
+        let mut joined = lines[i].text.clone();
+        let mut x_end = lines[i].x_end;
+        for j in (i + 1)..lines.len().min(i + 4) {
+            let (previous, line) = (&lines[j - 1], &lines[j]);
+            if line.page != previous.page
+                || (previous.y - line.y) > previous.font_size.max(1.0) * 2.0
+                || (line.char_x[0] - lines[i].char_x[0]).abs() > lines[i].font_size.max(1.0) * 4.0
+            {
+                break;
+            }
+            joined.push(' ');
+            joined.push_str(&line.text);
+            x_end = x_end.max(line.x_end);
+            if joined.contains(needle) {
+                return Some(TextMatch {
+                    first: i,
+                    last: j,
+                    x_end,
+                    confidence: 0.9,
+                });
+            }
+        }
+    }
+
+    // Début du libellé (au moins 4 mots) en tête de ligne
+    let words: Vec<&str> = needle.split(' ').collect();
+    if words.len() >= 5 {
+        let prefix = words[..4].join(" ");
+        for (i, line) in lines.iter().enumerate() {
+            if line.text.starts_with(&prefix) || line.text.contains(&format!(" {}", prefix)) {
+                return Some(TextMatch {
+                    first: i,
+                    last: i,
+                    x_end: line.x_end,
+                    confidence: 0.6,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Cherche chaque libellé dans le texte du document et propose une case de
+/// validation à droite du texte. Les cases d'une même page sont alignées en
+/// colonne lorsque la largeur de la page le permet.
+pub fn detect_positions(
+    doc: &Document,
+    labels: &[String],
+) -> Result<Vec<Option<DetectedBox>>, AppError> {
+    let runs = extract_text_runs(doc)?;
+    let lines = build_lines(&runs);
+
+    let page_widths: BTreeMap<u32, f32> = doc
+        .get_pages()
+        .into_iter()
+        .map(|(number, id)| {
+            let width = super::pdf_generator::get_page_dimensions_from_doc(doc, id)
+                .map(|(w, _)| w)
+                .unwrap_or(612.0);
+            (number, width)
+        })
+        .collect();
+
+    let matches: Vec<Option<TextMatch>> = labels
+        .iter()
+        .map(|label| {
+            let needle = normalize(label);
+            if needle.is_empty() {
+                None
+            } else {
+                find_in_lines(&lines, &needle)
+            }
+        })
+        .collect();
+
+    // Colonne commune par page: à droite du libellé le plus long
+    let mut column_x: BTreeMap<u32, f32> = BTreeMap::new();
+    for m in matches.iter().flatten() {
+        let page = lines[m.first].page;
+        let x = column_x.entry(page).or_insert(0.0);
+        *x = x.max(m.x_end + BOX_GAP);
+    }
+
+    Ok(matches
+        .into_iter()
+        .map(|m| {
+            let m = m?;
+            let (first, last) = (&lines[m.first], &lines[m.last]);
+            let page_width = page_widths.get(&first.page).copied().unwrap_or(612.0);
+            let font_size = first.font_size.max(1.0);
+
+            let fits = |x: f32| x + BOX_WIDTH <= page_width - PAGE_MARGIN;
+            let column = column_x.get(&first.page).copied().unwrap_or(m.x_end + BOX_GAP);
+            let x = if fits(column) {
+                column
+            } else if fits(m.x_end + BOX_GAP) {
+                m.x_end + BOX_GAP
+            } else {
+                (page_width - PAGE_MARGIN - BOX_WIDTH).max(0.0)
+            };
+
+            // Case centrée verticalement sur le bloc de texte
+            let text_top = first.y + font_size;
+            let text_bottom = last.y - font_size * 0.25;
+            let height = (text_top - text_bottom).max(BOX_MIN_HEIGHT);
+            let y = (text_top + text_bottom) / 2.0 - height / 2.0;
+
+            let matched_text = lines[m.first..=m.last]
+                .iter()
+                .map(|l| l.text.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            Some(DetectedBox {
+                page: first.page as i32,
+                x,
+                y,
+                width: BOX_WIDTH,
+                height,
+                font_size: 8.0_f32.min(height / 2.5),
+                matched_text,
+                confidence: m.confidence,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::content::Content;
+    use lopdf::{dictionary, Stream};
+
+    /// PDF d'une page A4 avec une ligne de texte par entrée (x, y, taille, texte)
+    fn sample_pdf(lines: &[(f32, f32, f32, &str)]) -> Document {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+        });
+
+        let mut operations = Vec::new();
+        for (x, y, size, text) in lines {
+            operations.push(Operation::new("BT", vec![]));
+            operations.push(Operation::new("Tf", vec!["F1".into(), (*size).into()]));
+            operations.push(Operation::new("Td", vec![(*x).into(), (*y).into()]));
+            operations.push(Operation::new("Tj", vec![Object::string_literal(*text)]));
+            operations.push(Operation::new("ET", vec![]));
+        }
+        let content = Content { operations };
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+        doc
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("  Remontée  en\nexpiration !"), "remontee en expiration");
+        assert_eq!(normalize("Vidage de masque (PMT)"), "vidage de masque pmt");
+    }
+
+    #[test]
+    fn test_detect_positions() {
+        let doc = sample_pdf(&[
+            (50.0, 700.0, 10.0, "Vidage de masque"),
+            (50.0, 650.0, 10.0, "Remontee en expiration controlee"),
+            (50.0, 600.0, 10.0, "Assistance d'un plongeur en"),
+            (50.0, 589.0, 10.0, "difficulte en surface"),
+        ]);
+        let labels = vec![
+            "Vidage de masque".to_string(),
+            "Remontée en expiration contrôlée".to_string(),
+            "Assistance d'un plongeur en difficulté en surface".to_string(),
+            "Lâcher et reprise d'embout".to_string(),
+        ];
+
+        let found = detect_positions(&doc, &labels).unwrap();
+
+        let mask = found[0].as_ref().unwrap();
+        assert_eq!(mask.page, 1);
+        assert_eq!(mask.confidence, 1.0);
+        assert!(mask.y < 700.0 && mask.y + mask.height > 700.0);
+
+        let ascent = found[1].as_ref().unwrap();
+        // Les cases sont alignées à droite du libellé le plus long
+        assert_eq!(ascent.x, mask.x);
+        assert!(ascent.x > 50.0 + 100.0);
+
+        let assistance = found[2].as_ref().unwrap();
+        assert_eq!(assistance.confidence, 0.9);
+        assert!(assistance.y < 589.0 && assistance.y + assistance.height > 600.0);
+
+        assert!(found[3].is_none());
+    }
+}
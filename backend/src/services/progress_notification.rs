@@ -0,0 +1,510 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use sea_orm::*;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::entities::prelude::*;
+use crate::entities::{
+    competency_modules, competency_skills, notification_preferences, people, progress_milestones,
+    skill_validation_history, skill_validations, validation_stages,
+};
+use crate::config::NotificationsConfig;
+use crate::errors::AppResult;
+use crate::models::{DigestRunResponse, NotificationPreferencesResponse, UpdateNotificationPreferencesRequest};
+use crate::services::{EmailService, MentorService, SkillValidationService};
+
+/// Ligne du résumé quotidien
+#[derive(Debug, Clone)]
+pub struct DigestEntry {
+    pub skill_name: String,
+    pub stage_name: String,
+    pub validator_name: Option<String>,
+    pub validated_at: Option<NaiveDate>,
+    pub notes: Option<String>,
+}
+
+/// Résumés quotidiens et emails d'étape envoyés aux élèves qui les ont activés
+pub struct ProgressNotificationService;
+
+impl ProgressNotificationService {
+    // ------------------------------------------------------------------------
+    // Préférences
+    // ------------------------------------------------------------------------
+
+    pub async fn preferences(
+        db: &DatabaseConnection,
+        person_id: Uuid,
+    ) -> AppResult<NotificationPreferencesResponse> {
+        let prefs = NotificationPreferences::find_by_id(person_id).one(db).await?;
+        Ok(Self::preferences_response(prefs))
+    }
+
+    /// Met à jour les préférences. Activer le résumé ne renvoie pas
+    /// l'historique: seules les validations suivantes y figureront.
+    pub async fn update_preferences(
+        db: &DatabaseConnection,
+        person_id: Uuid,
+        request: UpdateNotificationPreferencesRequest,
+    ) -> AppResult<NotificationPreferencesResponse> {
+        let now = Utc::now().naive_utc();
+        let existing = NotificationPreferences::find_by_id(person_id).one(db).await?;
+
+        let prefs = match existing {
+            Some(prefs) => {
+                let enabling_digest = request.progress_digest == Some(true) && !prefs.progress_digest;
+                let mut active: notification_preferences::ActiveModel = prefs.into();
+                if let Some(progress_digest) = request.progress_digest {
+                    active.progress_digest = Set(progress_digest);
+                }
+                if let Some(milestone_emails) = request.milestone_emails {
+                    active.milestone_emails = Set(milestone_emails);
+                }
+                if enabling_digest {
+                    active.last_digest_at = Set(Some(now));
+                }
+                active.updated_at = Set(now);
+                active.update(db).await?
+            }
+            None => {
+                let progress_digest = request.progress_digest.unwrap_or(false);
+                notification_preferences::ActiveModel {
+                    person_id: Set(person_id),
+                    progress_digest: Set(progress_digest),
+                    milestone_emails: Set(request.milestone_emails.unwrap_or(false)),
+                    last_digest_at: Set(progress_digest.then_some(now)),
+                    updated_at: Set(now),
+                }
+                .insert(db)
+                .await?
+            }
+        };
+
+        Ok(Self::preferences_response(Some(prefs)))
+    }
+
+    fn preferences_response(
+        prefs: Option<notification_preferences::Model>,
+    ) -> NotificationPreferencesResponse {
+        match prefs {
+            Some(prefs) => NotificationPreferencesResponse {
+                progress_digest: prefs.progress_digest,
+                milestone_emails: prefs.milestone_emails,
+                last_digest_at: prefs
+                    .last_digest_at
+                    .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string()),
+            },
+            None => NotificationPreferencesResponse {
+                progress_digest: false,
+                milestone_emails: false,
+                last_digest_at: None,
+            },
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Résumé quotidien
+    // ------------------------------------------------------------------------
+
+    /// Envoie chaque jour à `digest_hour` (UTC) les résumés en attente, puis
+    /// les alertes de stagnation aux référents. Un envoi manqué (serveur
+    /// arrêté) est rattrapé au passage suivant.
+    pub async fn run_digest_scheduler(
+        db: Arc<DatabaseConnection>,
+        email_service: Arc<EmailService>,
+        config: NotificationsConfig,
+    ) {
+        loop {
+            let now = Utc::now().naive_utc();
+            let wait = Self::next_digest_run(now, config.digest_hour) - now;
+            tokio::time::sleep(wait.to_std().unwrap_or_default()).await;
+
+            match Self::send_digests(db.as_ref(), &email_service, Utc::now().naive_utc()).await {
+                Ok(run) => tracing::info!(
+                    "Progress digests: {} sent, {} failed",
+                    run.sent,
+                    run.failed
+                ),
+                Err(e) => tracing::error!("Failed to send progress digests: {}", e),
+            }
+
+            match MentorService::send_stall_alerts(
+                db.as_ref(),
+                &email_service,
+                Utc::now().naive_utc(),
+                config.mentor_stall_weeks,
+            )
+            .await
+            {
+                Ok(run) => tracing::info!(
+                    "Mentor stall alerts: {} sent, {} failed",
+                    run.sent,
+                    run.failed
+                ),
+                Err(e) => tracing::error!("Failed to send mentor stall alerts: {}", e),
+            }
+        }
+    }
+
+    /// Prochain passage strictement après `now`
+    fn next_digest_run(now: NaiveDateTime, digest_hour: u32) -> NaiveDateTime {
+        let time = NaiveTime::from_hms_opt(digest_hour.min(23), 0, 0).unwrap_or_default();
+        let today = now.date().and_time(time);
+        if today > now {
+            today
+        } else {
+            today + Duration::days(1)
+        }
+    }
+
+    /// Envoie un résumé à chaque élève abonné ayant de nouvelles validations
+    /// depuis son dernier résumé. Un échec d'envoi est retenté au passage suivant.
+    pub async fn send_digests(
+        db: &DatabaseConnection,
+        email_service: &EmailService,
+        now: NaiveDateTime,
+    ) -> AppResult<DigestRunResponse> {
+        let subscribers = NotificationPreferences::find()
+            .filter(notification_preferences::Column::ProgressDigest.eq(true))
+            .all(db)
+            .await?;
+
+        let mut run = DigestRunResponse::default();
+        for prefs in subscribers {
+            let Some(person) = People::find_by_id(prefs.person_id).one(db).await? else {
+                continue;
+            };
+            let since = prefs.last_digest_at.unwrap_or(prefs.updated_at);
+            let entries = Self::digest_entries(db, person.id, since, now).await?;
+
+            if !entries.is_empty() {
+                let subject = if entries.len() == 1 {
+                    "Ta progression: 1 nouvelle validation".to_string()
+                } else {
+                    format!("Ta progression: {} nouvelles validations", entries.len())
+                };
+                let body = Self::digest_body(&person.first_name, &entries);
+                let to_name = format!("{} {}", person.first_name, person.last_name);
+
+                if let Err(e) = email_service
+                    .send_email(&person.email, &to_name, &subject, &body)
+                    .await
+                {
+                    tracing::warn!("Failed to send progress digest to {}: {}", person.email, e);
+                    run.failed += 1;
+                    continue;
+                }
+                run.sent += 1;
+            }
+
+            let mut active: notification_preferences::ActiveModel = prefs.into();
+            active.last_digest_at = Set(Some(now));
+            active.update(db).await?;
+        }
+
+        Ok(run)
+    }
+
+    /// Validations (créations et passages d'étape) enregistrées pour un élève
+    /// dans la période. Les auto-évaluations de l'élève sont ignorées.
+    async fn digest_entries(
+        db: &DatabaseConnection,
+        person_id: Uuid,
+        since: NaiveDateTime,
+        until: NaiveDateTime,
+    ) -> AppResult<Vec<DigestEntry>> {
+        let history = SkillValidationHistory::find()
+            .filter(skill_validation_history::Column::PersonId.eq(person_id))
+            .filter(skill_validation_history::Column::CreatedAt.gt(since))
+            .filter(skill_validation_history::Column::CreatedAt.lte(until))
+            .filter(skill_validation_history::Column::Action.is_in([
+                skill_validation_history::ACTION_CREATED,
+                skill_validation_history::ACTION_ADVANCED,
+            ]))
+            .order_by_asc(skill_validation_history::Column::CreatedAt)
+            .all(db)
+            .await?
+            .into_iter()
+            .filter(|h| h.validated_by_id != Some(person_id))
+            .collect::<Vec<_>>();
+
+        if history.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let skills: HashMap<Uuid, String> = CompetencySkills::find()
+            .filter(competency_skills::Column::Id.is_in(history.iter().map(|h| h.skill_id)))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|s| (s.id, s.name))
+            .collect();
+        let stages: HashMap<Uuid, String> = ValidationStages::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|s| (s.id, s.name))
+            .collect();
+        let validators: HashMap<Uuid, String> = People::find()
+            .filter(people::Column::Id.is_in(history.iter().filter_map(|h| h.validated_by_id)))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|p| (p.id, format!("{} {}", p.first_name, p.last_name)))
+            .collect();
+
+        Ok(history
+            .into_iter()
+            .map(|h| DigestEntry {
+                skill_name: skills.get(&h.skill_id).cloned().unwrap_or_default(),
+                stage_name: h
+                    .to_stage_id
+                    .and_then(|id| stages.get(&id).cloned())
+                    .unwrap_or_default(),
+                validator_name: h.validated_by_id.and_then(|id| validators.get(&id).cloned()),
+                validated_at: h.validated_at,
+                notes: h.notes,
+            })
+            .collect())
+    }
+
+    fn digest_body(first_name: &str, entries: &[DigestEntry]) -> String {
+        let rows: String = entries
+            .iter()
+            .map(|e| {
+                format!(
+                    "<tr><td>{}</td><td><strong>{}</strong></td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    e.validated_at
+                        .map(|d| d.format("%d/%m/%Y").to_string())
+                        .unwrap_or_default(),
+                    escape_html(&e.skill_name),
+                    escape_html(&e.stage_name),
+                    escape_html(e.validator_name.as_deref().unwrap_or("")),
+                    escape_html(e.notes.as_deref().unwrap_or("")),
+                )
+            })
+            .collect();
+
+        format!(
+            "<p>Bonjour {},</p>\
+             <p>Voici les acquis validés depuis ton dernier résumé:</p>\
+             <table border=\"1\" cellpadding=\"4\" cellspacing=\"0\">\
+             <tr><th>Date</th><th>Acquis</th><th>Étape</th><th>Encadrant</th><th>Notes</th></tr>\
+             {}</table>",
+            escape_html(first_name),
+            rows,
+        )
+    }
+
+    // ------------------------------------------------------------------------
+    // Modules et domaines complétés
+    // ------------------------------------------------------------------------
+
+    /// Appelé après une validation finale: enregistre le module et le domaine
+    /// de l'acquis s'ils sont désormais complets, et prévient l'élève s'il l'a
+    /// demandé. Les erreurs d'envoi sont seulement loggées.
+    pub async fn handle_final_validation(
+        db: &DatabaseConnection,
+        email_service: &EmailService,
+        person_id: Uuid,
+        skill_id: Uuid,
+    ) -> AppResult<()> {
+        let Some(skill) = CompetencySkills::find_by_id(skill_id).one(db).await? else {
+            return Ok(());
+        };
+        let Some(module) = CompetencyModules::find_by_id(skill.module_id).one(db).await? else {
+            return Ok(());
+        };
+        let Some(domain) = CompetencyDomains::find_by_id(module.domain_id).one(db).await? else {
+            return Ok(());
+        };
+
+        let module_skill_ids = Self::skill_ids(db, &[module.id]).await?;
+        let Some(module_completed_at) = Self::completion_date(db, person_id, &module_skill_ids).await? else {
+            return Ok(());
+        };
+
+        let module_ids: Vec<Uuid> = CompetencyModules::find()
+            .filter(competency_modules::Column::DomainId.eq(domain.id))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        let domain_skill_ids = Self::skill_ids(db, &module_ids).await?;
+        let domain_completed_at = Self::completion_date(db, person_id, &domain_skill_ids).await?;
+
+        let mut milestones = vec![(
+            progress_milestones::UNIT_MODULE,
+            module.id,
+            format!("le module <strong>{}</strong>", escape_html(&module.name)),
+            module_completed_at,
+        )];
+        if let Some(completed_at) = domain_completed_at {
+            milestones.push((
+                progress_milestones::UNIT_DOMAIN,
+                domain.id,
+                format!(
+                    "le domaine <strong>{}</strong> du niveau {}",
+                    escape_html(&domain.name),
+                    escape_html(&domain.diving_level)
+                ),
+                completed_at,
+            ));
+        }
+
+        let Some(person) = People::find_by_id(person_id).one(db).await? else {
+            return Ok(());
+        };
+        let wants_email = NotificationPreferences::find_by_id(person_id)
+            .one(db)
+            .await?
+            .is_some_and(|p| p.milestone_emails);
+
+        for (unit_type, unit_id, label, completed_at) in milestones {
+            let already_recorded = ProgressMilestones::find()
+                .filter(progress_milestones::Column::PersonId.eq(person_id))
+                .filter(progress_milestones::Column::UnitType.eq(unit_type))
+                .filter(progress_milestones::Column::UnitId.eq(unit_id))
+                .one(db)
+                .await?
+                .is_some();
+            if already_recorded {
+                continue;
+            }
+
+            let now = Utc::now().naive_utc();
+            let mut notified_at = None;
+            if wants_email {
+                let subject = if unit_type == progress_milestones::UNIT_MODULE {
+                    format!("Bravo - module {} terminé", module.name)
+                } else {
+                    format!("Bravo - domaine {} terminé", domain.name)
+                };
+                let body = format!(
+                    "<p>Bonjour {},</p><p>Tu as validé tous les acquis de {} (le {}).</p>",
+                    escape_html(&person.first_name),
+                    label,
+                    completed_at.format("%d/%m/%Y"),
+                );
+                let to_name = format!("{} {}", person.first_name, person.last_name);
+                match email_service.send_email(&person.email, &to_name, &subject, &body).await {
+                    Ok(()) => notified_at = Some(now),
+                    Err(e) => tracing::warn!(
+                        "Failed to send milestone email to {}: {}",
+                        person.email,
+                        e
+                    ),
+                }
+            }
+
+            progress_milestones::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                person_id: Set(person_id),
+                unit_type: Set(unit_type.to_string()),
+                unit_id: Set(unit_id),
+                completed_at: Set(completed_at),
+                notified_at: Set(notified_at),
+                created_at: Set(now),
+            }
+            .insert(db)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn skill_ids(db: &DatabaseConnection, module_ids: &[Uuid]) -> AppResult<Vec<Uuid>> {
+        Ok(CompetencySkills::find()
+            .filter(competency_skills::Column::ModuleId.is_in(module_ids.to_vec()))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|s| s.id)
+            .collect())
+    }
+
+    /// Date de la dernière validation si tous les acquis sont à une étape
+    /// finale non expirée, sinon None
+    async fn completion_date(
+        db: &DatabaseConnection,
+        person_id: Uuid,
+        skill_ids: &[Uuid],
+    ) -> AppResult<Option<NaiveDate>> {
+        if skill_ids.is_empty() {
+            return Ok(None);
+        }
+
+        let final_stage_ids: HashSet<Uuid> = ValidationStages::find()
+            .filter(validation_stages::Column::IsFinal.eq(true))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        let periods = SkillValidationService::validity_periods(db, skill_ids.iter().copied()).await?;
+        let today = Utc::now().naive_utc().date();
+
+        let validated: HashMap<Uuid, NaiveDate> = SkillValidations::find()
+            .filter(skill_validations::Column::PersonId.eq(person_id))
+            .filter(skill_validations::Column::SkillId.is_in(skill_ids.to_vec()))
+            .all(db)
+            .await?
+            .into_iter()
+            .filter(|v| final_stage_ids.contains(&v.stage_id))
+            .filter(|v| !SkillValidationService::is_validation_expired(v, &periods, today))
+            .map(|v| (v.skill_id, v.validated_at))
+            .collect();
+
+        if skill_ids.iter().any(|id| !validated.contains_key(id)) {
+            return Ok(None);
+        }
+        Ok(validated.values().max().copied())
+    }
+}
+
+/// Échappe le texte libre (notes, noms) inséré dans les emails HTML
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 5, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_next_digest_run() {
+        assert_eq!(ProgressNotificationService::next_digest_run(at(10, 9, 30), 18), at(10, 18, 0));
+        assert_eq!(ProgressNotificationService::next_digest_run(at(10, 18, 0), 18), at(11, 18, 0));
+        assert_eq!(ProgressNotificationService::next_digest_run(at(10, 23, 59), 18), at(11, 18, 0));
+    }
+
+    #[test]
+    fn test_digest_body_escapes_free_text() {
+        let body = ProgressNotificationService::digest_body(
+            "Léa",
+            &[DigestEntry {
+                skill_name: "Vidage de masque".to_string(),
+                stage_name: "Acquis".to_string(),
+                validator_name: Some("Jean Dupont".to_string()),
+                validated_at: NaiveDate::from_ymd_opt(2024, 5, 10),
+                notes: Some("<b>très</b> bien & propre".to_string()),
+            }],
+        );
+
+        assert!(body.contains("Bonjour Léa"));
+        assert!(body.contains("10/05/2024"));
+        assert!(body.contains("Jean Dupont"));
+        assert!(body.contains("&lt;b&gt;très&lt;/b&gt; bien &amp; propre"));
+    }
+}
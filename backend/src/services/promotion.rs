@@ -0,0 +1,434 @@
+use chrono::{NaiveDate, Utc};
+use sea_orm::*;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::entities::prelude::*;
+use crate::entities::{
+    certifications, competency_domains, competency_modules, competency_skills, level_promotions, people,
+    skill_validations, validation_stages,
+};
+use crate::errors::{AppError, AppResult};
+use crate::models::{LevelPromotionResponse, PromotionValidator};
+use crate::services::{CertificationService, EmailService, SkillValidationService};
+use crate::services::progress_notification::escape_html;
+
+/// Niveau dont tous les acquis ont atteint une étape finale
+#[derive(Debug)]
+pub struct CompletedLevel {
+    pub level: String,
+    pub completed_at: NaiveDate,
+    pub validator_ids: Vec<Uuid>,
+}
+
+/// Détection et application des passages de niveau
+pub struct PromotionService;
+
+impl PromotionService {
+    /// Retourne les IDs des acquis rattachés aux domaines d'un niveau
+    async fn skill_ids_for_level(db: &DatabaseConnection, level: &str) -> AppResult<Vec<Uuid>> {
+        let domain_ids: Vec<Uuid> = CompetencyDomains::find()
+            .filter(competency_domains::Column::DivingLevel.eq(level))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|d| d.id)
+            .collect();
+
+        if domain_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let module_ids: Vec<Uuid> = CompetencyModules::find()
+            .filter(competency_modules::Column::DomainId.is_in(domain_ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+
+        if module_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        Ok(CompetencySkills::find()
+            .filter(competency_skills::Column::ModuleId.is_in(module_ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|s| s.id)
+            .collect())
+    }
+
+    /// Retourne le niveau (diving_level du domaine) auquel appartient un acquis
+    async fn level_for_skill(db: &DatabaseConnection, skill_id: Uuid) -> AppResult<Option<String>> {
+        let Some(skill) = CompetencySkills::find_by_id(skill_id).one(db).await? else {
+            return Ok(None);
+        };
+        let Some(module) = CompetencyModules::find_by_id(skill.module_id).one(db).await? else {
+            return Ok(None);
+        };
+        Ok(CompetencyDomains::find_by_id(module.domain_id)
+            .one(db)
+            .await?
+            .map(|d| d.diving_level))
+    }
+
+    /// Vérifie si tous les acquis d'un niveau sont à une étape finale (non
+    /// expirée) pour une personne
+    pub async fn check_level_completion(
+        db: &DatabaseConnection,
+        person_id: Uuid,
+        level: &str,
+    ) -> AppResult<Option<CompletedLevel>> {
+        let skill_ids = Self::skill_ids_for_level(db, level).await?;
+        if skill_ids.is_empty() {
+            return Ok(None);
+        }
+
+        let final_stage_ids: HashSet<Uuid> = ValidationStages::find()
+            .filter(validation_stages::Column::IsFinal.eq(true))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+
+        // Les validations expirées (à revalider) ne comptent pas
+        let periods = SkillValidationService::validity_periods(db, skill_ids.iter().copied()).await?;
+        let today = Utc::now().naive_utc().date();
+
+        let final_validations: HashMap<Uuid, skill_validations::Model> = SkillValidations::find()
+            .filter(skill_validations::Column::PersonId.eq(person_id))
+            .filter(skill_validations::Column::SkillId.is_in(skill_ids.clone()))
+            .order_by_asc(skill_validations::Column::ValidatedAt)
+            .all(db)
+            .await?
+            .into_iter()
+            .filter(|v| final_stage_ids.contains(&v.stage_id))
+            .filter(|v| !SkillValidationService::is_validation_expired(v, &periods, today))
+            .map(|v| (v.skill_id, v))
+            .collect();
+
+        if skill_ids.iter().any(|id| !final_validations.contains_key(id)) {
+            return Ok(None);
+        }
+
+        let completed_at = final_validations
+            .values()
+            .map(|v| v.validated_at)
+            .max()
+            .unwrap_or_else(|| Utc::now().naive_utc().date());
+
+        let mut validator_ids: Vec<Uuid> = Vec::new();
+        for skill_id in &skill_ids {
+            let validator_id = final_validations[skill_id].validated_by_id;
+            if !validator_ids.contains(&validator_id) {
+                validator_ids.push(validator_id);
+            }
+        }
+
+        Ok(Some(CompletedLevel {
+            level: level.to_string(),
+            completed_at,
+            validator_ids,
+        }))
+    }
+
+    /// Crée une proposition de promotion si le niveau est complet et pas encore obtenu.
+    /// Retourne None si rien de nouveau n'a été détecté.
+    pub async fn propose(
+        db: &DatabaseConnection,
+        person: &people::Model,
+        level: &str,
+    ) -> AppResult<Option<level_promotions::Model>> {
+        let already_holds_level = Certifications::find()
+            .filter(certifications::Column::PersonId.eq(person.id))
+            .filter(certifications::Column::Level.eq(level))
+            .filter(certifications::Column::Status.eq(certifications::STATUS_OBTAINED))
+            .one(db)
+            .await?
+            .is_some();
+        if already_holds_level {
+            return Ok(None);
+        }
+
+        let existing = LevelPromotions::find()
+            .filter(level_promotions::Column::PersonId.eq(person.id))
+            .filter(level_promotions::Column::Level.eq(level))
+            .one(db)
+            .await?;
+        if existing.is_some() {
+            return Ok(None);
+        }
+
+        let Some(completed) = Self::check_level_completion(db, person.id, level).await? else {
+            return Ok(None);
+        };
+
+        let now = Utc::now().naive_utc();
+        let promotion = level_promotions::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            person_id: Set(person.id),
+            level: Set(completed.level),
+            status: Set(level_promotions::STATUS_PROPOSED.to_string()),
+            completed_at: Set(completed.completed_at),
+            validated_by_ids: Set(serde_json::json!(completed.validator_ids)),
+            promoted_at: Set(None),
+            promoted_by_id: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+        };
+
+        Ok(Some(promotion.insert(db).await?))
+    }
+
+    /// Applique une promotion: enregistre le niveau obtenu au club et la date
+    pub async fn apply(
+        db: &DatabaseConnection,
+        promotion: level_promotions::Model,
+        promoted_by_id: Option<Uuid>,
+    ) -> AppResult<level_promotions::Model> {
+        Self::check_can_apply(&promotion.status)?;
+
+        let person = People::find_by_id(promotion.person_id)
+            .one(db)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Person not found".to_string()))?;
+
+        let now = Utc::now().naive_utc();
+        let txn = db.begin().await?;
+
+        CertificationService::record_promotion(
+            &txn,
+            person.id,
+            &promotion.level,
+            now.date(),
+            promoted_by_id,
+        )
+        .await?;
+
+        let mut person: people::ActiveModel = person.into();
+        person.updated_at = Set(now);
+        person.update(&txn).await?;
+
+        let mut active: level_promotions::ActiveModel = promotion.into();
+        active.status = Set(level_promotions::STATUS_APPLIED.to_string());
+        active.promoted_at = Set(Some(now));
+        active.promoted_by_id = Set(promoted_by_id);
+        active.updated_at = Set(now);
+        let updated = active.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(updated)
+    }
+
+    /// Seules les promotions proposées peuvent être appliquées
+    fn check_can_apply(status: &str) -> AppResult<()> {
+        match status {
+            level_promotions::STATUS_PROPOSED => Ok(()),
+            level_promotions::STATUS_APPLIED => Err(AppError::Validation(
+                "Cette promotion a déjà été appliquée".to_string(),
+            )),
+            level_promotions::STATUS_REJECTED => Err(AppError::Validation(
+                "Cette promotion a été refusée".to_string(),
+            )),
+            _ => Err(AppError::Validation(
+                "Seules les promotions proposées peuvent être appliquées".to_string(),
+            )),
+        }
+    }
+
+    /// Marque une promotion proposée comme refusée
+    pub async fn reject(
+        db: &DatabaseConnection,
+        promotion: level_promotions::Model,
+    ) -> AppResult<level_promotions::Model> {
+        if promotion.status != level_promotions::STATUS_PROPOSED {
+            return Err(AppError::Validation(
+                "Seules les promotions proposées peuvent être refusées".to_string(),
+            ));
+        }
+
+        let mut active: level_promotions::ActiveModel = promotion.into();
+        active.status = Set(level_promotions::STATUS_REJECTED.to_string());
+        active.updated_at = Set(Utc::now().naive_utc());
+        Ok(active.update(db).await?)
+    }
+
+    /// Appelé après une validation finale: détecte un niveau complet,
+    /// applique la promotion si `auto_promote` et notifie l'élève et les admins
+    pub async fn handle_final_validation(
+        db: &DatabaseConnection,
+        email_service: &EmailService,
+        admin_emails: &[String],
+        auto_promote: bool,
+        person_id: Uuid,
+        skill_id: Uuid,
+    ) -> AppResult<Option<level_promotions::Model>> {
+        let Some(level) = Self::level_for_skill(db, skill_id).await? else {
+            return Ok(None);
+        };
+        let Some(person) = People::find_by_id(person_id).one(db).await? else {
+            return Ok(None);
+        };
+        let Some(mut promotion) = Self::propose(db, &person, &level).await? else {
+            return Ok(None);
+        };
+
+        if auto_promote {
+            promotion = Self::apply(db, promotion, None).await?;
+        }
+
+        Self::notify(db, email_service, admin_emails, &person, &promotion).await;
+
+        Ok(Some(promotion))
+    }
+
+    /// Envoie un email à l'élève et aux admins. Les erreurs d'envoi sont seulement loggées.
+    pub async fn notify(
+        db: &DatabaseConnection,
+        email_service: &EmailService,
+        admin_emails: &[String],
+        person: &people::Model,
+        promotion: &level_promotions::Model,
+    ) {
+        let person_name = format!("{} {}", person.first_name, person.last_name);
+        let validator_names = match Self::validators(db, promotion).await {
+            Ok(validators) => validators
+                .into_iter()
+                .map(|v| v.name)
+                .collect::<Vec<_>>()
+                .join(", "),
+            Err(e) => {
+                tracing::warn!("Failed to load validators for promotion {}: {}", promotion.id, e);
+                String::new()
+            }
+        };
+        let applied = promotion.status == level_promotions::STATUS_APPLIED;
+        let completed_at = promotion.completed_at.format("%d/%m/%Y").to_string();
+
+        let student_subject = format!("Félicitations - niveau {} complété", promotion.level);
+        let student_body = format!(
+            "<p>Bonjour {},</p>\
+             <p>Tous les acquis du niveau <strong>{}</strong> ont été validés (dernière validation le {}).</p>\
+             <p>{}</p>\
+             <p>Encadrants validateurs: {}</p>",
+            escape_html(&person.first_name),
+            escape_html(&promotion.level),
+            completed_at,
+            if applied {
+                "Ton niveau a été mis à jour automatiquement."
+            } else {
+                "Le passage de niveau a été proposé aux responsables techniques."
+            },
+            escape_html(&validator_names),
+        );
+        if let Err(e) = email_service
+            .send_email(&person.email, &person_name, &student_subject, &student_body)
+            .await
+        {
+            tracing::warn!("Failed to notify student {} of promotion: {}", person.email, e);
+        }
+
+        let admin_subject = format!(
+            "Passage de niveau {} {} - {}",
+            promotion.level,
+            if applied { "appliqué" } else { "à valider" },
+            person_name
+        );
+        let admin_body = format!(
+            "<p>{} a complété tous les acquis du niveau <strong>{}</strong> le {}.</p>\
+             <p>Encadrants validateurs: {}</p>\
+             <p>Statut: {}</p>",
+            escape_html(&person_name),
+            escape_html(&promotion.level),
+            completed_at,
+            escape_html(&validator_names),
+            escape_html(&promotion.status),
+        );
+        for admin_email in admin_emails {
+            if let Err(e) = email_service
+                .send_email(admin_email, admin_email, &admin_subject, &admin_body)
+                .await
+            {
+                tracing::warn!("Failed to notify admin {} of promotion: {}", admin_email, e);
+            }
+        }
+    }
+
+    /// Charge les encadrants validateurs d'une promotion
+    async fn validators(
+        db: &DatabaseConnection,
+        promotion: &level_promotions::Model,
+    ) -> AppResult<Vec<PromotionValidator>> {
+        let ids = promotion.validator_ids();
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let people_map: HashMap<Uuid, people::Model> = People::find()
+            .filter(people::Column::Id.is_in(ids.clone()))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|p| (p.id, p))
+            .collect();
+
+        Ok(ids
+            .into_iter()
+            .map(|id| PromotionValidator {
+                id,
+                name: people_map
+                    .get(&id)
+                    .map(|p| format!("{} {}", p.first_name, p.last_name))
+                    .unwrap_or_else(|| "Inconnu".to_string()),
+            })
+            .collect())
+    }
+
+    /// Construit la réponse API d'une promotion
+    pub async fn to_response(
+        db: &DatabaseConnection,
+        promotion: level_promotions::Model,
+    ) -> AppResult<LevelPromotionResponse> {
+        let validators = Self::validators(db, &promotion).await?;
+
+        let person = People::find_by_id(promotion.person_id).one(db).await?;
+        let promoted_by = match promotion.promoted_by_id {
+            Some(id) => People::find_by_id(id).one(db).await?,
+            None => None,
+        };
+
+        Ok(LevelPromotionResponse {
+            id: promotion.id,
+            person_id: promotion.person_id,
+            person_name: person.map(|p| format!("{} {}", p.first_name, p.last_name)),
+            level: promotion.level,
+            status: promotion.status,
+            completed_at: promotion.completed_at.to_string(),
+            validators,
+            promoted_at: promotion.promoted_at.map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string()),
+            promoted_by_name: promoted_by.map(|p| format!("{} {}", p.first_name, p.last_name)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_can_apply() {
+        assert!(PromotionService::check_can_apply(level_promotions::STATUS_PROPOSED).is_ok());
+        assert!(matches!(
+            PromotionService::check_can_apply(level_promotions::STATUS_APPLIED),
+            Err(AppError::Validation(_))
+        ));
+        assert!(matches!(
+            PromotionService::check_can_apply(level_promotions::STATUS_REJECTED),
+            Err(AppError::Validation(_))
+        ));
+    }
+}
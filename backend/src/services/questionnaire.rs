@@ -2,6 +2,7 @@ use crate::entities::prelude::*;
 use crate::entities::{email_jobs, people, questionnaires};
 use crate::errors::{AppError, AppResult};
 use crate::models::{CreateQuestionnaireRequest, QuestionnaireDetailResponse, QuestionnaireResponse, QuestionnaireTokenData, SubmitQuestionnaireRequest, UpdateQuestionnaireRequest};
+use crate::services::{CertificationService, LevelService, TeachingQualificationService};
 use chrono::Utc;
 use sea_orm::*;
 use uuid::Uuid;
@@ -91,14 +92,13 @@ impl QuestionnaireService {
         // Get session_id from email_job
         let _session_id = email_job.session_id;
 
-        let diving_level_display = person.diving_level.as_ref().and_then(|level_str| {
-            crate::models::DiverLevel::from_string(level_str).map(|diver_level| diver_level.display())
+        let diving_level = CertificationService::diving_level(db, person.id).await?;
+        let catalog = LevelService::catalog(db).await?;
+        let diving_level_display = diving_level.as_ref().and_then(|level_str| {
+            crate::models::DiverLevel::from_string(level_str, &catalog).map(|diver_level| diver_level.display())
         });
-        let is_instructor = person.diving_level.as_ref()
-            .and_then(|level_str| crate::models::DiverLevel::from_string(level_str))
-            .map(|diver_level| diver_level.is_instructor())
-            .unwrap_or(false);
-        let preparing_level = person.diving_level.as_ref()
+        let is_instructor = TeachingQualificationService::is_instructor(db, person.id).await?;
+        let preparing_level = diving_level.as_ref()
             .and_then(|level_str| crate::models::DiverLevel::extract_preparing_level(level_str));
 
         Ok(QuestionnaireTokenData {
@@ -115,7 +115,7 @@ impl QuestionnaireService {
                 default_wants_2nd_reg: person.default_wants_2nd_reg,
                 default_wants_stab: person.default_wants_stab,
                 default_stab_size: person.default_stab_size.clone(),
-                diving_level: person.diving_level,
+                diving_level,
                 diving_level_display,
                 is_instructor,
                 preparing_level,
@@ -301,10 +301,16 @@ impl QuestionnaireService {
                 AppError::Database(sea_orm::DbErr::Custom(format!("Failed to query questionnaires: {}", e)))
             })?;
 
+        let levels_map = CertificationService::diving_levels(
+            db,
+            questionnaires.iter().filter_map(|(_, p)| p.as_ref().map(|p| p.id)),
+        )
+        .await?;
         let mut responses = Vec::new();
 
         for (questionnaire, person_opt) in questionnaires {
             let person = person_opt.ok_or_else(|| AppError::NotFound("Person not found".to_string()))?;
+            let diving_level = levels_map.get(&person.id).cloned();
 
             // Find email job for this person and session to get magic link
             let email_job = EmailJobs::find()
@@ -345,10 +351,10 @@ impl QuestionnaireService {
                 comments: questionnaire.comments,
                 submitted_at: questionnaire.submitted_at.map(|dt| dt.to_string()),
                 magic_link,
-                diving_level: person.diving_level.clone(),
-                preparing_level: person.diving_level.as_ref()
+                preparing_level: diving_level.as_ref()
                     .and_then(|level_str| crate::models::DiverLevel::extract_preparing_level(level_str)),
-                email_status: email_status,
+                diving_level,
+                email_status,
             });
         }
 
@@ -542,7 +548,6 @@ impl QuestionnaireService {
                 default_wants_2nd_reg: Set(request.wants_2nd_reg),
                 default_wants_stab: Set(request.wants_stab),
                 default_stab_size: Set(request.stab_size.clone()),
-                diving_level: Set(None),
                 group_id: Set(None),
                 password_hash: Set(None),
                 temp_password: Set(None),
@@ -0,0 +1,525 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use rand::seq::SliceRandom;
+use sea_orm::*;
+use std::collections::{BTreeSet, HashMap};
+use uuid::Uuid;
+
+use crate::entities::prelude::*;
+use crate::entities::{people, quiz_attempts, quiz_questions, quizzes, skill_validations};
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    AttemptQuestion, QuestionCorrection, QuizAttemptResponse, QuizQuestionResponse, QuizResponse,
+};
+use crate::services::{SkillValidationService, ValidationSource};
+
+/// Délai accordé après l'heure limite (latence réseau à l'envoi)
+const SUBMIT_GRACE_SECONDS: i64 = 60;
+
+/// Résultat de la correction d'un passage
+#[derive(Debug)]
+pub struct Grade {
+    pub score: i32,
+    pub max_score: i32,
+    pub corrections: Vec<QuestionCorrection>,
+}
+
+/// Banque de questions et questionnaires théoriques en ligne
+pub struct QuizService;
+
+impl QuizService {
+    // ------------------------------------------------------------------------
+    // Banque de questions
+    // ------------------------------------------------------------------------
+
+    /// Vérifie les propositions d'une question: au moins deux, non vides, et
+    /// au moins une bonne réponse parmi elles
+    pub fn check_question(choices: &[String], correct_choices: &[usize]) -> AppResult<()> {
+        if choices.len() < 2 {
+            return Err(AppError::Validation(
+                "Une question doit avoir au moins deux propositions".to_string(),
+            ));
+        }
+        if choices.iter().any(|c| c.trim().is_empty()) {
+            return Err(AppError::Validation("Les propositions ne peuvent pas être vides".to_string()));
+        }
+        if correct_choices.is_empty() {
+            return Err(AppError::Validation("Indiquez au moins une bonne réponse".to_string()));
+        }
+        if correct_choices.iter().any(|i| *i >= choices.len()) {
+            return Err(AppError::Validation(
+                "Bonne réponse hors des propositions".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Bonnes réponses triées et sans doublon
+    pub fn normalize_choices(choices: &[usize]) -> Vec<usize> {
+        choices.iter().copied().collect::<BTreeSet<_>>().into_iter().collect()
+    }
+
+    pub fn question_response(
+        question: quiz_questions::Model,
+        domain_names: &HashMap<Uuid, String>,
+    ) -> QuizQuestionResponse {
+        QuizQuestionResponse {
+            id: question.id,
+            choices: question.choice_list(),
+            correct_choices: question.correct_list(),
+            domain_name: question.domain_id.and_then(|id| domain_names.get(&id).cloned()),
+            diving_level: question.diving_level,
+            domain_id: question.domain_id,
+            question: question.question,
+            explanation: question.explanation,
+            points: question.points,
+            is_active: question.is_active,
+        }
+    }
+
+    /// Questions actives pouvant être tirées pour un niveau (et un domaine)
+    pub async fn pool(
+        db: &DatabaseConnection,
+        diving_level: &str,
+        domain_id: Option<Uuid>,
+    ) -> AppResult<Vec<quiz_questions::Model>> {
+        let mut query = QuizQuestions::find()
+            .filter(quiz_questions::Column::DivingLevel.eq(diving_level))
+            .filter(quiz_questions::Column::IsActive.eq(true));
+        if let Some(domain_id) = domain_id {
+            query = query.filter(quiz_questions::Column::DomainId.eq(domain_id));
+        }
+        Ok(query.all(db).await?)
+    }
+
+    // ------------------------------------------------------------------------
+    // Questionnaires
+    // ------------------------------------------------------------------------
+
+    pub async fn quiz_response(db: &DatabaseConnection, quiz: quizzes::Model) -> AppResult<QuizResponse> {
+        let available = Self::pool(db, &quiz.diving_level, quiz.domain_id).await?.len();
+
+        Ok(QuizResponse {
+            id: quiz.id,
+            title: quiz.title,
+            description: quiz.description,
+            diving_level: quiz.diving_level,
+            domain_id: quiz.domain_id,
+            question_count: quiz.question_count,
+            available_questions: available as i32,
+            pass_percentage: quiz.pass_percentage,
+            time_limit_minutes: quiz.time_limit_minutes,
+            max_attempts: quiz.max_attempts,
+            show_corrections: quiz.show_corrections,
+            skill_id: quiz.skill_id,
+            stage_id: quiz.stage_id,
+            is_published: quiz.is_published,
+            created_by_id: quiz.created_by_id,
+        })
+    }
+
+    /// Vérifie l'acquis validé par un questionnaire: acquis et étape vont
+    /// ensemble, et l'encadrant responsable doit pouvoir les valider
+    pub async fn check_validation_target(
+        db: &DatabaseConnection,
+        responsible: &people::Model,
+        skill_id: Option<Uuid>,
+        stage_id: Option<Uuid>,
+    ) -> AppResult<()> {
+        let (skill_id, stage_id) = match (skill_id, stage_id) {
+            (None, None) => return Ok(()),
+            (Some(skill_id), Some(stage_id)) => (skill_id, stage_id),
+            _ => {
+                return Err(AppError::Validation(
+                    "L'acquis et l'étape de validation vont ensemble".to_string(),
+                ))
+            }
+        };
+
+        let skill = CompetencySkills::find_by_id(skill_id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Acquis non trouvé".to_string()))?;
+        let stage = ValidationStages::find_by_id(stage_id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Étape non trouvée".to_string()))?;
+
+        SkillValidationService::check_validator_level(db, responsible, &skill, &stage).await
+    }
+
+    // ------------------------------------------------------------------------
+    // Passages
+    // ------------------------------------------------------------------------
+
+    /// Heure limite d'envoi d'un passage chronométré
+    pub fn deadline(quiz: &quizzes::Model, attempt: &quiz_attempts::Model) -> Option<NaiveDateTime> {
+        quiz.time_limit_minutes
+            .map(|minutes| attempt.started_at + Duration::minutes(minutes as i64))
+    }
+
+    /// Commence un passage, ou reprend celui en cours
+    pub async fn start_attempt(
+        db: &DatabaseConnection,
+        quiz: &quizzes::Model,
+        person_id: Uuid,
+    ) -> AppResult<quiz_attempts::Model> {
+        if !quiz.is_published {
+            return Err(AppError::NotFound("Questionnaire non trouvé".to_string()));
+        }
+
+        let attempts = QuizAttempts::find()
+            .filter(quiz_attempts::Column::QuizId.eq(quiz.id))
+            .filter(quiz_attempts::Column::PersonId.eq(person_id))
+            .all(db)
+            .await?;
+
+        let now = Utc::now().naive_utc();
+        if let Some(open) = attempts.iter().find(|a| a.submitted_at.is_none()) {
+            let expired = Self::deadline(quiz, open)
+                .is_some_and(|d| now > d + Duration::seconds(SUBMIT_GRACE_SECONDS));
+            if !expired {
+                return Ok(open.clone());
+            }
+            // Temps écoulé sans envoi: le passage est clos sans réponse
+            Self::submit_attempt(db, quiz, open.clone(), HashMap::new()).await?;
+        }
+
+        if let Some(max) = quiz.max_attempts {
+            if attempts.len() as i32 >= max {
+                return Err(AppError::Validation(format!(
+                    "Nombre maximal de tentatives atteint ({})",
+                    max
+                )));
+            }
+        }
+
+        let mut pool = Self::pool(db, &quiz.diving_level, quiz.domain_id).await?;
+        if pool.is_empty() {
+            return Err(AppError::Validation(
+                "Aucune question disponible pour ce questionnaire".to_string(),
+            ));
+        }
+        pool.shuffle(&mut rand::thread_rng());
+        if let Some(count) = quiz.question_count {
+            pool.truncate(count.max(1) as usize);
+        }
+
+        let question_ids: Vec<Uuid> = pool.iter().map(|q| q.id).collect();
+        let max_score = pool.iter().map(|q| q.points).sum();
+
+        Ok(quiz_attempts::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            quiz_id: Set(quiz.id),
+            person_id: Set(person_id),
+            question_ids: Set(serde_json::json!(question_ids)),
+            answers: Set(None),
+            score: Set(None),
+            max_score: Set(max_score),
+            percentage: Set(None),
+            passed: Set(None),
+            started_at: Set(now),
+            submitted_at: Set(None),
+            recorded_by_id: Set(None),
+            validation_id: Set(None),
+        }
+        .insert(db)
+        .await?)
+    }
+
+    /// Corrige les réponses: une question rapporte ses points si les
+    /// propositions cochées sont exactement les bonnes
+    pub fn grade(questions: &[quiz_questions::Model], answers: &HashMap<Uuid, Vec<usize>>) -> Grade {
+        let mut score = 0;
+        let mut max_score = 0;
+        let mut corrections = Vec::with_capacity(questions.len());
+
+        for question in questions {
+            let selected = Self::normalize_choices(answers.get(&question.id).map(Vec::as_slice).unwrap_or(&[]));
+            let correct_choices = Self::normalize_choices(&question.correct_list());
+            let is_correct = selected == correct_choices;
+
+            max_score += question.points;
+            if is_correct {
+                score += question.points;
+            }
+            corrections.push(QuestionCorrection {
+                question_id: question.id,
+                selected,
+                correct_choices,
+                is_correct,
+                explanation: question.explanation.clone(),
+            });
+        }
+
+        Grade { score, max_score, corrections }
+    }
+
+    pub fn percentage(score: i32, max_score: i32) -> f32 {
+        if max_score <= 0 {
+            return 0.0;
+        }
+        score as f32 * 100.0 / max_score as f32
+    }
+
+    /// Questions d'un passage, dans l'ordre du tirage (les questions
+    /// supprimées de la banque depuis sont ignorées)
+    async fn attempt_questions(
+        db: &DatabaseConnection,
+        attempt: &quiz_attempts::Model,
+    ) -> AppResult<Vec<quiz_questions::Model>> {
+        let ids = attempt.question_list();
+        let mut by_id: HashMap<Uuid, quiz_questions::Model> = QuizQuestions::find()
+            .filter(quiz_questions::Column::Id.is_in(ids.clone()))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|q| (q.id, q))
+            .collect();
+        Ok(ids.into_iter().filter_map(|id| by_id.remove(&id)).collect())
+    }
+
+    /// Corrige et enregistre un passage. Envoyé après l'heure limite, il est
+    /// corrigé comme sans réponse.
+    pub async fn submit_attempt(
+        db: &DatabaseConnection,
+        quiz: &quizzes::Model,
+        attempt: quiz_attempts::Model,
+        answers: HashMap<Uuid, Vec<usize>>,
+    ) -> AppResult<quiz_attempts::Model> {
+        if attempt.submitted_at.is_some() {
+            return Err(AppError::Validation("Ce passage a déjà été envoyé".to_string()));
+        }
+
+        let now = Utc::now().naive_utc();
+        let late = Self::deadline(quiz, &attempt)
+            .is_some_and(|d| now > d + Duration::seconds(SUBMIT_GRACE_SECONDS));
+        let answers = if late { HashMap::new() } else { answers };
+
+        let questions = Self::attempt_questions(db, &attempt).await?;
+        let grade = Self::grade(&questions, &answers);
+        let percentage = Self::percentage(grade.score, grade.max_score);
+
+        let mut active: quiz_attempts::ActiveModel = attempt.into();
+        active.answers = Set(Some(serde_json::json!(answers)));
+        active.score = Set(Some(grade.score));
+        active.max_score = Set(grade.max_score);
+        active.percentage = Set(Some(percentage));
+        active.passed = Set(Some(percentage >= quiz.pass_percentage as f32));
+        active.submitted_at = Set(Some(now));
+        Ok(active.update(db).await?)
+    }
+
+    /// Enregistre le résultat d'un examen passé sur papier
+    pub async fn record_result(
+        db: &DatabaseConnection,
+        quiz: &quizzes::Model,
+        person_id: Uuid,
+        recorded_by: &people::Model,
+        score: i32,
+        max_score: i32,
+        taken_at: NaiveDate,
+    ) -> AppResult<quiz_attempts::Model> {
+        if score > max_score {
+            return Err(AppError::Validation("La note dépasse le barème".to_string()));
+        }
+
+        let taken_at = taken_at.and_hms_opt(0, 0, 0).unwrap_or_default();
+        let percentage = Self::percentage(score, max_score);
+
+        Ok(quiz_attempts::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            quiz_id: Set(quiz.id),
+            person_id: Set(person_id),
+            question_ids: Set(serde_json::json!([])),
+            answers: Set(None),
+            score: Set(Some(score)),
+            max_score: Set(max_score),
+            percentage: Set(Some(percentage)),
+            passed: Set(Some(percentage >= quiz.pass_percentage as f32)),
+            started_at: Set(taken_at),
+            submitted_at: Set(Some(taken_at)),
+            recorded_by_id: Set(Some(recorded_by.id)),
+            validation_id: Set(None),
+        }
+        .insert(db)
+        .await?)
+    }
+
+    /// Valide l'acquis du questionnaire pour un passage réussi, au nom de
+    /// l'encadrant responsable. Retourne la validation et si l'étape est finale.
+    pub async fn validate_skill(
+        db: &DatabaseConnection,
+        quiz: &quizzes::Model,
+        attempt: &quiz_attempts::Model,
+    ) -> AppResult<Option<(skill_validations::Model, bool)>> {
+        let (Some(skill_id), Some(stage_id)) = (quiz.skill_id, quiz.stage_id) else {
+            return Ok(None);
+        };
+        if attempt.passed != Some(true) {
+            return Ok(None);
+        }
+
+        let validator = match quiz.created_by_id {
+            Some(id) => People::find_by_id(id).one(db).await?,
+            None => None,
+        }
+        .ok_or(AppError::Validation(
+            "Le questionnaire n'a plus d'encadrant responsable".to_string(),
+        ))?;
+        let skill = CompetencySkills::find_by_id(skill_id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Acquis non trouvé".to_string()))?;
+        let stage = ValidationStages::find_by_id(stage_id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Étape non trouvée".to_string()))?;
+
+        let notes = format!(
+            "Examen théorique « {} »: {}/{} ({:.0} %)",
+            quiz.title,
+            attempt.score.unwrap_or(0),
+            attempt.max_score,
+            attempt.percentage.unwrap_or(0.0)
+        );
+        let validated_at = attempt
+            .submitted_at
+            .map(|d| d.date())
+            .unwrap_or_else(|| Utc::now().naive_utc().date());
+
+        let validation = SkillValidationService::upsert(
+            db,
+            &validator,
+            false,
+            attempt.person_id,
+            &skill,
+            &stage,
+            validated_at,
+            Some(notes),
+            ValidationSource::default(),
+        )
+        .await?;
+
+        let mut active: quiz_attempts::ActiveModel = attempt.clone().into();
+        active.validation_id = Set(Some(validation.id));
+        active.update(db).await?;
+
+        Ok(Some((validation, stage.is_final)))
+    }
+
+    pub async fn attempt_response(
+        db: &DatabaseConnection,
+        quiz: &quizzes::Model,
+        attempt: quiz_attempts::Model,
+        person_name: Option<String>,
+    ) -> AppResult<QuizAttemptResponse> {
+        let submitted = attempt.submitted_at.is_some();
+        let questions = Self::attempt_questions(db, &attempt).await?;
+
+        let (to_answer, corrections) = if !submitted {
+            let to_answer = questions
+                .iter()
+                .map(|q| AttemptQuestion {
+                    id: q.id,
+                    question: q.question.clone(),
+                    choices: q.choice_list(),
+                    points: q.points,
+                })
+                .collect();
+            (to_answer, None)
+        } else if quiz.show_corrections && attempt.recorded_by_id.is_none() {
+            let answers: HashMap<Uuid, Vec<usize>> = attempt
+                .answers
+                .clone()
+                .and_then(|a| serde_json::from_value(a).ok())
+                .unwrap_or_default();
+            (vec![], Some(Self::grade(&questions, &answers).corrections))
+        } else {
+            (vec![], None)
+        };
+
+        Ok(QuizAttemptResponse {
+            id: attempt.id,
+            quiz_id: quiz.id,
+            quiz_title: quiz.title.clone(),
+            person_id: attempt.person_id,
+            person_name,
+            started_at: attempt.started_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            deadline: Self::deadline(quiz, &attempt)
+                .filter(|_| !submitted)
+                .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string()),
+            submitted_at: attempt
+                .submitted_at
+                .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string()),
+            questions: to_answer,
+            score: attempt.score,
+            max_score: attempt.max_score,
+            percentage: attempt.percentage,
+            passed: attempt.passed,
+            recorded_by_id: attempt.recorded_by_id,
+            validation_id: attempt.validation_id,
+            corrections,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn question(points: i32, correct: &[usize]) -> quiz_questions::Model {
+        let now = Utc::now().naive_utc();
+        quiz_questions::Model {
+            id: Uuid::new_v4(),
+            diving_level: "N2".to_string(),
+            domain_id: None,
+            question: "?".to_string(),
+            choices: serde_json::json!(["a", "b", "c", "d"]),
+            correct_choices: serde_json::json!(correct),
+            explanation: None,
+            points,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_grade_requires_exact_choices() {
+        let single = question(1, &[2]);
+        let multiple = question(2, &[0, 3]);
+        let unanswered = question(1, &[1]);
+        let questions = vec![single.clone(), multiple.clone(), unanswered];
+
+        let answers = HashMap::from([
+            (single.id, vec![2]),
+            // Ordre et doublons sans importance
+            (multiple.id, vec![3, 0, 3]),
+        ]);
+        let grade = QuizService::grade(&questions, &answers);
+        assert_eq!((grade.score, grade.max_score), (3, 4));
+        assert!(!grade.corrections[2].is_correct);
+
+        // Une bonne réponse sur deux ne rapporte rien
+        let answers = HashMap::from([(multiple.id, vec![0])]);
+        let grade = QuizService::grade(&questions, &answers);
+        assert_eq!(grade.score, 0);
+        assert_eq!(grade.corrections[1].correct_choices, vec![0, 3]);
+    }
+
+    #[test]
+    fn test_check_question() {
+        let choices = vec!["Vrai".to_string(), "Faux".to_string()];
+        assert!(QuizService::check_question(&choices, &[0]).is_ok());
+        assert!(QuizService::check_question(&choices, &[]).is_err());
+        assert!(QuizService::check_question(&choices, &[2]).is_err());
+        assert!(QuizService::check_question(&choices[..1], &[0]).is_err());
+        assert!(QuizService::check_question(&["Vrai".to_string(), " ".to_string()], &[0]).is_err());
+    }
+
+    #[test]
+    fn test_percentage() {
+        assert_eq!(QuizService::percentage(15, 20), 75.0);
+        assert_eq!(QuizService::percentage(0, 0), 0.0);
+    }
+}
@@ -0,0 +1,459 @@
+use chrono::{Months, NaiveDate, Utc};
+use sea_orm::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::entities::prelude::*;
+use crate::entities::{
+    competency_domains, competency_modules, competency_skills, people, skill_validations,
+    validation_stages,
+};
+use crate::errors::{AppError, AppResult};
+use crate::models::{LevelCatalog, ValidatorRule};
+use crate::services::{LevelService, TeachingQualificationService, ValidationHistoryService};
+
+/// Origine d'une validation (plongée de formation)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidationSource {
+    pub session_id: Option<Uuid>,
+    pub palanquee_id: Option<Uuid>,
+}
+
+/// Règles communes aux validations unitaires et groupées
+pub struct SkillValidationService;
+
+impl SkillValidationService {
+    /// Règle effective d'un acquis: sa règle dédiée, sinon le niveau minimum
+    pub fn requirement(skill: &competency_skills::Model) -> String {
+        skill
+            .validator_rule
+            .clone()
+            .filter(|r| !r.trim().is_empty())
+            .unwrap_or_else(|| skill.min_validator_level.clone())
+    }
+
+    /// Indique si un plongeur (chaîne de niveaux "N4,E1") peut valider un
+    /// acquis, d'après sa règle ou à défaut son niveau minimum
+    pub fn can_validate(
+        catalog: &LevelCatalog,
+        diving_level: &str,
+        min_validator_level: &str,
+        validator_rule: Option<&str>,
+    ) -> bool {
+        match validator_rule.filter(|r| !r.trim().is_empty()) {
+            Some(rule) => ValidatorRule::parse(rule)
+                .map(|rule| rule.is_satisfied(diving_level, catalog))
+                .unwrap_or(false),
+            None => catalog.meets_min_level(diving_level, min_validator_level),
+        }
+    }
+
+    fn can_validate_skill(
+        catalog: &LevelCatalog,
+        diving_level: &str,
+        skill: &competency_skills::Model,
+    ) -> bool {
+        Self::can_validate(
+            catalog,
+            diving_level,
+            &skill.min_validator_level,
+            skill.validator_rule.as_deref(),
+        )
+    }
+
+    /// Vérifie une règle saisie et la met en forme. Une chaîne vide supprime la règle.
+    pub fn normalize_rule(catalog: &LevelCatalog, rule: &str) -> AppResult<Option<String>> {
+        if rule.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let parsed = ValidatorRule::parse(rule)
+            .map_err(|e| AppError::Validation(format!("Règle de validation invalide: {}", e)))?;
+        let unknown = parsed.unknown_levels(catalog);
+        if !unknown.is_empty() {
+            return Err(AppError::Validation(format!(
+                "Niveaux inconnus dans la règle de validation: {}",
+                unknown.join(", ")
+            )));
+        }
+
+        Ok(Some(parsed.to_string()))
+    }
+
+    /// Vérifie que le validateur satisfait la règle de l'acquis et, si
+    /// l'étape visée en a une, celle de l'étape
+    pub async fn check_validator_level<C: ConnectionTrait>(
+        conn: &C,
+        validator: &people::Model,
+        skill: &competency_skills::Model,
+        stage: &validation_stages::Model,
+    ) -> AppResult<()> {
+        // Les titres d'encadrement expirés faute de recyclage ne comptent pas
+        let Some(validator_level_str) = TeachingQualificationService::teaching_level(conn, validator.id).await? else {
+            return Err(AppError::Forbidden(
+                "Vous n'avez pas de niveau de plongée enregistré".to_string(),
+            ));
+        };
+
+        let catalog = LevelService::catalog(conn).await?;
+        if !Self::can_validate_skill(&catalog, &validator_level_str, skill) {
+            return Err(AppError::Forbidden(format!(
+                "Niveau requis pour valider: {} (votre niveau: {})",
+                Self::requirement(skill),
+                validator_level_str
+            )));
+        }
+
+        if let Some(rule) = stage.validator_rule.as_deref() {
+            if !Self::can_validate(&catalog, &validator_level_str, "", Some(rule)) {
+                return Err(AppError::Forbidden(format!(
+                    "Niveau requis pour l'étape « {} »: {} (votre niveau: {})",
+                    stage.name, rule, validator_level_str
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Vérifie le passage d'un acquis de l'étape `current` à `target` selon
+    /// le workflow des étapes. Sans transitions configurées, seule la
+    /// progression vers l'avant est permise. Les vrais administrateurs
+    /// (`is_admin`) peuvent effectuer n'importe quelle transition.
+    pub fn check_transition(
+        current: Option<&validation_stages::Model>,
+        target: &validation_stages::Model,
+        is_admin: bool,
+    ) -> AppResult<()> {
+        let Some(current) = current.filter(|c| c.id != target.id) else {
+            return Ok(());
+        };
+        if is_admin {
+            return Ok(());
+        }
+
+        match current.allowed_next() {
+            Some(allowed) if !allowed.contains(&target.id) => Err(AppError::Forbidden(format!(
+                "Passage de l'étape « {} » à « {} » non autorisé",
+                current.name, target.name
+            ))),
+            Some(_) => Ok(()),
+            None if target.sort_order < current.sort_order => Err(AppError::Forbidden(
+                "Vous ne pouvez pas revenir en arrière sur une étape de validation. Seuls les administrateurs peuvent le faire.".to_string()
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Étape suivante suggérée depuis `current` (`stages` triées par ordre):
+    /// la première des transitions configurées, sinon la première étape
+    /// d'ordre supérieur
+    pub fn next_stage<'a>(
+        stages: &'a [validation_stages::Model],
+        current: Option<&validation_stages::Model>,
+    ) -> Option<&'a validation_stages::Model> {
+        let Some(current) = current else {
+            return stages.first();
+        };
+        match current.allowed_next() {
+            Some(allowed) => stages
+                .iter()
+                .find(|s| s.id != current.id && allowed.contains(&s.id)),
+            None => stages.iter().find(|s| s.sort_order > current.sort_order),
+        }
+    }
+
+    /// Vérifie qu'un validateur peut placer l'acquis d'un élève à l'étape
+    /// visée: auto-évaluation sur ses propres acquis, sinon règles de
+    /// l'acquis et de l'étape, puis transition depuis l'étape actuelle
+    async fn check_stage_change<C: ConnectionTrait>(
+        conn: &C,
+        validator: &people::Model,
+        is_admin: bool,
+        person_id: Uuid,
+        skill: &competency_skills::Model,
+        current: Option<&validation_stages::Model>,
+        target: &validation_stages::Model,
+    ) -> AppResult<()> {
+        if person_id == validator.id && !is_admin {
+            if !target.self_assessable {
+                return Err(AppError::Forbidden(format!(
+                    "L'étape « {} » ne peut pas être attribuée à soi-même",
+                    target.name
+                )));
+            }
+        } else {
+            Self::check_validator_level(conn, validator, skill, target).await?;
+        }
+
+        Self::check_transition(current, target, is_admin)
+    }
+
+    /// Date à laquelle une validation expire (None: validité illimitée)
+    pub fn expires_at(validated_at: NaiveDate, validity_months: Option<i32>) -> Option<NaiveDate> {
+        let months = u32::try_from(validity_months?).ok().filter(|m| *m > 0)?;
+        validated_at.checked_add_months(Months::new(months))
+    }
+
+    /// Indique si une validation est expirée à la date `today`
+    pub fn is_expired(validated_at: NaiveDate, validity_months: Option<i32>, today: NaiveDate) -> bool {
+        Self::expires_at(validated_at, validity_months).is_some_and(|expires| expires <= today)
+    }
+
+    /// Durée de validité effective des acquis: celle de l'acquis, sinon celle
+    /// de son domaine. Les acquis à validité illimitée sont absents.
+    pub async fn validity_periods<C: ConnectionTrait>(
+        conn: &C,
+        skill_ids: impl IntoIterator<Item = Uuid>,
+    ) -> AppResult<HashMap<Uuid, i32>> {
+        let skills = CompetencySkills::find()
+            .filter(competency_skills::Column::Id.is_in(skill_ids))
+            .all(conn)
+            .await?;
+        let modules: HashMap<Uuid, Uuid> = CompetencyModules::find()
+            .filter(competency_modules::Column::Id.is_in(skills.iter().map(|s| s.module_id)))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|m| (m.id, m.domain_id))
+            .collect();
+        let domains: HashMap<Uuid, Option<i32>> = CompetencyDomains::find()
+            .filter(competency_domains::Column::Id.is_in(modules.values().copied()))
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|d| (d.id, d.validity_months))
+            .collect();
+
+        Ok(skills
+            .into_iter()
+            .filter_map(|skill| {
+                let domain_months = modules
+                    .get(&skill.module_id)
+                    .and_then(|domain_id| domains.get(domain_id).copied().flatten());
+                skill.validity_months.or(domain_months).map(|months| (skill.id, months))
+            })
+            .collect())
+    }
+
+    /// Indique si une validation est expirée, d'après les durées de `validity_periods`
+    pub fn is_validation_expired(
+        validation: &skill_validations::Model,
+        periods: &HashMap<Uuid, i32>,
+        today: NaiveDate,
+    ) -> bool {
+        Self::is_expired(validation.validated_at, periods.get(&validation.skill_id).copied(), today)
+    }
+
+    /// Personnes du club pouvant valider l'acquis, avec leur niveau
+    pub async fn eligible_validators<C: ConnectionTrait>(
+        conn: &C,
+        skill: &competency_skills::Model,
+    ) -> AppResult<Vec<(people::Model, String)>> {
+        let catalog = LevelService::catalog(conn).await?;
+        let people = People::find()
+            .order_by_asc(people::Column::LastName)
+            .order_by_asc(people::Column::FirstName)
+            .all(conn)
+            .await?;
+        let mut levels =
+            TeachingQualificationService::teaching_levels(conn, people.iter().map(|p| p.id)).await?;
+
+        Ok(people
+            .into_iter()
+            .filter_map(|person| {
+                let level = levels.remove(&person.id)?;
+                Self::can_validate_skill(&catalog, &level, skill).then_some((person, level))
+            })
+            .collect())
+    }
+
+    /// Crée ou met à jour la validation (person, skill) et enregistre la
+    /// transition dans l'historique, après avoir vérifié le workflow des
+    /// étapes (voir `check_stage_change`). `is_admin`: vrai administrateur.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn upsert(
+        db: &DatabaseConnection,
+        validator: &people::Model,
+        is_admin: bool,
+        person_id: Uuid,
+        skill: &competency_skills::Model,
+        stage: &validation_stages::Model,
+        validated_at: NaiveDate,
+        notes: Option<String>,
+        source: ValidationSource,
+    ) -> AppResult<skill_validations::Model> {
+        let txn = db.begin().await?;
+        let validation = Self::upsert_in(
+            &txn,
+            validator,
+            is_admin,
+            person_id,
+            skill,
+            stage,
+            validated_at,
+            notes,
+            source,
+        )
+        .await?;
+        txn.commit().await?;
+
+        Ok(validation)
+    }
+
+    /// Comme `upsert`, dans une transaction ouverte par l'appelant
+    #[allow(clippy::too_many_arguments)]
+    pub async fn upsert_in(
+        txn: &DatabaseTransaction,
+        validator: &people::Model,
+        is_admin: bool,
+        person_id: Uuid,
+        skill: &competency_skills::Model,
+        stage: &validation_stages::Model,
+        validated_at: NaiveDate,
+        notes: Option<String>,
+        source: ValidationSource,
+    ) -> AppResult<skill_validations::Model> {
+        let existing = SkillValidations::find()
+            .filter(skill_validations::Column::PersonId.eq(person_id))
+            .filter(skill_validations::Column::SkillId.eq(skill.id))
+            .one(txn)
+            .await?;
+
+        let current_stage = match &existing {
+            Some(existing) => ValidationStages::find_by_id(existing.stage_id).one(txn).await?,
+            None => None,
+        };
+        Self::check_stage_change(
+            txn,
+            validator,
+            is_admin,
+            person_id,
+            skill,
+            current_stage.as_ref(),
+            stage,
+        )
+        .await?;
+
+        let now = Utc::now().naive_utc();
+
+        let validation = if let Some(existing) = existing.clone() {
+            let mut existing: skill_validations::ActiveModel = existing.into();
+            existing.stage_id = Set(stage.id);
+            existing.validated_at = Set(validated_at);
+            existing.validated_by_id = Set(validator.id);
+            existing.notes = Set(notes);
+            // Sans plongée indiquée, la source enregistrée est conservée
+            if source.session_id.is_some() || source.palanquee_id.is_some() {
+                existing.session_id = Set(source.session_id);
+                existing.palanquee_id = Set(source.palanquee_id);
+            }
+            existing.updated_at = Set(now);
+            existing.update(txn).await.map_err(|e| {
+                AppError::Database(DbErr::Custom(format!("Failed to update: {}", e)))
+            })?
+        } else {
+            skill_validations::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                person_id: Set(person_id),
+                skill_id: Set(skill.id),
+                stage_id: Set(stage.id),
+                validated_at: Set(validated_at),
+                validated_by_id: Set(validator.id),
+                notes: Set(notes),
+                session_id: Set(source.session_id),
+                palanquee_id: Set(source.palanquee_id),
+                created_at: Set(now),
+                updated_at: Set(now),
+            }
+            .insert(txn)
+            .await
+            .map_err(|e| AppError::Database(DbErr::Custom(format!("Failed to create: {}", e))))?
+        };
+
+        ValidationHistoryService::record(txn, existing.as_ref(), &validation, Some(validator.id))
+            .await?;
+
+        Ok(validation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stage(name: &str, sort_order: i32, allowed_next: Option<Vec<Uuid>>) -> validation_stages::Model {
+        let now = Utc::now().naive_utc();
+        validation_stages::Model {
+            id: Uuid::new_v4(),
+            code: name.to_lowercase(),
+            name: name.to_string(),
+            description: None,
+            color: "#6B7280".to_string(),
+            icon: "⏳".to_string(),
+            sort_order,
+            is_final: false,
+            allowed_next_stage_ids: allowed_next.map(|ids| serde_json::json!(ids)),
+            validator_rule: None,
+            self_assessable: false,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_expiry() {
+        let validated_at = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        assert_eq!(
+            SkillValidationService::expires_at(validated_at, Some(1)),
+            NaiveDate::from_ymd_opt(2024, 2, 29)
+        );
+        assert_eq!(SkillValidationService::expires_at(validated_at, None), None);
+        assert_eq!(SkillValidationService::expires_at(validated_at, Some(0)), None);
+
+        let today = NaiveDate::from_ymd_opt(2025, 1, 31).unwrap();
+        assert!(SkillValidationService::is_expired(validated_at, Some(12), today));
+        assert!(!SkillValidationService::is_expired(validated_at, Some(13), today));
+        assert!(!SkillValidationService::is_expired(validated_at, None, today));
+    }
+
+    #[test]
+    fn test_next_stage() {
+        let aisance = stage("Aisance", 3, None);
+        let mer = stage("Mer", 2, None);
+        let piscine = stage("Piscine", 1, Some(vec![aisance.id]));
+        let stages = vec![piscine.clone(), mer.clone(), aisance.clone()];
+
+        let next = |current: Option<&validation_stages::Model>| {
+            SkillValidationService::next_stage(&stages, current).map(|s| s.id)
+        };
+        assert_eq!(next(None), Some(piscine.id));
+        // Transitions configurées: l'étape d'ordre suivant n'est pas proposée
+        assert_eq!(next(Some(&piscine)), Some(aisance.id));
+        assert_eq!(next(Some(&mer)), Some(aisance.id));
+        assert_eq!(next(Some(&aisance)), None);
+    }
+
+    #[test]
+    fn test_check_transition() {
+        let piscine = stage("Piscine", 1, None);
+        let fosse = stage("Fosse", 2, None);
+        let mer = stage("Mer", 3, None);
+
+        // Progression libre: vers l'avant uniquement, sauf administrateurs
+        assert!(SkillValidationService::check_transition(None, &mer, false).is_ok());
+        assert!(SkillValidationService::check_transition(Some(&piscine), &mer, false).is_ok());
+        assert!(SkillValidationService::check_transition(Some(&mer), &mer, false).is_ok());
+        assert!(SkillValidationService::check_transition(Some(&mer), &piscine, false).is_err());
+        assert!(SkillValidationService::check_transition(Some(&mer), &piscine, true).is_ok());
+
+        // Transitions configurées: seules les étapes listées sont accessibles
+        let piscine = stage("Piscine", 1, Some(vec![fosse.id]));
+        assert!(SkillValidationService::check_transition(Some(&piscine), &fosse, false).is_ok());
+        assert!(SkillValidationService::check_transition(Some(&piscine), &mer, false).is_err());
+        assert!(SkillValidationService::check_transition(Some(&piscine), &mer, true).is_ok());
+
+        let fosse = stage("Fosse", 2, Some(vec![piscine.id, mer.id]));
+        assert!(SkillValidationService::check_transition(Some(&fosse), &piscine, false).is_ok());
+    }
+}
@@ -0,0 +1,110 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::config::StorageConfig;
+use crate::errors::{AppError, AppResult};
+
+/// Backend de stockage des fichiers
+#[derive(Debug, Clone)]
+pub enum Storage {
+    /// Répertoire du système de fichiers local
+    Local { root: PathBuf },
+}
+
+impl Storage {
+    pub fn from_config(config: &StorageConfig) -> Result<Self, String> {
+        match config.backend.as_str() {
+            "local" => Ok(Storage::Local {
+                root: PathBuf::from(&config.path),
+            }),
+            other => Err(format!("Backend de stockage inconnu: {}", other)),
+        }
+    }
+
+    pub async fn put(&self, key: &str, data: &[u8]) -> AppResult<()> {
+        match self {
+            Storage::Local { root } => {
+                let path = local_path(root, key)?;
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                        AppError::Internal(format!("Failed to create storage directory: {}", e))
+                    })?;
+                }
+                tokio::fs::write(&path, data)
+                    .await
+                    .map_err(|e| AppError::Internal(format!("Failed to write file: {}", e)))
+            }
+        }
+    }
+
+    pub async fn get(&self, key: &str) -> AppResult<Vec<u8>> {
+        match self {
+            Storage::Local { root } => {
+                let path = local_path(root, key)?;
+                tokio::fs::read(&path).await.map_err(|e| match e.kind() {
+                    std::io::ErrorKind::NotFound => {
+                        AppError::NotFound("Fichier introuvable dans le stockage".to_string())
+                    }
+                    _ => AppError::Internal(format!("Failed to read file: {}", e)),
+                })
+            }
+        }
+    }
+
+    /// Supprime un fichier; un fichier déjà absent n'est pas une erreur
+    pub async fn delete(&self, key: &str) -> AppResult<()> {
+        match self {
+            Storage::Local { root } => {
+                let path = local_path(root, key)?;
+                match tokio::fs::remove_file(&path).await {
+                    Ok(()) => Ok(()),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                    Err(e) => Err(AppError::Internal(format!("Failed to delete file: {}", e))),
+                }
+            }
+        }
+    }
+}
+
+/// Chemin d'une clé sous la racine locale. Les clés sont générées par le
+/// serveur, mais on refuse tout ce qui pourrait sortir de la racine.
+fn local_path(root: &Path, key: &str) -> AppResult<PathBuf> {
+    let relative = Path::new(key);
+    if key.is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(AppError::Internal(format!("Invalid storage key: {}", key)));
+    }
+    Ok(root.join(relative))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_path_rejects_escaping_keys() {
+        let root = Path::new("/srv/storage");
+
+        assert_eq!(
+            local_path(root, "evidence/a/b").unwrap(),
+            PathBuf::from("/srv/storage/evidence/a/b")
+        );
+        assert!(local_path(root, "").is_err());
+        assert!(local_path(root, "../etc/passwd").is_err());
+        assert!(local_path(root, "evidence/../../x").is_err());
+        assert!(local_path(root, "/etc/passwd").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_local_roundtrip() {
+        let root = std::env::temp_dir().join(format!("storage-test-{}", uuid::Uuid::new_v4()));
+        let storage = Storage::Local { root: root.clone() };
+
+        storage.put("evidence/v/f", b"data").await.unwrap();
+        assert_eq!(storage.get("evidence/v/f").await.unwrap(), b"data");
+
+        storage.delete("evidence/v/f").await.unwrap();
+        assert!(matches!(storage.get("evidence/v/f").await, Err(AppError::NotFound(_))));
+        storage.delete("evidence/v/f").await.unwrap();
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,546 @@
+use chrono::{Duration, NaiveDate, Utc};
+use sea_orm::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::entities::prelude::*;
+use crate::entities::{diving_levels, people, teaching_qualifications, teaching_recyclings};
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    CreateTeachingQualificationRequest, DiverLevel, RecordRecyclingRequest,
+    TeachingQualificationResponse, TeachingQualificationsQuery, TeachingRecyclingResponse,
+    UpdateTeachingQualificationRequest,
+};
+use crate::services::equivalences::EffectiveLevel;
+use crate::services::{
+    CertificationService, EquivalenceService, LevelService, SkillValidationService,
+};
+
+fn parse_date(date: &str) -> AppResult<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| AppError::Validation("Format de date invalide (YYYY-MM-DD)".to_string()))
+}
+
+/// Titres d'encadrement et recyclages; un titre expiré ne compte plus pour encadrer
+pub struct TeachingQualificationService;
+
+impl TeachingQualificationService {
+    // ------------------------------------------------------------------------
+    // Niveaux d'encadrement effectifs
+    // ------------------------------------------------------------------------
+
+    /// Indique si un titre est encore valide à la date `today`
+    pub fn is_active(qualification: &teaching_qualifications::Model, today: NaiveDate) -> bool {
+        qualification.expires_at.is_none_or(|expires| expires > today)
+    }
+
+    /// Applique les titres suivis à une chaîne de niveaux: les titres expirés
+    /// en sont retirés, les titres valides ajoutés s'ils manquent
+    pub fn apply_qualifications(
+        diving_level: Option<&str>,
+        qualifications: &[teaching_qualifications::Model],
+        today: NaiveDate,
+    ) -> Option<String> {
+        let (active, expired): (Vec<_>, Vec<_>) = qualifications
+            .iter()
+            .partition(|q| Self::is_active(q, today));
+
+        let mut parts: Vec<String> = diving_level
+            .unwrap_or("")
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .filter(|p| !expired.iter().any(|q| q.level.eq_ignore_ascii_case(p)))
+            .map(str::to_string)
+            .collect();
+
+        for qualification in active {
+            if !parts.iter().any(|p| p.eq_ignore_ascii_case(&qualification.level)) {
+                parts.push(qualification.level.clone());
+            }
+        }
+
+        (!parts.is_empty()).then(|| parts.join(","))
+    }
+
+    /// Niveaux de plusieurs plongeurs au format historique, titres
+    /// d'encadrement expirés exclus (absents si aucun niveau)
+    pub async fn teaching_levels<C: ConnectionTrait>(
+        conn: &C,
+        person_ids: impl IntoIterator<Item = Uuid>,
+    ) -> AppResult<HashMap<Uuid, String>> {
+        let person_ids: Vec<Uuid> = person_ids.into_iter().collect();
+        let mut levels = CertificationService::diving_levels(conn, person_ids.iter().copied()).await?;
+        let mut qualifications = Self::by_person(conn, person_ids.iter().copied()).await?;
+        let today = Utc::now().naive_utc().date();
+
+        Ok(person_ids
+            .into_iter()
+            .filter_map(|person_id| {
+                let level = levels.remove(&person_id);
+                let qualifications = qualifications.remove(&person_id).unwrap_or_default();
+                Self::apply_qualifications(level.as_deref(), &qualifications, today)
+                    .map(|level| (person_id, level))
+            })
+            .collect())
+    }
+
+    /// Niveaux effectifs (équivalences comprises) de plusieurs plongeurs,
+    /// titres d'encadrement expirés exclus. `native_level` correspond alors
+    /// au niveau de `teaching_levels`.
+    pub async fn effective_levels<C: ConnectionTrait>(
+        conn: &C,
+        person_ids: impl IntoIterator<Item = Uuid>,
+    ) -> AppResult<HashMap<Uuid, EffectiveLevel>> {
+        let person_ids: Vec<Uuid> = person_ids.into_iter().collect();
+        let mut levels = EquivalenceService::effective_levels(conn, person_ids.iter().copied()).await?;
+        let mut qualifications = Self::by_person(conn, person_ids.iter().copied()).await?;
+        let today = Utc::now().naive_utc().date();
+
+        Ok(person_ids
+            .into_iter()
+            .map(|person_id| {
+                let mut level = levels.remove(&person_id).unwrap_or_default();
+                let qualifications = qualifications.remove(&person_id).unwrap_or_default();
+                level.native_level =
+                    Self::apply_qualifications(level.native_level.as_deref(), &qualifications, today);
+                level.diving_level =
+                    Self::apply_qualifications(level.diving_level.as_deref(), &qualifications, today);
+                (person_id, level)
+            })
+            .collect())
+    }
+
+    /// Niveaux effectifs d'un plongeur, titres d'encadrement expirés exclus
+    pub async fn effective_level<C: ConnectionTrait>(
+        conn: &C,
+        person_id: Uuid,
+    ) -> AppResult<EffectiveLevel> {
+        Ok(Self::effective_levels(conn, [person_id])
+            .await?
+            .remove(&person_id)
+            .unwrap_or_default())
+    }
+
+    /// Niveaux d'un plongeur au format historique, titres expirés exclus
+    pub async fn teaching_level<C: ConnectionTrait>(
+        conn: &C,
+        person_id: Uuid,
+    ) -> AppResult<Option<String>> {
+        Ok(Self::teaching_levels(conn, [person_id]).await?.remove(&person_id))
+    }
+
+    /// Indique si un plongeur est encadrant (`DiverLevel::is_instructor`),
+    /// titres expirés exclus
+    pub async fn is_instructor<C: ConnectionTrait>(conn: &C, person_id: Uuid) -> AppResult<bool> {
+        Ok(Self::diver_level(conn, person_id)
+            .await?
+            .is_some_and(|diver| diver.is_instructor()))
+    }
+
+    /// Indique si un plongeur détient au moins un titre d'encadrement valide,
+    /// même sous un niveau plongeur plus haut (ex: "N5,E1")
+    pub async fn holds_active_title<C: ConnectionTrait>(conn: &C, person_id: Uuid) -> AppResult<bool> {
+        Ok(Self::diver_level(conn, person_id)
+            .await?
+            .is_some_and(|diver| diver.holds_instructor_level()))
+    }
+
+    async fn diver_level<C: ConnectionTrait>(conn: &C, person_id: Uuid) -> AppResult<Option<DiverLevel>> {
+        let catalog = LevelService::catalog(conn).await?;
+        Ok(Self::teaching_level(conn, person_id)
+            .await?
+            .as_deref()
+            .and_then(|level| DiverLevel::from_string(level, &catalog)))
+    }
+
+    /// Refuse le rôle E à un encadrant dont les titres ont tous expiré
+    pub async fn check_can_supervise<C: ConnectionTrait>(conn: &C, person_id: Uuid) -> AppResult<()> {
+        let today = Utc::now().naive_utc().date();
+        let expired: Vec<teaching_qualifications::Model> = TeachingQualifications::find()
+            .filter(teaching_qualifications::Column::PersonId.eq(person_id))
+            .all(conn)
+            .await?
+            .into_iter()
+            .filter(|q| !Self::is_active(q, today))
+            .collect();
+        if expired.is_empty() || Self::holds_active_title(conn, person_id).await? {
+            return Ok(());
+        }
+
+        let titles: Vec<String> = expired
+            .iter()
+            .map(|q| match q.expires_at {
+                Some(expires) => format!("{} (expiré le {})", q.level, expires.format("%d/%m/%Y")),
+                None => q.level.clone(),
+            })
+            .collect();
+        Err(AppError::Validation(format!(
+            "Recyclage requis pour encadrer: {}",
+            titles.join(", ")
+        )))
+    }
+
+    async fn by_person<C: ConnectionTrait>(
+        conn: &C,
+        person_ids: impl IntoIterator<Item = Uuid>,
+    ) -> AppResult<HashMap<Uuid, Vec<teaching_qualifications::Model>>> {
+        let mut by_person: HashMap<Uuid, Vec<teaching_qualifications::Model>> = HashMap::new();
+        for qualification in TeachingQualifications::find()
+            .filter(teaching_qualifications::Column::PersonId.is_in(person_ids))
+            .all(conn)
+            .await?
+        {
+            by_person.entry(qualification.person_id).or_default().push(qualification);
+        }
+        Ok(by_person)
+    }
+
+    // ------------------------------------------------------------------------
+    // Titres et recyclages
+    // ------------------------------------------------------------------------
+
+    pub async fn list(
+        db: &DatabaseConnection,
+        query: TeachingQualificationsQuery,
+    ) -> AppResult<Vec<TeachingQualificationResponse>> {
+        let mut select = TeachingQualifications::find()
+            .order_by_asc(teaching_qualifications::Column::ExpiresAt)
+            .order_by_asc(teaching_qualifications::Column::Level);
+        if let Some(person_id) = query.person_id {
+            select = select.filter(teaching_qualifications::Column::PersonId.eq(person_id));
+        }
+        if let Some(level) = query.level {
+            select = select.filter(teaching_qualifications::Column::Level.eq(level));
+        }
+        if let Some(days) = query.expiring_within_days {
+            let limit = Utc::now().naive_utc().date() + Duration::days(days);
+            select = select.filter(teaching_qualifications::Column::ExpiresAt.lte(limit));
+        }
+        let qualifications = select.all(db).await?;
+
+        Self::responses(db, qualifications).await
+    }
+
+    pub async fn create(
+        db: &DatabaseConnection,
+        request: CreateTeachingQualificationRequest,
+    ) -> AppResult<TeachingQualificationResponse> {
+        let level = Self::instructor_level(db, &request.level).await?;
+        People::find_by_id(request.person_id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Personne non trouvée".to_string()))?;
+
+        let existing = TeachingQualifications::find()
+            .filter(teaching_qualifications::Column::PersonId.eq(request.person_id))
+            .filter(teaching_qualifications::Column::Level.eq(&level.code))
+            .one(db)
+            .await?;
+        if existing.is_some() {
+            return Err(AppError::Validation(format!(
+                "Le titre {} est déjà enregistré pour cette personne",
+                level.code
+            )));
+        }
+
+        let obtained_at = parse_date(&request.obtained_at)?;
+        let expires_at = match request.expires_at.as_deref() {
+            Some(date) => Some(parse_date(date)?),
+            None => SkillValidationService::expires_at(obtained_at, level.recycling_months),
+        };
+
+        let now = Utc::now().naive_utc();
+        let qualification = teaching_qualifications::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            person_id: Set(request.person_id),
+            level: Set(level.code),
+            obtained_at: Set(obtained_at),
+            expires_at: Set(expires_at),
+            certificate_number: Set(request.certificate_number),
+            notes: Set(request.notes),
+            created_at: Set(now),
+            updated_at: Set(now),
+        }
+        .insert(db)
+        .await?;
+
+        Self::response(db, qualification).await
+    }
+
+    pub async fn update(
+        db: &DatabaseConnection,
+        id: Uuid,
+        request: UpdateTeachingQualificationRequest,
+    ) -> AppResult<TeachingQualificationResponse> {
+        let qualification = Self::find(db, id).await?;
+
+        let mut active: teaching_qualifications::ActiveModel = qualification.into();
+        if let Some(obtained_at) = request.obtained_at.as_deref() {
+            active.obtained_at = Set(parse_date(obtained_at)?);
+        }
+        if let Some(expires_at) = request.expires_at.as_deref() {
+            active.expires_at = Set(match expires_at {
+                "" => None,
+                date => Some(parse_date(date)?),
+            });
+        }
+        if let Some(certificate_number) = request.certificate_number {
+            active.certificate_number = Set(Some(certificate_number).filter(|n| !n.is_empty()));
+        }
+        if let Some(notes) = request.notes {
+            active.notes = Set(Some(notes).filter(|n| !n.is_empty()));
+        }
+        active.updated_at = Set(Utc::now().naive_utc());
+        let qualification = active.update(db).await?;
+
+        Self::response(db, qualification).await
+    }
+
+    pub async fn delete(db: &DatabaseConnection, id: Uuid) -> AppResult<()> {
+        let result = TeachingQualifications::delete_by_id(id).exec(db).await?;
+        if result.rows_affected == 0 {
+            return Err(AppError::NotFound("Titre d'encadrement non trouvé".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Enregistre un recyclage et repousse l'échéance du titre. Un recyclage
+    /// antérieur au dernier enregistré ne modifie pas l'échéance.
+    pub async fn record_recycling(
+        db: &DatabaseConnection,
+        id: Uuid,
+        request: RecordRecyclingRequest,
+        recorded_by_id: Option<Uuid>,
+    ) -> AppResult<TeachingQualificationResponse> {
+        let qualification = Self::find(db, id).await?;
+        let recycled_at = parse_date(&request.recycled_at)?;
+        let explicit_expiry = request.expires_at.as_deref().map(parse_date).transpose()?;
+        if recycled_at < qualification.obtained_at {
+            return Err(AppError::Validation(
+                "Le recyclage ne peut pas précéder l'obtention du titre".to_string(),
+            ));
+        }
+
+        let latest = TeachingRecyclings::find()
+            .filter(teaching_recyclings::Column::QualificationId.eq(id))
+            .order_by_desc(teaching_recyclings::Column::RecycledAt)
+            .one(db)
+            .await?;
+
+        let txn = db.begin().await?;
+        teaching_recyclings::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            qualification_id: Set(id),
+            recycled_at: Set(recycled_at),
+            notes: Set(request.notes),
+            recorded_by_id: Set(recorded_by_id),
+            created_at: Set(Utc::now().naive_utc()),
+        }
+        .insert(&txn)
+        .await?;
+
+        if latest.is_none_or(|l| l.recycled_at <= recycled_at) {
+            let expires_at = match explicit_expiry {
+                Some(date) => Some(date),
+                None => {
+                    let recycling_months = DivingLevels::find()
+                        .filter(diving_levels::Column::Code.eq(&qualification.level))
+                        .one(&txn)
+                        .await?
+                        .and_then(|l| l.recycling_months);
+                    SkillValidationService::expires_at(recycled_at, recycling_months)
+                }
+            };
+            let mut active: teaching_qualifications::ActiveModel = qualification.into();
+            active.expires_at = Set(expires_at);
+            active.updated_at = Set(Utc::now().naive_utc());
+            active.update(&txn).await?;
+        }
+        txn.commit().await?;
+
+        Self::response(db, Self::find(db, id).await?).await
+    }
+
+    async fn find(db: &DatabaseConnection, id: Uuid) -> AppResult<teaching_qualifications::Model> {
+        TeachingQualifications::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Titre d'encadrement non trouvé".to_string()))
+    }
+
+    /// Niveau d'encadrement du référentiel correspondant au titre
+    async fn instructor_level(db: &DatabaseConnection, code: &str) -> AppResult<diving_levels::Model> {
+        let level = DivingLevels::find()
+            .filter(diving_levels::Column::Code.eq(code.trim().to_uppercase()))
+            .one(db)
+            .await?
+            .ok_or_else(|| AppError::Validation(format!("Niveau inconnu: {}", code)))?;
+        if !level.is_instructor {
+            return Err(AppError::Validation(format!(
+                "{} n'est pas un niveau d'encadrement",
+                level.code
+            )));
+        }
+        Ok(level)
+    }
+
+    async fn response(
+        db: &DatabaseConnection,
+        qualification: teaching_qualifications::Model,
+    ) -> AppResult<TeachingQualificationResponse> {
+        Self::responses(db, vec![qualification])
+            .await?
+            .pop()
+            .ok_or_else(|| AppError::Internal("Titre d'encadrement introuvable".to_string()))
+    }
+
+    async fn responses(
+        db: &DatabaseConnection,
+        qualifications: Vec<teaching_qualifications::Model>,
+    ) -> AppResult<Vec<TeachingQualificationResponse>> {
+        let mut recyclings: HashMap<Uuid, Vec<teaching_recyclings::Model>> = HashMap::new();
+        for recycling in TeachingRecyclings::find()
+            .filter(
+                teaching_recyclings::Column::QualificationId
+                    .is_in(qualifications.iter().map(|q| q.id)),
+            )
+            .order_by_desc(teaching_recyclings::Column::RecycledAt)
+            .all(db)
+            .await?
+        {
+            recyclings.entry(recycling.qualification_id).or_default().push(recycling);
+        }
+
+        let names: HashMap<Uuid, String> = People::find()
+            .filter(
+                people::Column::Id.is_in(
+                    qualifications
+                        .iter()
+                        .map(|q| q.person_id)
+                        .chain(recyclings.values().flatten().filter_map(|r| r.recorded_by_id)),
+                ),
+            )
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|p| (p.id, format!("{} {}", p.first_name, p.last_name)))
+            .collect();
+        let level_names: HashMap<String, String> = DivingLevels::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|l| (l.code, l.name))
+            .collect();
+
+        let today = Utc::now().naive_utc().date();
+        Ok(qualifications
+            .into_iter()
+            .map(|q| {
+                let recyclings: Vec<TeachingRecyclingResponse> = recyclings
+                    .remove(&q.id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|r| TeachingRecyclingResponse {
+                        id: r.id,
+                        recycled_at: r.recycled_at.format("%Y-%m-%d").to_string(),
+                        notes: r.notes,
+                        recorded_by_id: r.recorded_by_id,
+                        recorded_by_name: r.recorded_by_id.and_then(|id| names.get(&id).cloned()),
+                    })
+                    .collect();
+                TeachingQualificationResponse {
+                    id: q.id,
+                    person_id: q.person_id,
+                    person_name: names.get(&q.person_id).cloned().unwrap_or_default(),
+                    level_name: level_names.get(&q.level).cloned().unwrap_or_else(|| q.level.clone()),
+                    obtained_at: q.obtained_at.format("%Y-%m-%d").to_string(),
+                    expires_at: q.expires_at.map(|d| d.format("%Y-%m-%d").to_string()),
+                    is_active: Self::is_active(&q, today),
+                    last_recycled_at: recyclings.first().map(|r| r.recycled_at.clone()),
+                    level: q.level,
+                    certificate_number: q.certificate_number,
+                    notes: q.notes,
+                    recyclings,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn qualification(level: &str, expires_at: Option<NaiveDate>) -> teaching_qualifications::Model {
+        let now = Utc::now().naive_utc();
+        teaching_qualifications::Model {
+            id: Uuid::new_v4(),
+            person_id: Uuid::nil(),
+            level: level.to_string(),
+            obtained_at: date(2018, 6, 1),
+            expires_at,
+            certificate_number: None,
+            notes: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_is_active() {
+        let today = date(2024, 5, 10);
+        assert!(TeachingQualificationService::is_active(&qualification("E1", None), today));
+        assert!(TeachingQualificationService::is_active(
+            &qualification("E1", Some(date(2024, 5, 11))),
+            today
+        ));
+        // Expire le jour de l'échéance
+        assert!(!TeachingQualificationService::is_active(
+            &qualification("E1", Some(today)),
+            today
+        ));
+    }
+
+    #[test]
+    fn test_apply_qualifications() {
+        let today = date(2024, 5, 10);
+        let expired = qualification("E2", Some(date(2023, 12, 31)));
+        let active = qualification("E3", Some(date(2026, 1, 1)));
+
+        // Titre expiré retiré, autres niveaux conservés
+        assert_eq!(
+            TeachingQualificationService::apply_qualifications(
+                Some("N4,E2,preparing_N5"),
+                std::slice::from_ref(&expired),
+                today
+            )
+            .as_deref(),
+            Some("N4,preparing_N5")
+        );
+        // Titre valide ajouté s'il manque, sans doublon sinon
+        assert_eq!(
+            TeachingQualificationService::apply_qualifications(Some("N4"), std::slice::from_ref(&active), today)
+                .as_deref(),
+            Some("N4,E3")
+        );
+        assert_eq!(
+            TeachingQualificationService::apply_qualifications(Some("N4,e3"), &[active], today)
+                .as_deref(),
+            Some("N4,e3")
+        );
+        // Sans titre suivi, les niveaux sont inchangés
+        assert_eq!(
+            TeachingQualificationService::apply_qualifications(Some("N5,E1"), &[], today).as_deref(),
+            Some("N5,E1")
+        );
+        assert_eq!(
+            TeachingQualificationService::apply_qualifications(Some("E2"), &[expired], today),
+            None
+        );
+    }
+}
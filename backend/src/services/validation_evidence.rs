@@ -0,0 +1,330 @@
+use chrono::Utc;
+use sea_orm::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::entities::prelude::*;
+use crate::entities::{
+    people, skill_validation_comments, skill_validation_evidence, skill_validation_history,
+};
+use crate::errors::{AppError, AppResult};
+use crate::middleware::acl::{current_person, AuthUser};
+use crate::models::{Permission, ValidationCommentResponse, ValidationEvidenceResponse};
+
+/// Types de fichiers acceptés comme preuve (en plus des images et vidéos)
+const ALLOWED_DOCUMENT_TYPES: &[&str] = &["application/pdf"];
+
+/// Validation (éventuellement supprimée) à laquelle se rattachent preuves
+/// et commentaires
+#[derive(Debug, Clone)]
+pub struct ValidationSubject {
+    pub validation_id: Uuid,
+    pub person_id: Uuid,
+    pub skill_id: Uuid,
+    /// La validation existe encore (sinon seule la lecture est possible)
+    pub live: bool,
+}
+
+/// Utilisateur qui consulte ou modifie les preuves d'une validation
+pub struct EvidenceViewer {
+    pub person: people::Model,
+    /// Encadrant: peut voir et compléter les preuves de tous les élèves
+    pub is_encadrant: bool,
+    /// Admin réel (hors impersonation)
+    pub is_real_admin: bool,
+}
+
+impl EvidenceViewer {
+    /// Peut supprimer un élément créé par `author_id`
+    pub fn can_delete(&self, author_id: Option<Uuid>) -> bool {
+        self.is_real_admin || author_id == Some(self.person.id)
+    }
+}
+
+/// Preuves et commentaires des validations, réservés à l'élève et aux encadrants
+pub struct ValidationEvidenceService;
+
+impl ValidationEvidenceService {
+    /// Retrouve la validation, ou à défaut sa trace dans l'historique
+    pub async fn subject(db: &DatabaseConnection, validation_id: Uuid) -> AppResult<ValidationSubject> {
+        if let Some(validation) = SkillValidations::find_by_id(validation_id).one(db).await? {
+            return Ok(ValidationSubject {
+                validation_id,
+                person_id: validation.person_id,
+                skill_id: validation.skill_id,
+                live: true,
+            });
+        }
+
+        SkillValidationHistory::find()
+            .filter(skill_validation_history::Column::ValidationId.eq(validation_id))
+            .one(db)
+            .await?
+            .map(|entry| ValidationSubject {
+                validation_id,
+                person_id: entry.person_id,
+                skill_id: entry.skill_id,
+                live: false,
+            })
+            .ok_or(AppError::NotFound("Validation non trouvée".to_string()))
+    }
+
+    /// Vérifie que l'utilisateur est l'élève concerné ou un encadrant
+    pub async fn authorize(
+        db: &DatabaseConnection,
+        auth: &AuthUser,
+        validation_id: Uuid,
+    ) -> AppResult<(ValidationSubject, EvidenceViewer)> {
+        let subject = Self::subject(db, validation_id).await?;
+
+        let person = current_person(db, auth).await?;
+
+        let viewer = EvidenceViewer {
+            is_encadrant: auth.has_permission(Permission::CompetenciesValidate),
+            is_real_admin: auth.claims.is_admin && auth.claims.impersonating.is_none(),
+            person,
+        };
+
+        if viewer.person.id != subject.person_id && !viewer.is_encadrant {
+            return Err(AppError::Forbidden(
+                "Accès réservé à l'élève et aux encadrants".to_string(),
+            ));
+        }
+
+        Ok((subject, viewer))
+    }
+
+    /// Clé de stockage du fichier d'une preuve
+    pub fn storage_key(validation_id: Uuid, evidence_id: Uuid) -> String {
+        format!("evidence/{}/{}", validation_id, evidence_id)
+    }
+
+    /// Photos, vidéos et PDF (scan de carnet) sont acceptés. Le SVG est
+    /// refusé: affiché dans le navigateur, il peut exécuter des scripts.
+    pub fn is_allowed_content_type(content_type: &str) -> bool {
+        let essence = content_type
+            .split(';')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_ascii_lowercase();
+        (essence.starts_with("image/") && essence != "image/svg+xml")
+            || essence.starts_with("video/")
+            || ALLOWED_DOCUMENT_TYPES.contains(&essence.as_str())
+    }
+
+    pub async fn list_evidence(
+        db: &DatabaseConnection,
+        validation_id: Uuid,
+    ) -> AppResult<Vec<ValidationEvidenceResponse>> {
+        let evidence = SkillValidationEvidence::find()
+            .filter(skill_validation_evidence::Column::ValidationId.eq(validation_id))
+            .order_by_asc(skill_validation_evidence::Column::CreatedAt)
+            .all(db)
+            .await?;
+
+        let names = Self::person_names(db, evidence.iter().filter_map(|e| e.uploaded_by_id)).await?;
+
+        Ok(evidence
+            .into_iter()
+            .map(|e| Self::evidence_response(e, &names))
+            .collect())
+    }
+
+    pub fn evidence_response(
+        evidence: skill_validation_evidence::Model,
+        names: &HashMap<Uuid, String>,
+    ) -> ValidationEvidenceResponse {
+        ValidationEvidenceResponse {
+            id: evidence.id,
+            validation_id: evidence.validation_id,
+            uploaded_by_name: evidence.uploaded_by_id.and_then(|id| names.get(&id).cloned()),
+            kind: evidence.kind,
+            file_name: evidence.file_name,
+            content_type: evidence.content_type,
+            size_bytes: evidence.size_bytes,
+            reference: evidence.reference,
+            caption: evidence.caption,
+            uploaded_by_id: evidence.uploaded_by_id,
+            created_at: evidence.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        }
+    }
+
+    /// Fil de commentaires d'une validation
+    pub async fn list_comments(
+        db: &DatabaseConnection,
+        validation_id: Uuid,
+    ) -> AppResult<Vec<ValidationCommentResponse>> {
+        let comments = SkillValidationComments::find()
+            .filter(skill_validation_comments::Column::ValidationId.eq(validation_id))
+            .order_by_asc(skill_validation_comments::Column::CreatedAt)
+            .all(db)
+            .await?;
+
+        let names = Self::person_names(db, comments.iter().filter_map(|c| c.author_id)).await?;
+
+        Ok(Self::build_thread(comments, &names))
+    }
+
+    /// Ajoute un commentaire, éventuellement en réponse à un autre
+    pub async fn add_comment(
+        db: &DatabaseConnection,
+        subject: &ValidationSubject,
+        author: &people::Model,
+        body: String,
+        parent_id: Option<Uuid>,
+    ) -> AppResult<ValidationCommentResponse> {
+        if let Some(parent_id) = parent_id {
+            let parent = SkillValidationComments::find_by_id(parent_id)
+                .one(db)
+                .await?
+                .ok_or(AppError::NotFound("Commentaire parent non trouvé".to_string()))?;
+            if parent.validation_id != subject.validation_id {
+                return Err(AppError::Validation(
+                    "Le commentaire parent appartient à une autre validation".to_string(),
+                ));
+            }
+        }
+
+        let now = Utc::now().naive_utc();
+        let comment = skill_validation_comments::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            validation_id: Set(subject.validation_id),
+            person_id: Set(subject.person_id),
+            skill_id: Set(subject.skill_id),
+            parent_id: Set(parent_id),
+            author_id: Set(Some(author.id)),
+            body: Set(body),
+            created_at: Set(now),
+            updated_at: Set(now),
+        }
+        .insert(db)
+        .await?;
+
+        let names = HashMap::from([(author.id, format!("{} {}", author.first_name, author.last_name))]);
+        Ok(Self::comment_response(comment, &names))
+    }
+
+    /// Imbrique les commentaires sous leur parent. Les réponses dont le
+    /// parent est introuvable remontent au premier niveau.
+    fn build_thread(
+        comments: Vec<skill_validation_comments::Model>,
+        names: &HashMap<Uuid, String>,
+    ) -> Vec<ValidationCommentResponse> {
+        let known: std::collections::HashSet<Uuid> = comments.iter().map(|c| c.id).collect();
+        let mut children: HashMap<Option<Uuid>, Vec<skill_validation_comments::Model>> = HashMap::new();
+        for comment in comments {
+            let parent = comment.parent_id.filter(|p| known.contains(p));
+            children.entry(parent).or_default().push(comment);
+        }
+
+        fn attach(
+            parent: Option<Uuid>,
+            children: &mut HashMap<Option<Uuid>, Vec<skill_validation_comments::Model>>,
+            names: &HashMap<Uuid, String>,
+        ) -> Vec<ValidationCommentResponse> {
+            children
+                .remove(&parent)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|comment| {
+                    let replies = attach(Some(comment.id), children, names);
+                    let mut response = ValidationEvidenceService::comment_response(comment, names);
+                    response.replies = replies;
+                    response
+                })
+                .collect()
+        }
+
+        attach(None, &mut children, names)
+    }
+
+    pub fn comment_response(
+        comment: skill_validation_comments::Model,
+        names: &HashMap<Uuid, String>,
+    ) -> ValidationCommentResponse {
+        ValidationCommentResponse {
+            id: comment.id,
+            validation_id: comment.validation_id,
+            parent_id: comment.parent_id,
+            author_name: comment.author_id.and_then(|id| names.get(&id).cloned()),
+            author_id: comment.author_id,
+            body: comment.body,
+            created_at: comment.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            updated_at: comment.updated_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            replies: Vec::new(),
+        }
+    }
+
+    async fn person_names(
+        db: &DatabaseConnection,
+        ids: impl Iterator<Item = Uuid>,
+    ) -> AppResult<HashMap<Uuid, String>> {
+        let ids: Vec<Uuid> = ids.collect();
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        Ok(People::find()
+            .filter(people::Column::Id.is_in(ids))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|p| (p.id, format!("{} {}", p.first_name, p.last_name)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(id: u128, parent: Option<u128>, minute: u32) -> skill_validation_comments::Model {
+        let at = chrono::NaiveDate::from_ymd_opt(2024, 5, 1)
+            .unwrap()
+            .and_hms_opt(10, minute, 0)
+            .unwrap();
+        skill_validation_comments::Model {
+            id: Uuid::from_u128(id),
+            validation_id: Uuid::from_u128(100),
+            person_id: Uuid::from_u128(200),
+            skill_id: Uuid::from_u128(300),
+            parent_id: parent.map(Uuid::from_u128),
+            author_id: None,
+            body: format!("comment {}", id),
+            created_at: at,
+            updated_at: at,
+        }
+    }
+
+    #[test]
+    fn test_build_thread_nests_replies_in_order() {
+        let comments = vec![
+            comment(1, None, 0),
+            comment(2, Some(1), 1),
+            comment(3, None, 2),
+            comment(4, Some(2), 3),
+            comment(5, Some(1), 4),
+            // Parent supprimé: remonte au premier niveau
+            comment(6, Some(99), 5),
+        ];
+
+        let thread = ValidationEvidenceService::build_thread(comments, &HashMap::new());
+
+        let ids = |list: &[ValidationCommentResponse]| -> Vec<Uuid> { list.iter().map(|c| c.id).collect() };
+        assert_eq!(ids(&thread), vec![Uuid::from_u128(1), Uuid::from_u128(3), Uuid::from_u128(6)]);
+        assert_eq!(ids(&thread[0].replies), vec![Uuid::from_u128(2), Uuid::from_u128(5)]);
+        assert_eq!(ids(&thread[0].replies[0].replies), vec![Uuid::from_u128(4)]);
+        assert!(thread[1].replies.is_empty());
+    }
+
+    #[test]
+    fn test_allowed_content_types() {
+        assert!(ValidationEvidenceService::is_allowed_content_type("image/jpeg"));
+        assert!(ValidationEvidenceService::is_allowed_content_type("video/mp4"));
+        assert!(ValidationEvidenceService::is_allowed_content_type("application/pdf; charset=binary"));
+        assert!(!ValidationEvidenceService::is_allowed_content_type("application/x-msdownload"));
+        assert!(!ValidationEvidenceService::is_allowed_content_type("text/html"));
+        assert!(!ValidationEvidenceService::is_allowed_content_type("image/svg+xml"));
+    }
+}
@@ -0,0 +1,102 @@
+use chrono::Utc;
+use sea_orm::*;
+use uuid::Uuid;
+
+use crate::entities::prelude::*;
+use crate::entities::{skill_validation_history, skill_validations};
+use crate::errors::AppResult;
+
+/// Historique append-only des transitions d'étape des acquis
+pub struct ValidationHistoryService;
+
+impl ValidationHistoryService {
+    /// Détermine le type de transition à partir de l'ordre des étapes
+    pub fn action_for(previous_order: Option<i32>, new_order: i32) -> &'static str {
+        match previous_order {
+            None => skill_validation_history::ACTION_CREATED,
+            Some(prev) if new_order > prev => skill_validation_history::ACTION_ADVANCED,
+            Some(prev) if new_order < prev => skill_validation_history::ACTION_ROLLBACK,
+            Some(_) => skill_validation_history::ACTION_UPDATED,
+        }
+    }
+
+    /// Enregistre la transition `previous` -> `current` d'une validation
+    pub async fn record<C: ConnectionTrait>(
+        conn: &C,
+        previous: Option<&skill_validations::Model>,
+        current: &skill_validations::Model,
+        performed_by_id: Option<Uuid>,
+    ) -> AppResult<skill_validation_history::Model> {
+        let new_order = Self::stage_order(conn, current.stage_id).await?;
+        let previous_order = match previous {
+            Some(p) => Some(Self::stage_order(conn, p.stage_id).await?),
+            None => None,
+        };
+
+        let entry = skill_validation_history::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            validation_id: Set(Some(current.id)),
+            person_id: Set(current.person_id),
+            skill_id: Set(current.skill_id),
+            action: Set(Self::action_for(previous_order, new_order).to_string()),
+            from_stage_id: Set(previous.map(|p| p.stage_id)),
+            to_stage_id: Set(Some(current.stage_id)),
+            validated_at: Set(Some(current.validated_at)),
+            validated_by_id: Set(Some(current.validated_by_id)),
+            performed_by_id: Set(performed_by_id),
+            notes: Set(current.notes.clone()),
+            session_id: Set(current.session_id),
+            palanquee_id: Set(current.palanquee_id),
+            created_at: Set(Utc::now().naive_utc()),
+        };
+
+        Ok(entry.insert(conn).await?)
+    }
+
+    /// Enregistre la suppression d'une validation (l'historique est conservé)
+    pub async fn record_deletion<C: ConnectionTrait>(
+        conn: &C,
+        deleted: &skill_validations::Model,
+        performed_by_id: Option<Uuid>,
+    ) -> AppResult<skill_validation_history::Model> {
+        let entry = skill_validation_history::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            validation_id: Set(Some(deleted.id)),
+            person_id: Set(deleted.person_id),
+            skill_id: Set(deleted.skill_id),
+            action: Set(skill_validation_history::ACTION_DELETED.to_string()),
+            from_stage_id: Set(Some(deleted.stage_id)),
+            to_stage_id: Set(None),
+            validated_at: Set(None),
+            validated_by_id: Set(None),
+            performed_by_id: Set(performed_by_id),
+            notes: Set(None),
+            session_id: Set(deleted.session_id),
+            palanquee_id: Set(deleted.palanquee_id),
+            created_at: Set(Utc::now().naive_utc()),
+        };
+
+        Ok(entry.insert(conn).await?)
+    }
+
+    async fn stage_order<C: ConnectionTrait>(conn: &C, stage_id: Uuid) -> AppResult<i32> {
+        Ok(ValidationStages::find_by_id(stage_id)
+            .one(conn)
+            .await?
+            .map(|s| s.sort_order)
+            .unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_for() {
+        assert_eq!(ValidationHistoryService::action_for(None, 1), "created");
+        assert_eq!(ValidationHistoryService::action_for(Some(1), 3), "advanced");
+        assert_eq!(ValidationHistoryService::action_for(Some(5), 2), "rollback");
+        assert_eq!(ValidationHistoryService::action_for(Some(3), 3), "updated");
+    }
+}
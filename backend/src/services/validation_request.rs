@@ -0,0 +1,529 @@
+use chrono::{NaiveDate, Utc};
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::entities::prelude::*;
+use crate::entities::validation_requests::{self, STATUS_APPROVED, STATUS_PENDING, STATUS_REJECTED};
+use crate::entities::{
+    competency_skills, people, sessions, skill_validations, validation_stages,
+};
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    ApproveValidationRequestRequest, CreateValidationRequestRequest, LevelCatalog,
+    ValidationRequestResponse,
+};
+use crate::services::{
+    LevelService, SkillValidationService, TeachingQualificationService, ValidationSource,
+};
+
+fn parse_date(date: &str) -> AppResult<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| AppError::Validation("Format de date invalide (YYYY-MM-DD)".to_string()))
+}
+
+/// Demandes de validation d'acquis et file des validateurs habilités
+pub struct ValidationRequestService;
+
+impl ValidationRequestService {
+    /// Crée une demande pour `person_id`. Une seule demande en attente par
+    /// acquis; un acquis déjà validé (et non expiré) ne peut pas être demandé.
+    pub async fn create(
+        db: &DatabaseConnection,
+        requester: &people::Model,
+        person_id: Uuid,
+        request: CreateValidationRequestRequest,
+    ) -> AppResult<ValidationRequestResponse> {
+        People::find_by_id(person_id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Élève non trouvé".to_string()))?;
+        let skill = CompetencySkills::find_by_id(request.skill_id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Acquis non trouvé".to_string()))?;
+        if let Some(stage_id) = request.stage_id {
+            ValidationStages::find_by_id(stage_id)
+                .one(db)
+                .await?
+                .ok_or(AppError::NotFound("Étape non trouvée".to_string()))?;
+        }
+        if let Some(session_id) = request.session_id {
+            Sessions::find_by_id(session_id)
+                .one(db)
+                .await?
+                .ok_or(AppError::NotFound("Session non trouvée".to_string()))?;
+        }
+        let demonstrated_at = request.demonstrated_at.as_deref().map(parse_date).transpose()?;
+
+        let pending = ValidationRequests::find()
+            .filter(validation_requests::Column::PersonId.eq(person_id))
+            .filter(validation_requests::Column::SkillId.eq(skill.id))
+            .filter(validation_requests::Column::Status.eq(STATUS_PENDING))
+            .one(db)
+            .await?;
+        if pending.is_some() {
+            return Err(AppError::Validation(
+                "Une demande est déjà en attente pour cet acquis".to_string(),
+            ));
+        }
+        if Self::is_validated(db, person_id, &skill).await? {
+            return Err(AppError::Validation("Cet acquis est déjà validé".to_string()));
+        }
+
+        let now = Utc::now().naive_utc();
+        let model = validation_requests::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            person_id: Set(person_id),
+            skill_id: Set(skill.id),
+            stage_id: Set(request.stage_id),
+            requested_by_id: Set(Some(requester.id)),
+            session_id: Set(request.session_id),
+            demonstrated_at: Set(demonstrated_at),
+            comment: Set(request.comment.filter(|c| !c.trim().is_empty())),
+            status: Set(STATUS_PENDING.to_string()),
+            reviewed_by_id: Set(None),
+            reviewed_at: Set(None),
+            review_comment: Set(None),
+            validation_id: Set(None),
+            created_at: Set(now),
+            updated_at: Set(now),
+        }
+        .insert(db)
+        .await?;
+
+        Self::response(db, model).await
+    }
+
+    /// Acquis validé à une étape finale et non expiré
+    async fn is_validated(
+        db: &DatabaseConnection,
+        person_id: Uuid,
+        skill: &competency_skills::Model,
+    ) -> AppResult<bool> {
+        let Some(validation) = SkillValidations::find()
+            .filter(skill_validations::Column::PersonId.eq(person_id))
+            .filter(skill_validations::Column::SkillId.eq(skill.id))
+            .one(db)
+            .await?
+        else {
+            return Ok(false);
+        };
+        let is_final = ValidationStages::find_by_id(validation.stage_id)
+            .one(db)
+            .await?
+            .is_some_and(|s| s.is_final);
+        let periods = SkillValidationService::validity_periods(db, [skill.id]).await?;
+        let today = Utc::now().naive_utc().date();
+
+        Ok(is_final && !SkillValidationService::is_validation_expired(&validation, &periods, today))
+    }
+
+    /// Demandes d'un élève, les plus récentes d'abord
+    pub async fn list_for_person(
+        db: &DatabaseConnection,
+        person_id: Uuid,
+        status: Option<String>,
+    ) -> AppResult<Vec<ValidationRequestResponse>> {
+        let mut select = ValidationRequests::find()
+            .filter(validation_requests::Column::PersonId.eq(person_id))
+            .order_by_desc(validation_requests::Column::CreatedAt);
+        if let Some(status) = status {
+            select = select.filter(validation_requests::Column::Status.eq(status));
+        }
+        let requests = select.all(db).await?;
+
+        Self::responses(db, requests).await
+    }
+
+    /// File d'attente d'un validateur: demandes en attente sur les acquis
+    /// qu'il peut valider, hors les siennes. Les vrais administrateurs voient
+    /// toutes les demandes.
+    pub async fn queue(
+        db: &DatabaseConnection,
+        validator: &people::Model,
+        is_admin: bool,
+        person_id: Option<Uuid>,
+    ) -> AppResult<Vec<ValidationRequestResponse>> {
+        let mut select = ValidationRequests::find()
+            .filter(validation_requests::Column::Status.eq(STATUS_PENDING))
+            .order_by_asc(validation_requests::Column::CreatedAt);
+        if let Some(person_id) = person_id {
+            select = select.filter(validation_requests::Column::PersonId.eq(person_id));
+        }
+        let requests = select.all(db).await?;
+
+        let requests = if is_admin {
+            requests
+        } else {
+            let skills = Self::skills(db, &requests).await?;
+            let level = TeachingQualificationService::teaching_level(db, validator.id).await?;
+            let catalog = LevelService::catalog(db).await?;
+            requests
+                .into_iter()
+                .filter(|r| r.person_id != validator.id)
+                .filter(|r| {
+                    skills
+                        .get(&r.skill_id)
+                        .is_some_and(|skill| Self::can_review(&catalog, level.as_deref(), skill))
+                })
+                .collect()
+        };
+
+        Self::responses(db, requests).await
+    }
+
+    /// Un validateur peut traiter une demande s'il satisfait la règle de
+    /// l'acquis (ou son niveau minimum)
+    fn can_review(
+        catalog: &LevelCatalog,
+        diving_level: Option<&str>,
+        skill: &competency_skills::Model,
+    ) -> bool {
+        diving_level.is_some_and(|level| {
+            SkillValidationService::can_validate(
+                catalog,
+                level,
+                &skill.min_validator_level,
+                skill.validator_rule.as_deref(),
+            )
+        })
+    }
+
+    /// Accepte une demande: crée (ou fait progresser) la validation de
+    /// l'acquis au nom du validateur, selon les règles habituelles du
+    /// workflow. Retourne la demande, la validation et si l'étape est finale.
+    pub async fn approve(
+        db: &DatabaseConnection,
+        id: Uuid,
+        validator: &people::Model,
+        is_admin: bool,
+        request: ApproveValidationRequestRequest,
+    ) -> AppResult<(ValidationRequestResponse, skill_validations::Model, bool)> {
+        let pending = Self::find_pending(db, id).await?;
+        Self::check_not_own(&pending, validator)?;
+        let skill = CompetencySkills::find_by_id(pending.skill_id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Acquis non trouvé".to_string()))?;
+
+        let stage = match request.stage_id.or(pending.stage_id) {
+            Some(stage_id) => ValidationStages::find_by_id(stage_id)
+                .one(db)
+                .await?
+                .ok_or(AppError::NotFound("Étape non trouvée".to_string()))?,
+            None => ValidationStages::find()
+                .filter(validation_stages::Column::IsFinal.eq(true))
+                .order_by_asc(validation_stages::Column::SortOrder)
+                .one(db)
+                .await?
+                .ok_or_else(|| AppError::Validation("Aucune étape finale configurée".to_string()))?,
+        };
+        let validated_at = match request.validated_at.as_deref() {
+            Some(date) => parse_date(date)?,
+            None => pending
+                .demonstrated_at
+                .unwrap_or_else(|| Utc::now().naive_utc().date()),
+        };
+        let notes = request
+            .notes
+            .filter(|n| !n.trim().is_empty())
+            .or_else(|| pending.comment.clone());
+
+        // La demande est réservée (UPDATE conditionnel) avant la validation,
+        // dans la même transaction: une approbation concurrente attend puis
+        // ne trouve plus de demande en attente
+        let now = Utc::now().naive_utc();
+        let txn = db.begin().await?;
+        let claimed = ValidationRequests::update_many()
+            .col_expr(validation_requests::Column::Status, Expr::value(STATUS_APPROVED))
+            .col_expr(validation_requests::Column::StageId, Expr::value(Some(stage.id)))
+            .col_expr(validation_requests::Column::ReviewedById, Expr::value(Some(validator.id)))
+            .col_expr(validation_requests::Column::ReviewedAt, Expr::value(Some(now)))
+            .col_expr(validation_requests::Column::ReviewComment, Expr::value(notes.clone()))
+            .col_expr(validation_requests::Column::UpdatedAt, Expr::value(now))
+            .filter(validation_requests::Column::Id.eq(pending.id))
+            .filter(validation_requests::Column::Status.eq(STATUS_PENDING))
+            .exec(&txn)
+            .await?;
+        if claimed.rows_affected == 0 {
+            return Err(AppError::Validation("Cette demande a déjà été traitée".to_string()));
+        }
+
+        // Niveau du validateur et workflow des étapes vérifiés par le service
+        let validation = SkillValidationService::upsert_in(
+            &txn,
+            validator,
+            is_admin,
+            pending.person_id,
+            &skill,
+            &stage,
+            validated_at,
+            notes,
+            ValidationSource {
+                session_id: pending.session_id,
+                palanquee_id: None,
+            },
+        )
+        .await?;
+
+        ValidationRequests::update_many()
+            .col_expr(validation_requests::Column::ValidationId, Expr::value(Some(validation.id)))
+            .filter(validation_requests::Column::Id.eq(pending.id))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+
+        let model = ValidationRequests::find_by_id(pending.id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Demande non trouvée".to_string()))?;
+
+        Ok((Self::response(db, model).await?, validation, stage.is_final))
+    }
+
+    /// Refuse une demande, avec un commentaire pour l'élève
+    pub async fn reject(
+        db: &DatabaseConnection,
+        id: Uuid,
+        reviewer: &people::Model,
+        is_admin: bool,
+        comment: Option<String>,
+    ) -> AppResult<ValidationRequestResponse> {
+        let pending = Self::find_pending(db, id).await?;
+        Self::check_not_own(&pending, reviewer)?;
+        if !is_admin {
+            let skill = CompetencySkills::find_by_id(pending.skill_id)
+                .one(db)
+                .await?
+                .ok_or(AppError::NotFound("Acquis non trouvé".to_string()))?;
+            let level = TeachingQualificationService::teaching_level(db, reviewer.id).await?;
+            let catalog = LevelService::catalog(db).await?;
+            if !Self::can_review(&catalog, level.as_deref(), &skill) {
+                return Err(AppError::Forbidden(format!(
+                    "Niveau requis pour traiter cette demande: {}",
+                    SkillValidationService::requirement(&skill)
+                )));
+            }
+        }
+
+        // Comme pour l'approbation: seule une demande encore en attente change
+        let now = Utc::now().naive_utc();
+        let rejected = ValidationRequests::update_many()
+            .col_expr(validation_requests::Column::Status, Expr::value(STATUS_REJECTED))
+            .col_expr(validation_requests::Column::ReviewedById, Expr::value(Some(reviewer.id)))
+            .col_expr(validation_requests::Column::ReviewedAt, Expr::value(Some(now)))
+            .col_expr(
+                validation_requests::Column::ReviewComment,
+                Expr::value(comment.filter(|c| !c.trim().is_empty())),
+            )
+            .col_expr(validation_requests::Column::UpdatedAt, Expr::value(now))
+            .filter(validation_requests::Column::Id.eq(pending.id))
+            .filter(validation_requests::Column::Status.eq(STATUS_PENDING))
+            .exec(db)
+            .await?;
+        if rejected.rows_affected == 0 {
+            return Err(AppError::Validation("Cette demande a déjà été traitée".to_string()));
+        }
+
+        let model = ValidationRequests::find_by_id(pending.id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Demande non trouvée".to_string()))?;
+        Self::response(db, model).await
+    }
+
+    /// Retire une demande en attente: l'élève, l'auteur de la demande ou un
+    /// vrai administrateur
+    pub async fn cancel(
+        db: &DatabaseConnection,
+        id: Uuid,
+        person: &people::Model,
+        is_admin: bool,
+    ) -> AppResult<()> {
+        let pending = Self::find_pending(db, id).await?;
+        if !is_admin
+            && pending.person_id != person.id
+            && pending.requested_by_id != Some(person.id)
+        {
+            return Err(AppError::Forbidden(
+                "Vous ne pouvez pas retirer cette demande".to_string(),
+            ));
+        }
+
+        ValidationRequests::delete_by_id(pending.id).exec(db).await?;
+        Ok(())
+    }
+
+    async fn find_pending(db: &DatabaseConnection, id: Uuid) -> AppResult<validation_requests::Model> {
+        let request = ValidationRequests::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(AppError::NotFound("Demande non trouvée".to_string()))?;
+        if request.status != STATUS_PENDING {
+            return Err(AppError::Validation("Cette demande a déjà été traitée".to_string()));
+        }
+        Ok(request)
+    }
+
+    fn check_not_own(request: &validation_requests::Model, reviewer: &people::Model) -> AppResult<()> {
+        if request.person_id == reviewer.id {
+            return Err(AppError::Forbidden(
+                "Vous ne pouvez pas traiter votre propre demande".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn skills(
+        db: &DatabaseConnection,
+        requests: &[validation_requests::Model],
+    ) -> AppResult<HashMap<Uuid, competency_skills::Model>> {
+        Ok(CompetencySkills::find()
+            .filter(competency_skills::Column::Id.is_in(requests.iter().map(|r| r.skill_id)))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|s| (s.id, s))
+            .collect())
+    }
+
+    async fn response(
+        db: &DatabaseConnection,
+        request: validation_requests::Model,
+    ) -> AppResult<ValidationRequestResponse> {
+        Self::responses(db, vec![request])
+            .await?
+            .pop()
+            .ok_or_else(|| AppError::Internal("Demande introuvable".to_string()))
+    }
+
+    async fn responses(
+        db: &DatabaseConnection,
+        requests: Vec<validation_requests::Model>,
+    ) -> AppResult<Vec<ValidationRequestResponse>> {
+        let skills = Self::skills(db, &requests).await?;
+        let names: HashMap<Uuid, String> = People::find()
+            .filter(people::Column::Id.is_in(requests.iter().flat_map(|r| {
+                [Some(r.person_id), r.requested_by_id, r.reviewed_by_id]
+                    .into_iter()
+                    .flatten()
+            })))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|p| (p.id, format!("{} {}", p.first_name, p.last_name)))
+            .collect();
+        let stages: HashMap<Uuid, String> = ValidationStages::find()
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|s| (s.id, s.name))
+            .collect();
+        let sessions: HashMap<Uuid, String> = Sessions::find()
+            .filter(sessions::Column::Id.is_in(requests.iter().filter_map(|r| r.session_id)))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|s| (s.id, s.name))
+            .collect();
+
+        Ok(requests
+            .into_iter()
+            .map(|r| {
+                let skill = skills.get(&r.skill_id);
+                ValidationRequestResponse {
+                    id: r.id,
+                    person_id: r.person_id,
+                    person_name: names.get(&r.person_id).cloned().unwrap_or_default(),
+                    skill_id: r.skill_id,
+                    skill_name: skill.map(|s| s.name.clone()).unwrap_or_default(),
+                    min_validator_level: skill
+                        .map(SkillValidationService::requirement)
+                        .unwrap_or_default(),
+                    stage_id: r.stage_id,
+                    stage_name: r.stage_id.and_then(|id| stages.get(&id).cloned()),
+                    requested_by_id: r.requested_by_id,
+                    requested_by_name: r.requested_by_id.and_then(|id| names.get(&id).cloned()),
+                    session_id: r.session_id,
+                    session_name: r.session_id.and_then(|id| sessions.get(&id).cloned()),
+                    demonstrated_at: r.demonstrated_at.map(|d| d.format("%Y-%m-%d").to_string()),
+                    comment: r.comment,
+                    status: r.status,
+                    reviewed_by_id: r.reviewed_by_id,
+                    reviewed_by_name: r.reviewed_by_id.and_then(|id| names.get(&id).cloned()),
+                    reviewed_at: r
+                        .reviewed_at
+                        .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string()),
+                    review_comment: r.review_comment,
+                    validation_id: r.validation_id,
+                    created_at: r.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::teaching_qualifications;
+
+    fn skill(min_validator_level: &str, validator_rule: Option<&str>) -> competency_skills::Model {
+        let now = Utc::now().naive_utc();
+        competency_skills::Model {
+            id: Uuid::new_v4(),
+            module_id: Uuid::new_v4(),
+            name: "Vidage de masque".to_string(),
+            description: None,
+            sort_order: 0,
+            min_validator_level: min_validator_level.to_string(),
+            validator_rule: validator_rule.map(str::to_string),
+            validity_months: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_can_review() {
+        let catalog = LevelCatalog::builtin();
+        let e2 = skill("E2", None);
+
+        assert!(ValidationRequestService::can_review(&catalog, Some("N4,E2"), &e2));
+        assert!(ValidationRequestService::can_review(&catalog, Some("E3"), &e2));
+        assert!(!ValidationRequestService::can_review(&catalog, Some("N4,E1"), &e2));
+        // Plongeur sans niveau d'encadrement
+        assert!(!ValidationRequestService::can_review(&catalog, Some("N4"), &e2));
+        assert!(!ValidationRequestService::can_review(&catalog, None, &e2));
+    }
+
+    #[test]
+    fn test_expired_title_cannot_review() {
+        let catalog = LevelCatalog::builtin();
+        let e2 = skill("E2", None);
+        let today = NaiveDate::from_ymd_opt(2024, 5, 10).unwrap();
+        let now = Utc::now().naive_utc();
+        let expired = teaching_qualifications::Model {
+            id: Uuid::new_v4(),
+            person_id: Uuid::nil(),
+            level: "E2".to_string(),
+            obtained_at: NaiveDate::from_ymd_opt(2018, 6, 1).unwrap(),
+            expires_at: NaiveDate::from_ymd_opt(2024, 1, 1),
+            certificate_number: None,
+            notes: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        // Même niveau saisi "N4,E2": le titre E2 expiré n'habilite plus
+        let level = TeachingQualificationService::apply_qualifications(
+            Some("N4,E2"),
+            std::slice::from_ref(&expired),
+            today,
+        );
+        assert_eq!(level.as_deref(), Some("N4"));
+        assert!(!ValidationRequestService::can_review(&catalog, level.as_deref(), &e2));
+    }
+}